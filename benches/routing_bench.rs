@@ -32,7 +32,9 @@ fn bench_config_find_model(c: &mut Criterion) {
                     model: "openai/gpt-4".to_string(),
                     api_key: "sk-test".to_string(),
                     api_base: "https://api.openai.com/v1".to_string(),
+                    ..Default::default()
                 },
+                ..Default::default()
             },
             ModelConfig {
                 model_name: "claude".to_string(),
@@ -40,7 +42,9 @@ fn bench_config_find_model(c: &mut Criterion) {
                     model: "anthropic/claude-opus-4-5".to_string(),
                     api_key: "sk-ant-test".to_string(),
                     api_base: "https://api.anthropic.com".to_string(),
+                    ..Default::default()
                 },
+                ..Default::default()
             },
             ModelConfig {
                 model_name: "gemini".to_string(),
@@ -48,9 +52,12 @@ fn bench_config_find_model(c: &mut Criterion) {
                     model: "gemini/gemini-pro".to_string(),
                     api_key: "AIza-test".to_string(),
                     api_base: "https://generativelanguage.googleapis.com".to_string(),
+                    ..Default::default()
                 },
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
 
     c.bench_function("find_model_first", |b| {
@@ -144,7 +151,10 @@ fn bench_serialization(c: &mut Criterion) {
         temperature: Some(0.7),
         max_tokens: Some(100),
         stream: Some(false),
+        stream_options: None,
         top_p: Some(1.0),
+        stop: None,
+        user: None,
     };
 
     c.bench_function("serialize_chat_request", |b| {
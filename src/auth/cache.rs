@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// 缓存的查询结果的有效期：其他副本修改、封禁或删除记录后，最迟在这段时间后生效
+pub(super) const CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// 最多缓存的不存在的记录数量，超过时清空这部分缓存
+const MAX_NEGATIVE_ENTRIES: usize = 10_000;
+
+/// 缓存的查询结果，`value` 为 None 表示数据库中不存在
+struct Cached<T> {
+    value: Option<Arc<T>>,
+    loaded_at: Instant,
+}
+
+/// 主键 → 查询结果
+struct Entries<T> {
+    map: HashMap<String, Cached<T>>,
+    /// 其中不存在的记录的数量
+    negative: usize,
+}
+
+impl<T> Entries<T> {
+    fn forget(&mut self, cached: &Cached<T>) {
+        if cached.value.is_none() {
+            self.negative -= 1;
+        }
+    }
+}
+
+/// 数据库查询结果的内存缓存，按 `ttl` 过期后重新读取数据库
+pub(super) struct LookupCache<T> {
+    entries: RwLock<Entries<T>>,
+    pub(super) ttl: Duration,
+}
+
+impl<T> Default for LookupCache<T> {
+    fn default() -> Self {
+        LookupCache {
+            entries: RwLock::new(Entries {
+                map: HashMap::new(),
+                negative: 0,
+            }),
+            ttl: CACHE_TTL,
+        }
+    }
+}
+
+impl<T> LookupCache<T> {
+    /// 读取未过期的查询结果，外层 None 表示需要重新查询
+    fn get(&self, id: &str) -> Option<Option<Arc<T>>> {
+        self.entries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .map
+            .get(id)
+            .filter(|cached| cached.loaded_at.elapsed() < self.ttl)
+            .map(|cached| cached.value.clone())
+    }
+
+    /// 缓存未命中或已过期时调用 `load` 查询数据库并写入缓存
+    pub(super) fn get_or_load(
+        &self,
+        id: &str,
+        load: impl FnOnce() -> crate::Result<Option<T>>,
+    ) -> crate::Result<Option<Arc<T>>> {
+        if let Some(value) = self.get(id) {
+            return Ok(value);
+        }
+        let value = load()?.map(Arc::new);
+        self.insert(id.to_string(), value.clone());
+        Ok(value)
+    }
+
+    /// 写入查询结果，不存在的记录超过上限时先清空这部分缓存
+    fn insert(&self, id: String, value: Option<Arc<T>>) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        if value.is_none() && entries.negative >= MAX_NEGATIVE_ENTRIES {
+            entries.map.retain(|_, cached| cached.value.is_some());
+            entries.negative = 0;
+        }
+        if value.is_none() {
+            entries.negative += 1;
+        }
+        let cached = Cached {
+            value,
+            loaded_at: Instant::now(),
+        };
+        if let Some(replaced) = entries.map.insert(id, cached) {
+            entries.forget(&replaced);
+        }
+    }
+
+    /// 记录被修改或删除后清除本副本的缓存（其他副本等缓存过期）
+    pub(super) fn remove(&self, id: &str) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(removed) = entries.map.remove(id) {
+            entries.forget(&removed);
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn negative(&self) -> usize {
+        self.entries.read().unwrap().negative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_entries_are_bounded() {
+        let cache = LookupCache::default();
+        cache.insert("known".to_string(), Some(Arc::new(1)));
+        for i in 0..=MAX_NEGATIVE_ENTRIES {
+            cache.insert(format!("unknown-{}", i), None);
+        }
+
+        assert_eq!(cache.negative(), 1);
+        assert_eq!(cache.entries.read().unwrap().map.len(), 2);
+        assert_eq!(cache.get("known"), Some(Some(Arc::new(1))));
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let mut cache = LookupCache::<u32>::default();
+        cache.insert("missing".to_string(), None);
+        assert_eq!(cache.get("missing"), Some(None));
+
+        // 重新查询不存在的记录不会重复计数
+        cache.ttl = Duration::ZERO;
+        assert_eq!(cache.get("missing"), None);
+        assert!(cache.get_or_load("missing", || Ok(None)).unwrap().is_none());
+        assert_eq!(cache.negative(), 1);
+
+        cache.remove("missing");
+        assert_eq!(cache.negative(), 0);
+    }
+}
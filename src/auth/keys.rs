@@ -0,0 +1,653 @@
+use super::cache::LookupCache;
+use super::teams;
+use crate::db::audit::{self, AuditAction, AuditEntry};
+use crate::db::{self, Database};
+use crate::error::FeatherGateError;
+use crate::spend::{EntityType, SpendTracker};
+use crate::Result;
+use chrono::{DateTime, SubsecRound, Utc};
+use once_cell::sync::Lazy;
+use rand::distributions::{Alphanumeric, DistString};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// 虚拟 key 的前缀
+pub const KEY_PREFIX: &str = "sk-fg-";
+
+/// 虚拟 key（只保存哈希，明文只在生成时返回一次）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VirtualKey {
+    /// key 的 SHA-256 哈希（十六进制）
+    pub token: String,
+    /// 脱敏后的 key（`sk-fg-...abcd`），用于展示
+    pub key_name: String,
+    pub key_alias: Option<String>,
+    /// 允许使用的 model_name，空列表表示不限制；以 `*` 结尾时按前缀匹配
+    pub models: Vec<String>,
+    pub expires: Option<DateTime<Utc>>,
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    pub blocked: bool,
+    pub user_id: Option<String>,
+    pub team_id: Option<String>,
+    /// 预算上限（美元）
+    pub max_budget: Option<f64>,
+    /// 预算周期（如 `30d`），到期后花费清零
+    pub budget_duration: Option<String>,
+    /// 每分钟请求数限制
+    pub rpm_limit: Option<u64>,
+    /// 每分钟 token 数限制
+    pub tpm_limit: Option<u64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl VirtualKey {
+    /// 是否允许访问指定模型
+    pub fn allows_model(&self, model: &str) -> bool {
+        models_allow(&self.models, model)
+    }
+
+    /// 是否已过期
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires <= Utc::now())
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(VirtualKey {
+            token: row.get("token")?,
+            key_name: row.get("key_name")?,
+            key_alias: row.get("key_alias")?,
+            models: db::json_column(row, "models")?,
+            expires: row.get::<_, Option<i64>>("expires")?.map(db::timestamp),
+            metadata: db::json_column(row, "metadata")?,
+            blocked: row.get("blocked")?,
+            user_id: row.get("user_id")?,
+            team_id: row.get("team_id")?,
+            max_budget: row.get("max_budget")?,
+            budget_duration: row.get("budget_duration")?,
+            rpm_limit: row.get("rpm_limit")?,
+            tpm_limit: row.get("tpm_limit")?,
+            created_at: db::timestamp(row.get("created_at")?),
+            updated_at: db::timestamp(row.get("updated_at")?),
+        })
+    }
+}
+
+/// 模型列表是否允许访问指定模型：空列表不限制，以 `*` 结尾时按前缀匹配
+pub(crate) fn models_allow(models: &[String], model: &str) -> bool {
+    models.is_empty()
+        || models.iter().any(|allowed| match allowed.strip_suffix('*') {
+            Some(prefix) => model.starts_with(prefix),
+            None => allowed == model,
+        })
+}
+
+/// 生成虚拟 key 的参数
+#[derive(Debug, Clone, Default)]
+pub struct NewKey {
+    pub key_alias: Option<String>,
+    pub models: Vec<String>,
+    pub expires: Option<DateTime<Utc>>,
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    pub blocked: bool,
+    pub user_id: Option<String>,
+    pub team_id: Option<String>,
+    pub max_budget: Option<f64>,
+    pub budget_duration: Option<String>,
+    pub rpm_limit: Option<u64>,
+    pub tpm_limit: Option<u64>,
+}
+
+/// 修改虚拟 key 的参数，None 表示不修改
+#[derive(Debug, Clone, Default)]
+pub struct KeyUpdate {
+    pub key_alias: Option<String>,
+    pub models: Option<Vec<String>>,
+    pub expires: Option<DateTime<Utc>>,
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+    pub blocked: Option<bool>,
+    pub user_id: Option<String>,
+    pub team_id: Option<String>,
+    pub max_budget: Option<f64>,
+    pub budget_duration: Option<String>,
+    pub rpm_limit: Option<u64>,
+    pub tpm_limit: Option<u64>,
+}
+
+impl KeyUpdate {
+    fn apply(self, key: &mut VirtualKey) {
+        if let Some(key_alias) = self.key_alias {
+            key.key_alias = Some(key_alias);
+        }
+        if let Some(models) = self.models {
+            key.models = models;
+        }
+        if let Some(expires) = self.expires {
+            key.expires = Some(expires.trunc_subsecs(0));
+        }
+        if let Some(metadata) = self.metadata {
+            key.metadata = metadata;
+        }
+        if let Some(blocked) = self.blocked {
+            key.blocked = blocked;
+        }
+        if let Some(user_id) = self.user_id {
+            key.user_id = Some(user_id);
+        }
+        if let Some(team_id) = self.team_id {
+            key.team_id = Some(team_id);
+        }
+        if let Some(max_budget) = self.max_budget {
+            key.max_budget = Some(max_budget);
+        }
+        if let Some(budget_duration) = self.budget_duration {
+            key.budget_duration = Some(budget_duration);
+        }
+        if let Some(rpm_limit) = self.rpm_limit {
+            key.rpm_limit = Some(rpm_limit);
+        }
+        if let Some(tpm_limit) = self.tpm_limit {
+            key.tpm_limit = Some(tpm_limit);
+        }
+    }
+}
+
+/// 列出虚拟 key 时的过滤条件
+#[derive(Debug, Clone, Default)]
+pub struct KeyFilter {
+    pub key_alias: Option<String>,
+    pub user_id: Option<String>,
+    pub team_id: Option<String>,
+}
+
+/// 审计日志中虚拟 key 的表名
+const AUDIT_TABLE: &str = "virtual_keys";
+
+/// 计算 key 的哈希
+pub fn hash_key(api_key: &str) -> String {
+    hex::encode(Sha256::digest(api_key.as_bytes()))
+}
+
+/// 管理 API 中的 key 参数可以是明文 key 或 token（哈希）
+pub fn resolve_token(key: &str) -> String {
+    if key.starts_with(KEY_PREFIX) {
+        hash_key(key)
+    } else {
+        key.to_string()
+    }
+}
+
+/// 生成新的明文 key
+fn new_api_key() -> String {
+    format!(
+        "{}{}",
+        KEY_PREFIX,
+        Alphanumeric.sample_string(&mut rand::thread_rng(), 32)
+    )
+}
+
+/// 脱敏展示：保留前缀和最后 4 位
+fn mask_key(api_key: &str) -> String {
+    format!("{}...{}", KEY_PREFIX, &api_key[api_key.len() - 4..])
+}
+
+/// 虚拟 key 存储：SQLite 持久化，认证热路径走内存缓存
+pub struct KeyStore {
+    db: &'static Database,
+    /// token → 查询结果，不存在的 key 也会缓存
+    cache: LookupCache<VirtualKey>,
+}
+
+impl KeyStore {
+    pub fn new(db: &'static Database) -> Self {
+        KeyStore {
+            db,
+            cache: LookupCache::default(),
+        }
+    }
+
+    /// 生成新的虚拟 key，返回明文 key 和保存的记录
+    pub fn generate(&self, new_key: NewKey, changed_by: &str) -> Result<(String, VirtualKey)> {
+        let api_key = new_api_key();
+        // 数据库按秒保存时间
+        let now = Utc::now().trunc_subsecs(0);
+        let key = VirtualKey {
+            token: hash_key(&api_key),
+            key_name: mask_key(&api_key),
+            key_alias: new_key.key_alias,
+            models: new_key.models,
+            expires: new_key.expires.map(|expires| expires.trunc_subsecs(0)),
+            metadata: new_key.metadata,
+            blocked: new_key.blocked,
+            user_id: new_key.user_id,
+            team_id: new_key.team_id,
+            max_budget: new_key.max_budget,
+            budget_duration: new_key.budget_duration,
+            rpm_limit: new_key.rpm_limit,
+            tpm_limit: new_key.tpm_limit,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.db.transaction(|tx| {
+            teams::check_key_membership(tx, key.user_id.as_deref(), key.team_id.as_deref())?;
+            insert(tx, &key)?;
+            let entry = AuditEntry::new(changed_by, AuditAction::Created, AUDIT_TABLE, &key.token);
+            audit::record(tx, &entry.updated(&key))
+        })?;
+
+        Ok((api_key, key))
+    }
+
+    /// 修改虚拟 key
+    pub fn update(&self, token: &str, update: KeyUpdate, changed_by: &str) -> Result<VirtualKey> {
+        let key = self.db.transaction(|tx| {
+            let before = select(tx, token)?;
+            let mut key = before.clone();
+            update.apply(&mut key);
+            if (&key.user_id, &key.team_id) != (&before.user_id, &before.team_id) {
+                teams::check_key_membership(tx, key.user_id.as_deref(), key.team_id.as_deref())?;
+            }
+            key.updated_at = Utc::now().trunc_subsecs(0);
+
+            write(tx, token, &key)?;
+            let entry = AuditEntry::new(changed_by, AuditAction::Updated, AUDIT_TABLE, token);
+            audit::record(tx, &entry.before(&before).updated(&key))?;
+            Ok(key)
+        })?;
+
+        self.invalidate(token);
+        Ok(key)
+    }
+
+    /// 轮换虚拟 key：旧 key 立即失效，其余属性保留（可同时修改），返回新的明文 key
+    ///
+    /// 花费（包括 `spend` 中尚未写入数据库的部分）跟随 key 转移到新 token。
+    pub fn regenerate(
+        &self,
+        token: &str,
+        update: KeyUpdate,
+        spend: &SpendTracker,
+        changed_by: &str,
+    ) -> Result<(String, VirtualKey)> {
+        let api_key = new_api_key();
+        let key = self.db.transaction(|tx| {
+            let before = select(tx, token)?;
+            let mut key = before.clone();
+            update.apply(&mut key);
+            if (&key.user_id, &key.team_id) != (&before.user_id, &before.team_id) {
+                teams::check_key_membership(tx, key.user_id.as_deref(), key.team_id.as_deref())?;
+            }
+            key.token = hash_key(&api_key);
+            key.key_name = mask_key(&api_key);
+            key.updated_at = Utc::now().trunc_subsecs(0);
+
+            write(tx, token, &key)?;
+            // 审计日志记在新 token 下，before_value 中保留旧 token
+            let entry = AuditEntry::new(changed_by, AuditAction::Rotated, AUDIT_TABLE, &key.token);
+            audit::record(tx, &entry.before(&before).updated(&key))?;
+            Ok(key)
+        })?;
+
+        self.invalidate(token);
+        spend.transfer(EntityType::Key, token, &key.token)?;
+        Ok((api_key, key))
+    }
+
+    /// 删除虚拟 key（任何一个不存在时都不删除）及其花费，返回被删除的记录
+    pub fn delete(&self, tokens: &[String], spend: &SpendTracker, changed_by: &str) -> Result<Vec<VirtualKey>> {
+        let deleted = self.db.transaction(|tx| {
+            let mut deleted = Vec::with_capacity(tokens.len());
+            for token in tokens {
+                let key = select(tx, token)?;
+                tx.execute("DELETE FROM virtual_keys WHERE token = ?1", [token])
+                    .map_err(db::db_error)?;
+                let entry = AuditEntry::new(changed_by, AuditAction::Deleted, AUDIT_TABLE, token);
+                audit::record(tx, &entry.before(&key))?;
+                deleted.push(key);
+            }
+            Ok(deleted)
+        })?;
+
+        for key in &deleted {
+            self.invalidate(&key.token);
+        }
+        spend.remove(EntityType::Key, tokens)?;
+        Ok(deleted)
+    }
+
+    /// 按 token 从数据库读取 key
+    pub fn get(&self, token: &str) -> Result<Option<VirtualKey>> {
+        self.db.with_conn(|conn| {
+            conn.query_row(
+                "SELECT * FROM virtual_keys WHERE token = ?1",
+                [token],
+                VirtualKey::from_row,
+            )
+            .optional()
+        })
+    }
+
+    /// 按别名读取 key
+    pub fn get_by_alias(&self, key_alias: &str) -> Result<Option<VirtualKey>> {
+        self.db.with_conn(|conn| {
+            conn.query_row(
+                "SELECT * FROM virtual_keys WHERE key_alias = ?1",
+                [key_alias],
+                VirtualKey::from_row,
+            )
+            .optional()
+        })
+    }
+
+    /// 分页列出 key（按创建时间倒序），同时返回符合条件的总数
+    pub fn list(&self, filter: &KeyFilter, limit: u32, offset: u32) -> Result<(Vec<VirtualKey>, u64)> {
+        const CONDITION: &str = "(?1 IS NULL OR key_alias = ?1)
+            AND (?2 IS NULL OR user_id = ?2)
+            AND (?3 IS NULL OR team_id = ?3)";
+        let filter_params = params![filter.key_alias, filter.user_id, filter.team_id];
+
+        self.db.with_conn(|conn| {
+            let total = conn.query_row(
+                &format!("SELECT COUNT(*) FROM virtual_keys WHERE {}", CONDITION),
+                filter_params,
+                |row| row.get(0),
+            )?;
+            let keys = conn
+                .prepare(&format!(
+                    "SELECT * FROM virtual_keys WHERE {} ORDER BY created_at DESC, token LIMIT ?4 OFFSET ?5",
+                    CONDITION
+                ))?
+                .query_map(
+                    params![filter.key_alias, filter.user_id, filter.team_id, limit, offset],
+                    VirtualKey::from_row,
+                )?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok((keys, total))
+        })
+    }
+
+    /// 按明文 key 查找（认证使用，优先读缓存，不存在的 key 同样缓存）
+    pub fn lookup(&self, api_key: &str) -> Result<Option<Arc<VirtualKey>>> {
+        let token = hash_key(api_key);
+        self.cache.get_or_load(&token, || self.get(&token))
+    }
+
+    /// 某个 key 的审计日志
+    pub fn audit_log(&self, token: &str) -> Result<Vec<AuditEntry>> {
+        audit::entries(self.db, token)
+    }
+
+    /// key 被修改或删除后清除本副本的缓存（其他副本等缓存过期）
+    pub fn invalidate(&self, token: &str) {
+        self.cache.remove(token);
+    }
+}
+
+/// 事务内按 token 读取 key，不存在时返回 KeyNotFound
+fn select(conn: &Connection, token: &str) -> Result<VirtualKey> {
+    conn.query_row(
+        "SELECT * FROM virtual_keys WHERE token = ?1",
+        [token],
+        VirtualKey::from_row,
+    )
+    .optional()
+    .map_err(db::db_error)?
+    .ok_or_else(|| FeatherGateError::KeyNotFound(token.to_string()))
+}
+
+fn insert(conn: &Connection, key: &VirtualKey) -> Result<()> {
+    conn.execute(
+        "INSERT INTO virtual_keys
+            (token, key_name, key_alias, models, expires, metadata, blocked, user_id, team_id,
+             max_budget, budget_duration, rpm_limit, tpm_limit, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            key.token,
+            key.key_name,
+            key.key_alias,
+            serde_json::to_string(&key.models).unwrap_or_default(),
+            key.expires.map(|expires| expires.timestamp()),
+            serde_json::to_string(&key.metadata).unwrap_or_default(),
+            key.blocked,
+            key.user_id,
+            key.team_id,
+            key.max_budget,
+            key.budget_duration,
+            key.rpm_limit,
+            key.tpm_limit,
+            key.created_at.timestamp(),
+            key.updated_at.timestamp(),
+        ],
+    )
+    .map_err(|e| write_error(e, key))?;
+    Ok(())
+}
+
+/// 用 key 覆盖 token 对应的记录（轮换时 token 也会改变）
+fn write(conn: &Connection, token: &str, key: &VirtualKey) -> Result<()> {
+    conn.execute(
+        "UPDATE virtual_keys SET
+            token = ?1, key_name = ?2, key_alias = ?3, models = ?4, expires = ?5, metadata = ?6,
+            blocked = ?7, user_id = ?8, team_id = ?9, max_budget = ?10, budget_duration = ?11,
+            rpm_limit = ?12, tpm_limit = ?13, updated_at = ?14
+         WHERE token = ?15",
+        params![
+            key.token,
+            key.key_name,
+            key.key_alias,
+            serde_json::to_string(&key.models).unwrap_or_default(),
+            key.expires.map(|expires| expires.timestamp()),
+            serde_json::to_string(&key.metadata).unwrap_or_default(),
+            key.blocked,
+            key.user_id,
+            key.team_id,
+            key.max_budget,
+            key.budget_duration,
+            key.rpm_limit,
+            key.tpm_limit,
+            key.updated_at.timestamp(),
+            token,
+        ],
+    )
+    .map_err(|e| write_error(e, key))?;
+    Ok(())
+}
+
+/// 写入失败时，别名冲突归类为请求错误
+fn write_error(e: rusqlite::Error, key: &VirtualKey) -> FeatherGateError {
+    match &key.key_alias {
+        Some(alias) if db::is_constraint_violation(&e) => FeatherGateError::KeyAliasExists(alias.clone()),
+        _ => db::db_error(e),
+    }
+}
+
+/// 全局虚拟 key 存储
+pub fn key_store() -> &'static KeyStore {
+    static STORE: Lazy<KeyStore> = Lazy::new(|| KeyStore::new(db::global()));
+    &STORE
+}
+
+#[cfg(test)]
+pub(crate) fn test_store() -> KeyStore {
+    KeyStore::new(Box::leak(Box::new(Database::open_in_memory().unwrap())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_generate_and_lookup() {
+        let store = test_store();
+        let (api_key, key) = store
+            .generate(NewKey {
+                key_alias: Some("search-team".to_string()),
+                models: vec!["gpt-4o".to_string(), "claude-*".to_string()],
+                metadata: serde_json::json!({"team": "search"}).as_object().unwrap().clone(),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+
+        assert!(api_key.starts_with(KEY_PREFIX));
+        assert_eq!(key.key_name, format!("sk-fg-...{}", &api_key[api_key.len() - 4..]));
+        // 数据库中只保存哈希
+        assert_ne!(key.token, api_key);
+
+        let found = store.lookup(&api_key).unwrap().unwrap();
+        assert_eq!(*found, key);
+        assert!(found.allows_model("gpt-4o"));
+        assert!(found.allows_model("claude-sonnet"));
+        assert!(!found.allows_model("gpt-4o-mini"));
+
+        assert!(store.lookup("sk-fg-unknown").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_expiry_and_unrestricted_models() {
+        let store = test_store();
+        let (_, key) = store
+            .generate(NewKey {
+                expires: Some(Utc::now() - chrono::Duration::hours(1)),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+
+        assert!(key.is_expired());
+        assert!(key.allows_model("anything"));
+    }
+
+    #[test]
+    fn test_update_regenerate_and_delete_are_audited() {
+        let store = test_store();
+        let (api_key, key) = store
+            .generate(NewKey {
+                key_alias: Some("ci".to_string()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        // 先放入缓存，确认修改后缓存失效
+        assert!(!store.lookup(&api_key).unwrap().unwrap().blocked);
+
+        let update = KeyUpdate {
+            blocked: Some(true),
+            ..Default::default()
+        };
+        let updated = store.update(&key.token, update, "alice").unwrap();
+        assert!(updated.blocked);
+        assert_eq!(updated.key_alias.as_deref(), Some("ci"));
+        assert!(store.lookup(&api_key).unwrap().unwrap().blocked);
+
+        let tracker = SpendTracker::new(store.db);
+        let (new_key, rotated) = store.regenerate(&key.token, KeyUpdate::default(), &tracker, "alice").unwrap();
+        assert!(store.lookup(&api_key).unwrap().is_none());
+        assert_eq!(store.lookup(&new_key).unwrap().unwrap().token, rotated.token);
+        assert_eq!(rotated.created_at, key.created_at);
+
+        let actions: Vec<_> = store
+            .audit_log(&key.token)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.action, entry.changed_by))
+            .collect();
+        assert_eq!(
+            actions,
+            [("created".to_string(), "master_key".to_string()), ("updated".to_string(), "alice".to_string())]
+        );
+        let rotation = &store.audit_log(&rotated.token).unwrap()[0];
+        assert_eq!(rotation.action, "rotated");
+        assert_eq!(rotation.before_value.as_ref().unwrap()["token"], key.token.as_str());
+
+        // 任何一个 key 不存在时整体不删除
+        let missing = vec![rotated.token.clone(), "missing".to_string()];
+        assert!(store.delete(&missing, &tracker, "alice").is_err());
+        assert!(store.get(&rotated.token).unwrap().is_some());
+
+        let deleted = store.delete(std::slice::from_ref(&rotated.token), &tracker, "alice").unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert!(store.lookup(&new_key).unwrap().is_none());
+        assert_eq!(store.audit_log(&rotated.token).unwrap()[1].action, "deleted");
+    }
+
+    #[test]
+    fn test_spend_follows_regenerated_and_deleted_keys() {
+        let store = test_store();
+        let tracker = SpendTracker::new(store.db);
+        let budget = crate::config::BudgetConfig {
+            max_budget: Some(1.0),
+            budget_duration: None,
+        };
+        let rows = |token: &str| -> i64 {
+            store
+                .db
+                .with_conn(|conn| {
+                    conn.query_row(
+                        "SELECT COUNT(*) FROM spend WHERE entity_type = 'key' AND entity_id = ?1",
+                        [token],
+                        |row| row.get(0),
+                    )
+                })
+                .unwrap()
+        };
+
+        // 一部分花费已写入数据库，一部分还在内存中
+        let (_, key) = store.generate(NewKey::default(), "master_key").unwrap();
+        tracker.add(EntityType::Key, &key.token, 0.5, &budget).unwrap();
+        tracker.flush().unwrap();
+        tracker.add(EntityType::Key, &key.token, 0.25, &budget).unwrap();
+
+        let (_, rotated) = store.regenerate(&key.token, KeyUpdate::default(), &tracker, "alice").unwrap();
+        tracker.flush().unwrap();
+        assert_eq!(rows(&key.token), 0);
+        assert_eq!(rows(&rotated.token), 1);
+        assert_eq!(tracker.summary(EntityType::Key, &key.token).unwrap().spend, 0.0);
+        assert!((tracker.summary(EntityType::Key, &rotated.token).unwrap().spend - 0.75).abs() < 1e-9);
+
+        tracker.add(EntityType::Key, &rotated.token, 0.1, &budget).unwrap();
+        store.delete(std::slice::from_ref(&rotated.token), &tracker, "alice").unwrap();
+        tracker.flush().unwrap();
+        assert_eq!(rows(&rotated.token), 0);
+        assert_eq!(tracker.summary(EntityType::Key, &rotated.token).unwrap().spend, 0.0);
+    }
+
+    #[test]
+    fn test_cached_keys_expire() {
+        let mut store = test_store();
+        let (api_key, key) = store.generate(NewKey::default(), "master_key").unwrap();
+        assert!(!store.lookup(&api_key).unwrap().unwrap().blocked);
+        assert!(store.lookup("sk-fg-unknown").unwrap().is_none());
+        assert_eq!(store.cache.negative(), 1);
+
+        // 其他副本封禁了 key：缓存过期前不可见，过期后重新读取
+        store
+            .db
+            .with_conn(|conn| {
+                conn.execute("UPDATE virtual_keys SET blocked = 1 WHERE token = ?1", [&key.token])
+            })
+            .unwrap();
+        assert!(!store.lookup(&api_key).unwrap().unwrap().blocked);
+        store.cache.ttl = Duration::ZERO;
+        assert!(store.lookup(&api_key).unwrap().unwrap().blocked);
+
+        // 重新读取不存在的 key 不会重复计数
+        assert!(store.lookup("sk-fg-unknown").unwrap().is_none());
+        assert_eq!(store.cache.negative(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_alias_is_rejected() {
+        let store = test_store();
+        let new_key = NewKey {
+            key_alias: Some("search".to_string()),
+            ..Default::default()
+        };
+        store.generate(new_key.clone(), "master_key").unwrap();
+        assert!(matches!(
+            store.generate(new_key, "master_key"),
+            Err(FeatherGateError::KeyAliasExists(alias)) if alias == "search"
+        ));
+    }
+}
@@ -0,0 +1,507 @@
+mod cache;
+pub mod jwt;
+pub mod keys;
+pub mod teams;
+
+use crate::config::Config;
+use crate::error::FeatherGateError;
+use crate::Result;
+use hyper::header::{self, HeaderMap};
+use jwt::{JwksCache, JwtIdentity};
+use keys::{KeyStore, VirtualKey};
+use teams::{Organization, Team, TeamStore};
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::Arc;
+
+/// 发起请求的调用方
+#[derive(Debug, Clone, Default)]
+pub enum Caller {
+    /// 未开启认证，或公开路由
+    #[default]
+    Anonymous,
+    /// 使用 master key
+    Master,
+    /// 使用虚拟 key
+    Key(Arc<VirtualKey>),
+    /// 使用 OIDC 签发的 JWT
+    Jwt(Arc<JwtIdentity>),
+}
+
+impl Caller {
+    /// 调用方自身的 models（只有虚拟 key 和 JWT 受 models 限制）
+    pub fn models(&self) -> &[String] {
+        match self {
+            Caller::Key(key) => &key.models,
+            Caller::Jwt(identity) => &identity.models,
+            Caller::Anonymous | Caller::Master => &[],
+        }
+    }
+
+    /// 调用方自身的 models 是否允许访问指定模型（不考虑所属团队和组织）
+    pub fn allows_model(&self, config: &Config, model: &str) -> bool {
+        models_allow(config, self.models(), model)
+    }
+
+    /// 调用方所属的用户（虚拟 key 的 user_id 或 JWT 映射出的用户）
+    pub fn user_id(&self) -> Option<&str> {
+        match self {
+            Caller::Key(key) => key.user_id.as_deref(),
+            Caller::Jwt(identity) => identity.user_id.as_deref(),
+            Caller::Anonymous | Caller::Master => None,
+        }
+    }
+
+    /// 调用方所属的团队
+    pub fn team_id(&self) -> Option<&str> {
+        match self {
+            Caller::Key(key) => key.team_id.as_deref(),
+            Caller::Jwt(identity) => identity.team_id.as_deref(),
+            Caller::Anonymous | Caller::Master => None,
+        }
+    }
+
+    /// 调用方的标识（虚拟 key 的 token 或 JWT 的 `sub`），用于隔离不同调用方保存的数据
+    pub fn owner_id(&self) -> String {
+        match self {
+            Caller::Anonymous => "anonymous".to_string(),
+            Caller::Master => "master".to_string(),
+            Caller::Key(key) => format!("key:{}", key.token),
+            Caller::Jwt(identity) => format!("jwt:{}", identity.subject),
+        }
+    }
+}
+
+tokio::task_local! {
+    static CALLER: Caller;
+    /// 请求体中的 `user` 字段（调用方代为请求的终端用户）
+    static END_USER: RefCell<Option<String>>;
+}
+
+/// 在请求处理期间记录调用方，供路由层检查模型权限和预算
+pub async fn with_caller<F: Future>(caller: Caller, f: F) -> F::Output {
+    CALLER.scope(caller, END_USER.scope(RefCell::new(None), f)).await
+}
+
+/// 当前请求的调用方（不在请求上下文中时视为不受限）
+pub fn current_caller() -> Caller {
+    CALLER.try_with(Caller::clone).unwrap_or_default()
+}
+
+/// 记录当前请求的终端用户（路由层解析请求体后设置）
+pub fn set_end_user(user: Option<&str>) {
+    let _ = END_USER.try_with(|end_user| *end_user.borrow_mut() = user.map(str::to_string));
+}
+
+/// 当前请求的终端用户
+pub fn end_user() -> Option<String> {
+    END_USER.try_with(|end_user| end_user.borrow().clone()).ok().flatten()
+}
+
+/// 调用方所属团队和组织的记录（只在配置文件中设置预算的团队没有记录）
+#[derive(Debug, Clone, Default)]
+pub struct Membership {
+    pub team: Option<Arc<Team>>,
+    pub organization: Option<Arc<Organization>>,
+}
+
+impl Membership {
+    /// 调用方、团队和组织中限制了模型的 models 列表（空列表表示继承上一级，不参与检查）
+    fn model_scopes<'a>(&'a self, caller: &'a Caller) -> impl Iterator<Item = &'a [String]> {
+        let team = self.team.iter().map(|team| team.settings.models.as_slice());
+        let organization = self.organization.iter().map(|org| org.settings.models.as_slice());
+        std::iter::once(caller.models())
+            .chain(team)
+            .chain(organization)
+            .filter(|models| !models.is_empty())
+    }
+}
+
+/// 查找调用方所属的团队和组织
+pub fn membership(caller: &Caller) -> Result<Membership> {
+    membership_with(teams::team_store(), caller)
+}
+
+fn membership_with(store: &TeamStore, caller: &Caller) -> Result<Membership> {
+    let Some(team) = caller.team_id().map(|team_id| store.lookup_team(team_id)).transpose()?.flatten() else {
+        return Ok(Membership::default());
+    };
+    let organization = match &team.organization_id {
+        Some(organization_id) => store.lookup_organization(organization_id)?,
+        None => None,
+    };
+    Ok(Membership {
+        team: Some(team),
+        organization,
+    })
+}
+
+/// models 列表是否允许访问指定模型：列表项可以是 model_name、以 `*` 结尾的前缀或访问组（`model_info.access_groups`）
+pub fn models_allow(config: &Config, models: &[String], model: &str) -> bool {
+    keys::models_allow(models, model) || {
+        let groups = config.access_groups(model);
+        models.iter().any(|allowed| groups.contains(&allowed.as_str()))
+    }
+}
+
+/// 检查当前调用方是否可以访问指定模型：调用方、所属团队和组织的 models 都必须允许
+pub fn check_model_access(config: &Config, model: &str) -> Result<()> {
+    check_model_access_with(teams::team_store(), &current_caller(), config, model)
+}
+
+fn check_model_access_with(store: &TeamStore, caller: &Caller, config: &Config, model: &str) -> Result<()> {
+    let membership = membership_with(store, caller)?;
+    if membership
+        .model_scopes(caller)
+        .all(|models| models_allow(config, models, model))
+    {
+        Ok(())
+    } else {
+        Err(FeatherGateError::ModelNotAllowed(model.to_string()))
+    }
+}
+
+/// 当前调用方是否不受模型限制（提供商透传等无法按模型检查的路由需要）
+pub fn is_unrestricted() -> Result<bool> {
+    let caller = current_caller();
+    let membership = membership(&caller)?;
+    let unrestricted = membership.model_scopes(&caller).next().is_none();
+    Ok(unrestricted)
+}
+
+/// 校验调用方凭据
+///
+/// 未配置 `general_settings.master_key` 和 `jwt_auth` 时不做认证；公开路由（`public_routes`）始终放行。
+/// 除 master key 外，还接受 `sk-fg-` 开头的虚拟 key，以及配置了 `jwt_auth` 时的 JWT。
+pub async fn authenticate(config: &Config, path: &str, headers: &HeaderMap) -> Result<Caller> {
+    authenticate_with(keys::key_store(), jwt::jwks_cache(), config, path, headers).await
+}
+
+async fn authenticate_with(
+    store: &KeyStore,
+    jwks: &JwksCache,
+    config: &Config,
+    path: &str,
+    headers: &HeaderMap,
+) -> Result<Caller> {
+    let settings = &config.general_settings;
+    if settings.master_key.is_none() && settings.jwt_auth.is_none() {
+        return Ok(Caller::Anonymous);
+    }
+    if is_public_route(&settings.public_routes, path) {
+        return Ok(Caller::Anonymous);
+    }
+
+    let api_key = extract_api_key(headers).ok_or(FeatherGateError::MissingApiKey)?;
+    if let Some(master_key) = settings.master_key.as_deref() {
+        if constant_time_eq(api_key.as_bytes(), master_key.as_bytes()) {
+            return Ok(Caller::Master);
+        }
+    }
+    if let Some(jwt_auth) = &settings.jwt_auth {
+        if jwt::looks_like_jwt(api_key) {
+            let identity = jwks.verify(jwt_auth, api_key).await?;
+            return Ok(Caller::Jwt(Arc::new(identity)));
+        }
+    }
+
+    let key = match api_key.starts_with(keys::KEY_PREFIX) {
+        true => store.lookup(api_key)?,
+        false => None,
+    };
+    let key = key.ok_or(FeatherGateError::InvalidApiKey)?;
+    if key.blocked {
+        return Err(FeatherGateError::KeyBlocked);
+    }
+    if key.is_expired() {
+        return Err(FeatherGateError::KeyExpired);
+    }
+    Ok(Caller::Key(key))
+}
+
+/// 是否为公开路由（精确匹配，或以 `/*` 结尾的前缀匹配）
+fn is_public_route(public_routes: &[String], path: &str) -> bool {
+    public_routes.iter().any(|route| match route.strip_suffix("/*") {
+        Some(prefix) => path == prefix || path.starts_with(&format!("{}/", prefix)),
+        None => path == route,
+    })
+}
+
+/// 从请求头中提取调用方的 API key
+///
+/// 依次检查 `Authorization: Bearer`（OpenAI SDK）、`x-api-key`（Anthropic SDK）和 `x-goog-api-key`（Google GenAI SDK）。
+fn extract_api_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            let (scheme, token) = value.trim().split_once(' ')?;
+            scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+        });
+
+    bearer
+        .or_else(|| headers.get("x-api-key").and_then(|value| value.to_str().ok()))
+        .or_else(|| headers.get("x-goog-api-key").and_then(|value| value.to_str().ok()))
+        .filter(|key| !key.is_empty())
+}
+
+/// 常量时间比较，避免通过响应时间推测 key
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+    use keys::NewKey;
+
+    fn config_with_master_key() -> Config {
+        let mut config = Config::default();
+        config.general_settings.master_key = Some("sk-master".to_string());
+        config.general_settings.public_routes = vec!["/health".to_string(), "/gemini/*".to_string()];
+        config
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_header_variants() {
+        let config = config_with_master_key();
+
+        for (name, value) in [
+            ("authorization", "Bearer sk-master"),
+            ("authorization", "bearer  sk-master"),
+            ("x-api-key", "sk-master"),
+            ("x-goog-api-key", "sk-master"),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_static(value));
+            assert!(authenticate(&config, "/v1/chat/completions", &headers).await.is_ok(), "{}", name);
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer sk-wrong"));
+        assert!(matches!(
+            authenticate(&config, "/v1/chat/completions", &headers).await,
+            Err(FeatherGateError::InvalidApiKey)
+        ));
+        assert!(matches!(
+            authenticate(&config, "/v1/models", &HeaderMap::new()).await,
+            Err(FeatherGateError::MissingApiKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_public_routes_and_disabled_auth() {
+        let config = config_with_master_key();
+        let headers = HeaderMap::new();
+
+        assert!(authenticate(&config, "/health", &headers).await.is_ok());
+        assert!(authenticate(&config, "/gemini/v1beta/cachedContents", &headers).await.is_ok());
+        assert!(authenticate(&config, "/metrics", &headers).await.is_err());
+        assert!(authenticate(&config, "/geminix", &headers).await.is_err());
+
+        // 未配置 master_key 时不做认证
+        assert!(authenticate(&Config::default(), "/v1/models", &headers).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_virtual_keys() {
+        let store = keys::test_store();
+        let config = config_with_master_key();
+        let bearer = |api_key: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("authorization", HeaderValue::from_str(&format!("Bearer {}", api_key)).unwrap());
+            headers
+        };
+
+        let (api_key, _) = store
+            .generate(NewKey {
+                models: vec!["gpt-4o".to_string()],
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let caller = authenticate_with(&store, jwt::jwks_cache(), &config, "/v1/chat/completions", &bearer(&api_key))
+            .await
+            .unwrap();
+        assert!(caller.allows_model(&config, "gpt-4o"));
+        assert!(!caller.allows_model(&config, "claude"));
+        assert!(!caller.models().is_empty());
+
+        let (blocked, _) = store
+            .generate(NewKey {
+                blocked: true,
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        assert!(matches!(
+            authenticate_with(&store, jwt::jwks_cache(), &config, "/v1/models", &bearer(&blocked)).await,
+            Err(FeatherGateError::KeyBlocked)
+        ));
+
+        let (expired, _) = store
+            .generate(NewKey {
+                expires: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        assert!(matches!(
+            authenticate_with(&store, jwt::jwks_cache(), &config, "/v1/models", &bearer(&expired)).await,
+            Err(FeatherGateError::KeyExpired)
+        ));
+
+        assert!(matches!(
+            authenticate_with(&store, jwt::jwks_cache(), &config, "/v1/models", &bearer("sk-fg-unknown")).await,
+            Err(FeatherGateError::InvalidApiKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_jwt() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/jwks.json")
+            .with_body(jwt::tests::test_jwks().to_string())
+            .create_async()
+            .await;
+        let mut config = Config::default();
+        config.general_settings.jwt_auth = Some(jwt::tests::test_settings(&format!("{}/jwks.json", server.url())));
+        let jwks = JwksCache::new();
+        let store = keys::test_store();
+        let bearer = |token: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("authorization", HeaderValue::from_str(&format!("Bearer {}", token)).unwrap());
+            headers
+        };
+
+        let token = jwt::tests::sign(
+            jsonwebtoken::Algorithm::ES256,
+            "ec-1",
+            serde_json::json!({"team_id": "search", "models": ["gpt-4o"]}),
+        );
+        let caller = authenticate_with(&store, &jwks, &config, "/v1/chat/completions", &bearer(&token))
+            .await
+            .unwrap();
+        assert_eq!(caller.user_id(), Some("alice"));
+        assert_eq!(caller.team_id(), Some("search"));
+        with_caller(caller, async {
+            let err = check_model_access(&config, "claude").unwrap_err();
+            assert_eq!(err.status_code(), hyper::StatusCode::FORBIDDEN);
+        })
+        .await;
+
+        // 只配置 jwt_auth 时同样需要认证
+        assert!(matches!(
+            authenticate_with(&store, &jwks, &config, "/v1/models", &HeaderMap::new()).await,
+            Err(FeatherGateError::MissingApiKey)
+        ));
+        let err = authenticate_with(&store, &jwks, &config, "/v1/models", &bearer("eyJhbGciOi.e30.c2ln"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FeatherGateError::InvalidJwt(_)));
+        assert_eq!(err.status_code(), hyper::StatusCode::UNAUTHORIZED);
+        assert!(matches!(
+            authenticate_with(&store, &jwks, &config, "/v1/models", &bearer("not-a-jwt")).await,
+            Err(FeatherGateError::InvalidApiKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_model_access_intersects_team_and_organization() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+model_list:
+  - model_name: gpt-4o
+    litellm_params:
+      model: openai/gpt-4o
+      api_key: sk-test
+    model_info:
+      access_groups: ["beta-models"]
+  - model_name: gpt-4o-mini
+    litellm_params:
+      model: openai/gpt-4o-mini
+      api_key: sk-test
+  - model_name: claude
+    litellm_params:
+      model: anthropic/claude-opus-4-5
+      api_key: sk-test
+    model_info:
+      access_groups: ["beta-models"]
+"#,
+        )
+        .unwrap();
+        let teams = teams::test_store();
+        let org = teams
+            .create_organization(teams::NewOrganization {
+                settings: teams::EntitySettings {
+                    models: vec!["gpt-*".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        teams
+            .create_team(teams::NewTeam {
+                team_id: Some("search".to_string()),
+                organization_id: Some(org.organization_id),
+                settings: teams::EntitySettings {
+                    models: vec!["beta-models".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+
+        let store = keys::test_store();
+        let (api_key, _) = store
+            .generate(NewKey {
+                team_id: Some("search".to_string()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let caller = Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+
+        // 团队只允许访问组 beta-models，组织只允许 gpt-*，key 自身不限制
+        assert!(check_model_access_with(&teams, &caller, &config, "gpt-4o").is_ok());
+        assert!(check_model_access_with(&teams, &caller, &config, "gpt-4o-mini").is_err());
+        assert!(check_model_access_with(&teams, &caller, &config, "claude").is_err());
+        let membership = membership_with(&teams, &caller).unwrap();
+        assert_eq!(membership.model_scopes(&caller).count(), 2);
+
+        // 只在配置文件中出现的团队没有记录，不限制模型
+        let caller = Caller::Jwt(Arc::new(JwtIdentity {
+            subject: "bob".to_string(),
+            user_id: None,
+            team_id: Some("unregistered".to_string()),
+            models: vec!["beta-models".to_string()],
+        }));
+        assert!(check_model_access_with(&teams, &caller, &config, "claude").is_ok());
+        assert!(check_model_access_with(&teams, &caller, &config, "gpt-4o-mini").is_err());
+        assert!(membership_with(&teams, &caller).unwrap().team.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_model_access_uses_current_caller() {
+        let store = keys::test_store();
+        let (api_key, _) = store
+            .generate(NewKey {
+                models: vec!["gpt-4o".to_string()],
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let caller = Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+
+        let config = Config::default();
+
+        with_caller(caller, async {
+            assert!(check_model_access(&config, "gpt-4o").is_ok());
+            assert!(matches!(
+                check_model_access(&config, "claude"),
+                Err(FeatherGateError::ModelNotAllowed(model)) if model == "claude"
+            ));
+        })
+        .await;
+
+        // 请求上下文之外不受限
+        assert!(check_model_access(&config, "claude").is_ok());
+    }
+}
@@ -0,0 +1,828 @@
+use super::cache::LookupCache;
+use crate::config::{BudgetConfig, RateLimitConfig};
+use crate::db::audit::{self, AuditAction, AuditEntry};
+use crate::db::{self, Database};
+use crate::error::FeatherGateError;
+use crate::types::ValidationError;
+use crate::Result;
+use chrono::{DateTime, SubsecRound, Utc};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 审计日志中的表名
+const TEAMS_TABLE: &str = "teams";
+const ORGANIZATIONS_TABLE: &str = "organizations";
+
+/// 团队和组织共有的设置：模型范围、预算和限额，约束其下所有的 key
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EntitySettings {
+    /// 允许使用的 model_name 或访问组，空列表表示继承上一级；以 `*` 结尾时按前缀匹配
+    pub models: Vec<String>,
+    /// 预算上限（美元）
+    pub max_budget: Option<f64>,
+    /// 预算周期（如 `30d`），到期后花费清零
+    pub budget_duration: Option<String>,
+    /// 每分钟请求数限制
+    pub rpm_limit: Option<u64>,
+    /// 每分钟 token 数限制
+    pub tpm_limit: Option<u64>,
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl EntitySettings {
+    pub fn budget(&self) -> BudgetConfig {
+        self.budget_or(BudgetConfig::default())
+    }
+
+    /// 预算，未设置的字段使用配置文件中的值
+    pub fn budget_or(&self, configured: BudgetConfig) -> BudgetConfig {
+        BudgetConfig {
+            max_budget: self.max_budget.or(configured.max_budget),
+            budget_duration: self.budget_duration.clone().or(configured.budget_duration),
+        }
+    }
+
+    pub fn rate_limits(&self) -> RateLimitConfig {
+        self.rate_limits_or(RateLimitConfig::default())
+    }
+
+    /// 限额，未设置的字段使用配置文件中的值
+    pub fn rate_limits_or(&self, configured: RateLimitConfig) -> RateLimitConfig {
+        RateLimitConfig {
+            rpm_limit: self.rpm_limit.or(configured.rpm_limit),
+            tpm_limit: self.tpm_limit.or(configured.tpm_limit),
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(EntitySettings {
+            models: db::json_column(row, "models")?,
+            max_budget: row.get("max_budget")?,
+            budget_duration: row.get("budget_duration")?,
+            rpm_limit: row.get("rpm_limit")?,
+            tpm_limit: row.get("tpm_limit")?,
+            metadata: db::json_column(row, "metadata")?,
+        })
+    }
+}
+
+/// 修改团队或组织设置的参数，None 表示不修改
+#[derive(Debug, Clone, Default)]
+pub struct SettingsUpdate {
+    pub models: Option<Vec<String>>,
+    pub max_budget: Option<f64>,
+    pub budget_duration: Option<String>,
+    pub rpm_limit: Option<u64>,
+    pub tpm_limit: Option<u64>,
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl SettingsUpdate {
+    fn apply(self, settings: &mut EntitySettings) {
+        if let Some(models) = self.models {
+            settings.models = models;
+        }
+        if let Some(max_budget) = self.max_budget {
+            settings.max_budget = Some(max_budget);
+        }
+        if let Some(budget_duration) = self.budget_duration {
+            settings.budget_duration = Some(budget_duration);
+        }
+        if let Some(rpm_limit) = self.rpm_limit {
+            settings.rpm_limit = Some(rpm_limit);
+        }
+        if let Some(tpm_limit) = self.tpm_limit {
+            settings.tpm_limit = Some(tpm_limit);
+        }
+        if let Some(metadata) = self.metadata {
+            settings.metadata = metadata;
+        }
+    }
+}
+
+/// 组织：一组团队
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Organization {
+    pub organization_id: String,
+    pub organization_alias: Option<String>,
+    #[serde(flatten)]
+    pub settings: EntitySettings,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Organization {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Organization {
+            organization_id: row.get("organization_id")?,
+            organization_alias: row.get("organization_alias")?,
+            settings: EntitySettings::from_row(row)?,
+            created_at: db::timestamp(row.get("created_at")?),
+            updated_at: db::timestamp(row.get("updated_at")?),
+        })
+    }
+}
+
+/// 团队：成员，以及 team_id 指向它的虚拟 key
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Team {
+    pub team_id: String,
+    pub team_alias: Option<String>,
+    /// 所属组织
+    pub organization_id: Option<String>,
+    #[serde(flatten)]
+    pub settings: EntitySettings,
+    pub members: Vec<TeamMember>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Team {
+    /// 是否为团队成员
+    pub fn has_member(&self, user_id: &str) -> bool {
+        self.members.iter().any(|member| member.user_id == user_id)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Team {
+            team_id: row.get("team_id")?,
+            team_alias: row.get("team_alias")?,
+            organization_id: row.get("organization_id")?,
+            settings: EntitySettings::from_row(row)?,
+            members: Vec::new(),
+            created_at: db::timestamp(row.get("created_at")?),
+            updated_at: db::timestamp(row.get("updated_at")?),
+        })
+    }
+}
+
+/// 团队成员（litellm 的 `members_with_roles`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamMember {
+    pub user_id: String,
+    #[serde(default)]
+    pub role: MemberRole,
+}
+
+/// 成员角色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberRole {
+    Admin,
+    #[default]
+    User,
+}
+
+impl MemberRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemberRole::Admin => "admin",
+            MemberRole::User => "user",
+        }
+    }
+}
+
+/// 创建组织的参数
+#[derive(Debug, Clone, Default)]
+pub struct NewOrganization {
+    /// 未指定时自动生成
+    pub organization_id: Option<String>,
+    pub organization_alias: Option<String>,
+    pub settings: EntitySettings,
+}
+
+/// 修改组织的参数，None 表示不修改
+#[derive(Debug, Clone, Default)]
+pub struct OrganizationUpdate {
+    pub organization_alias: Option<String>,
+    pub settings: SettingsUpdate,
+}
+
+/// 创建团队的参数
+#[derive(Debug, Clone, Default)]
+pub struct NewTeam {
+    /// 未指定时自动生成
+    pub team_id: Option<String>,
+    pub team_alias: Option<String>,
+    pub organization_id: Option<String>,
+    pub settings: EntitySettings,
+    pub members: Vec<TeamMember>,
+}
+
+/// 修改团队的参数，None 表示不修改
+#[derive(Debug, Clone, Default)]
+pub struct TeamUpdate {
+    pub team_alias: Option<String>,
+    pub organization_id: Option<String>,
+    pub settings: SettingsUpdate,
+}
+
+/// 团队和组织存储：SQLite 持久化，请求热路径走内存缓存
+pub struct TeamStore {
+    db: &'static Database,
+    /// team_id → 团队；未登记的 team_id（只在配置文件中设置预算的团队）也缓存为 None
+    teams: LookupCache<Team>,
+    organizations: LookupCache<Organization>,
+}
+
+impl TeamStore {
+    pub fn new(db: &'static Database) -> Self {
+        TeamStore {
+            db,
+            teams: LookupCache::default(),
+            organizations: LookupCache::default(),
+        }
+    }
+
+    /// 创建组织
+    pub fn create_organization(&self, new_org: NewOrganization, changed_by: &str) -> Result<Organization> {
+        let now = Utc::now().trunc_subsecs(0);
+        let org = Organization {
+            organization_id: new_org
+                .organization_id
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            organization_alias: new_org.organization_alias,
+            settings: new_org.settings,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.db.transaction(|tx| {
+            tx.execute(
+                "INSERT INTO organizations
+                    (organization_id, organization_alias, models, max_budget, budget_duration,
+                     rpm_limit, tpm_limit, metadata, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    org.organization_id,
+                    org.organization_alias,
+                    serde_json::to_string(&org.settings.models).unwrap_or_default(),
+                    org.settings.max_budget,
+                    org.settings.budget_duration,
+                    org.settings.rpm_limit,
+                    org.settings.tpm_limit,
+                    serde_json::to_string(&org.settings.metadata).unwrap_or_default(),
+                    org.created_at.timestamp(),
+                    org.updated_at.timestamp(),
+                ],
+            )
+            .map_err(|e| match db::is_constraint_violation(&e) {
+                true => FeatherGateError::OrganizationExists(org.organization_id.clone()),
+                false => db::db_error(e),
+            })?;
+            let entry = AuditEntry::new(changed_by, AuditAction::Created, ORGANIZATIONS_TABLE, &org.organization_id);
+            audit::record(tx, &entry.updated(&org))
+        })?;
+
+        self.invalidate_organization(&org.organization_id);
+        Ok(org)
+    }
+
+    /// 修改组织
+    pub fn update_organization(
+        &self,
+        organization_id: &str,
+        update: OrganizationUpdate,
+        changed_by: &str,
+    ) -> Result<Organization> {
+        let org = self.db.transaction(|tx| {
+            let before = select_organization(tx, organization_id)?;
+            let mut org = before.clone();
+            if let Some(alias) = update.organization_alias {
+                org.organization_alias = Some(alias);
+            }
+            update.settings.apply(&mut org.settings);
+            org.updated_at = Utc::now().trunc_subsecs(0);
+
+            tx.execute(
+                "UPDATE organizations SET
+                    organization_alias = ?1, models = ?2, max_budget = ?3, budget_duration = ?4,
+                    rpm_limit = ?5, tpm_limit = ?6, metadata = ?7, updated_at = ?8
+                 WHERE organization_id = ?9",
+                params![
+                    org.organization_alias,
+                    serde_json::to_string(&org.settings.models).unwrap_or_default(),
+                    org.settings.max_budget,
+                    org.settings.budget_duration,
+                    org.settings.rpm_limit,
+                    org.settings.tpm_limit,
+                    serde_json::to_string(&org.settings.metadata).unwrap_or_default(),
+                    org.updated_at.timestamp(),
+                    organization_id,
+                ],
+            )
+            .map_err(db::db_error)?;
+            let entry = AuditEntry::new(changed_by, AuditAction::Updated, ORGANIZATIONS_TABLE, organization_id);
+            audit::record(tx, &entry.before(&before).updated(&org))?;
+            Ok(org)
+        })?;
+
+        self.invalidate_organization(organization_id);
+        Ok(org)
+    }
+
+    /// 删除组织（任何一个不存在或还有团队时都不删除），返回被删除的记录
+    pub fn delete_organizations(&self, organization_ids: &[String], changed_by: &str) -> Result<Vec<Organization>> {
+        let deleted = self.db.transaction(|tx| {
+            let mut deleted = Vec::with_capacity(organization_ids.len());
+            for organization_id in organization_ids {
+                let org = select_organization(tx, organization_id)?;
+                let teams = count(tx, "SELECT COUNT(*) FROM teams WHERE organization_id = ?1", organization_id)?;
+                if teams > 0 {
+                    return Err(FeatherGateError::OrganizationInUse(organization_id.clone(), teams));
+                }
+                tx.execute("DELETE FROM organizations WHERE organization_id = ?1", [organization_id])
+                    .map_err(db::db_error)?;
+                let entry = AuditEntry::new(changed_by, AuditAction::Deleted, ORGANIZATIONS_TABLE, organization_id);
+                audit::record(tx, &entry.before(&org))?;
+                deleted.push(org);
+            }
+            Ok(deleted)
+        })?;
+
+        for org in &deleted {
+            self.invalidate_organization(&org.organization_id);
+        }
+        Ok(deleted)
+    }
+
+    /// 按 organization_id 从数据库读取组织
+    pub fn get_organization(&self, organization_id: &str) -> Result<Option<Organization>> {
+        self.db.with_conn(|conn| {
+            conn.query_row(
+                "SELECT * FROM organizations WHERE organization_id = ?1",
+                [organization_id],
+                Organization::from_row,
+            )
+            .optional()
+        })
+    }
+
+    /// 列出所有组织（按创建时间排序）
+    pub fn list_organizations(&self) -> Result<Vec<Organization>> {
+        self.db.with_conn(|conn| {
+            conn.prepare("SELECT * FROM organizations ORDER BY created_at, organization_id")?
+                .query_map([], Organization::from_row)?
+                .collect()
+        })
+    }
+
+    /// 按 organization_id 查找组织（请求热路径使用，优先读缓存）
+    pub fn lookup_organization(&self, organization_id: &str) -> Result<Option<Arc<Organization>>> {
+        self.organizations
+            .get_or_load(organization_id, || self.get_organization(organization_id))
+    }
+
+    /// 创建团队（指定的组织必须存在）
+    pub fn create_team(&self, new_team: NewTeam, changed_by: &str) -> Result<Team> {
+        let now = Utc::now().trunc_subsecs(0);
+        let team = Team {
+            team_id: new_team.team_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            team_alias: new_team.team_alias,
+            organization_id: new_team.organization_id,
+            settings: new_team.settings,
+            members: dedup_members(new_team.members),
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.db.transaction(|tx| {
+            if let Some(organization_id) = &team.organization_id {
+                select_organization(tx, organization_id)?;
+            }
+            tx.execute(
+                "INSERT INTO teams
+                    (team_id, team_alias, organization_id, models, max_budget, budget_duration,
+                     rpm_limit, tpm_limit, metadata, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    team.team_id,
+                    team.team_alias,
+                    team.organization_id,
+                    serde_json::to_string(&team.settings.models).unwrap_or_default(),
+                    team.settings.max_budget,
+                    team.settings.budget_duration,
+                    team.settings.rpm_limit,
+                    team.settings.tpm_limit,
+                    serde_json::to_string(&team.settings.metadata).unwrap_or_default(),
+                    team.created_at.timestamp(),
+                    team.updated_at.timestamp(),
+                ],
+            )
+            .map_err(|e| match db::is_constraint_violation(&e) {
+                true => FeatherGateError::TeamExists(team.team_id.clone()),
+                false => db::db_error(e),
+            })?;
+            write_members(tx, &team)?;
+            let entry = AuditEntry::new(changed_by, AuditAction::Created, TEAMS_TABLE, &team.team_id);
+            audit::record(tx, &entry.updated(&team))
+        })?;
+
+        self.invalidate_team(&team.team_id);
+        Ok(team)
+    }
+
+    /// 修改团队
+    pub fn update_team(&self, team_id: &str, update: TeamUpdate, changed_by: &str) -> Result<Team> {
+        self.modify_team(team_id, changed_by, |tx, team| {
+            if let Some(organization_id) = update.organization_id {
+                select_organization(tx, &organization_id)?;
+                team.organization_id = Some(organization_id);
+            }
+            if let Some(team_alias) = update.team_alias {
+                team.team_alias = Some(team_alias);
+            }
+            update.settings.apply(&mut team.settings);
+            Ok(())
+        })
+    }
+
+    /// 添加成员；已是成员时更新角色
+    pub fn add_members(&self, team_id: &str, members: Vec<TeamMember>, changed_by: &str) -> Result<Team> {
+        self.modify_team(team_id, changed_by, |_, team| {
+            for member in members {
+                match team.members.iter_mut().find(|m| m.user_id == member.user_id) {
+                    Some(existing) => existing.role = member.role,
+                    None => team.members.push(member),
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// 移除成员（不存在的成员忽略）
+    pub fn remove_members(&self, team_id: &str, user_ids: &[String], changed_by: &str) -> Result<Team> {
+        self.modify_team(team_id, changed_by, |_, team| {
+            team.members.retain(|member| !user_ids.contains(&member.user_id));
+            Ok(())
+        })
+    }
+
+    /// 在事务中读取、修改并写回团队，记录审计日志
+    fn modify_team(
+        &self,
+        team_id: &str,
+        changed_by: &str,
+        modify: impl FnOnce(&Connection, &mut Team) -> Result<()>,
+    ) -> Result<Team> {
+        let team = self.db.transaction(|tx| {
+            let before = select_team(tx, team_id)?;
+            let mut team = before.clone();
+            modify(tx, &mut team)?;
+            team.updated_at = Utc::now().trunc_subsecs(0);
+
+            tx.execute(
+                "UPDATE teams SET
+                    team_alias = ?1, organization_id = ?2, models = ?3, max_budget = ?4,
+                    budget_duration = ?5, rpm_limit = ?6, tpm_limit = ?7, metadata = ?8, updated_at = ?9
+                 WHERE team_id = ?10",
+                params![
+                    team.team_alias,
+                    team.organization_id,
+                    serde_json::to_string(&team.settings.models).unwrap_or_default(),
+                    team.settings.max_budget,
+                    team.settings.budget_duration,
+                    team.settings.rpm_limit,
+                    team.settings.tpm_limit,
+                    serde_json::to_string(&team.settings.metadata).unwrap_or_default(),
+                    team.updated_at.timestamp(),
+                    team_id,
+                ],
+            )
+            .map_err(db::db_error)?;
+            write_members(tx, &team)?;
+            let entry = AuditEntry::new(changed_by, AuditAction::Updated, TEAMS_TABLE, team_id);
+            audit::record(tx, &entry.before(&before).updated(&team))?;
+            Ok(team)
+        })?;
+
+        self.invalidate_team(team_id);
+        Ok(team)
+    }
+
+    /// 删除团队（任何一个不存在或还有虚拟 key 时都不删除），返回被删除的记录
+    pub fn delete_teams(&self, team_ids: &[String], changed_by: &str) -> Result<Vec<Team>> {
+        let deleted = self.db.transaction(|tx| {
+            let mut deleted = Vec::with_capacity(team_ids.len());
+            for team_id in team_ids {
+                let team = select_team(tx, team_id)?;
+                let keys = count(tx, "SELECT COUNT(*) FROM virtual_keys WHERE team_id = ?1", team_id)?;
+                if keys > 0 {
+                    return Err(FeatherGateError::TeamInUse(team_id.clone(), keys));
+                }
+                tx.execute("DELETE FROM team_members WHERE team_id = ?1", [team_id])
+                    .map_err(db::db_error)?;
+                tx.execute("DELETE FROM teams WHERE team_id = ?1", [team_id])
+                    .map_err(db::db_error)?;
+                let entry = AuditEntry::new(changed_by, AuditAction::Deleted, TEAMS_TABLE, team_id);
+                audit::record(tx, &entry.before(&team))?;
+                deleted.push(team);
+            }
+            Ok(deleted)
+        })?;
+
+        for team in &deleted {
+            self.invalidate_team(&team.team_id);
+        }
+        Ok(deleted)
+    }
+
+    /// 按 team_id 从数据库读取团队
+    pub fn get_team(&self, team_id: &str) -> Result<Option<Team>> {
+        self.db.with_conn(|conn| {
+            let Some(mut team) = conn
+                .query_row("SELECT * FROM teams WHERE team_id = ?1", [team_id], Team::from_row)
+                .optional()?
+            else {
+                return Ok(None);
+            };
+            team.members = read_members(conn, team_id)?;
+            Ok(Some(team))
+        })
+    }
+
+    /// 列出团队（按创建时间排序），可以按组织过滤
+    pub fn list_teams(&self, organization_id: Option<&str>) -> Result<Vec<Team>> {
+        self.db.with_conn(|conn| {
+            let mut teams = conn
+                .prepare(
+                    "SELECT * FROM teams WHERE ?1 IS NULL OR organization_id = ?1 ORDER BY created_at, team_id",
+                )?
+                .query_map([organization_id], Team::from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for team in &mut teams {
+                team.members = read_members(conn, &team.team_id)?;
+            }
+            Ok(teams)
+        })
+    }
+
+    /// 按 team_id 查找团队（请求热路径使用，优先读缓存）
+    pub fn lookup_team(&self, team_id: &str) -> Result<Option<Arc<Team>>> {
+        self.teams.get_or_load(team_id, || self.get_team(team_id))
+    }
+
+    /// 某个团队或组织的审计日志
+    pub fn audit_log(&self, object_id: &str) -> Result<Vec<AuditEntry>> {
+        audit::entries(self.db, object_id)
+    }
+
+    fn invalidate_team(&self, team_id: &str) {
+        self.teams.remove(team_id);
+    }
+
+    fn invalidate_organization(&self, organization_id: &str) {
+        self.organizations.remove(organization_id);
+    }
+}
+
+/// 同一用户出现多次时保留最后一次的角色
+fn dedup_members(members: Vec<TeamMember>) -> Vec<TeamMember> {
+    let mut deduped: Vec<TeamMember> = Vec::with_capacity(members.len());
+    for member in members {
+        match deduped.iter_mut().find(|m| m.user_id == member.user_id) {
+            Some(existing) => existing.role = member.role,
+            None => deduped.push(member),
+        }
+    }
+    deduped
+}
+
+/// 事务内按 organization_id 读取组织，不存在时返回 OrganizationNotFound
+fn select_organization(conn: &Connection, organization_id: &str) -> Result<Organization> {
+    conn.query_row(
+        "SELECT * FROM organizations WHERE organization_id = ?1",
+        [organization_id],
+        Organization::from_row,
+    )
+    .optional()
+    .map_err(db::db_error)?
+    .ok_or_else(|| FeatherGateError::OrganizationNotFound(organization_id.to_string()))
+}
+
+/// 事务内按 team_id 读取团队，不存在时返回 TeamNotFound
+fn select_team(conn: &Connection, team_id: &str) -> Result<Team> {
+    let mut team = conn
+        .query_row("SELECT * FROM teams WHERE team_id = ?1", [team_id], Team::from_row)
+        .optional()
+        .map_err(db::db_error)?
+        .ok_or_else(|| FeatherGateError::TeamNotFound(team_id.to_string()))?;
+    team.members = read_members(conn, team_id).map_err(db::db_error)?;
+    Ok(team)
+}
+
+/// key 指定了已登记的团队时，key 的 user_id 必须是该团队的成员；未登记的 team_id（只在配置文件中设置预算的团队）不检查
+pub(crate) fn check_key_membership(conn: &Connection, user_id: Option<&str>, team_id: Option<&str>) -> Result<()> {
+    let Some(team_id) = team_id else {
+        return Ok(());
+    };
+    let team = match select_team(conn, team_id) {
+        Ok(team) => team,
+        Err(FeatherGateError::TeamNotFound(_)) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    match user_id {
+        Some(user_id) if team.has_member(user_id) => Ok(()),
+        Some(user_id) => Err(FeatherGateError::NotTeamMember(user_id.to_string(), team_id.to_string())),
+        None => Err(ValidationError::MissingField("user_id").into()),
+    }
+}
+
+fn read_members(conn: &Connection, team_id: &str) -> rusqlite::Result<Vec<TeamMember>> {
+    conn.prepare("SELECT user_id, role FROM team_members WHERE team_id = ?1 ORDER BY rowid")?
+        .query_map([team_id], |row| {
+            let role: String = row.get(1)?;
+            Ok(TeamMember {
+                user_id: row.get(0)?,
+                role: match role.as_str() {
+                    "admin" => MemberRole::Admin,
+                    _ => MemberRole::User,
+                },
+            })
+        })?
+        .collect()
+}
+
+/// 用团队的成员列表覆盖数据库中的成员
+fn write_members(conn: &Connection, team: &Team) -> Result<()> {
+    conn.execute("DELETE FROM team_members WHERE team_id = ?1", [&team.team_id])
+        .map_err(db::db_error)?;
+    for member in &team.members {
+        conn.execute(
+            "INSERT INTO team_members (team_id, user_id, role) VALUES (?1, ?2, ?3)",
+            params![team.team_id, member.user_id, member.role.as_str()],
+        )
+        .map_err(db::db_error)?;
+    }
+    Ok(())
+}
+
+fn count(conn: &Connection, sql: &str, id: &str) -> Result<u64> {
+    conn.query_row(sql, [id], |row| row.get(0)).map_err(db::db_error)
+}
+
+/// 全局团队和组织存储
+pub fn team_store() -> &'static TeamStore {
+    static STORE: Lazy<TeamStore> = Lazy::new(|| TeamStore::new(db::global()));
+    &STORE
+}
+
+#[cfg(test)]
+pub(crate) fn test_store() -> TeamStore {
+    TeamStore::new(Box::leak(Box::new(Database::open_in_memory().unwrap())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(user_id: &str, role: MemberRole) -> TeamMember {
+        TeamMember {
+            user_id: user_id.to_string(),
+            role,
+        }
+    }
+
+    #[test]
+    fn test_team_lifecycle_and_members() {
+        let store = test_store();
+        let org = store
+            .create_organization(NewOrganization {
+                organization_alias: Some("research".to_string()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+
+        // 组织必须存在
+        let missing_org = NewTeam {
+            organization_id: Some("missing".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            store.create_team(missing_org, "master_key"),
+            Err(FeatherGateError::OrganizationNotFound(id)) if id == "missing"
+        ));
+
+        let team = store
+            .create_team(NewTeam {
+                team_id: Some("search".to_string()),
+                organization_id: Some(org.organization_id.clone()),
+                settings: EntitySettings {
+                    models: vec!["gpt-4o".to_string()],
+                    ..Default::default()
+                },
+                members: vec![member("alice", MemberRole::Admin)],
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        assert_eq!(store.lookup_team("search").unwrap().unwrap().members, team.members);
+        assert!(matches!(
+            store.create_team(NewTeam {
+                team_id: Some("search".to_string()),
+                ..Default::default()
+            }, "master_key"),
+            Err(FeatherGateError::TeamExists(_))
+        ));
+
+        // 修改后缓存失效
+        let team = store
+            .add_members("search", vec![member("bob", MemberRole::User), member("alice", MemberRole::User)], "alice")
+            .unwrap();
+        assert_eq!(team.members, vec![member("alice", MemberRole::User), member("bob", MemberRole::User)]);
+        assert!(store.lookup_team("search").unwrap().unwrap().has_member("bob"));
+
+        let team = store.remove_members("search", &["alice".to_string()], "alice").unwrap();
+        assert_eq!(team.members, vec![member("bob", MemberRole::User)]);
+
+        let update = TeamUpdate {
+            settings: SettingsUpdate {
+                max_budget: Some(10.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let team = store.update_team("search", update, "alice").unwrap();
+        assert_eq!(team.settings.max_budget, Some(10.0));
+        assert_eq!(team.settings.models, vec!["gpt-4o"]);
+        assert_eq!(store.list_teams(Some(&org.organization_id)).unwrap().len(), 1);
+
+        // 还有团队的组织不能删除
+        assert!(matches!(
+            store.delete_organizations(std::slice::from_ref(&org.organization_id), "alice"),
+            Err(FeatherGateError::OrganizationInUse(_, 1))
+        ));
+        store.delete_teams(&["search".to_string()], "alice").unwrap();
+        assert!(store.lookup_team("search").unwrap().is_none());
+        store.delete_organizations(std::slice::from_ref(&org.organization_id), "alice").unwrap();
+
+        let actions: Vec<_> = store
+            .audit_log("search")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.action)
+            .collect();
+        assert_eq!(actions, ["created", "updated", "updated", "updated", "deleted"]);
+    }
+
+    #[test]
+    fn test_team_with_keys_cannot_be_deleted() {
+        let store = test_store();
+        store
+            .create_team(NewTeam {
+                team_id: Some("ads".to_string()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        store
+            .db
+            .with_conn(|conn| {
+                conn.execute(
+                    "INSERT INTO virtual_keys (token, key_name, team_id, created_at, updated_at)
+                     VALUES ('t', 'n', 'ads', 0, 0)",
+                    [],
+                )
+            })
+            .unwrap();
+
+        assert!(matches!(
+            store.delete_teams(&["ads".to_string()], "master_key"),
+            Err(FeatherGateError::TeamInUse(_, 1))
+        ));
+        // 未登记的 team_id 查不到记录
+        assert!(store.lookup_team("unregistered").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cached_teams_expire() {
+        let mut store = test_store();
+        assert!(store.lookup_team("ads").unwrap().is_none());
+        assert!(store.lookup_organization("marketing").unwrap().is_none());
+
+        // 其他副本登记了团队和组织：缓存过期前不可见，过期后重新读取
+        let other = TeamStore::new(store.db);
+        other
+            .create_organization(NewOrganization {
+                organization_id: Some("marketing".to_string()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        other
+            .create_team(NewTeam {
+                team_id: Some("ads".to_string()),
+                organization_id: Some("marketing".to_string()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        assert!(store.lookup_team("ads").unwrap().is_none());
+        assert!(store.lookup_organization("marketing").unwrap().is_none());
+
+        store.teams.ttl = std::time::Duration::ZERO;
+        store.organizations.ttl = std::time::Duration::ZERO;
+        assert!(store.lookup_team("ads").unwrap().is_some());
+        assert!(store.lookup_organization("marketing").unwrap().is_some());
+        assert_eq!(store.teams.negative(), 0);
+        assert_eq!(store.organizations.negative(), 0);
+    }
+}
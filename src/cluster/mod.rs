@@ -0,0 +1,121 @@
+use crate::config::GeneralSettings;
+use crate::error::FeatherGateError;
+use crate::providers::cooldown;
+use crate::ratelimit;
+use crate::spend;
+use crate::Result;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 连接和单次请求的超时，Redis 不可用时尽快回退到本地计数
+const REDIS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 多副本共享状态：定期把本地的 RPM/TPM 消耗、花费和部署冷却批量同步到 Redis
+///
+/// 请求路径只读写本地状态，不等待 Redis；Redis 不可用时各副本按本地计数继续工作，
+/// 恢复后补报期间的消耗。
+pub struct RedisSync {
+    client: redis::Client,
+    conn: Option<ConnectionManager>,
+    healthy: bool,
+}
+
+impl RedisSync {
+    pub fn new(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url)
+            .map_err(|e| FeatherGateError::config(format!("redis_url 无效: {}", e)))?;
+        Ok(RedisSync {
+            client,
+            conn: None,
+            healthy: true,
+        })
+    }
+
+    /// 同步一次；失败时记录日志（只在状态变化时），本地计数不受影响
+    pub async fn sync(&mut self) -> bool {
+        match self.try_sync().await {
+            Ok(()) => {
+                if !self.healthy {
+                    info!("Redis 已恢复，继续同步共享计数");
+                }
+                self.healthy = true;
+            }
+            Err(e) => {
+                if self.healthy {
+                    warn!("Redis 不可用，暂时只使用本地计数: {}", e);
+                }
+                self.healthy = false;
+            }
+        }
+        self.healthy
+    }
+
+    async fn try_sync(&mut self) -> redis::RedisResult<()> {
+        let conn = match &mut self.conn {
+            Some(conn) => conn,
+            None => {
+                let config = ConnectionManagerConfig::new()
+                    .set_connection_timeout(REDIS_TIMEOUT)
+                    .set_response_timeout(REDIS_TIMEOUT)
+                    .set_number_of_retries(1);
+                let conn = ConnectionManager::new_with_config(self.client.clone(), config).await?;
+                self.conn.insert(conn)
+            }
+        };
+
+        ratelimit::limiter().sync(conn).await?;
+        spend::tracker().sync(conn).await?;
+        cooldown::cooldowns().sync(conn).await
+    }
+}
+
+/// 配置了 `redis_url` 时启动后台同步任务
+pub fn start(settings: &GeneralSettings) -> Result<()> {
+    let Some(url) = settings.redis_url.as_deref() else {
+        return Ok(());
+    };
+    let mut redis_sync = RedisSync::new(url)?;
+    let interval = Duration::from_millis(settings.redis_sync_interval_ms);
+    info!("启用 Redis 共享计数，同步间隔 {}ms", interval.as_millis());
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            redis_sync.sync().await;
+        }
+    });
+    Ok(())
+}
+
+/// 测试用的 Redis 连接：未设置 `REDIS_URL` 或无法连接时返回 None，相关测试跳过
+#[cfg(test)]
+pub(crate) async fn test_connection() -> Option<ConnectionManager> {
+    let url = std::env::var("REDIS_URL").ok()?;
+    let client = redis::Client::open(url).ok()?;
+    let config = ConnectionManagerConfig::new()
+        .set_connection_timeout(REDIS_TIMEOUT)
+        .set_number_of_retries(0);
+    ConnectionManager::new_with_config(client, config).await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sync_degrades_when_redis_unavailable() {
+        // 没有服务监听的端口
+        let mut redis_sync = RedisSync::new("redis://127.0.0.1:1/").unwrap();
+        assert!(!redis_sync.sync().await);
+        assert!(redis_sync.conn.is_none());
+        assert!(!redis_sync.sync().await);
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        assert!(RedisSync::new("http://localhost").is_err());
+    }
+}
@@ -1,68 +1,504 @@
 use crate::error::FeatherGateError;
+use crate::i18n::Locale;
+use crate::types::keys::parse_duration;
 use crate::Result;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+mod model_defaults;
+mod secrets;
+
 /// 主配置结构
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Config {
     pub model_list: Vec<ModelConfig>,
+    #[serde(default)]
+    pub router_settings: RouterSettings,
+    #[serde(default)]
+    pub general_settings: GeneralSettings,
 }
 
-/// 模型配置
+/// 通用设置（兼容 litellm 的 general_settings）
 #[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GeneralSettings {
+    /// API 错误消息语言
+    #[serde(default)]
+    pub api_locale: Locale,
+    /// 日志中错误消息的语言
+    #[serde(default = "default_log_locale")]
+    pub log_locale: Locale,
+    /// 内容审核使用的模型（model_name），未配置时使用第一个 mode 为 moderation 的模型
+    #[serde(default)]
+    pub moderation_model: Option<String>,
+    /// 调用网关需要提供的 key（Bearer 或 x-api-key），未配置时不做认证
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub master_key: Option<String>,
+    /// 不需要认证的路由，支持以 `/*` 结尾的前缀匹配
+    #[serde(default = "default_public_routes")]
+    pub public_routes: Vec<String>,
+    /// 接受 OIDC 签发的 JWT 作为调用方凭据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_auth: Option<JwtAuthConfig>,
+    /// SQLite 数据库文件（虚拟 key 等），未配置时只保存在内存中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_path: Option<String>,
+    /// 用户预算（按虚拟 key 的 user_id 和请求中的 `user` 字段）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub user_budgets: HashMap<String, BudgetConfig>,
+    /// 团队预算（按虚拟 key 的 team_id）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub team_budgets: HashMap<String, BudgetConfig>,
+    /// 用户的 RPM/TPM 限制（按虚拟 key 的 user_id 和请求中的 `user` 字段）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub user_rate_limits: HashMap<String, RateLimitConfig>,
+    /// 团队的 RPM/TPM 限制（按虚拟 key 的 team_id）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub team_rate_limits: HashMap<String, RateLimitConfig>,
+    /// 多副本共享 RPM/TPM、花费和部署冷却状态的 Redis（如 `redis://:password@host:6379/0`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redis_url: Option<String>,
+    /// 与 Redis 批量同步的间隔（毫秒）
+    #[serde(default = "default_redis_sync_interval_ms")]
+    pub redis_sync_interval_ms: u64,
+    /// 解析 `vault://` 密钥引用使用的 Vault（KV v2）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<VaultConfig>,
+    /// 重新读取配置文件、刷新各部署上游 key（`api_key` / `api_keys`）的间隔（秒），
+    /// 未配置时只在收到 SIGHUP 时刷新；其他配置和密钥引用只在启动时解析
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_refresh_interval: Option<u64>,
+}
+
+/// HashiCorp Vault 连接设置，未配置的字段使用 `VAULT_ADDR`、`VAULT_TOKEN`、`VAULT_NAMESPACE` 环境变量
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct VaultConfig {
+    /// Vault 地址（如 `https://vault.example.com:8200`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Vault Enterprise 命名空间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// JWT 认证：校验签发方、受众和签名（RS256 / ES256），并把声明映射为用户、团队和可用模型
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct JwtAuthConfig {
+    /// 签发方（`iss`）
+    pub issuer: String,
+    /// 受众（`aud`）
+    pub audience: String,
+    /// 本地 JWKS 文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_file: Option<String>,
+    /// JWKS 地址（如 `https://idp.example.com/.well-known/jwks.json`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwks_url: Option<String>,
+    /// JWKS 缓存时间（秒），过期后重新加载
+    #[serde(default = "default_jwks_cache_ttl")]
+    pub jwks_cache_ttl: u64,
+    /// 允许的时钟偏差（秒），用于 `exp` 和 `nbf`
+    #[serde(default = "default_jwt_leeway")]
+    pub leeway: u64,
+    /// 映射为 user_id 的声明，支持 `a.b` 形式的嵌套路径
+    #[serde(default = "default_user_id_claim")]
+    pub user_id_claim: String,
+    /// 映射为 team_id 的声明
+    #[serde(default = "default_team_id_claim")]
+    pub team_id_claim: String,
+    /// 可用模型列表的声明（字符串数组或空格分隔的字符串），缺省时不限制模型
+    #[serde(default = "default_models_claim")]
+    pub models_claim: String,
+}
+
+fn default_jwks_cache_ttl() -> u64 {
+    300
+}
+
+fn default_jwt_leeway() -> u64 {
+    60
+}
+
+fn default_user_id_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_team_id_claim() -> String {
+    "team_id".to_string()
+}
+
+fn default_models_claim() -> String {
+    "models".to_string()
+}
+
+/// 预算上限
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct BudgetConfig {
+    /// 预算上限（美元）
+    pub max_budget: Option<f64>,
+    /// 预算周期（如 `1d`、`7d`、`1mo`），到期后花费清零；未配置时不重置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_duration: Option<String>,
+}
+
+/// 每分钟请求数和 token 数限制
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct RateLimitConfig {
+    /// 每分钟请求数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm_limit: Option<u64>,
+    /// 每分钟 token 数（输入加输出）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm_limit: Option<u64>,
+}
+
+impl Default for GeneralSettings {
+    fn default() -> Self {
+        Self {
+            api_locale: Locale::En,
+            log_locale: default_log_locale(),
+            moderation_model: None,
+            master_key: None,
+            public_routes: default_public_routes(),
+            jwt_auth: None,
+            database_path: None,
+            user_budgets: HashMap::new(),
+            team_budgets: HashMap::new(),
+            user_rate_limits: HashMap::new(),
+            team_rate_limits: HashMap::new(),
+            redis_url: None,
+            redis_sync_interval_ms: default_redis_sync_interval_ms(),
+            vault: None,
+            api_key_refresh_interval: None,
+        }
+    }
+}
+
+fn default_log_locale() -> Locale {
+    Locale::Zh
+}
+
+fn default_public_routes() -> Vec<String> {
+    vec!["/health".to_string()]
+}
+
+fn default_redis_sync_interval_ms() -> u64 {
+    1000
+}
+
+/// 路由设置（兼容 litellm 的 router_settings）
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RouterSettings {
+    /// 可重试错误（限流、过载、5xx、超时）的重试次数
+    #[serde(default)]
+    pub num_retries: u32,
+    /// 通用回退：[{"gpt-4": ["claude"]}]
+    #[serde(default)]
+    pub fallbacks: Vec<HashMap<String, Vec<String>>>,
+    /// 上下文窗口超限时的回退
+    #[serde(default)]
+    pub context_window_fallbacks: Vec<HashMap<String, Vec<String>>>,
+    /// 触发内容策略时的回退
+    #[serde(default)]
+    pub content_policy_fallbacks: Vec<HashMap<String, Vec<String>>>,
+    /// 部署冷却时间（秒），未配置时不冷却
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_time: Option<f64>,
+    /// 一分钟内允许的失败次数，超过后进入冷却
+    #[serde(default)]
+    pub allowed_fails: u32,
+}
+
+impl RouterSettings {
+    /// 查找某个模型在回退列表中的候选模型
+    pub fn lookup<'a>(
+        fallbacks: &'a [HashMap<String, Vec<String>>],
+        model_name: &'a str,
+    ) -> impl Iterator<Item = &'a String> {
+        fallbacks
+            .iter()
+            .filter_map(move |entry| entry.get(model_name))
+            .flatten()
+    }
+}
+
+/// 模型配置
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct ModelConfig {
     pub model_name: String,
     pub litellm_params: LitellmParams,
+    #[serde(default)]
+    pub model_info: ModelInfo,
+    /// 转发前先用审核模型检查用户输入
+    #[serde(default)]
+    pub moderation: bool,
+}
+
+/// 模型元数据（兼容 litellm 的 model_info）
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ModelInfo {
+    /// 模型类型，未声明时不限制请求类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ModelMode>,
+    /// 最大输入 token 数（上下文窗口）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_input_tokens: Option<u32>,
+    /// 单次请求最多生成的 token 数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    /// 每个输入 token 的成本（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cost_per_token: Option<f64>,
+    /// 每个输出 token 的成本（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_cost_per_token: Option<f64>,
+    /// 每张生成图像的成本（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_cost_per_image: Option<f64>,
+    /// 重排序每个 search unit 的成本（美元），上游未报告 search unit 时按每次请求 1 个计
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cost_per_query: Option<f64>,
+    /// 文本转语音每个输入字符的成本（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cost_per_character: Option<f64>,
+    /// 语音转文本每秒音频的成本（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cost_per_second: Option<f64>,
+    /// 每次请求的固定成本（美元），与按用量计算的成本累加
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cost_per_request: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_vision: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_function_calling: Option<bool>,
+    /// 模型所属的访问组；虚拟 key、团队和组织的 models 中可以用组名代替逐个列出模型
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub access_groups: Vec<String>,
+}
+
+impl ModelInfo {
+    /// 用另一份元数据补全未声明的字段
+    fn or(self, defaults: ModelInfo) -> ModelInfo {
+        ModelInfo {
+            mode: self.mode.or(defaults.mode),
+            max_input_tokens: self.max_input_tokens.or(defaults.max_input_tokens),
+            max_output_tokens: self.max_output_tokens.or(defaults.max_output_tokens),
+            input_cost_per_token: self.input_cost_per_token.or(defaults.input_cost_per_token),
+            output_cost_per_token: self.output_cost_per_token.or(defaults.output_cost_per_token),
+            output_cost_per_image: self.output_cost_per_image.or(defaults.output_cost_per_image),
+            input_cost_per_query: self.input_cost_per_query.or(defaults.input_cost_per_query),
+            input_cost_per_character: self.input_cost_per_character.or(defaults.input_cost_per_character),
+            input_cost_per_second: self.input_cost_per_second.or(defaults.input_cost_per_second),
+            input_cost_per_request: self.input_cost_per_request.or(defaults.input_cost_per_request),
+            supports_vision: self.supports_vision.or(defaults.supports_vision),
+            supports_function_calling: self
+                .supports_function_calling
+                .or(defaults.supports_function_calling),
+            access_groups: self.access_groups,
+        }
+    }
+}
+
+/// 模型类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelMode {
+    Chat,
+    Completion,
+    Embedding,
+    ImageGeneration,
+    AudioTranscription,
+    AudioSpeech,
+    Moderation,
+    Rerank,
+}
+
+impl ModelMode {
+    /// 配置中使用的名称
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModelMode::Chat => "chat",
+            ModelMode::Completion => "completion",
+            ModelMode::Embedding => "embedding",
+            ModelMode::ImageGeneration => "image_generation",
+            ModelMode::AudioTranscription => "audio_transcription",
+            ModelMode::AudioSpeech => "audio_speech",
+            ModelMode::Moderation => "moderation",
+            ModelMode::Rerank => "rerank",
+        }
+    }
+}
+
+impl ModelConfig {
+    /// 是否可以处理指定类型的请求
+    pub fn supports(&self, modes: &[ModelMode]) -> bool {
+        self.model_info.mode.is_none_or(|mode| modes.contains(&mode))
+    }
+
+    /// 对外展示的模型元数据：配置中声明的字段优先，其余使用内置默认值
+    ///
+    /// 内置默认值只用于展示和计费，不影响 `supports` 的请求类型检查。
+    pub fn resolved_model_info(&self) -> ModelInfo {
+        let defaults = parse_model_string(&self.litellm_params.model)
+            .ok()
+            .and_then(|(_, model_id)| model_defaults::lookup(&model_id))
+            .unwrap_or_default();
+        self.model_info.clone().or(defaults)
+    }
 }
 
 /// Litellm 参数（兼容 litellm 格式）
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct LitellmParams {
     pub model: String, // 格式: provider/model-id
+    #[serde(default)]
     pub api_key: String,
+    /// 更多上游 key，与 api_key 一起组成 key 池
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    /// 多个 key 时的选择策略
+    #[serde(default)]
+    pub api_key_strategy: ApiKeyStrategy,
+    /// key 认证失败或额度耗尽后停用的秒数（默认 60，最多 MAX_API_KEY_DISABLE_TIME）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_disable_time: Option<f64>,
     #[serde(default = "default_api_base")]
     pub api_base: String,
+    /// 该部署每分钟最多转发的请求数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm: Option<u64>,
+    /// 该部署每分钟最多消耗的 token 数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm: Option<u64>,
 }
 
+/// `api_key_disable_time` 的上限（一天）
+pub const MAX_API_KEY_DISABLE_TIME: f64 = 86_400.0;
+
 fn default_api_base() -> String {
     String::new()
 }
 
+impl LitellmParams {
+    /// key 池：api_key 在前，api_keys 按配置顺序，去掉空值和重复项
+    pub fn key_pool(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::with_capacity(self.api_keys.len() + 1);
+        for key in std::iter::once(&self.api_key).chain(&self.api_keys) {
+            if !key.is_empty() && !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        keys
+    }
+}
+
+/// 部署有多个上游 key 时的选择策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyStrategy {
+    /// 依次轮流使用
+    #[default]
+    RoundRobin,
+    /// 使用最近一分钟请求数最少的 key
+    LeastUsage,
+}
+
 impl Config {
-    /// 从 YAML 文件加载配置
+    /// 从 YAML 文件加载配置，并解析其中的环境变量、密钥文件和 Vault 引用
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
-        let content = Self::replace_env_vars(&content)?;
-        let config: Config = serde_yaml::from_str(&content)?;
+        let content = secrets::expand_env_vars(&content)?;
+        let mut value: serde_yaml::Value = serde_yaml::from_str(&content)?;
+        secrets::resolve(&mut value)?;
+        let config: Config = serde_yaml::from_value(value)?;
         config.validate()?;
         Ok(config)
     }
 
-    /// 替换配置中的环境变量 ${VAR}
-    fn replace_env_vars(content: &str) -> Result<String> {
-        let re = Regex::new(r"\$\{([A-Z_][A-Z0-9_]*)\}").unwrap();
-        let mut result = content.to_string();
-
-        for cap in re.captures_iter(content) {
-            let var_name = &cap[1];
-            let var_value = std::env::var(var_name).map_err(|_| {
-                FeatherGateError::config(format!("环境变量未找到: {}", var_name))
-            })?;
-            result = result.replace(&cap[0], &var_value);
-        }
-
-        Ok(result)
-    }
-
     /// 验证配置
     fn validate(&self) -> Result<()> {
         if self.model_list.is_empty() {
             return Err(FeatherGateError::config("model_list 不能为空"));
         }
 
+        let settings = &self.router_settings;
+        for fallbacks in [
+            &settings.fallbacks,
+            &settings.context_window_fallbacks,
+            &settings.content_policy_fallbacks,
+        ] {
+            for target in fallbacks.iter().flat_map(|entry| entry.values()).flatten() {
+                if self.find_model(target).is_none() {
+                    return Err(FeatherGateError::config(format!(
+                        "回退模型未在 model_list 中定义: {}",
+                        target
+                    )));
+                }
+            }
+        }
+
+        if let Some(name) = &self.general_settings.moderation_model {
+            if self.find_model(name).is_none() {
+                return Err(FeatherGateError::config(format!(
+                    "moderation_model 未在 model_list 中定义: {}",
+                    name
+                )));
+            }
+        }
+        if self.model_list.iter().any(|m| m.moderation) && self.moderation_model().is_none() {
+            return Err(FeatherGateError::config(
+                "开启 moderation 的模型需要配置 moderation_model 或 mode 为 moderation 的模型",
+            ));
+        }
+
+        if self.general_settings.master_key.as_deref() == Some("") {
+            return Err(FeatherGateError::config("master_key 不能为空"));
+        }
+        if let Some(jwt_auth) = &self.general_settings.jwt_auth {
+            if jwt_auth.issuer.is_empty() || jwt_auth.audience.is_empty() {
+                return Err(FeatherGateError::config("jwt_auth 需要配置 issuer 和 audience"));
+            }
+            if jwt_auth.jwks_file.is_some() == jwt_auth.jwks_url.is_some() {
+                return Err(FeatherGateError::config("jwt_auth 需要配置 jwks_file 或 jwks_url 之一"));
+            }
+        }
+        if settings.cooldown_time.is_some_and(|seconds| !seconds.is_finite() || seconds < 0.0) {
+            return Err(FeatherGateError::config("cooldown_time 必须是非负数"));
+        }
+        if let Some(url) = &self.general_settings.redis_url {
+            if let Err(e) = redis::Client::open(url.as_str()) {
+                return Err(FeatherGateError::config(format!("redis_url 无效: {}", e)));
+            }
+        }
+        if self.general_settings.redis_sync_interval_ms == 0 {
+            return Err(FeatherGateError::config("redis_sync_interval_ms 必须大于 0"));
+        }
+        if self.general_settings.api_key_refresh_interval == Some(0) {
+            return Err(FeatherGateError::config("api_key_refresh_interval 必须大于 0"));
+        }
+
+        let budgets = self
+            .general_settings
+            .user_budgets
+            .iter()
+            .chain(&self.general_settings.team_budgets);
+        for (name, budget) in budgets {
+            if budget.max_budget.is_some_and(|max_budget| max_budget < 0.0) {
+                return Err(FeatherGateError::config(format!("{} 的 max_budget 不能为负数", name)));
+            }
+            if let Some(duration) = &budget.budget_duration {
+                if parse_duration(duration).is_none() {
+                    return Err(FeatherGateError::config(format!(
+                        "{} 的 budget_duration 无效: {}",
+                        name, duration
+                    )));
+                }
+            }
+        }
+
         for model in &self.model_list {
             if model.model_name.is_empty() {
                 return Err(FeatherGateError::config("model_name 不能为空"));
@@ -70,8 +506,18 @@ impl Config {
             if model.litellm_params.model.is_empty() {
                 return Err(FeatherGateError::config("model 参数不能为空"));
             }
-            if model.litellm_params.api_key.is_empty() {
-                return Err(FeatherGateError::config("api_key 不能为空"));
+            if model.litellm_params.key_pool().is_empty() {
+                return Err(FeatherGateError::config("api_key 和 api_keys 不能都为空"));
+            }
+            if model
+                .litellm_params
+                .api_key_disable_time
+                .is_some_and(|seconds| !(0.0..=MAX_API_KEY_DISABLE_TIME).contains(&seconds))
+            {
+                return Err(FeatherGateError::config(format!(
+                    "api_key_disable_time 必须在 0 到 {} 秒之间",
+                    MAX_API_KEY_DISABLE_TIME
+                )));
             }
         }
 
@@ -84,6 +530,30 @@ impl Config {
             .iter()
             .find(|m| m.model_name == model_name)
     }
+
+    /// model_name 所属的访问组（同名的多个部署取并集）
+    pub fn access_groups(&self, model_name: &str) -> Vec<&str> {
+        let mut groups: Vec<&str> = self
+            .model_list
+            .iter()
+            .filter(|m| m.model_name == model_name)
+            .flat_map(|m| m.model_info.access_groups.iter().map(String::as_str))
+            .collect();
+        groups.sort_unstable();
+        groups.dedup();
+        groups
+    }
+
+    /// 内容审核使用的模型
+    pub fn moderation_model(&self) -> Option<&ModelConfig> {
+        match &self.general_settings.moderation_model {
+            Some(name) => self.find_model(name),
+            None => self
+                .model_list
+                .iter()
+                .find(|m| m.model_info.mode == Some(ModelMode::Moderation)),
+        }
+    }
 }
 
 /// 解析模型字符串 (provider/model-id)
@@ -166,6 +636,8 @@ model_list:
             "https://api.openai.com/v1"
         );
         assert_eq!(config.model_list[1].litellm_params.api_base, ""); // 默认值
+        assert_eq!(config.general_settings.api_locale, Locale::En);
+        assert_eq!(config.general_settings.log_locale, Locale::Zh);
     }
 
     #[test]
@@ -207,6 +679,40 @@ model_list:
         assert!(result.unwrap_err().to_string().contains("MISSING_VAR"));
     }
 
+    #[test]
+    fn test_config_with_secret_references() {
+        env::set_var("TEST_SECRET_KEY", "sk-from-os-environ");
+        let mut secret = NamedTempFile::new().unwrap();
+        secret.write_all(b"sk-from-file\n").unwrap();
+
+        let yaml = format!(
+            r#"
+model_list:
+  - model_name: test
+    litellm_params:
+      model: openai/gpt-4
+      api_key: os.environ/TEST_SECRET_KEY
+      api_keys:
+        - file://{}
+      api_base: ${{TEST_SECRET_BASE:-https://gateway.internal/v1}}
+general_settings:
+  api_key_refresh_interval: 300
+"#,
+            secret.path().display()
+        );
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        let params = &config.model_list[0].litellm_params;
+        assert_eq!(params.key_pool(), vec!["sk-from-os-environ", "sk-from-file"]);
+        assert_eq!(params.api_base, "https://gateway.internal/v1");
+        assert_eq!(config.general_settings.api_key_refresh_interval, Some(300));
+
+        env::remove_var("TEST_SECRET_KEY");
+    }
+
     #[test]
     fn test_config_validation_empty_model_list() {
         let yaml = r#"
@@ -220,6 +726,262 @@ model_list: []
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_general_settings_locale() {
+        let yaml = r#"
+model_list:
+  - model_name: gpt-4
+    litellm_params:
+      model: openai/gpt-4
+      api_key: sk-test
+general_settings:
+  api_locale: zh
+  log_locale: en
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        assert_eq!(config.general_settings.api_locale, Locale::Zh);
+        assert_eq!(config.general_settings.log_locale, Locale::En);
+    }
+
+    #[test]
+    fn test_jwt_auth_settings() {
+        let yaml = r#"
+model_list:
+  - model_name: gpt-4
+    litellm_params:
+      model: openai/gpt-4
+      api_key: sk-test
+general_settings:
+  jwt_auth:
+    issuer: https://idp.example.com
+    audience: feathergate
+    jwks_url: https://idp.example.com/.well-known/jwks.json
+    team_id_claim: groups.team
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        let jwt_auth = config.general_settings.jwt_auth.unwrap();
+        assert_eq!(jwt_auth.audience, "feathergate");
+        assert_eq!(jwt_auth.jwks_cache_ttl, 300);
+        assert_eq!(jwt_auth.leeway, 60);
+        assert_eq!(jwt_auth.user_id_claim, "sub");
+        assert_eq!(jwt_auth.team_id_claim, "groups.team");
+
+        // jwks_file 和 jwks_url 必须且只能配置一个
+        let yaml = yaml.replace("    jwks_url:", "    jwks_file: /etc/jwks.json\n    jwks_url:");
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+        assert!(Config::from_file(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_model_mode() {
+        let yaml = r#"
+model_list:
+  - model_name: embed
+    litellm_params:
+      model: openai/text-embedding-3-small
+      api_key: sk-test
+    model_info:
+      mode: embedding
+  - model_name: gpt-4
+    litellm_params:
+      model: openai/gpt-4
+      api_key: sk-test
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        let embed = config.find_model("embed").unwrap();
+        assert_eq!(embed.model_info.mode, Some(ModelMode::Embedding));
+        assert!(embed.supports(&[ModelMode::Embedding]));
+        assert!(!embed.supports(&[ModelMode::Chat]));
+
+        // 未声明 mode 的模型不限制请求类型
+        let gpt = config.find_model("gpt-4").unwrap();
+        assert!(gpt.supports(&[ModelMode::Embedding]));
+    }
+
+    #[test]
+    fn test_resolved_model_info() {
+        let yaml = r#"
+model_list:
+  - model_name: gpt-4o
+    litellm_params:
+      model: openai/gpt-4o-2024-08-06
+      api_key: sk-test
+    model_info:
+      max_output_tokens: 4096
+      input_cost_per_token: 0.000002
+  - model_name: custom
+    litellm_params:
+      model: openai/my-finetune
+      api_key: sk-test
+    model_info:
+      supports_vision: false
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        let gpt = config.find_model("gpt-4o").unwrap();
+        let info = gpt.resolved_model_info();
+        // 配置中声明的字段优先，其余来自内置默认值
+        assert_eq!(info.max_output_tokens, Some(4096));
+        assert_eq!(info.input_cost_per_token, Some(0.000002));
+        assert_eq!(info.max_input_tokens, Some(128_000));
+        assert_eq!(info.supports_vision, Some(true));
+        // 内置的 mode 不限制请求类型
+        assert_eq!(info.mode, Some(ModelMode::Chat));
+        assert!(gpt.supports(&[ModelMode::Embedding]));
+
+        let info = config.find_model("custom").unwrap().resolved_model_info();
+        assert_eq!(info.supports_vision, Some(false));
+        assert_eq!(info.max_input_tokens, None);
+    }
+
+    #[test]
+    fn test_moderation_settings() {
+        let yaml = r#"
+model_list:
+  - model_name: claude
+    litellm_params:
+      model: anthropic/claude-opus-4-5
+      api_key: sk-ant-test
+    moderation: true
+  - model_name: omni-moderation
+    litellm_params:
+      model: openai/omni-moderation-latest
+      api_key: sk-test
+    model_info:
+      mode: moderation
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        assert!(config.find_model("claude").unwrap().moderation);
+        assert_eq!(config.moderation_model().unwrap().model_name, "omni-moderation");
+
+        // 没有可用的审核模型时拒绝启动
+        let yaml = yaml.replace("mode: moderation", "mode: chat");
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+        assert!(Config::from_file(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_router_settings() {
+        let yaml = r#"
+model_list:
+  - model_name: gpt-4
+    litellm_params:
+      model: openai/gpt-4
+      api_key: sk-test
+  - model_name: claude
+    litellm_params:
+      model: anthropic/claude-opus-4-5
+      api_key: sk-ant-test
+router_settings:
+  num_retries: 2
+  context_window_fallbacks:
+    - gpt-4: [claude]
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        assert_eq!(config.router_settings.num_retries, 2);
+        assert!(config.router_settings.fallbacks.is_empty());
+
+        let targets: Vec<_> = RouterSettings::lookup(
+            &config.router_settings.context_window_fallbacks,
+            "gpt-4",
+        )
+        .collect();
+        assert_eq!(targets, vec!["claude"]);
+    }
+
+    #[test]
+    fn test_router_settings_unknown_fallback() {
+        let yaml = r#"
+model_list:
+  - model_name: gpt-4
+    litellm_params:
+      model: openai/gpt-4
+      api_key: sk-test
+router_settings:
+  fallbacks:
+    - gpt-4: [missing-model]
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let result = Config::from_file(file.path());
+        assert!(result.unwrap_err().to_string().contains("missing-model"));
+    }
+
+    #[test]
+    fn test_api_key_disable_time_range() {
+        for disable_time in ["-1", "1e30", "86401"] {
+            let yaml = format!(
+                "model_list:\n  - model_name: gpt-4\n    litellm_params:\n      model: openai/gpt-4\n      api_key: sk-test\n      api_key_disable_time: {}\n",
+                disable_time
+            );
+            let mut file = NamedTempFile::new().unwrap();
+            file.write_all(yaml.as_bytes()).unwrap();
+
+            let result = Config::from_file(file.path());
+            assert!(result.unwrap_err().to_string().contains("api_key_disable_time"));
+        }
+    }
+
+    #[test]
+    fn test_access_groups() {
+        let yaml = r#"
+model_list:
+  - model_name: gpt-4o
+    litellm_params:
+      model: openai/gpt-4o
+      api_key: sk-test
+    model_info:
+      access_groups: ["beta-models", "openai"]
+  - model_name: gpt-4o
+    litellm_params:
+      model: azure/gpt-4o
+      api_key: sk-azure
+      api_base: https://example.openai.azure.com
+    model_info:
+      access_groups: ["enterprise", "openai"]
+  - model_name: claude
+    litellm_params:
+      model: anthropic/claude-opus-4-5
+      api_key: sk-ant-test
+"#;
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+
+        let config = Config::from_file(file.path()).unwrap();
+        // 同一 model_name 的所有部署的访问组取并集
+        assert_eq!(config.access_groups("gpt-4o"), ["beta-models", "enterprise", "openai"]);
+        assert!(config.access_groups("claude").is_empty());
+        assert!(config.access_groups("missing").is_empty());
+    }
+
     #[test]
     fn test_find_model() {
         let yaml = r#"
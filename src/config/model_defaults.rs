@@ -0,0 +1,132 @@
+use super::{ModelInfo, ModelMode};
+
+/// 常见模型的内置元数据（上下文长度、单价、能力），用于补全配置中未声明的 model_info 字段
+struct ModelDefaults {
+    /// 模型 ID 前缀，带日期或版本后缀的模型（如 `gpt-4o-2024-08-06`）同样匹配
+    model: &'static str,
+    mode: ModelMode,
+    max_input_tokens: Option<u32>,
+    max_output_tokens: Option<u32>,
+    /// 每个输入 token 的成本（美元）
+    input_cost_per_token: f64,
+    /// 每个输出 token 的成本（美元）
+    output_cost_per_token: f64,
+    supports_vision: bool,
+    supports_function_calling: bool,
+}
+
+const fn chat(
+    model: &'static str,
+    max_input_tokens: u32,
+    max_output_tokens: u32,
+    input_cost_per_token: f64,
+    output_cost_per_token: f64,
+    supports_vision: bool,
+) -> ModelDefaults {
+    ModelDefaults {
+        model,
+        mode: ModelMode::Chat,
+        max_input_tokens: Some(max_input_tokens),
+        max_output_tokens: Some(max_output_tokens),
+        input_cost_per_token,
+        output_cost_per_token,
+        supports_vision,
+        supports_function_calling: true,
+    }
+}
+
+const fn embedding(model: &'static str, max_input_tokens: u32, input_cost_per_token: f64) -> ModelDefaults {
+    ModelDefaults {
+        model,
+        mode: ModelMode::Embedding,
+        max_input_tokens: Some(max_input_tokens),
+        max_output_tokens: None,
+        input_cost_per_token,
+        output_cost_per_token: 0.0,
+        supports_vision: false,
+        supports_function_calling: false,
+    }
+}
+
+const MODEL_DEFAULTS: &[ModelDefaults] = &[
+    // OpenAI
+    chat("gpt-4o", 128_000, 16_384, 2.5e-6, 1.0e-5, true),
+    chat("gpt-4o-mini", 128_000, 16_384, 1.5e-7, 6.0e-7, true),
+    chat("gpt-4.1", 1_047_576, 32_768, 2.0e-6, 8.0e-6, true),
+    chat("gpt-4.1-mini", 1_047_576, 32_768, 4.0e-7, 1.6e-6, true),
+    chat("gpt-4.1-nano", 1_047_576, 32_768, 1.0e-7, 4.0e-7, true),
+    chat("gpt-4-turbo", 128_000, 4_096, 1.0e-5, 3.0e-5, true),
+    chat("gpt-4", 8_192, 4_096, 3.0e-5, 6.0e-5, false),
+    chat("gpt-3.5-turbo", 16_385, 4_096, 5.0e-7, 1.5e-6, false),
+    chat("o1", 200_000, 100_000, 1.5e-5, 6.0e-5, true),
+    chat("o1-mini", 128_000, 65_536, 1.1e-6, 4.4e-6, false),
+    chat("o3", 200_000, 100_000, 2.0e-6, 8.0e-6, true),
+    chat("o3-mini", 200_000, 100_000, 1.1e-6, 4.4e-6, false),
+    chat("o4-mini", 200_000, 100_000, 1.1e-6, 4.4e-6, true),
+    embedding("text-embedding-3-small", 8_191, 2.0e-8),
+    embedding("text-embedding-3-large", 8_191, 1.3e-7),
+    embedding("text-embedding-ada-002", 8_191, 1.0e-7),
+    // Anthropic
+    chat("claude-opus-4-5", 200_000, 64_000, 5.0e-6, 2.5e-5, true),
+    chat("claude-opus-4", 200_000, 32_000, 1.5e-5, 7.5e-5, true),
+    chat("claude-sonnet-4", 200_000, 64_000, 3.0e-6, 1.5e-5, true),
+    chat("claude-haiku-4-5", 200_000, 64_000, 1.0e-6, 5.0e-6, true),
+    chat("claude-3-7-sonnet", 200_000, 64_000, 3.0e-6, 1.5e-5, true),
+    chat("claude-3-5-sonnet", 200_000, 8_192, 3.0e-6, 1.5e-5, true),
+    chat("claude-3-5-haiku", 200_000, 8_192, 8.0e-7, 4.0e-6, true),
+    // Gemini
+    chat("gemini-2.5-pro", 1_048_576, 65_535, 1.25e-6, 1.0e-5, true),
+    chat("gemini-2.5-flash", 1_048_576, 65_535, 3.0e-7, 2.5e-6, true),
+    chat("gemini-2.5-flash-lite", 1_048_576, 65_535, 1.0e-7, 4.0e-7, true),
+    chat("gemini-2.0-flash", 1_048_576, 8_192, 1.0e-7, 4.0e-7, true),
+    chat("gemini-1.5-pro", 2_097_152, 8_192, 1.25e-6, 5.0e-6, true),
+    chat("gemini-1.5-flash", 1_048_576, 8_192, 7.5e-8, 3.0e-7, true),
+    embedding("text-embedding-004", 2_048, 0.0),
+    // Cohere
+    embedding("embed-english-v3.0", 512, 1.0e-7),
+    embedding("embed-multilingual-v3.0", 512, 1.0e-7),
+];
+
+/// 查找模型 ID 的内置元数据（最长前缀匹配，前缀之后只能是 `-` 或 `@` 开头的版本后缀）
+pub(super) fn lookup(model_id: &str) -> Option<ModelInfo> {
+    MODEL_DEFAULTS
+        .iter()
+        .filter(|defaults| {
+            model_id
+                .strip_prefix(defaults.model)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '@']))
+        })
+        .max_by_key(|defaults| defaults.model.len())
+        .map(|defaults| ModelInfo {
+            mode: Some(defaults.mode),
+            max_input_tokens: defaults.max_input_tokens,
+            max_output_tokens: defaults.max_output_tokens,
+            input_cost_per_token: Some(defaults.input_cost_per_token),
+            output_cost_per_token: Some(defaults.output_cost_per_token),
+            supports_vision: Some(defaults.supports_vision),
+            supports_function_calling: Some(defaults.supports_function_calling),
+            ..Default::default()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_longest_prefix() {
+        let info = lookup("gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(info.input_cost_per_token, Some(1.5e-7));
+
+        let info = lookup("claude-opus-4-5-20251101").unwrap();
+        assert_eq!(info.max_output_tokens, Some(64_000));
+
+        // gpt-4.1 不能退化匹配到 gpt-4
+        assert_eq!(lookup("gpt-4.1").unwrap().max_input_tokens, Some(1_047_576));
+        assert!(lookup("gpt-4.5-preview").is_none());
+
+        let info = lookup("text-embedding-3-small").unwrap();
+        assert_eq!(info.mode, Some(ModelMode::Embedding));
+        assert_eq!(info.max_output_tokens, None);
+    }
+}
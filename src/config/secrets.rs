@@ -0,0 +1,303 @@
+//! 配置中的密钥引用
+//!
+//! - `${VAR}` / `${VAR:-default}`：在解析 YAML 之前按文本替换为环境变量
+//! - `os.environ/VAR`：整个值替换为环境变量（litellm 的写法）
+//! - `file:///run/secrets/x`：整个值替换为文件内容（去掉末尾换行），用于 Docker / Kubernetes secret
+//! - `vault://<mount>/<path>#<field>`：整个值替换为 Vault KV v2 中密钥的字段
+
+use super::VaultConfig;
+use crate::error::FeatherGateError;
+use crate::Result;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde_yaml::Value;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const ENV_PREFIX: &str = "os.environ/";
+const FILE_PREFIX: &str = "file://";
+const VAULT_PREFIX: &str = "vault://";
+
+/// 读取 Vault 的超时时间
+const VAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 替换配置文本中的 `${VAR}` 和 `${VAR:-default}`，没有默认值的变量未设置时报错
+pub(super) fn expand_env_vars(content: &str) -> Result<String> {
+    static ENV_VAR: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}").unwrap());
+
+    let mut missing = None;
+    let result = ENV_VAR.replace_all(content, |cap: &Captures| {
+        match (std::env::var(&cap[1]), cap.get(2)) {
+            (Ok(value), _) => value,
+            (Err(_), Some(default)) => default.as_str().to_string(),
+            (Err(_), None) => {
+                missing.get_or_insert_with(|| cap[1].to_string());
+                String::new()
+            }
+        }
+    });
+    match missing {
+        Some(var_name) => Err(FeatherGateError::config(format!("环境变量未找到: {}", var_name))),
+        None => Ok(result.into_owned()),
+    }
+}
+
+/// 解析 YAML 中所有字符串值里的 `os.environ/`、`file://` 和 `vault://` 引用
+///
+/// Vault 的连接设置（`general_settings.vault`）本身可以使用环境变量和文件引用。
+pub(super) fn resolve(config: &mut Value) -> Result<()> {
+    visit_strings(config, &mut |value| {
+        if let Some(var_name) = value.strip_prefix(ENV_PREFIX) {
+            *value = std::env::var(var_name)
+                .map_err(|_| FeatherGateError::config(format!("环境变量未找到: {}", var_name)))?;
+        } else if let Some(path) = value.strip_prefix(FILE_PREFIX) {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| FeatherGateError::config(format!("读取密钥文件 {} 失败: {}", path, e)))?;
+            *value = content.trim_end_matches(['\r', '\n']).to_string();
+        }
+        Ok(())
+    })?;
+
+    let mut refs = Vec::new();
+    visit_strings(config, &mut |value| {
+        if value.starts_with(VAULT_PREFIX) {
+            refs.push(VaultRef::parse(value)?);
+        }
+        Ok(())
+    })?;
+    if refs.is_empty() {
+        return Ok(());
+    }
+
+    let settings = match config.get("general_settings").and_then(|settings| settings.get("vault")) {
+        Some(vault) => serde_yaml::from_value(vault.clone())?,
+        None => VaultConfig::default(),
+    };
+    let secrets = fetch_vault_secrets(&VaultClient::new(settings)?, &refs)?;
+    visit_strings(config, &mut |value| {
+        if value.starts_with(VAULT_PREFIX) {
+            let secret = VaultRef::parse(value)?;
+            *value = secrets
+                .get(&secret.location())
+                .and_then(|data| data.get(&secret.field))
+                .cloned()
+                .ok_or_else(|| {
+                    FeatherGateError::config(format!("Vault 密钥 {} 中没有字段 {}", secret.location(), secret.field))
+                })?;
+        }
+        Ok(())
+    })
+}
+
+/// 对 YAML 中的每个字符串值（不含映射的键）调用 f
+fn visit_strings(value: &mut Value, f: &mut impl FnMut(&mut String) -> Result<()>) -> Result<()> {
+    match value {
+        Value::String(value) => f(value),
+        Value::Sequence(items) => items.iter_mut().try_for_each(|item| visit_strings(item, f)),
+        Value::Mapping(mapping) => mapping.values_mut().try_for_each(|item| visit_strings(item, f)),
+        Value::Tagged(tagged) => visit_strings(&mut tagged.value, f),
+        _ => Ok(()),
+    }
+}
+
+/// `vault://<mount>/<path>#<field>` 形式的引用
+#[derive(Debug, Clone, PartialEq)]
+struct VaultRef {
+    mount: String,
+    path: String,
+    field: String,
+}
+
+impl VaultRef {
+    fn parse(value: &str) -> Result<Self> {
+        let invalid = || {
+            FeatherGateError::config(format!(
+                "无效的 Vault 引用: {}（格式为 vault://<mount>/<path>#<field>）",
+                value
+            ))
+        };
+        let (location, field) = value.strip_prefix(VAULT_PREFIX).and_then(|rest| rest.split_once('#')).ok_or_else(invalid)?;
+        let (mount, path) = location.split_once('/').ok_or_else(invalid)?;
+        let path = path.trim_matches('/');
+        if mount.is_empty() || path.is_empty() || field.is_empty() {
+            return Err(invalid());
+        }
+        Ok(VaultRef {
+            mount: mount.to_string(),
+            path: path.to_string(),
+            field: field.to_string(),
+        })
+    }
+
+    /// 不含字段名的密钥位置，同一个密钥的多个字段只读取一次
+    fn location(&self) -> String {
+        format!("{}/{}", self.mount, self.path)
+    }
+}
+
+struct VaultClient {
+    address: String,
+    token: String,
+    namespace: Option<String>,
+}
+
+impl VaultClient {
+    fn new(settings: VaultConfig) -> Result<Self> {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let address = settings
+            .address
+            .or_else(|| env("VAULT_ADDR"))
+            .ok_or_else(|| FeatherGateError::config("使用 vault:// 引用时必须配置 general_settings.vault.address 或 VAULT_ADDR"))?;
+        let token = settings
+            .token
+            .or_else(|| env("VAULT_TOKEN"))
+            .ok_or_else(|| FeatherGateError::config("使用 vault:// 引用时必须配置 general_settings.vault.token 或 VAULT_TOKEN"))?;
+        Ok(VaultClient {
+            address: address.trim_end_matches('/').to_string(),
+            token,
+            namespace: settings.namespace.or_else(|| env("VAULT_NAMESPACE")),
+        })
+    }
+
+    /// 读取 KV v2 密钥的最新版本（`GET /v1/<mount>/data/<path>`）
+    async fn read(&self, client: &reqwest::Client, secret: &VaultRef) -> Result<HashMap<String, String>> {
+        let url = format!("{}/v1/{}/data/{}", self.address, secret.mount, secret.path);
+        let mut request = client.get(&url).header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            FeatherGateError::config(format!("读取 Vault 密钥 {} 失败: {}", secret.location(), e))
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FeatherGateError::config(format!(
+                "读取 Vault 密钥 {} 失败: HTTP {}",
+                secret.location(),
+                status.as_u16()
+            )));
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        let data = body
+            .pointer("/data/data")
+            .and_then(|data| data.as_object())
+            .ok_or_else(|| FeatherGateError::config(format!("Vault 密钥 {} 不是 KV v2 格式", secret.location())))?;
+        Ok(data
+            .iter()
+            .map(|(field, value)| {
+                let value = match value {
+                    serde_json::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                (field.clone(), value)
+            })
+            .collect())
+    }
+}
+
+/// 读取所有引用到的 Vault 密钥
+///
+/// 加载配置是同步的，可能在 tokio 运行时中调用，因此在单独的线程中运行请求。
+fn fetch_vault_secrets(vault: &VaultClient, refs: &[VaultRef]) -> Result<HashMap<String, HashMap<String, String>>> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+                runtime.block_on(async {
+                    let client = reqwest::Client::builder().timeout(VAULT_TIMEOUT).build()?;
+                    let mut secrets = HashMap::new();
+                    for secret in refs {
+                        if let Entry::Vacant(entry) = secrets.entry(secret.location()) {
+                            entry.insert(vault.read(&client, secret).await?);
+                        }
+                    }
+                    Ok(secrets)
+                })
+            })
+            .join()
+            .unwrap_or_else(|_| Err(FeatherGateError::internal("读取 Vault 密钥的线程异常退出")))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_expand_env_vars_with_defaults() {
+        std::env::set_var("SECRETS_TEST_region", "eu");
+        let expanded = expand_env_vars(
+            "a: ${SECRETS_TEST_region}\nb: ${SECRETS_TEST_UNSET:-https://api.openai.com/v1}\nc: ${SECRETS_TEST_UNSET:-}",
+        )
+        .unwrap();
+        assert_eq!(expanded, "a: eu\nb: https://api.openai.com/v1\nc: ");
+
+        let err = expand_env_vars("a: ${SECRETS_TEST_UNSET}").unwrap_err();
+        assert!(err.to_string().contains("SECRETS_TEST_UNSET"));
+        std::env::remove_var("SECRETS_TEST_region");
+    }
+
+    #[test]
+    fn test_resolve_env_and_file_references() {
+        std::env::set_var("SECRETS_TEST_OPENAI_KEY", "sk-from-env");
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"sk-from-file\n").unwrap();
+
+        let mut config: Value = serde_yaml::from_str(&format!(
+            "keys:\n  - os.environ/SECRETS_TEST_OPENAI_KEY\n  - file://{}\nother: plain",
+            file.path().display()
+        ))
+        .unwrap();
+        resolve(&mut config).unwrap();
+        assert_eq!(config["keys"][0].as_str(), Some("sk-from-env"));
+        assert_eq!(config["keys"][1].as_str(), Some("sk-from-file"));
+        assert_eq!(config["other"].as_str(), Some("plain"));
+
+        let mut config: Value = serde_yaml::from_str("key: os.environ/SECRETS_TEST_UNSET").unwrap();
+        assert!(resolve(&mut config).unwrap_err().to_string().contains("SECRETS_TEST_UNSET"));
+        let mut config: Value = serde_yaml::from_str("key: file:///nonexistent/secret").unwrap();
+        assert!(resolve(&mut config).is_err());
+        std::env::remove_var("SECRETS_TEST_OPENAI_KEY");
+    }
+
+    #[test]
+    fn test_resolve_vault_references() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/v1/secret/data/llm/openai")
+            .match_header("x-vault-token", "s.test-token")
+            .with_status(200)
+            .with_body(r#"{"data": {"data": {"primary": "sk-vault-1", "backup": "sk-vault-2"}, "metadata": {"version": 3}}}"#)
+            .expect(1)
+            .create();
+        server
+            .mock("GET", "/v1/secret/data/llm/missing")
+            .with_status(404)
+            .with_body(r#"{"errors": []}"#)
+            .create();
+
+        let settings = format!("general_settings:\n  vault:\n    address: {}\n    token: s.test-token\n", server.url());
+        let mut config: Value = serde_yaml::from_str(&format!(
+            "keys:\n  - vault://secret/llm/openai#primary\n  - vault://secret/llm/openai#backup\n{}",
+            settings
+        ))
+        .unwrap();
+        resolve(&mut config).unwrap();
+        assert_eq!(config["keys"][0].as_str(), Some("sk-vault-1"));
+        assert_eq!(config["keys"][1].as_str(), Some("sk-vault-2"));
+        mock.assert();
+
+        for reference in ["vault://secret/llm/openai#missing", "vault://secret/llm/missing#primary"] {
+            let mut config: Value = serde_yaml::from_str(&format!("key: {}\n{}", reference, settings)).unwrap();
+            assert!(resolve(&mut config).is_err(), "{}", reference);
+        }
+        assert!(VaultRef::parse("vault://secret#primary").is_err());
+        assert!(VaultRef::parse("vault://secret/llm/openai").is_err());
+    }
+}
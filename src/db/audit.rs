@@ -0,0 +1,111 @@
+use super::{db_error, timestamp, Database};
+use crate::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 审计操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Created,
+    Updated,
+    Deleted,
+    /// key 轮换（旧 key 失效，生成新 key）
+    Rotated,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::Created => "created",
+            AuditAction::Updated => "updated",
+            AuditAction::Deleted => "deleted",
+            AuditAction::Rotated => "rotated",
+        }
+    }
+}
+
+/// 一条审计日志：谁在什么时候对哪条记录做了什么修改
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEntry {
+    pub created_at: DateTime<Utc>,
+    /// 操作人（`litellm-changed-by` 请求头，未提供时为调用方身份）
+    pub changed_by: String,
+    pub action: String,
+    pub table_name: String,
+    pub object_id: String,
+    /// 修改前的记录（创建时为空）
+    pub before_value: Option<Value>,
+    /// 修改后的记录（删除时为空）
+    pub updated_values: Option<Value>,
+}
+
+impl AuditEntry {
+    pub fn new(changed_by: &str, action: AuditAction, table_name: &str, object_id: &str) -> Self {
+        AuditEntry {
+            created_at: Utc::now(),
+            changed_by: changed_by.to_string(),
+            action: action.as_str().to_string(),
+            table_name: table_name.to_string(),
+            object_id: object_id.to_string(),
+            before_value: None,
+            updated_values: None,
+        }
+    }
+
+    pub fn before(mut self, value: &impl Serialize) -> Self {
+        self.before_value = serde_json::to_value(value).ok();
+        self
+    }
+
+    pub fn updated(mut self, value: &impl Serialize) -> Self {
+        self.updated_values = serde_json::to_value(value).ok();
+        self
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let json = |column: &str| -> rusqlite::Result<Option<Value>> {
+            let text: Option<String> = row.get(column)?;
+            Ok(text.and_then(|text| serde_json::from_str(&text).ok()))
+        };
+        Ok(AuditEntry {
+            created_at: timestamp(row.get("created_at")?),
+            changed_by: row.get("changed_by")?,
+            action: row.get("action")?,
+            table_name: row.get("table_name")?,
+            object_id: row.get("object_id")?,
+            before_value: json("before_value")?,
+            updated_values: json("updated_values")?,
+        })
+    }
+}
+
+/// 写入审计日志（与被审计的修改在同一事务中执行）
+pub fn record(conn: &Connection, entry: &AuditEntry) -> Result<()> {
+    conn.execute(
+        "INSERT INTO audit_log
+            (created_at, changed_by, action, table_name, object_id, before_value, updated_values)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            entry.created_at.timestamp(),
+            entry.changed_by,
+            entry.action,
+            entry.table_name,
+            entry.object_id,
+            entry.before_value.as_ref().map(Value::to_string),
+            entry.updated_values.as_ref().map(Value::to_string),
+        ],
+    )
+    .map_err(db_error)?;
+    Ok(())
+}
+
+/// 按时间顺序读取某条记录的审计日志
+pub fn entries(db: &Database, object_id: &str) -> Result<Vec<AuditEntry>> {
+    db.with_conn(|conn| {
+        conn.prepare("SELECT * FROM audit_log WHERE object_id = ?1 ORDER BY id")?
+            .query_map([object_id], AuditEntry::from_row)?
+            .collect()
+    })
+}
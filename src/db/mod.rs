@@ -0,0 +1,263 @@
+pub mod audit;
+
+use crate::error::FeatherGateError;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use rusqlite::{Connection, ErrorCode, Row, Transaction};
+use serde::de::DeserializeOwned;
+use std::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// 数据库迁移，按顺序执行；已执行的版本记录在 `PRAGMA user_version` 中
+///
+/// 只能在末尾追加新的迁移，不能修改已发布的迁移。
+const MIGRATIONS: &[&str] = &[
+    // 1: 虚拟 key
+    "CREATE TABLE virtual_keys (
+        token TEXT PRIMARY KEY,
+        key_name TEXT NOT NULL,
+        key_alias TEXT UNIQUE,
+        models TEXT NOT NULL DEFAULT '[]',
+        expires INTEGER,
+        metadata TEXT NOT NULL DEFAULT '{}',
+        blocked INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    )",
+    // 2: key 所属用户和团队；管理操作审计日志
+    "ALTER TABLE virtual_keys ADD COLUMN user_id TEXT;
+     ALTER TABLE virtual_keys ADD COLUMN team_id TEXT;
+     CREATE TABLE audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        created_at INTEGER NOT NULL,
+        changed_by TEXT NOT NULL,
+        action TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        object_id TEXT NOT NULL,
+        before_value TEXT,
+        updated_values TEXT
+     );
+     CREATE INDEX audit_log_object_id ON audit_log (object_id);",
+    // 3: 预算和花费
+    "ALTER TABLE virtual_keys ADD COLUMN max_budget REAL;
+     ALTER TABLE virtual_keys ADD COLUMN budget_duration TEXT;
+     CREATE TABLE spend (
+        entity_type TEXT NOT NULL,
+        entity_id TEXT NOT NULL,
+        spend REAL NOT NULL DEFAULT 0,
+        budget_reset_at INTEGER,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (entity_type, entity_id)
+     );",
+    // 4: RPM/TPM 限制
+    "ALTER TABLE virtual_keys ADD COLUMN rpm_limit INTEGER;
+     ALTER TABLE virtual_keys ADD COLUMN tpm_limit INTEGER;",
+    // 5: 组织、团队和团队成员
+    "CREATE TABLE organizations (
+        organization_id TEXT PRIMARY KEY,
+        organization_alias TEXT,
+        models TEXT NOT NULL DEFAULT '[]',
+        max_budget REAL,
+        budget_duration TEXT,
+        rpm_limit INTEGER,
+        tpm_limit INTEGER,
+        metadata TEXT NOT NULL DEFAULT '{}',
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+     );
+     CREATE TABLE teams (
+        team_id TEXT PRIMARY KEY,
+        team_alias TEXT,
+        organization_id TEXT,
+        models TEXT NOT NULL DEFAULT '[]',
+        max_budget REAL,
+        budget_duration TEXT,
+        rpm_limit INTEGER,
+        tpm_limit INTEGER,
+        metadata TEXT NOT NULL DEFAULT '{}',
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+     );
+     CREATE TABLE team_members (
+        team_id TEXT NOT NULL,
+        user_id TEXT NOT NULL,
+        role TEXT NOT NULL,
+        PRIMARY KEY (team_id, user_id)
+     );
+     CREATE INDEX teams_organization_id ON teams (organization_id);
+     CREATE INDEX virtual_keys_team_id ON virtual_keys (team_id);",
+];
+
+/// 嵌入式 SQLite 数据库（虚拟 key 等持久化数据）
+pub struct Database {
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    /// 打开数据库文件（不存在时创建）并执行迁移
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).map_err(db_error)?;
+        conn.pragma_update(None, "journal_mode", "WAL").map_err(db_error)?;
+        Self::migrate(conn)
+    }
+
+    /// 打开内存数据库（重启后数据丢失）
+    pub fn open_in_memory() -> Result<Self> {
+        Self::migrate(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn migrate(mut conn: Connection) -> Result<Self> {
+        apply_migrations(&mut conn, MIGRATIONS)?;
+        Ok(Database {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// 在数据库连接上执行操作
+    pub fn with_conn<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        f(&conn).map_err(db_error)
+    }
+
+    /// 在事务中执行操作，返回错误时回滚
+    pub fn transaction<T>(&self, f: impl FnOnce(&Transaction) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let tx = conn.transaction().map_err(db_error)?;
+        let value = f(&tx)?;
+        tx.commit().map_err(db_error)?;
+        Ok(value)
+    }
+}
+
+/// 执行尚未执行的迁移；每个迁移和对应的版本号在同一事务中提交，失败时整体回滚
+fn apply_migrations(conn: &mut Connection, migrations: &[&str]) -> Result<()> {
+    let version: usize = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(db_error)?;
+
+    for (index, migration) in migrations.iter().enumerate().skip(version) {
+        let tx = conn.transaction().map_err(db_error)?;
+        tx.execute_batch(migration).map_err(db_error)?;
+        tx.pragma_update(None, "user_version", index + 1).map_err(db_error)?;
+        tx.commit().map_err(db_error)?;
+    }
+    Ok(())
+}
+
+/// SQLite 错误记录到日志，返回给调用方的错误不包含 SQL 细节
+pub(crate) fn db_error(e: rusqlite::Error) -> FeatherGateError {
+    error!("数据库错误: {}", e);
+    FeatherGateError::DatabaseError
+}
+
+/// 是否违反唯一约束等表约束
+pub(crate) fn is_constraint_violation(e: &rusqlite::Error) -> bool {
+    matches!(e, rusqlite::Error::SqliteFailure(err, _) if err.code == ErrorCode::ConstraintViolation)
+}
+
+/// 读取保存为 JSON 文本的列
+pub(crate) fn json_column<T: DeserializeOwned>(row: &Row, column: &str) -> rusqlite::Result<T> {
+    let text: String = row.get(column)?;
+    serde_json::from_str(&text).map_err(|e| {
+        let index = row.as_ref().column_index(column).unwrap_or_default();
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// 秒级 Unix 时间戳转换为 UTC 时间
+pub(crate) fn timestamp(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or_default()
+}
+
+static DATABASE: OnceCell<Database> = OnceCell::new();
+
+/// 初始化全局数据库（启动时调用一次），未配置路径时使用内存数据库
+pub fn init(path: Option<&str>) -> Result<()> {
+    let database = match path {
+        Some(path) => {
+            info!("使用数据库: {}", path);
+            Database::open(path)?
+        }
+        None => {
+            warn!("未配置 database_path，虚拟 key 等数据只保存在内存中");
+            Database::open_in_memory()?
+        }
+    };
+    DATABASE
+        .set(database)
+        .map_err(|_| FeatherGateError::internal("数据库已经初始化"))
+}
+
+/// 获取全局数据库（未初始化时使用内存数据库）
+pub fn global() -> &'static Database {
+    DATABASE.get_or_init(|| Database::open_in_memory().expect("打开内存数据库失败"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_idempotent() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let db = Database::open(path).unwrap();
+        db.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO virtual_keys (token, key_name, created_at, updated_at) VALUES ('t', 'n', 0, 0)",
+                [],
+            )
+        })
+        .unwrap();
+        drop(db);
+
+        // 重新打开时跳过已执行的迁移，数据保留
+        let db = Database::open(path).unwrap();
+        let count: i64 = db
+            .with_conn(|conn| conn.query_row("SELECT COUNT(*) FROM virtual_keys", [], |row| row.get(0)))
+            .unwrap();
+        assert_eq!(count, 1);
+        let version: usize = db
+            .with_conn(|conn| conn.pragma_query_value(None, "user_version", |row| row.get(0)))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn test_db_error_hides_sqlite_details() {
+        let db = Database::open_in_memory().unwrap();
+        let err = db
+            .with_conn(|conn| conn.execute("INSERT INTO missing_table VALUES (1)", []))
+            .unwrap_err();
+        assert!(matches!(err, FeatherGateError::DatabaseError));
+        let body = err.to_json(crate::i18n::Locale::En);
+        assert!(!body["error"]["message"].as_str().unwrap().contains("missing_table"));
+        assert_eq!(err.status_code(), hyper::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let migrations = [
+            "CREATE TABLE a (id INTEGER)",
+            "CREATE TABLE b (id INTEGER); INSERT INTO missing VALUES (1)",
+        ];
+        assert!(apply_migrations(&mut conn, &migrations).is_err());
+
+        // 失败的迁移不留下部分结果，版本停在上一个迁移
+        let version: usize = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 1);
+        let tables: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(tables, ["a"]);
+    }
+}
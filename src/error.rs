@@ -1,39 +1,252 @@
+use crate::config::ModelMode;
+use crate::i18n::{self, Locale, MessageKey};
+use crate::ratelimit::LimitKind;
+use crate::types::ValidationError;
+use hyper::StatusCode;
+use serde_json::json;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum FeatherGateError {
-    #[error("配置错误: {0}")]
     ConfigError(String),
-
-    #[error("IO 错误: {0}")]
     IoError(#[from] std::io::Error),
-
-    #[error("YAML 解析错误: {0}")]
     YamlError(#[from] serde_yaml::Error),
-
-    #[error("JSON 解析错误: {0}")]
     JsonError(#[from] serde_json::Error),
-
-    #[error("HTTP 请求错误: {0}")]
     HttpError(#[from] reqwest::Error),
-
-    #[error("模型未找到: {0}")]
+    InvalidRequest(String),
+    Validation(#[from] ValidationError),
     ModelNotFound(String),
-
-    #[error("提供商不支持: {0}")]
+    /// 模型声明的 mode 不支持该类型的请求
+    ModelModeMismatch(String, ModelMode),
     UnsupportedProvider(String),
-
-    #[error("无效的模型字符串: {0}")]
     InvalidModelString(String),
+    UpstreamError {
+        status: u16,
+        kind: UpstreamErrorKind,
+        message: String,
+    },
+    InternalError(String),
+    /// 数据库操作失败（具体错误只记录在日志中）
+    DatabaseError,
+    /// 上游返回的向量数与输入数不一致（返回数量, 期望数量）
+    EmbeddingCountMismatch(usize, usize),
+    /// 部署的 api_key 不能作为请求头发送（参数为提供商）
+    InvalidUpstreamApiKey(String),
+    /// 网关侧内容审核标记了输入（参数为被标记的类别）
+    ModerationFlagged(String),
+    /// 开启认证时请求未携带 API key
+    MissingApiKey,
+    /// API key 无效
+    InvalidApiKey,
+    /// 虚拟 key 已被禁用
+    KeyBlocked,
+    /// 虚拟 key 已过期
+    KeyExpired,
+    /// JWT 无效（格式、签名、签发方或受众不正确）
+    InvalidJwt(String),
+    /// JWT 已过期
+    JwtExpired,
+    /// 无法加载校验 JWT 的公钥（JWKS），具体原因只写入日志
+    JwksUnavailable,
+    /// 虚拟 key 不允许访问该模型
+    ModelNotAllowed(String),
+    /// 受模型限制的虚拟 key 不能访问该路由（如提供商透传）
+    RouteNotAllowed(String),
+    /// 管理 API 中指定的虚拟 key 不存在
+    KeyNotFound(String),
+    /// 虚拟 key 别名已被使用
+    KeyAliasExists(String),
+    /// 管理 API 中指定的团队不存在
+    TeamNotFound(String),
+    /// team_id 已被使用
+    TeamExists(String),
+    /// 团队还有虚拟 key，不能删除（team_id, key 数量）
+    TeamInUse(String, u64),
+    /// key 的 user_id 不是所属团队的成员（user_id, team_id）
+    NotTeamMember(String, String),
+    /// 管理 API 中指定的组织不存在
+    OrganizationNotFound(String),
+    /// organization_id 已被使用
+    OrganizationExists(String),
+    /// 组织下还有团队，不能删除（organization_id, 团队数量）
+    OrganizationInUse(String, u64),
+    /// 预算已用尽（预算主体, 当前花费, 预算上限）
+    BudgetExceeded(String, f64, f64),
+    /// 超出每分钟请求数或 token 数限制（限流主体, 维度, 每分钟限额）
+    RateLimitExceeded(String, LimitKind, u64),
+    /// 部署连续失败，正在冷却
+    DeploymentCoolingDown(String),
+}
 
-    #[error("上游 API 错误: {status} - {message}")]
-    UpstreamError { status: u16, message: String },
+/// 日志等场景使用日志语言
+impl fmt::Display for FeatherGateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(i18n::log_locale()))
+    }
+}
 
-    #[error("内部错误: {0}")]
-    InternalError(String),
+/// 上游错误分类（由各 provider 的错误响应归一化而来）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpstreamErrorKind {
+    /// 请求参数错误
+    BadRequest,
+    /// 上游认证失败（API key 无效）
+    Authentication,
+    /// 上游拒绝访问
+    PermissionDenied,
+    /// 上游资源不存在
+    NotFound,
+    /// 输入超出模型上下文窗口
+    ContextWindowExceeded,
+    /// 触发内容安全策略
+    ContentPolicyViolation,
+    /// 限流
+    RateLimited,
+    /// 额度耗尽（重试无效）
+    QuotaExceeded,
+    /// 上游过载（如 Anthropic 529）
+    Overloaded,
+    /// 上游超时
+    Timeout,
+    /// 上游服务端错误
+    ServerError,
+}
+
+impl UpstreamErrorKind {
+    /// 仅根据 HTTP 状态码分类
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 => UpstreamErrorKind::Authentication,
+            403 => UpstreamErrorKind::PermissionDenied,
+            404 => UpstreamErrorKind::NotFound,
+            408 | 504 => UpstreamErrorKind::Timeout,
+            429 => UpstreamErrorKind::RateLimited,
+            529 => UpstreamErrorKind::Overloaded,
+            400..=499 => UpstreamErrorKind::BadRequest,
+            _ => UpstreamErrorKind::ServerError,
+        }
+    }
+
+    /// 根据错误消息识别上下文超限和内容策略错误，识别不出时保持原分类
+    pub fn refine_by_message(self, message: &str) -> Self {
+        let message = message.to_lowercase();
+        let context_window = [
+            "context length",
+            "context window",
+            "context_length_exceeded",
+            "prompt is too long",
+            "exceeds the maximum number of tokens",
+            "input token count",
+            "too many tokens",
+        ];
+        let content_policy = [
+            "content policy",
+            "content_policy",
+            "content management policy",
+            "safety system",
+            "content filtering",
+        ];
+
+        if context_window.iter().any(|p| message.contains(p)) {
+            UpstreamErrorKind::ContextWindowExceeded
+        } else if content_policy.iter().any(|p| message.contains(p)) {
+            UpstreamErrorKind::ContentPolicyViolation
+        } else {
+            self
+        }
+    }
+
+    /// 是否值得对同一部署重试
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            UpstreamErrorKind::RateLimited
+                | UpstreamErrorKind::Overloaded
+                | UpstreamErrorKind::Timeout
+                | UpstreamErrorKind::ServerError
+        )
+    }
 }
 
 impl FeatherGateError {
+    /// 按指定语言渲染错误消息
+    pub fn message(&self, locale: Locale) -> String {
+        use FeatherGateError::*;
+        match self {
+            ConfigError(msg) => i18n::message(MessageKey::ConfigError, locale, &[msg]),
+            IoError(e) => i18n::message(MessageKey::IoError, locale, &[e]),
+            YamlError(e) => i18n::message(MessageKey::YamlError, locale, &[e]),
+            JsonError(e) => i18n::message(MessageKey::JsonError, locale, &[e]),
+            HttpError(e) => i18n::message(MessageKey::HttpError, locale, &[e]),
+            InvalidRequest(msg) => i18n::message(MessageKey::InvalidRequest, locale, &[msg]),
+            Validation(e) => e.message(locale),
+            ModelNotFound(model) => i18n::message(MessageKey::ModelNotFound, locale, &[model]),
+            ModelModeMismatch(model, mode) => {
+                i18n::message(MessageKey::ModelModeMismatch, locale, &[model, &mode.as_str()])
+            }
+            UnsupportedProvider(provider) => {
+                i18n::message(MessageKey::UnsupportedProvider, locale, &[provider])
+            }
+            InvalidModelString(msg) => {
+                i18n::message(MessageKey::InvalidModelString, locale, &[msg])
+            }
+            UpstreamError {
+                status, message, ..
+            } => i18n::message(MessageKey::UpstreamError, locale, &[status, message]),
+            InternalError(msg) => i18n::message(MessageKey::InternalError, locale, &[msg]),
+            DatabaseError => i18n::message(MessageKey::DatabaseError, locale, &[]),
+            InvalidUpstreamApiKey(provider) => {
+                i18n::message(MessageKey::InvalidUpstreamApiKey, locale, &[provider])
+            }
+            EmbeddingCountMismatch(returned, expected) => {
+                i18n::message(MessageKey::EmbeddingCountMismatch, locale, &[returned, expected])
+            }
+            ModerationFlagged(categories) => {
+                i18n::message(MessageKey::ModerationFlagged, locale, &[categories])
+            }
+            MissingApiKey => i18n::message(MessageKey::MissingApiKey, locale, &[]),
+            InvalidApiKey => i18n::message(MessageKey::InvalidApiKey, locale, &[]),
+            KeyBlocked => i18n::message(MessageKey::KeyBlocked, locale, &[]),
+            KeyExpired => i18n::message(MessageKey::KeyExpired, locale, &[]),
+            InvalidJwt(reason) => i18n::message(MessageKey::InvalidJwt, locale, &[reason]),
+            JwtExpired => i18n::message(MessageKey::JwtExpired, locale, &[]),
+            JwksUnavailable => i18n::message(MessageKey::JwksUnavailable, locale, &[]),
+            ModelNotAllowed(model) => i18n::message(MessageKey::ModelNotAllowed, locale, &[model]),
+            RouteNotAllowed(route) => i18n::message(MessageKey::RouteNotAllowed, locale, &[route]),
+            KeyNotFound(key) => i18n::message(MessageKey::KeyNotFound, locale, &[key]),
+            KeyAliasExists(alias) => i18n::message(MessageKey::KeyAliasExists, locale, &[alias]),
+            TeamNotFound(team_id) => i18n::message(MessageKey::TeamNotFound, locale, &[team_id]),
+            TeamExists(team_id) => i18n::message(MessageKey::TeamExists, locale, &[team_id]),
+            TeamInUse(team_id, keys) => {
+                i18n::message(MessageKey::TeamInUse, locale, &[team_id, &keys.to_string()])
+            }
+            NotTeamMember(user_id, team_id) => {
+                i18n::message(MessageKey::NotTeamMember, locale, &[user_id, team_id])
+            }
+            OrganizationNotFound(org_id) => i18n::message(MessageKey::OrganizationNotFound, locale, &[org_id]),
+            OrganizationExists(org_id) => i18n::message(MessageKey::OrganizationExists, locale, &[org_id]),
+            OrganizationInUse(org_id, teams) => {
+                i18n::message(MessageKey::OrganizationInUse, locale, &[org_id, &teams.to_string()])
+            }
+            BudgetExceeded(entity, spend, max_budget) => i18n::message(
+                MessageKey::BudgetExceeded,
+                locale,
+                &[entity, &format!("{:.4}", spend), &format!("{:.4}", max_budget)],
+            ),
+            RateLimitExceeded(entity, kind, limit) => {
+                let key = match kind {
+                    LimitKind::Requests => MessageKey::RequestRateLimitExceeded,
+                    LimitKind::Tokens => MessageKey::TokenRateLimitExceeded,
+                };
+                i18n::message(key, locale, &[entity, limit])
+            }
+            DeploymentCoolingDown(model) => {
+                i18n::message(MessageKey::DeploymentCoolingDown, locale, &[model])
+            }
+        }
+    }
+
     pub fn config(msg: impl Into<String>) -> Self {
         FeatherGateError::ConfigError(msg.into())
     }
@@ -43,11 +256,264 @@ impl FeatherGateError {
     }
 
     pub fn upstream(status: u16, message: impl Into<String>) -> Self {
+        Self::upstream_with_kind(status, UpstreamErrorKind::from_status(status), message)
+    }
+
+    pub fn upstream_with_kind(
+        status: u16,
+        kind: UpstreamErrorKind,
+        message: impl Into<String>,
+    ) -> Self {
         FeatherGateError::UpstreamError {
             status,
+            kind,
             message: message.into(),
         }
     }
+
+    /// 上游错误分类（网络错误视为超时或服务端错误）
+    pub fn upstream_kind(&self) -> Option<UpstreamErrorKind> {
+        match self {
+            FeatherGateError::UpstreamError { kind, .. } => Some(*kind),
+            FeatherGateError::HttpError(e) if e.is_timeout() => Some(UpstreamErrorKind::Timeout),
+            FeatherGateError::HttpError(_) => Some(UpstreamErrorKind::ServerError),
+            _ => None,
+        }
+    }
+
+    /// 无效请求（如请求体不是合法 JSON）
+    pub fn invalid_request(msg: impl Into<String>) -> Self {
+        FeatherGateError::InvalidRequest(msg.into())
+    }
+
+    /// 对应的 HTTP 状态码
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            FeatherGateError::InvalidRequest(_) | FeatherGateError::Validation(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            FeatherGateError::ModelNotFound(_)
+            | FeatherGateError::KeyNotFound(_)
+            | FeatherGateError::TeamNotFound(_)
+            | FeatherGateError::OrganizationNotFound(_) => StatusCode::NOT_FOUND,
+            FeatherGateError::KeyAliasExists(_)
+            | FeatherGateError::TeamExists(_)
+            | FeatherGateError::TeamInUse(..)
+            | FeatherGateError::NotTeamMember(..)
+            | FeatherGateError::OrganizationExists(_)
+            | FeatherGateError::OrganizationInUse(..) => StatusCode::BAD_REQUEST,
+            FeatherGateError::BudgetExceeded(..) | FeatherGateError::RateLimitExceeded(..) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            FeatherGateError::DeploymentCoolingDown(_) | FeatherGateError::JwksUnavailable => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            FeatherGateError::MissingApiKey
+            | FeatherGateError::InvalidApiKey
+            | FeatherGateError::KeyBlocked
+            | FeatherGateError::KeyExpired
+            | FeatherGateError::InvalidJwt(_)
+            | FeatherGateError::JwtExpired => StatusCode::UNAUTHORIZED,
+            FeatherGateError::ModelNotAllowed(_) | FeatherGateError::RouteNotAllowed(_) => {
+                StatusCode::FORBIDDEN
+            }
+            FeatherGateError::UnsupportedProvider(_)
+            | FeatherGateError::ModelModeMismatch(..)
+            | FeatherGateError::ModerationFlagged(_) => StatusCode::BAD_REQUEST,
+            FeatherGateError::UpstreamError {
+                kind: UpstreamErrorKind::Overloaded,
+                ..
+            } => StatusCode::SERVICE_UNAVAILABLE,
+            FeatherGateError::UpstreamError {
+                kind: UpstreamErrorKind::ContentPolicyViolation,
+                ..
+            } => StatusCode::BAD_REQUEST,
+            FeatherGateError::UpstreamError { status, .. } => {
+                StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_GATEWAY)
+            }
+            FeatherGateError::HttpError(e) if e.is_timeout() => StatusCode::GATEWAY_TIMEOUT,
+            FeatherGateError::HttpError(_) | FeatherGateError::EmbeddingCountMismatch(..) => {
+                StatusCode::BAD_GATEWAY
+            }
+            FeatherGateError::ConfigError(_)
+            | FeatherGateError::IoError(_)
+            | FeatherGateError::YamlError(_)
+            | FeatherGateError::JsonError(_)
+            | FeatherGateError::InvalidModelString(_)
+            | FeatherGateError::InternalError(_)
+            | FeatherGateError::DatabaseError
+            | FeatherGateError::InvalidUpstreamApiKey(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// OpenAI 错误类型（`error.type`）
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            FeatherGateError::InvalidRequest(_)
+            | FeatherGateError::Validation(_)
+            | FeatherGateError::ModelNotFound(_)
+            | FeatherGateError::ModelModeMismatch(..)
+            | FeatherGateError::ModerationFlagged(_)
+            | FeatherGateError::UnsupportedProvider(_)
+            | FeatherGateError::MissingApiKey
+            | FeatherGateError::InvalidApiKey
+            | FeatherGateError::KeyBlocked
+            | FeatherGateError::KeyExpired
+            | FeatherGateError::InvalidJwt(_)
+            | FeatherGateError::JwtExpired
+            | FeatherGateError::KeyNotFound(_)
+            | FeatherGateError::KeyAliasExists(_)
+            | FeatherGateError::TeamNotFound(_)
+            | FeatherGateError::TeamExists(_)
+            | FeatherGateError::TeamInUse(..)
+            | FeatherGateError::NotTeamMember(..)
+            | FeatherGateError::OrganizationNotFound(_)
+            | FeatherGateError::OrganizationExists(_)
+            | FeatherGateError::OrganizationInUse(..) => "invalid_request_error",
+            FeatherGateError::ModelNotAllowed(_) | FeatherGateError::RouteNotAllowed(_) => {
+                "permission_error"
+            }
+            FeatherGateError::BudgetExceeded(..) => "budget_exceeded",
+            FeatherGateError::RateLimitExceeded(..) => "rate_limit_error",
+            FeatherGateError::UpstreamError { kind, .. } => match kind {
+                UpstreamErrorKind::BadRequest
+                | UpstreamErrorKind::ContextWindowExceeded
+                | UpstreamErrorKind::ContentPolicyViolation => "invalid_request_error",
+                UpstreamErrorKind::Authentication => "authentication_error",
+                UpstreamErrorKind::PermissionDenied => "permission_error",
+                UpstreamErrorKind::NotFound => "not_found_error",
+                UpstreamErrorKind::RateLimited => "rate_limit_error",
+                UpstreamErrorKind::QuotaExceeded => "insufficient_quota",
+                UpstreamErrorKind::Overloaded
+                | UpstreamErrorKind::Timeout
+                | UpstreamErrorKind::ServerError => "api_error",
+            },
+            _ => "api_error",
+        }
+    }
+
+    /// OpenAI 错误码（`error.code`）
+    pub fn error_code(&self) -> Option<&'static str> {
+        match self {
+            FeatherGateError::Validation(e) => Some(e.code()),
+            FeatherGateError::ModelNotFound(_) => Some("model_not_found"),
+            FeatherGateError::ModelModeMismatch(..) => Some("model_not_supported"),
+            FeatherGateError::ModerationFlagged(_) => Some("content_policy_violation"),
+            FeatherGateError::UnsupportedProvider(_) => Some("unsupported_provider"),
+            FeatherGateError::MissingApiKey => Some("missing_api_key"),
+            FeatherGateError::InvalidApiKey => Some("invalid_api_key"),
+            FeatherGateError::KeyBlocked => Some("key_blocked"),
+            FeatherGateError::KeyExpired => Some("key_expired"),
+            FeatherGateError::InvalidJwt(_) => Some("invalid_jwt"),
+            FeatherGateError::JwtExpired => Some("jwt_expired"),
+            FeatherGateError::JwksUnavailable => Some("jwks_unavailable"),
+            FeatherGateError::ModelNotAllowed(_) => Some("model_not_allowed"),
+            FeatherGateError::RouteNotAllowed(_) => Some("route_not_allowed"),
+            FeatherGateError::KeyNotFound(_) => Some("key_not_found"),
+            FeatherGateError::KeyAliasExists(_) => Some("key_alias_exists"),
+            FeatherGateError::TeamNotFound(_) => Some("team_not_found"),
+            FeatherGateError::TeamExists(_) => Some("team_exists"),
+            FeatherGateError::TeamInUse(..) => Some("team_in_use"),
+            FeatherGateError::NotTeamMember(..) => Some("not_team_member"),
+            FeatherGateError::OrganizationNotFound(_) => Some("organization_not_found"),
+            FeatherGateError::OrganizationExists(_) => Some("organization_exists"),
+            FeatherGateError::OrganizationInUse(..) => Some("organization_in_use"),
+            FeatherGateError::BudgetExceeded(..) => Some("budget_exceeded"),
+            FeatherGateError::RateLimitExceeded(..) => Some("rate_limit_exceeded"),
+            FeatherGateError::DeploymentCoolingDown(_) => Some("deployment_cooldown"),
+            FeatherGateError::UpstreamError { kind, .. } => match kind {
+                UpstreamErrorKind::ContextWindowExceeded => Some("context_length_exceeded"),
+                UpstreamErrorKind::ContentPolicyViolation => Some("content_policy_violation"),
+                UpstreamErrorKind::RateLimited => Some("rate_limit_exceeded"),
+                UpstreamErrorKind::QuotaExceeded => Some("insufficient_quota"),
+                UpstreamErrorKind::Overloaded => Some("overloaded"),
+                UpstreamErrorKind::Timeout => Some("upstream_timeout"),
+                _ => None,
+            },
+            FeatherGateError::HttpError(e) if e.is_timeout() => Some("upstream_timeout"),
+            FeatherGateError::HttpError(_) => Some("upstream_connection_error"),
+            _ => None,
+        }
+    }
+
+    /// 出错的请求参数（`error.param`）
+    pub fn param(&self) -> Option<&'static str> {
+        match self {
+            FeatherGateError::Validation(e) => Some(e.param()),
+            FeatherGateError::UnsupportedProvider(_)
+            | FeatherGateError::ModelModeMismatch(..)
+            | FeatherGateError::ModelNotAllowed(_) => Some("model"),
+            FeatherGateError::KeyNotFound(_) => Some("key"),
+            FeatherGateError::KeyAliasExists(_) => Some("key_alias"),
+            FeatherGateError::NotTeamMember(..) => Some("user_id"),
+            FeatherGateError::TeamNotFound(_) | FeatherGateError::TeamExists(_) | FeatherGateError::TeamInUse(..) => {
+                Some("team_id")
+            }
+            FeatherGateError::OrganizationNotFound(_)
+            | FeatherGateError::OrganizationExists(_)
+            | FeatherGateError::OrganizationInUse(..) => Some("organization_id"),
+            FeatherGateError::UpstreamError {
+                kind: UpstreamErrorKind::ContextWindowExceeded,
+                ..
+            } => Some("messages"),
+            _ => None,
+        }
+    }
+
+    /// OpenAI 风格的错误响应体（消息使用 API 语言）
+    pub fn to_json(&self, locale: Locale) -> serde_json::Value {
+        json!({
+            "error": {
+                "message": self.message(locale),
+                "type": self.error_type(),
+                "param": self.param(),
+                "code": self.error_code()
+            }
+        })
+    }
+
+    /// Gemini 风格的错误响应体（/v1beta 原生端点使用，status 按状态码映射）
+    pub fn to_gemini_json(&self, locale: Locale) -> serde_json::Value {
+        let code = self.status_code().as_u16();
+        let status = match code {
+            400 => "INVALID_ARGUMENT",
+            401 => "UNAUTHENTICATED",
+            403 => "PERMISSION_DENIED",
+            404 => "NOT_FOUND",
+            429 => "RESOURCE_EXHAUSTED",
+            503 => "UNAVAILABLE",
+            504 => "DEADLINE_EXCEEDED",
+            _ => "INTERNAL",
+        };
+        json!({
+            "error": {
+                "code": code,
+                "message": self.message(locale),
+                "status": status
+            }
+        })
+    }
+
+    /// Anthropic 风格的错误响应体（/v1/messages 使用，错误类型按状态码映射）
+    pub fn to_anthropic_json(&self, locale: Locale) -> serde_json::Value {
+        let error_type = match self.status_code().as_u16() {
+            400 => "invalid_request_error",
+            401 => "authentication_error",
+            403 => "permission_error",
+            404 => "not_found_error",
+            413 => "request_too_large",
+            429 => "rate_limit_error",
+            503 | 529 => "overloaded_error",
+            _ => "api_error",
+        };
+        json!({
+            "type": "error",
+            "error": {
+                "type": error_type,
+                "message": self.message(locale)
+            }
+        })
+    }
 }
 
 #[cfg(test)]
@@ -67,6 +533,19 @@ mod tests {
         assert_eq!(err.to_string(), "上游 API 错误: 404 - Not Found");
     }
 
+    #[test]
+    fn test_error_message_locale() {
+        let err = FeatherGateError::ModelNotFound("gpt-4".to_string());
+        assert_eq!(err.message(Locale::En), "Model not found: gpt-4");
+        assert_eq!(err.message(Locale::Zh), "模型未找到: gpt-4");
+
+        let err = FeatherGateError::from(ValidationError::EmptyMessages);
+        assert_eq!(err.message(Locale::En), "messages must not be empty");
+
+        let json = FeatherGateError::upstream(404, "Not Found").to_json(Locale::Zh);
+        assert_eq!(json["error"]["message"], "上游 API 错误: 404 - Not Found");
+    }
+
     #[test]
     fn test_error_conversion_from_io() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "文件未找到");
@@ -90,4 +569,112 @@ mod tests {
         let err = FeatherGateError::internal("内部错误");
         assert!(matches!(err, FeatherGateError::InternalError(_)));
     }
+
+    #[test]
+    fn test_status_code_mapping() {
+        let err = FeatherGateError::ModelNotFound("gpt-4".to_string());
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+
+        let err = FeatherGateError::upstream(429, "rate limited");
+        assert_eq!(err.status_code(), StatusCode::TOO_MANY_REQUESTS);
+
+        let err = FeatherGateError::upstream(1000, "invalid status");
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+
+        let err = FeatherGateError::internal("boom");
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let err = FeatherGateError::invalid_request("expected value at line 1 column 1");
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_openai_error_envelope() {
+        let json = FeatherGateError::ModelNotFound("gpt-5".to_string()).to_json(Locale::En);
+        assert_eq!(json["error"]["type"], "invalid_request_error");
+        assert_eq!(json["error"]["code"], "model_not_found");
+        assert!(json["error"]["param"].is_null());
+
+        let json = FeatherGateError::from(ValidationError::TopPOutOfRange(1.5)).to_json(Locale::En);
+        assert_eq!(json["error"]["type"], "invalid_request_error");
+        assert_eq!(json["error"]["param"], "top_p");
+        assert_eq!(json["error"]["code"], "decimal_above_max_value");
+
+        let json = FeatherGateError::upstream(401, "Invalid API key").to_json(Locale::En);
+        assert_eq!(json["error"]["type"], "authentication_error");
+
+        let json = FeatherGateError::upstream(429, "Too many requests").to_json(Locale::En);
+        assert_eq!(json["error"]["type"], "rate_limit_error");
+        assert_eq!(json["error"]["code"], "rate_limit_exceeded");
+
+        let json = FeatherGateError::upstream(503, "Unavailable").to_json(Locale::En);
+        assert_eq!(json["error"]["type"], "api_error");
+        assert!(json["error"]["code"].is_null());
+    }
+
+    #[test]
+    fn test_anthropic_error_envelope() {
+        let json = FeatherGateError::ModelNotFound("claude-9".to_string()).to_anthropic_json(Locale::En);
+        assert_eq!(json["type"], "error");
+        assert_eq!(json["error"]["type"], "not_found_error");
+        assert_eq!(json["error"]["message"], "Model not found: claude-9");
+
+        let json = FeatherGateError::upstream(529, "Overloaded").to_anthropic_json(Locale::En);
+        assert_eq!(json["error"]["type"], "overloaded_error");
+    }
+
+    #[test]
+    fn test_gemini_error_envelope() {
+        let json = FeatherGateError::ModelNotFound("gemini-9".to_string()).to_gemini_json(Locale::En);
+        assert_eq!(json["error"]["code"], 404);
+        assert_eq!(json["error"]["status"], "NOT_FOUND");
+
+        let json = FeatherGateError::upstream(429, "Quota").to_gemini_json(Locale::En);
+        assert_eq!(json["error"]["status"], "RESOURCE_EXHAUSTED");
+    }
+
+    #[test]
+    fn test_upstream_error_kind() {
+        assert_eq!(UpstreamErrorKind::from_status(429), UpstreamErrorKind::RateLimited);
+        assert_eq!(UpstreamErrorKind::from_status(529), UpstreamErrorKind::Overloaded);
+        assert_eq!(UpstreamErrorKind::from_status(422), UpstreamErrorKind::BadRequest);
+        assert_eq!(UpstreamErrorKind::from_status(502), UpstreamErrorKind::ServerError);
+
+        assert_eq!(
+            UpstreamErrorKind::BadRequest.refine_by_message("prompt is too long: 250000 tokens"),
+            UpstreamErrorKind::ContextWindowExceeded
+        );
+        assert_eq!(
+            UpstreamErrorKind::BadRequest.refine_by_message("missing field"),
+            UpstreamErrorKind::BadRequest
+        );
+
+        assert!(UpstreamErrorKind::Overloaded.is_retryable());
+        assert!(!UpstreamErrorKind::QuotaExceeded.is_retryable());
+        assert!(!UpstreamErrorKind::ContextWindowExceeded.is_retryable());
+    }
+
+    #[test]
+    fn test_upstream_kind_envelope() {
+        let err = FeatherGateError::upstream_with_kind(
+            400,
+            UpstreamErrorKind::ContextWindowExceeded,
+            "too long",
+        );
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        let json = err.to_json(Locale::En);
+        assert_eq!(json["error"]["code"], "context_length_exceeded");
+        assert_eq!(json["error"]["param"], "messages");
+
+        let err = FeatherGateError::upstream(529, "Overloaded");
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.to_json(Locale::En)["error"]["code"], "overloaded");
+
+        let err = FeatherGateError::upstream_with_kind(
+            429,
+            UpstreamErrorKind::QuotaExceeded,
+            "You exceeded your current quota",
+        );
+        assert_eq!(err.to_json(Locale::En)["error"]["type"], "insufficient_quota");
+    }
 }
@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 消息语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// 英文（API 错误消息默认）
+    #[default]
+    En,
+    /// 中文
+    Zh,
+}
+
+/// 日志中错误消息使用的语言（默认中文，与其余日志保持一致）
+static LOG_LOCALE: AtomicU8 = AtomicU8::new(Locale::Zh as u8);
+
+/// 设置日志语言
+pub fn set_log_locale(locale: Locale) {
+    LOG_LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+/// 获取日志语言
+pub fn log_locale() -> Locale {
+    match LOG_LOCALE.load(Ordering::Relaxed) {
+        x if x == Locale::En as u8 => Locale::En,
+        _ => Locale::Zh,
+    }
+}
+
+/// 消息目录中的键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKey {
+    ConfigError,
+    IoError,
+    YamlError,
+    JsonError,
+    HttpError,
+    InvalidRequest,
+    ModelNotFound,
+    UnsupportedProvider,
+    InvalidModelString,
+    UpstreamError,
+    InternalError,
+    DatabaseError,
+    EmbeddingCountMismatch,
+    InvalidUpstreamApiKey,
+    TemperatureOutOfRange,
+    TopPOutOfRange,
+    EmptyMessages,
+    EmptyPrompt,
+    TokenPromptUnsupported,
+    BatchStreamUnsupported,
+    CompletionParameterUnsupported,
+    EmptyInput,
+    TokenInputUnsupported,
+    ModelModeMismatch,
+    ImageCountOutOfRange,
+    MissingField,
+    AudioFileTooLarge,
+    FieldAfterFile,
+    ModerationFlagged,
+    UnsupportedInputItem,
+    PreviousResponseNotFound,
+    UnsupportedContentBlock,
+    ToolsUnsupported,
+    NativeParameterUnsupported,
+    EmptyQuery,
+    EmptyDocuments,
+    TopNOutOfRange,
+    InvalidDuration,
+    MissingApiKey,
+    InvalidApiKey,
+    KeyBlocked,
+    KeyExpired,
+    InvalidJwt,
+    JwtExpired,
+    JwksUnavailable,
+    ModelNotAllowed,
+    RouteNotAllowed,
+    KeyNotFound,
+    KeyAliasExists,
+    TeamNotFound,
+    TeamExists,
+    TeamInUse,
+    NotTeamMember,
+    OrganizationNotFound,
+    OrganizationExists,
+    OrganizationInUse,
+    BudgetExceeded,
+    RequestRateLimitExceeded,
+    TokenRateLimitExceeded,
+    DeploymentCoolingDown,
+}
+
+impl MessageKey {
+    /// 消息模板，`{0}`、`{1}` 为参数占位符
+    pub fn template(self, locale: Locale) -> &'static str {
+        use MessageKey::*;
+        match locale {
+            Locale::En => match self {
+                ConfigError => "Configuration error: {0}",
+                IoError => "IO error: {0}",
+                YamlError => "YAML parse error: {0}",
+                JsonError => "JSON parse error: {0}",
+                HttpError => "HTTP request error: {0}",
+                InvalidRequest => "Invalid request: {0}",
+                ModelNotFound => "Model not found: {0}",
+                UnsupportedProvider => "Unsupported provider: {0}",
+                InvalidModelString => "Invalid model string: {0}",
+                UpstreamError => "Upstream API error: {0} - {1}",
+                InternalError => "Internal error: {0}",
+                DatabaseError => "Database error, see the gateway logs for details",
+                EmbeddingCountMismatch => "Upstream returned {0} embeddings, expected {1}",
+                InvalidUpstreamApiKey => "The api_key configured for {0} contains characters not allowed in HTTP headers",
+                TemperatureOutOfRange => "temperature must be between 0.0 and 2.0, got: {0}",
+                TopPOutOfRange => "top_p must be between 0.0 and 1.0, got: {0}",
+                EmptyMessages => "messages must not be empty",
+                EmptyPrompt => "prompt must not be empty",
+                TokenPromptUnsupported => "token array prompts are only supported by OpenAI models",
+                BatchStreamUnsupported => {
+                    "streaming multiple prompts is only supported by OpenAI models"
+                }
+                CompletionParameterUnsupported => "{0} is only supported for completions by OpenAI models",
+                EmptyInput => "input must not be empty",
+                TokenInputUnsupported => "token array inputs are only supported by OpenAI models",
+                ModelModeMismatch => "Model {0} does not support {1} requests",
+                ImageCountOutOfRange => "n must be between 1 and 10, got: {0}",
+                MissingField => "missing required field: {0}",
+                AudioFileTooLarge => "audio file exceeds the {0} MB limit",
+                FieldAfterFile => "form field {0} must be sent before file when model precedes file",
+                ModerationFlagged => "Input was flagged by the content policy: {0}",
+                UnsupportedInputItem => "input item type '{0}' is not supported by this model",
+                PreviousResponseNotFound => "Previous response with id '{0}' not found",
+                UnsupportedContentBlock => "content block type '{0}' is not supported by this model",
+                ToolsUnsupported => "tools are only supported when the model's provider matches this endpoint's format",
+                NativeParameterUnsupported => "{0} is only supported when the model's provider matches this endpoint's format",
+                EmptyQuery => "query must not be empty",
+                EmptyDocuments => "documents must not be empty",
+                TopNOutOfRange => "top_n must be at least 1, got: {0}",
+                InvalidDuration => "{0} must be a duration like 30s, 30m, 30h, 30d or 1mo, got: {1}",
+                MissingApiKey => "You didn't provide an API key. Pass it via 'Authorization: Bearer YOUR_KEY' or the 'x-api-key' header",
+                InvalidApiKey => "Incorrect API key provided",
+                KeyBlocked => "This API key has been blocked",
+                KeyExpired => "This API key has expired",
+                InvalidJwt => "Invalid JWT: {0}",
+                JwtExpired => "This JWT has expired",
+                JwksUnavailable => "Unable to load signing keys for JWT verification",
+                ModelNotAllowed => "This API key is not allowed to access model {0}",
+                RouteNotAllowed => "This API key is not allowed to access {0}",
+                KeyNotFound => "API key not found: {0}",
+                KeyAliasExists => "key_alias '{0}' is already in use",
+                TeamNotFound => "Team not found: {0}",
+                TeamExists => "team_id '{0}' is already in use",
+                TeamInUse => "Team {0} still has {1} virtual key(s); delete them or move them to another team first",
+                NotTeamMember => "User {0} is not a member of team {1}; add the user with /team/member_add first",
+                OrganizationNotFound => "Organization not found: {0}",
+                OrganizationExists => "organization_id '{0}' is already in use",
+                OrganizationInUse => "Organization {0} still has {1} team(s); delete them or move them to another organization first",
+                BudgetExceeded => "Budget exceeded for {0}: current spend ${1}, max budget ${2}",
+                RequestRateLimitExceeded => "Rate limit exceeded for {0}: {1} requests per minute",
+                TokenRateLimitExceeded => "Rate limit exceeded for {0}: {1} tokens per minute",
+                DeploymentCoolingDown => "Model {0} is cooling down after repeated failures",
+            },
+            Locale::Zh => match self {
+                ConfigError => "配置错误: {0}",
+                IoError => "IO 错误: {0}",
+                YamlError => "YAML 解析错误: {0}",
+                JsonError => "JSON 解析错误: {0}",
+                HttpError => "HTTP 请求错误: {0}",
+                InvalidRequest => "无效的请求: {0}",
+                ModelNotFound => "模型未找到: {0}",
+                UnsupportedProvider => "提供商不支持: {0}",
+                InvalidModelString => "无效的模型字符串: {0}",
+                UpstreamError => "上游 API 错误: {0} - {1}",
+                InternalError => "内部错误: {0}",
+                DatabaseError => "数据库错误，详细信息见网关日志",
+                EmbeddingCountMismatch => "上游返回 {0} 个向量，期望 {1} 个",
+                InvalidUpstreamApiKey => "{0} 的 api_key 包含非法字符",
+                TemperatureOutOfRange => "temperature 必须在 0.0 到 2.0 之间，当前值: {0}",
+                TopPOutOfRange => "top_p 必须在 0.0 到 1.0 之间，当前值: {0}",
+                EmptyMessages => "messages 不能为空",
+                EmptyPrompt => "prompt 不能为空",
+                TokenPromptUnsupported => "仅 OpenAI 模型支持 token 数组形式的 prompt",
+                BatchStreamUnsupported => "仅 OpenAI 模型支持多个 prompt 的流式补全",
+                CompletionParameterUnsupported => "仅 OpenAI 模型的文本补全支持 {0} 参数",
+                EmptyInput => "input 不能为空",
+                TokenInputUnsupported => "仅 OpenAI 模型支持 token 数组形式的 input",
+                ModelModeMismatch => "模型 {0} 不支持 {1} 请求",
+                ImageCountOutOfRange => "n 必须在 1 到 10 之间，当前值: {0}",
+                MissingField => "缺少必需字段: {0}",
+                AudioFileTooLarge => "音频文件超过 {0} MB 上限",
+                FieldAfterFile => "model 在 file 之前时，表单字段 {0} 必须在 file 之前发送",
+                ModerationFlagged => "输入未通过内容审核: {0}",
+                UnsupportedInputItem => "该模型不支持 '{0}' 类型的输入项",
+                PreviousResponseNotFound => "未找到 ID 为 '{0}' 的历史响应",
+                UnsupportedContentBlock => "该模型不支持 '{0}' 类型的内容块",
+                ToolsUnsupported => "只有模型的提供商与端点格式一致时才支持 tools",
+                NativeParameterUnsupported => "只有模型的提供商与端点格式一致时才支持 {0}",
+                EmptyQuery => "query 不能为空",
+                EmptyDocuments => "documents 不能为空",
+                TopNOutOfRange => "top_n 至少为 1，当前值: {0}",
+                InvalidDuration => "{0} 应为 30s、30m、30h、30d 或 1mo 这样的时长，当前值: {1}",
+                MissingApiKey => "未提供 API key，请通过 'Authorization: Bearer YOUR_KEY' 或 'x-api-key' 请求头传入",
+                InvalidApiKey => "API key 无效",
+                KeyBlocked => "该 API key 已被禁用",
+                KeyExpired => "该 API key 已过期",
+                InvalidJwt => "JWT 无效: {0}",
+                JwtExpired => "该 JWT 已过期",
+                JwksUnavailable => "无法加载校验 JWT 的公钥",
+                ModelNotAllowed => "该 API key 无权访问模型 {0}",
+                RouteNotAllowed => "该 API key 无权访问 {0}",
+                KeyNotFound => "API key 不存在: {0}",
+                KeyAliasExists => "key_alias '{0}' 已被使用",
+                TeamNotFound => "团队不存在: {0}",
+                TeamExists => "team_id '{0}' 已被使用",
+                TeamInUse => "团队 {0} 还有 {1} 个虚拟 key，请先删除或移到其他团队",
+                NotTeamMember => "用户 {0} 不是团队 {1} 的成员，请先通过 /team/member_add 添加",
+                OrganizationNotFound => "组织不存在: {0}",
+                OrganizationExists => "organization_id '{0}' 已被使用",
+                OrganizationInUse => "组织 {0} 下还有 {1} 个团队，请先删除或移到其他组织",
+                BudgetExceeded => "{0} 的预算已用尽：当前花费 ${1}，预算上限 ${2}",
+                RequestRateLimitExceeded => "{0} 超出速率限制：每分钟最多 {1} 个请求",
+                TokenRateLimitExceeded => "{0} 超出速率限制：每分钟最多 {1} 个 token",
+                DeploymentCoolingDown => "模型 {0} 连续失败，正在冷却",
+            },
+        }
+    }
+}
+
+/// 按语言渲染消息
+pub fn message(key: MessageKey, locale: Locale, args: &[&dyn Display]) -> String {
+    let mut output = String::new();
+    let mut rest = key.template(locale);
+
+    // 单次扫描替换占位符，避免参数内容中的 `{n}` 被再次替换
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let arg = rest[start..].find('}').and_then(|end| {
+            let index: usize = rest[start + 1..start + end].parse().ok()?;
+            Some((args.get(index)?, end))
+        });
+        match arg {
+            Some((arg, end)) => {
+                output.push_str(&arg.to_string());
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_rendering() {
+        assert_eq!(
+            message(MessageKey::ModelNotFound, Locale::En, &[&"gpt-5"]),
+            "Model not found: gpt-5"
+        );
+        assert_eq!(
+            message(MessageKey::ModelNotFound, Locale::Zh, &[&"gpt-5"]),
+            "模型未找到: gpt-5"
+        );
+        assert_eq!(
+            message(MessageKey::UpstreamError, Locale::En, &[&429, &"slow down"]),
+            "Upstream API error: 429 - slow down"
+        );
+        assert_eq!(
+            message(MessageKey::EmptyMessages, Locale::En, &[]),
+            "messages must not be empty"
+        );
+        assert_eq!(
+            message(MessageKey::UpstreamError, Locale::En, &[&"{1}", &"x"]),
+            "Upstream API error: {1} - x"
+        );
+    }
+
+    #[test]
+    fn test_locale_deserialize() {
+        let locale: Locale = serde_yaml::from_str("zh").unwrap();
+        assert_eq!(locale, Locale::Zh);
+        assert_eq!(Locale::default(), Locale::En);
+    }
+}
@@ -1,9 +1,15 @@
+pub mod auth;
+pub mod cluster;
 pub mod config;
+pub mod db;
 pub mod error;
+pub mod i18n;
 pub mod types;
 pub mod server;
 pub mod providers;
 pub mod metrics;
+pub mod spend;
+pub mod ratelimit;
 
 pub use error::FeatherGateError;
 pub type Result<T> = std::result::Result<T, FeatherGateError>;
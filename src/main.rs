@@ -1,8 +1,14 @@
 use clap::Parser;
+use feathergate::cluster;
 use feathergate::config::Config;
+use feathergate::db;
+use feathergate::i18n;
+use feathergate::providers::api_keys;
 use feathergate::server;
+use feathergate::spend;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(name = "feathergate")]
@@ -32,6 +38,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 加载配置
     let config = Config::from_file(&args.config)?;
+    i18n::set_log_locale(config.general_settings.log_locale);
+    db::init(config.general_settings.database_path.as_deref())?;
+    cluster::start(&config.general_settings)?;
+    spend::start();
+    api_keys::key_pool().reload(&config);
+    api_keys::reload_on_sighup(args.config.clone().into());
+    if let Some(interval) = config.general_settings.api_key_refresh_interval {
+        api_keys::reload_every(args.config.into(), Duration::from_secs(interval));
+    }
     let config = Arc::new(config);
 
     // 解析监听地址
@@ -39,6 +54,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 启动服务器
     server::start_server(config, addr).await?;
+    spend::flush().await;
 
     Ok(())
 }
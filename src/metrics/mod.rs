@@ -7,6 +7,15 @@ pub struct Metrics {
     total_requests: AtomicU64,
     successful_requests: AtomicU64,
     failed_requests: AtomicU64,
+    /// 认证失败的请求（同时计入失败请求）
+    auth_failures: AtomicU64,
+    images_generated: AtomicU64,
+    /// 图像生成成本（百万分之一美元）
+    image_cost_micros: AtomicU64,
+    /// 按 token 和图像单价计算的总花费（百万分之一美元）
+    spend_micros: AtomicU64,
+    /// 被 RPM/TPM 限制拒绝的请求
+    rate_limited: AtomicU64,
 }
 
 impl Metrics {
@@ -26,6 +35,30 @@ impl Metrics {
         self.failed_requests.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// 记录认证失败的请求
+    pub fn record_auth_failure(&self) {
+        self.record_failure();
+        self.auth_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录生成的图像数量和成本（美元）
+    pub fn record_images(&self, count: u64, cost: f64) {
+        self.images_generated.fetch_add(count, Ordering::Relaxed);
+        self.image_cost_micros
+            .fetch_add((cost * 1_000_000.0).round() as u64, Ordering::Relaxed);
+    }
+
+    /// 记录请求花费（美元）
+    pub fn record_spend(&self, cost: f64) {
+        self.spend_micros
+            .fetch_add((cost * 1_000_000.0).round() as u64, Ordering::Relaxed);
+    }
+
+    /// 记录被限流拒绝的请求
+    pub fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// 导出 Prometheus 格式
     pub fn export_prometheus(&self) -> String {
         format!(
@@ -37,10 +70,30 @@ impl Metrics {
              feathergate_requests_successful {}\n\
              # HELP feathergate_requests_failed Failed requests\n\
              # TYPE feathergate_requests_failed counter\n\
-             feathergate_requests_failed {}\n",
+             feathergate_requests_failed {}\n\
+             # HELP feathergate_auth_failures_total Requests rejected by authentication\n\
+             # TYPE feathergate_auth_failures_total counter\n\
+             feathergate_auth_failures_total {}\n\
+             # HELP feathergate_images_generated_total Generated images\n\
+             # TYPE feathergate_images_generated_total counter\n\
+             feathergate_images_generated_total {}\n\
+             # HELP feathergate_image_cost_usd_total Image generation cost in USD\n\
+             # TYPE feathergate_image_cost_usd_total counter\n\
+             feathergate_image_cost_usd_total {}\n\
+             # HELP feathergate_spend_usd_total Total spend in USD\n\
+             # TYPE feathergate_spend_usd_total counter\n\
+             feathergate_spend_usd_total {}\n\
+             # HELP feathergate_rate_limited_total Requests rejected by RPM/TPM limits\n\
+             # TYPE feathergate_rate_limited_total counter\n\
+             feathergate_rate_limited_total {}\n",
             self.total_requests.load(Ordering::Relaxed),
             self.successful_requests.load(Ordering::Relaxed),
-            self.failed_requests.load(Ordering::Relaxed)
+            self.failed_requests.load(Ordering::Relaxed),
+            self.auth_failures.load(Ordering::Relaxed),
+            self.images_generated.load(Ordering::Relaxed),
+            self.image_cost_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+            self.spend_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+            self.rate_limited.load(Ordering::Relaxed)
         )
     }
 }
@@ -80,4 +133,25 @@ mod tests {
         assert!(output.contains("feathergate_requests_successful 1"));
         assert!(output.contains("feathergate_requests_failed 1"));
     }
+
+    #[test]
+    fn test_record_auth_failure() {
+        let metrics = Metrics::new();
+        metrics.record_auth_failure();
+
+        let output = metrics.export_prometheus();
+        assert!(output.contains("feathergate_requests_failed 1"));
+        assert!(output.contains("feathergate_auth_failures_total 1"));
+    }
+
+    #[test]
+    fn test_record_images() {
+        let metrics = Metrics::new();
+        metrics.record_images(2, 0.08);
+        metrics.record_images(1, 0.04);
+
+        let output = metrics.export_prometheus();
+        assert!(output.contains("feathergate_images_generated_total 3"));
+        assert!(output.contains("feathergate_image_cost_usd_total 0.12"));
+    }
 }
@@ -1,6 +1,8 @@
 use crate::config::{parse_model_string, ModelConfig};
-use crate::error::FeatherGateError;
-use crate::types::{ChatRequest, ChatResponse, Choice, Message, Usage};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::providers::send_with_key;
+use crate::types::messages::{MessagesRequest, MessagesResponse};
+use crate::types::{ChatRequest, ChatResponse, Choice, Message, Stop, Usage};
 use crate::Result;
 use futures_util::Stream;
 use hyper::body::Bytes;
@@ -33,6 +35,8 @@ struct AnthropicRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
 }
 
@@ -64,9 +68,11 @@ struct ContentBlock {
     text: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct AnthropicUsage {
+    #[serde(default)]
     input_tokens: u32,
+    #[serde(default)]
     output_tokens: u32,
 }
 
@@ -95,16 +101,18 @@ enum AnthropicEvent {
         index: u32,
     },
     #[serde(rename = "message_delta")]
-    MessageDelta { delta: MessageDeltaData },
+    MessageDelta {
+        delta: MessageDeltaData,
+        /// 累计输出 token 数
+        #[serde(default)]
+        usage: Option<AnthropicUsage>,
+    },
     #[serde(rename = "message_stop")]
     MessageStop,
     #[serde(rename = "ping")]
     Ping,
     #[serde(rename = "error")]
-    Error {
-        #[allow(dead_code)]
-        error: ErrorData,
-    },
+    Error { error: ErrorData },
 }
 
 #[derive(Debug, Deserialize)]
@@ -112,6 +120,8 @@ struct MessageStartData {
     id: String,
     #[allow(dead_code)]
     model: String,
+    #[serde(default)]
+    usage: AnthropicUsage,
 }
 
 #[derive(Debug, Deserialize)]
@@ -138,9 +148,16 @@ struct MessageDeltaData {
     stop_reason: Option<String>,
 }
 
+/// Anthropic API 错误格式
+#[derive(Debug, Deserialize)]
+struct AnthropicErrorResponse {
+    error: ErrorData,
+}
+
 #[derive(Debug, Deserialize)]
 struct ErrorData {
-    #[allow(dead_code)]
+    #[serde(rename = "type", default)]
+    error_type: String,
     message: String,
 }
 
@@ -167,6 +184,7 @@ fn convert_request(req: &ChatRequest, model_id: &str) -> AnthropicRequest {
         system: system_message,
         max_tokens: req.max_tokens.unwrap_or(1024),
         temperature: req.temperature,
+        stop_sequences: req.stop.as_ref().map(Stop::sequences),
         stream: None,
     }
 }
@@ -217,19 +235,10 @@ fn convert_response(resp: AnthropicResponse) -> ChatResponse {
     }
 }
 
-/// 转发请求到 Anthropic
-pub async fn forward_request(
-    config: &ModelConfig,
-    req: &ChatRequest,
-) -> Result<ChatResponse> {
+/// 发送请求到 Anthropic Messages API，非 2xx 响应归一化为错误
+async fn post_json<T: Serialize + ?Sized>(config: &ModelConfig, body: &T) -> Result<reqwest::Response> {
     let client = get_http_client();
 
-    // 解析模型 ID（使用统一的解析函数）
-    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
-
-    // 转换请求
-    let anthropic_req = convert_request(req, &model_id);
-
     // 构建 URL
     let api_base = if config.litellm_params.api_base.is_empty() {
         "https://api.anthropic.com"
@@ -239,31 +248,29 @@ pub async fn forward_request(
     let url = format!("{}/v1/messages", api_base.trim_end_matches('/'));
 
     // 发送请求
-    let response = client
-        .post(&url)
-        .header("x-api-key", &config.litellm_params.api_key)
-        .header("anthropic-version", "2023-06-01")
-        .header("Content-Type", "application/json")
-        .json(&anthropic_req)
-        .send()
-        .await?;
-
-    // 检查状态码
-    let status = response.status();
-    if !status.is_success() {
-        // 限制错误响应体大小，防止 DoS 攻击
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_default()
-            .chars()
-            .take(4096)
-            .collect::<String>();
-        return Err(FeatherGateError::upstream(
-            status.as_u16(),
-            format!("Anthropic API 错误: {}", error_body),
-        ));
-    }
+    send_with_key(config, parse_error, |api_key| {
+        client
+            .post(&url)
+            .header("x-api-key", api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("Content-Type", "application/json")
+            .json(body)
+    })
+    .await
+}
+
+/// 转发请求到 Anthropic
+pub async fn forward_request(
+    config: &ModelConfig,
+    req: &ChatRequest,
+) -> Result<ChatResponse> {
+    // 解析模型 ID（使用统一的解析函数）
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+
+    // 转换请求
+    let anthropic_req = convert_request(req, &model_id);
+
+    let response = post_json(config, &anthropic_req).await?;
 
     // 解析响应
     let anthropic_resp: AnthropicResponse = response.json().await?;
@@ -275,47 +282,13 @@ pub async fn forward_request_stream(
     config: &ModelConfig,
     req: &ChatRequest,
 ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
-    let client = get_http_client();
-
     // 解析模型 ID
     let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
 
     // 转换为流式请求
     let anthropic_req = convert_request_stream(req, &model_id);
 
-    // 构建 URL
-    let api_base = if config.litellm_params.api_base.is_empty() {
-        "https://api.anthropic.com"
-    } else {
-        &config.litellm_params.api_base
-    };
-    let url = format!("{}/v1/messages", api_base.trim_end_matches('/'));
-
-    // 发送请求
-    let response = client
-        .post(&url)
-        .header("x-api-key", &config.litellm_params.api_key)
-        .header("anthropic-version", "2023-06-01")
-        .header("Content-Type", "application/json")
-        .json(&anthropic_req)
-        .send()
-        .await?;
-
-    // 检查状态码
-    let status = response.status();
-    if !status.is_success() {
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_default()
-            .chars()
-            .take(4096)
-            .collect::<String>();
-        return Err(FeatherGateError::upstream(
-            status.as_u16(),
-            format!("Anthropic API 错误: {}", error_body),
-        ));
-    }
+    let response = post_json(config, &anthropic_req).await?;
 
     // 创建 SSE 转换流
     let model_id_owned = model_id.clone();
@@ -324,6 +297,41 @@ pub async fn forward_request_stream(
     Ok(Box::pin(stream))
 }
 
+/// 转发原生 Messages API 请求到 Anthropic（直接 passthrough，使用部署的真实模型 ID）
+pub async fn forward_messages(
+    config: &ModelConfig,
+    req: &MessagesRequest,
+) -> Result<MessagesResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = MessagesRequest {
+        model: model_id,
+        ..req.clone()
+    };
+    let response = post_json(config, &upstream_req).await?;
+
+    Ok(response.json().await?)
+}
+
+/// 转发流式原生 Messages API 请求到 Anthropic，事件流原样返回
+pub async fn forward_messages_stream(
+    config: &ModelConfig,
+    req: &MessagesRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    use futures_util::StreamExt;
+
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = MessagesRequest {
+        model: model_id,
+        ..req.clone()
+    };
+    let response = post_json(config, &upstream_req).await?;
+
+    let stream = response
+        .bytes_stream()
+        .map(|result| result.map_err(FeatherGateError::HttpError));
+    Ok(Box::pin(stream))
+}
+
 /// 创建 Anthropic SSE 转换流
 fn create_anthropic_stream(
     response: reqwest::Response,
@@ -333,40 +341,52 @@ fn create_anthropic_stream(
 
     // 状态变量
     let mut message_id = String::new();
+    let mut input_tokens = 0;
     let mut buffer = String::new();
 
-    response.bytes_stream().filter_map(move |result| {
-        let output = match result {
-            Ok(bytes) => {
-                buffer.push_str(&String::from_utf8_lossy(&bytes));
-                process_sse_buffer(&mut buffer, &mut message_id, &model_id)
-            }
-            Err(e) => Some(Err(FeatherGateError::HttpError(e))),
-        };
-        std::future::ready(output)
-    })
+    response
+        .bytes_stream()
+        .map(move |result| {
+            let outputs = match result {
+                Ok(bytes) => {
+                    buffer.push_str(&String::from_utf8_lossy(&bytes));
+                    process_sse_buffer(&mut buffer, &mut message_id, &mut input_tokens, &model_id)
+                }
+                Err(e) => vec![Err(FeatherGateError::HttpError(e))],
+            };
+            futures_util::stream::iter(outputs)
+        })
+        .flatten()
 }
 
-/// 处理 SSE 缓冲区，提取完整事件
+/// 处理 SSE 缓冲区，提取所有完整事件
 fn process_sse_buffer(
     buffer: &mut String,
     message_id: &mut String,
+    input_tokens: &mut u32,
     model_id: &str,
-) -> Option<Result<Bytes>> {
+) -> Vec<Result<Bytes>> {
+    let mut outputs = Vec::new();
+
     // 查找完整的 SSE 事件（以 \n\n 结尾）
     while let Some(pos) = buffer.find("\n\n") {
         let event_str = buffer[..pos].to_string();
         *buffer = buffer[pos + 2..].to_string();
 
-        if let Some(chunk) = parse_sse_event(&event_str, message_id, model_id) {
-            return Some(Ok(chunk));
+        if let Some(output) = parse_sse_event(&event_str, message_id, input_tokens, model_id) {
+            outputs.push(output);
         }
     }
-    None
+    outputs
 }
 
 /// 解析单个 SSE 事件并转换为 OpenAI 格式
-fn parse_sse_event(event_str: &str, message_id: &mut String, model_id: &str) -> Option<Bytes> {
+fn parse_sse_event(
+    event_str: &str,
+    message_id: &mut String,
+    input_tokens: &mut u32,
+    model_id: &str,
+) -> Option<Result<Bytes>> {
     // 提取 data 行
     let mut data_line = None;
     for line in event_str.lines() {
@@ -380,48 +400,102 @@ fn parse_sse_event(event_str: &str, message_id: &mut String, model_id: &str) ->
     // 解析 JSON
     let event: AnthropicEvent = serde_json::from_str(data).ok()?;
 
-    convert_event_to_openai(event, message_id, model_id)
+    convert_event_to_openai(event, message_id, input_tokens, model_id)
 }
 
 /// 将 Anthropic 事件转换为 OpenAI SSE 格式
+///
+/// message_start 中的输入 token 数保存在 `input_tokens`，收到 message_delta 的用量时
+/// 输出一个 OpenAI 格式的用量数据块（`choices` 为空），供计费读取。
 fn convert_event_to_openai(
     event: AnthropicEvent,
     message_id: &mut String,
+    input_tokens: &mut u32,
     model_id: &str,
-) -> Option<Bytes> {
+) -> Option<Result<Bytes>> {
     match event {
         AnthropicEvent::MessageStart { message } => {
             *message_id = message.id;
+            *input_tokens = message.usage.input_tokens;
             None // 不输出，等待内容
         }
         AnthropicEvent::ContentBlockDelta { delta, .. } => {
             if let DeltaData::TextDelta { text } = delta {
                 let chunk = create_openai_chunk(message_id, model_id, Some(&text), None);
-                Some(Bytes::from(chunk))
+                Some(Ok(Bytes::from(chunk)))
             } else {
                 None
             }
         }
-        AnthropicEvent::MessageDelta { delta } => {
+        AnthropicEvent::MessageDelta { delta, usage } => {
             let finish = delta.stop_reason.map(|r| match r.as_str() {
                 "end_turn" => "stop",
                 "max_tokens" => "length",
                 _ => "stop",
             });
+            let mut chunk = String::new();
             if finish.is_some() {
-                let chunk = create_openai_chunk(message_id, model_id, None, finish);
-                Some(Bytes::from(chunk))
-            } else {
-                None
+                chunk.push_str(&create_openai_chunk(message_id, model_id, None, finish));
             }
+            if let Some(usage) = usage {
+                let input = usage.input_tokens.max(*input_tokens);
+                chunk.push_str(&create_usage_chunk(message_id, model_id, input, usage.output_tokens));
+            }
+            (!chunk.is_empty()).then(|| Ok(Bytes::from(chunk)))
         }
         AnthropicEvent::MessageStop => {
-            Some(Bytes::from("data: [DONE]\n\n"))
+            Some(Ok(Bytes::from("data: [DONE]\n\n")))
         }
+        AnthropicEvent::Error { error } => Some(Err(convert_error(error, None))),
         _ => None, // 忽略其他事件
     }
 }
 
+/// 将 Anthropic 错误类型映射为 HTTP 状态码
+fn error_status(error_type: &str) -> u16 {
+    match error_type {
+        "invalid_request_error" => 400,
+        "authentication_error" => 401,
+        "permission_error" => 403,
+        "not_found_error" => 404,
+        "request_too_large" => 413,
+        "rate_limit_error" => 429,
+        "overloaded_error" => 529,
+        _ => 500,
+    }
+}
+
+/// 归一化 Anthropic 错误（status 为空时按错误类型推断，用于流中的 error 事件）
+fn convert_error(error: ErrorData, status: Option<u16>) -> FeatherGateError {
+    let status = status.unwrap_or_else(|| error_status(&error.error_type));
+    let kind = match error.error_type.as_str() {
+        "authentication_error" => UpstreamErrorKind::Authentication,
+        "permission_error" => UpstreamErrorKind::PermissionDenied,
+        "not_found_error" => UpstreamErrorKind::NotFound,
+        // 请求体字节数超限，换上下文更大的模型也一样会失败
+        "request_too_large" => UpstreamErrorKind::BadRequest,
+        "rate_limit_error" => UpstreamErrorKind::RateLimited,
+        "overloaded_error" => UpstreamErrorKind::Overloaded,
+        "api_error" => UpstreamErrorKind::ServerError,
+        _ => UpstreamErrorKind::from_status(status),
+    }
+    .refine_by_message(&error.message);
+
+    FeatherGateError::upstream_with_kind(
+        status,
+        kind,
+        format!("Anthropic: {}", error.message),
+    )
+}
+
+/// 解析 Anthropic 错误响应并归一化
+pub(crate) fn parse_error(status: u16, body: &str) -> FeatherGateError {
+    match serde_json::from_str::<AnthropicErrorResponse>(body) {
+        Ok(resp) => convert_error(resp.error, Some(status)),
+        Err(_) => FeatherGateError::upstream(status, format!("Anthropic: {}", body)),
+    }
+}
+
 /// 创建 OpenAI 格式的 SSE chunk
 fn create_openai_chunk(
     id: &str,
@@ -452,6 +526,26 @@ fn create_openai_chunk(
     )
 }
 
+/// 创建只包含用量的 OpenAI SSE 数据块（与 `stream_options.include_usage` 的格式一致）
+fn create_usage_chunk(id: &str, model: &str, input_tokens: u32, output_tokens: u32) -> String {
+    let created = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    format!(
+        r#"data: {{"id":"{}","object":"chat.completion.chunk","created":{},"model":"{}","choices":[],"usage":{{"prompt_tokens":{},"completion_tokens":{},"total_tokens":{}}}}}
+
+"#,
+        id,
+        created,
+        model,
+        input_tokens,
+        output_tokens,
+        input_tokens + output_tokens
+    )
+}
+
 /// 转义 JSON 字符串
 fn escape_json(s: &str) -> String {
     s.replace('\\', "\\\\")
@@ -478,7 +572,9 @@ mod tests {
                 model: "anthropic/claude-opus-4-5".to_string(),
                 api_key: "sk-ant-test".to_string(),
                 api_base: api_base.to_string(),
+                ..Default::default()
             },
+            ..Default::default()
         }
     }
 
@@ -493,7 +589,10 @@ mod tests {
             temperature: Some(0.7),
             max_tokens: Some(100),
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: Some(Stop::Single("END".to_string())),
+            user: None,
         };
 
         let anthropic_req = convert_request(&req, "claude-opus-4-5");
@@ -504,6 +603,7 @@ mod tests {
         assert_eq!(anthropic_req.messages[0].role, "user");
         assert_eq!(anthropic_req.max_tokens, 100);
         assert_eq!(anthropic_req.temperature, Some(0.7));
+        assert_eq!(anthropic_req.stop_sequences, Some(vec!["END".to_string()]));
     }
 
     #[test]
@@ -514,7 +614,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let anthropic_req = convert_request(&req, "claude-opus-4-5");
@@ -556,6 +659,82 @@ mod tests {
         assert_eq!(openai_resp.usage.as_ref().unwrap().total_tokens, 30);
     }
 
+    #[test]
+    fn test_stream_reports_usage_chunk() {
+        let mut buffer = String::from(
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude-opus-4-5\",\"usage\":{\"input_tokens\":12,\"output_tokens\":1}}}\n\n\
+             event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":34}}\n\n",
+        );
+        let mut message_id = String::new();
+        let mut input_tokens = 0;
+
+        let outputs = process_sse_buffer(&mut buffer, &mut message_id, &mut input_tokens, "claude-opus-4-5");
+
+        assert_eq!(outputs.len(), 1);
+        let text = String::from_utf8(outputs[0].as_ref().unwrap().to_vec()).unwrap();
+        let events: Vec<serde_json::Value> = text
+            .split("\n\n")
+            .filter_map(|event| event.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["choices"][0]["finish_reason"], "stop");
+        assert_eq!(events[1]["choices"], serde_json::json!([]));
+        assert_eq!(events[1]["usage"]["prompt_tokens"], 12);
+        assert_eq!(events[1]["usage"]["completion_tokens"], 34);
+        assert_eq!(events[1]["usage"]["total_tokens"], 46);
+    }
+
+    #[test]
+    fn test_stream_error_event_is_surfaced() {
+        let mut buffer = String::from(
+            "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n\
+             event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n",
+        );
+        let mut message_id = "msg_1".to_string();
+
+        let outputs = process_sse_buffer(&mut buffer, &mut message_id, &mut 0, "claude-opus-4-5");
+
+        assert_eq!(outputs.len(), 2);
+        assert!(outputs[0].is_ok());
+        match &outputs[1] {
+            Err(FeatherGateError::UpstreamError { status, kind, message }) => {
+                assert_eq!(*status, 529);
+                assert_eq!(*kind, UpstreamErrorKind::Overloaded);
+                assert!(message.contains("Overloaded"));
+            }
+            other => panic!("Expected UpstreamError, got {:?}", other),
+        }
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_classification() {
+        let err = parse_error(
+            529,
+            r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::Overloaded));
+
+        let err = parse_error(
+            429,
+            r#"{"type": "error", "error": {"type": "rate_limit_error", "message": "Number of request tokens has exceeded your per-minute rate limit"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::RateLimited));
+
+        let err = parse_error(
+            400,
+            r#"{"type": "error", "error": {"type": "invalid_request_error", "message": "prompt is too long: 215000 tokens > 200000 maximum"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::ContextWindowExceeded));
+
+        let err = parse_error(
+            413,
+            r#"{"type": "error", "error": {"type": "request_too_large", "message": "Request exceeds the maximum allowed number of bytes."}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::BadRequest));
+    }
+
     #[tokio::test]
     async fn test_forward_request_success() {
         let mut server = setup_mock_server().await;
@@ -592,7 +771,10 @@ mod tests {
             temperature: Some(0.7),
             max_tokens: Some(100),
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let result = forward_request(&config, &req).await;
@@ -624,7 +806,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let result = forward_request(&config, &req).await;
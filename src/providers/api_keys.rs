@@ -0,0 +1,681 @@
+use crate::config::{ApiKeyStrategy, Config, ModelConfig};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// key 认证失败或额度耗尽后默认停用的时间
+const DEFAULT_DISABLE_TIME: Duration = Duration::from_secs(60);
+
+/// 停用时间和上游报告的额度恢复时间的上限（响应头的值由上游决定）
+pub(crate) const MAX_KEY_WAIT: Duration = Duration::from_secs(86_400);
+
+/// 统计 key 用量的时间窗口
+const USAGE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 上游报告剩余额度的响应头（OpenAI 风格, Anthropic 风格）
+const REMAINING_REQUESTS_HEADERS: [&str; 2] = [
+    "x-ratelimit-remaining-requests",
+    "anthropic-ratelimit-requests-remaining",
+];
+const REMAINING_TOKENS_HEADERS: [&str; 2] = [
+    "x-ratelimit-remaining-tokens",
+    "anthropic-ratelimit-tokens-remaining",
+];
+/// 额度恢复时间：OpenAI 为 `6m0s` 这样的时长，Anthropic 为 RFC 3339 时间，`retry-after` 为秒数
+const RESET_HEADERS: [&str; 5] = [
+    "x-ratelimit-reset-requests",
+    "x-ratelimit-reset-tokens",
+    "anthropic-ratelimit-requests-reset",
+    "anthropic-ratelimit-tokens-reset",
+    "retry-after",
+];
+
+/// 上游响应头中报告的 key 额度
+#[derive(Debug, Clone, Default, PartialEq)]
+struct UpstreamLimits {
+    remaining_requests: Option<u64>,
+    remaining_tokens: Option<u64>,
+    /// 额度恢复的时间（多个响应头时取最晚的一个）
+    reset_at: Option<Instant>,
+}
+
+impl UpstreamLimits {
+    /// 从响应头解析，没有任何限额响应头时返回 None
+    fn from_headers(headers: &HeaderMap, now: Instant) -> Option<Self> {
+        let header = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| headers.get(*name).and_then(|value| value.to_str().ok()))
+        };
+        let limits = UpstreamLimits {
+            remaining_requests: header(&REMAINING_REQUESTS_HEADERS).and_then(|value| value.trim().parse().ok()),
+            remaining_tokens: header(&REMAINING_TOKENS_HEADERS).and_then(|value| value.trim().parse().ok()),
+            reset_at: RESET_HEADERS
+                .iter()
+                .filter_map(|name| headers.get(*name)?.to_str().ok())
+                .filter_map(parse_reset)
+                .max()
+                .and_then(|reset| now.checked_add(reset)),
+        };
+        (limits != UpstreamLimits::default()).then_some(limits)
+    }
+
+    /// 上游报告额度已用完，且尚未恢复
+    fn exhausted(&self, now: Instant) -> bool {
+        let empty = self.remaining_requests == Some(0) || self.remaining_tokens == Some(0);
+        empty && self.reset_at.is_some_and(|reset_at| reset_at > now)
+    }
+}
+
+/// 单个上游 key 的状态
+#[derive(Debug)]
+struct KeyState {
+    disabled_until: Option<Instant>,
+    disabled_reason: Option<&'static str>,
+    window_start: Instant,
+    /// 当前统计窗口内的请求数
+    window_requests: u64,
+    upstream: UpstreamLimits,
+}
+
+impl KeyState {
+    fn new(now: Instant) -> Self {
+        KeyState {
+            disabled_until: None,
+            disabled_reason: None,
+            window_start: now,
+            window_requests: 0,
+            upstream: UpstreamLimits::default(),
+        }
+    }
+
+    fn is_disabled(&self, now: Instant) -> bool {
+        self.disabled_until.is_some_and(|until| until > now)
+    }
+
+    /// 最近一个统计窗口内的请求数
+    fn recent_requests(&self, now: Instant) -> u64 {
+        match now.duration_since(self.window_start) < USAGE_WINDOW {
+            true => self.window_requests,
+            false => 0,
+        }
+    }
+
+    fn count_request(&mut self, now: Instant) {
+        if now.duration_since(self.window_start) >= USAGE_WINDOW {
+            self.window_start = now;
+            self.window_requests = 0;
+        }
+        self.window_requests += 1;
+    }
+}
+
+/// key 状态的索引：同一上游（api_base，未配置时为 provider）的同一个 key 共享状态
+type StateKey = (String, String);
+
+/// 部署的索引：model_name 和上游，同名模型的多个部署各自有 key 池
+type DeploymentId = (String, String);
+
+#[derive(Default)]
+struct PoolState {
+    /// 部署 → 下一次轮询的位置
+    cursors: HashMap<DeploymentId, usize>,
+    keys: HashMap<StateKey, KeyState>,
+}
+
+/// 部署的上游 key 池
+///
+/// 一个部署可以配置多个上游 key（`api_key` 和 `api_keys`），每次请求按 `api_key_strategy` 选择一个：
+/// 轮询，或选最近一分钟请求数最少的 key。认证失败（401）或额度耗尽的 key 在 `api_key_disable_time` 内不再使用，
+/// 上游响应头报告额度用完的 key 在恢复之前优先跳过。重新加载配置后 key 列表立即替换，无需重启。
+#[derive(Default)]
+pub struct KeyPool {
+    /// 部署 → 重新加载配置后的 key 列表（覆盖启动时的配置）
+    overrides: RwLock<HashMap<DeploymentId, Vec<String>>>,
+    state: Mutex<PoolState>,
+}
+
+/// 一次请求选中的上游 key，请求完成后用于回报结果
+pub struct KeyLease<'a> {
+    pool: &'a KeyPool,
+    model_name: String,
+    state_key: StateKey,
+    /// key 池的大小，只有一个 key 时不停用（没有可替换的 key）
+    pool_size: usize,
+    disable_time: Duration,
+}
+
+impl KeyLease<'_> {
+    /// 选中的 key
+    pub fn key(&self) -> &str {
+        &self.state_key.1
+    }
+
+    /// 记录上游响应头中报告的额度
+    pub fn observe(&self, headers: &HeaderMap) {
+        let now = Instant::now();
+        if let Some(limits) = UpstreamLimits::from_headers(headers, now) {
+            let mut state = self.pool.lock_state();
+            let key_state = state
+                .keys
+                .entry(self.state_key.clone())
+                .or_insert_with(|| KeyState::new(now));
+            key_state.upstream = limits;
+        }
+    }
+
+    /// 记录上游错误：认证失败或额度耗尽时暂时停用该 key，返回是否停用（池中还有其他 key 可以重试）
+    pub fn record_error(&self, err: &FeatherGateError) -> bool {
+        let reason = match err.upstream_kind() {
+            Some(UpstreamErrorKind::Authentication) => "authentication",
+            Some(UpstreamErrorKind::QuotaExceeded) => "quota_exceeded",
+            _ => return false,
+        };
+        if self.pool_size < 2 {
+            return false;
+        }
+
+        let now = Instant::now();
+        let mut state = self.pool.lock_state();
+        let key_state = state
+            .keys
+            .entry(self.state_key.clone())
+            .or_insert_with(|| KeyState::new(now));
+        key_state.disabled_until = now.checked_add(self.disable_time);
+        key_state.disabled_reason = Some(reason);
+        warn!(
+            "模型 {} 的上游 key {} 不可用（{}），停用 {} 秒",
+            self.model_name,
+            mask_key(self.key()),
+            reason,
+            self.disable_time.as_secs_f64()
+        );
+        true
+    }
+}
+
+/// 一个部署的 key 池状态（管理 API 展示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentKeys {
+    pub model_name: String,
+    /// 部署的上游：api_base，未配置时为 provider
+    pub upstream: String,
+    pub api_key_strategy: ApiKeyStrategy,
+    pub keys: Vec<KeyStatus>,
+}
+
+/// 单个上游 key 的状态（key 已脱敏）
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyStatus {
+    pub key: String,
+    pub disabled_until: Option<DateTime<Utc>>,
+    pub disabled_reason: Option<&'static str>,
+    /// 最近一分钟的请求数
+    pub recent_requests: u64,
+    pub upstream_remaining_requests: Option<u64>,
+    pub upstream_remaining_tokens: Option<u64>,
+    pub upstream_reset_at: Option<DateTime<Utc>>,
+}
+
+impl KeyPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为一次请求选择部署的上游 key
+    ///
+    /// 所有 key 都被停用时返回 DeploymentCoolingDown，由路由层回退到其他模型。
+    pub fn acquire(&self, config: &ModelConfig) -> Result<KeyLease<'_>> {
+        let params = &config.litellm_params;
+        let keys = self.keys_for(config);
+        let upstream = upstream_id(config);
+        let now = Instant::now();
+        let mut state = self.lock_state();
+        let PoolState { cursors, keys: states } = &mut *state;
+
+        let status = |key: &String| {
+            let state = states.get(&(upstream.clone(), key.clone()));
+            let disabled = state.is_some_and(|s| s.is_disabled(now));
+            let exhausted = state.is_some_and(|s| s.upstream.exhausted(now));
+            (disabled, exhausted)
+        };
+        // 优先使用未停用且上游额度未用完的 key；都用完时仍然尝试（上游可能已经恢复）
+        let mut candidates: Vec<usize> = (0..keys.len())
+            .filter(|&i| status(&keys[i]) == (false, false))
+            .collect();
+        if candidates.is_empty() {
+            candidates = (0..keys.len()).filter(|&i| !status(&keys[i]).0).collect();
+        }
+        if candidates.is_empty() {
+            return Err(FeatherGateError::DeploymentCoolingDown(config.model_name.clone()));
+        }
+
+        let chosen = match params.api_key_strategy {
+            ApiKeyStrategy::RoundRobin => {
+                let cursor = cursors.entry(deployment_id(config)).or_default();
+                let chosen = (0..keys.len())
+                    .map(|offset| (*cursor + offset) % keys.len())
+                    .find(|i| candidates.contains(i))
+                    .unwrap_or(candidates[0]);
+                *cursor = chosen + 1;
+                chosen
+            }
+            ApiKeyStrategy::LeastUsage => candidates
+                .iter()
+                .copied()
+                .min_by_key(|&i| {
+                    states
+                        .get(&(upstream.clone(), keys[i].clone()))
+                        .map_or(0, |s| s.recent_requests(now))
+                })
+                .unwrap_or(candidates[0]),
+        };
+
+        let state_key = (upstream, keys[chosen].clone());
+        states
+            .entry(state_key.clone())
+            .or_insert_with(|| KeyState::new(now))
+            .count_request(now);
+        let disable_time = params
+            .api_key_disable_time
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .map_or(DEFAULT_DISABLE_TIME, |disable_time| disable_time.min(MAX_KEY_WAIT));
+        Ok(KeyLease {
+            pool: self,
+            model_name: config.model_name.clone(),
+            state_key,
+            pool_size: keys.len(),
+            disable_time,
+        })
+    }
+
+    /// 用新配置替换各部署的 key 列表（不影响正在进行的请求），并清理不再使用的 key 的状态
+    pub fn reload(&self, config: &Config) {
+        let mut pools: HashMap<DeploymentId, Vec<String>> = HashMap::new();
+        for model in &config.model_list {
+            pools
+                .entry(deployment_id(model))
+                .or_insert_with(|| model.litellm_params.key_pool());
+        }
+
+        let mut overrides = self.overrides.write().unwrap_or_else(|e| e.into_inner());
+        for ((model_name, upstream), keys) in &pools {
+            if let Some(old) = overrides.get(&(model_name.clone(), upstream.clone())) {
+                let added = keys.iter().filter(|key| !old.contains(key)).count();
+                let removed = old.iter().filter(|key| !keys.contains(key)).count();
+                if added > 0 || removed > 0 {
+                    info!(
+                        "模型 {}（{}）的上游 key 已更新：新增 {} 个，移除 {} 个",
+                        model_name, upstream, added, removed
+                    );
+                }
+            }
+        }
+        *overrides = pools;
+
+        let mut state = self.lock_state();
+        state
+            .keys
+            .retain(|(_, key), _| overrides.values().any(|keys| keys.contains(key)));
+    }
+
+    /// 各部署的 key 池状态
+    pub fn status(&self, config: &Config) -> Vec<DeploymentKeys> {
+        let now = Instant::now();
+        let wall_clock = |at: Instant| Utc::now() + chrono::Duration::from_std(at.saturating_duration_since(now)).unwrap_or_default();
+        let state = self.lock_state();
+
+        let mut deployments: Vec<DeploymentKeys> = Vec::new();
+        for model in &config.model_list {
+            let upstream = upstream_id(model);
+            if deployments
+                .iter()
+                .any(|d| d.model_name == model.model_name && d.upstream == upstream)
+            {
+                continue;
+            }
+            let keys = self
+                .keys_for(model)
+                .iter()
+                .map(|key| {
+                    let key_state = state.keys.get(&(upstream.clone(), key.clone()));
+                    let disabled_until = key_state
+                        .and_then(|s| s.disabled_until)
+                        .filter(|until| *until > now);
+                    KeyStatus {
+                        key: mask_key(key),
+                        disabled_until: disabled_until.map(wall_clock),
+                        disabled_reason: key_state.and_then(|s| disabled_until.and(s.disabled_reason)),
+                        recent_requests: key_state.map_or(0, |s| s.recent_requests(now)),
+                        upstream_remaining_requests: key_state.and_then(|s| s.upstream.remaining_requests),
+                        upstream_remaining_tokens: key_state.and_then(|s| s.upstream.remaining_tokens),
+                        upstream_reset_at: key_state
+                            .and_then(|s| s.upstream.reset_at)
+                            .filter(|reset_at| *reset_at > now)
+                            .map(wall_clock),
+                    }
+                })
+                .collect();
+            deployments.push(DeploymentKeys {
+                model_name: model.model_name.clone(),
+                upstream,
+                api_key_strategy: model.litellm_params.api_key_strategy,
+                keys,
+            });
+        }
+        deployments
+    }
+
+    /// 部署当前的 key 列表：重新加载过配置时使用新的列表
+    fn keys_for(&self, config: &ModelConfig) -> Vec<String> {
+        let overrides = self.overrides.read().unwrap_or_else(|e| e.into_inner());
+        match overrides.get(&deployment_id(config)) {
+            Some(keys) if !keys.is_empty() => keys.clone(),
+            _ => config.litellm_params.key_pool(),
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 上游标识：api_base，未配置时使用 provider 的默认地址
+fn upstream_id(config: &ModelConfig) -> String {
+    let params = &config.litellm_params;
+    match params.api_base.as_str() {
+        "" => params.model.split('/').next().unwrap_or_default().to_string(),
+        api_base => api_base.to_string(),
+    }
+}
+
+fn deployment_id(config: &ModelConfig) -> DeploymentId {
+    (config.model_name.clone(), upstream_id(config))
+}
+
+/// 脱敏后的上游 key（`sk-...abcd`），用于日志和管理 API
+fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "...".to_string();
+    }
+    let prefix: String = chars[..3].iter().collect();
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", prefix, suffix)
+}
+
+/// 解析额度恢复时间：RFC 3339 时间、秒数，或 `1m30s`、`120ms` 这样的时长
+///
+/// 超过 MAX_KEY_WAIT 的值按 MAX_KEY_WAIT 处理，负数和无法表示的值忽略。
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        let reset = (at.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or_default();
+        return Some(reset.min(MAX_KEY_WAIT));
+    }
+    if let Ok(seconds) = value.parse::<f64>() {
+        return clamp_wait(seconds);
+    }
+
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let unit_start = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let amount: f64 = rest[..unit_start].parse().ok()?;
+        let unit_len = rest[unit_start..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - unit_start);
+        let seconds = match &rest[unit_start..unit_start + unit_len] {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        total += amount * seconds;
+        rest = &rest[unit_start + unit_len..];
+    }
+    clamp_wait(total)
+}
+
+/// 秒数转换为不超过 MAX_KEY_WAIT 的时长（NaN 和负数返回 None）
+fn clamp_wait(seconds: f64) -> Option<Duration> {
+    if seconds.is_nan() {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds.min(MAX_KEY_WAIT.as_secs_f64())).ok()
+}
+
+/// 全局上游 key 池
+pub fn key_pool() -> &'static KeyPool {
+    static POOL: Lazy<KeyPool> = Lazy::new(KeyPool::new);
+    &POOL
+}
+
+/// 重新读取配置文件（重新解析其中的密钥引用），替换各部署的上游 key
+///
+/// 其他配置的修改需要重启；配置文件有错误时继续使用原有的 key。
+async fn reload_from(path: PathBuf) {
+    match tokio::task::spawn_blocking(move || Config::from_file(path)).await {
+        Ok(Ok(config)) => key_pool().reload(&config),
+        Ok(Err(e)) => warn!("重新加载配置失败，继续使用原有的上游 key: {}", e),
+        Err(e) => warn!("重新加载配置的任务异常退出: {}", e),
+    }
+}
+
+/// 收到 SIGHUP 时重新加载上游 key
+pub fn reload_on_sighup(path: PathBuf) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                warn!("设置 SIGHUP 信号处理失败，上游 key 不能热替换: {}", e);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            info!("收到 SIGHUP 信号，重新加载上游 key");
+            reload_from(path.clone()).await;
+        }
+    });
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+/// 按 `general_settings.api_key_refresh_interval` 定期重新加载上游 key（轮换后的密钥无需重启即可生效）
+pub fn reload_every(path: PathBuf, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // 第一次 tick 立即完成，启动时已经加载过
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            reload_from(path.clone()).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LitellmParams;
+    use reqwest::header::HeaderValue;
+
+    fn deployment(keys: &[&str], strategy: ApiKeyStrategy) -> ModelConfig {
+        ModelConfig {
+            model_name: "gpt-4o".to_string(),
+            litellm_params: LitellmParams {
+                model: "openai/gpt-4o".to_string(),
+                api_key: keys[0].to_string(),
+                api_keys: keys[1..].iter().map(|key| key.to_string()).collect(),
+                api_key_strategy: strategy,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn upstream_error(status: u16) -> FeatherGateError {
+        FeatherGateError::upstream(status, "")
+    }
+
+    #[test]
+    fn test_round_robin_skips_disabled_keys() {
+        let pool = KeyPool::new();
+        let config = deployment(&["sk-a", "sk-b", "sk-c"], ApiKeyStrategy::RoundRobin);
+
+        let picked: Vec<String> = (0..4).map(|_| pool.acquire(&config).unwrap().key().to_string()).collect();
+        assert_eq!(picked, ["sk-a", "sk-b", "sk-c", "sk-a"]);
+
+        // 401 的 key 暂时停用，其他错误不影响
+        pool.acquire(&config).unwrap().record_error(&upstream_error(401));
+        pool.acquire(&config).unwrap().record_error(&upstream_error(500));
+        let picked: Vec<String> = (0..3).map(|_| pool.acquire(&config).unwrap().key().to_string()).collect();
+        assert_eq!(picked, ["sk-a", "sk-c", "sk-a"]);
+
+        let status = pool.status(&Config {
+            model_list: vec![config.clone()],
+            ..Default::default()
+        });
+        assert_eq!(status[0].keys[1].disabled_reason, Some("authentication"));
+        assert!(status[0].keys[1].disabled_until.is_some());
+
+        // 所有 key 都停用时部署进入冷却，由路由层回退
+        for _ in 0..2 {
+            pool.acquire(&config).unwrap().record_error(&upstream_error(401));
+        }
+        assert!(matches!(
+            pool.acquire(&config),
+            Err(FeatherGateError::DeploymentCoolingDown(model)) if model == "gpt-4o"
+        ));
+    }
+
+    #[test]
+    fn test_single_key_is_never_disabled() {
+        let pool = KeyPool::new();
+        let config = deployment(&["sk-only"], ApiKeyStrategy::RoundRobin);
+        pool.acquire(&config).unwrap().record_error(&upstream_error(401));
+        assert_eq!(pool.acquire(&config).unwrap().key(), "sk-only");
+    }
+
+    #[test]
+    fn test_least_usage_and_upstream_headers() {
+        let pool = KeyPool::new();
+        let config = deployment(&["sk-a", "sk-b"], ApiKeyStrategy::LeastUsage);
+
+        let picked: Vec<String> = (0..4).map(|_| pool.acquire(&config).unwrap().key().to_string()).collect();
+        assert_eq!(picked, ["sk-a", "sk-b", "sk-a", "sk-b"]);
+
+        // 上游报告 sk-a 的额度用完，恢复之前跳过
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining-requests", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset-requests", HeaderValue::from_static("1m30s"));
+        let lease = pool.acquire(&config).unwrap();
+        assert_eq!(lease.key(), "sk-a");
+        lease.observe(&headers);
+        for _ in 0..3 {
+            assert_eq!(pool.acquire(&config).unwrap().key(), "sk-b");
+        }
+        let status = pool.status(&Config {
+            model_list: vec![config],
+            ..Default::default()
+        });
+        assert_eq!(status[0].keys[0].key, "...");
+        assert_eq!(status[0].keys[0].upstream_remaining_requests, Some(0));
+        assert!(status[0].keys[0].upstream_reset_at.is_some());
+    }
+
+    #[test]
+    fn test_reload_swaps_keys() {
+        let pool = KeyPool::new();
+        let old = deployment(&["sk-leaked-0001", "sk-spare-0002"], ApiKeyStrategy::RoundRobin);
+        let new = deployment(&["sk-spare-0002", "sk-fresh-0003"], ApiKeyStrategy::RoundRobin);
+        pool.reload(&Config {
+            model_list: vec![old.clone()],
+            ..Default::default()
+        });
+        assert_eq!(pool.acquire(&old).unwrap().key(), "sk-leaked-0001");
+
+        pool.reload(&Config {
+            model_list: vec![new],
+            ..Default::default()
+        });
+        // 正在运行的配置仍然是旧的，key 列表已替换
+        let picked: Vec<String> = (0..2).map(|_| pool.acquire(&old).unwrap().key().to_string()).collect();
+        assert_eq!(picked, ["sk-fresh-0003", "sk-spare-0002"]);
+        assert!(!pool.lock_state().keys.keys().any(|(_, key)| key == "sk-leaked-0001"));
+    }
+
+    #[test]
+    fn test_reload_keeps_keys_per_deployment() {
+        let pool = KeyPool::new();
+        let primary = deployment(&["sk-primary-0001"], ApiKeyStrategy::RoundRobin);
+        let mut secondary = deployment(&["sk-secondary-0001"], ApiKeyStrategy::RoundRobin);
+        secondary.litellm_params.api_base = "https://backup.example.com/v1".to_string();
+
+        let mut rotated = secondary.clone();
+        rotated.litellm_params.api_key = "sk-secondary-0002".to_string();
+        let config = Config {
+            model_list: vec![primary.clone(), rotated],
+            ..Default::default()
+        };
+        pool.reload(&config);
+
+        // 同名模型的两个部署各自使用自己的 key
+        assert_eq!(pool.acquire(&primary).unwrap().key(), "sk-primary-0001");
+        assert_eq!(pool.acquire(&secondary).unwrap().key(), "sk-secondary-0002");
+
+        let status = pool.status(&config);
+        assert_eq!(status.len(), 2);
+        assert_eq!(status[1].upstream, "https://backup.example.com/v1");
+    }
+
+    #[test]
+    fn test_oversized_reset_is_clamped() {
+        assert_eq!(parse_reset("1e30"), Some(MAX_KEY_WAIT));
+        assert_eq!(parse_reset("inf"), Some(MAX_KEY_WAIT));
+        assert_eq!(parse_reset("NaN"), None);
+        assert_eq!(parse_reset("99999999999999999999999h"), Some(MAX_KEY_WAIT));
+        assert_eq!(parse_reset("9999-12-31T23:59:59Z"), Some(MAX_KEY_WAIT));
+
+        let now = Instant::now();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining-requests", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset-requests", HeaderValue::from_static("1e300"));
+        let limits = UpstreamLimits::from_headers(&headers, now).unwrap();
+        assert_eq!(limits.reset_at, now.checked_add(MAX_KEY_WAIT));
+        assert!(limits.exhausted(now));
+
+        // 停用时间超出范围时同样不会溢出
+        let pool = KeyPool::new();
+        let mut config = deployment(&["sk-a", "sk-b"], ApiKeyStrategy::RoundRobin);
+        config.litellm_params.api_key_disable_time = Some(1e300);
+        assert!(pool.acquire(&config).unwrap().record_error(&upstream_error(401)));
+        assert_eq!(pool.acquire(&config).unwrap().key(), "sk-b");
+    }
+
+    #[test]
+    fn test_parse_reset() {
+        assert_eq!(parse_reset("1m30s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_reset("120ms"), Some(Duration::from_millis(120)));
+        assert_eq!(parse_reset("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset("20"), Some(Duration::from_secs(20)));
+        assert_eq!(parse_reset("2000-01-01T00:00:00Z"), Some(Duration::ZERO));
+        assert!(parse_reset("2999-01-01T00:00:00Z").unwrap() > Duration::from_secs(3600));
+        assert_eq!(parse_reset("soon"), None);
+        assert_eq!(parse_reset("-5"), None);
+        assert_eq!(mask_key("sk-proj-abcdef123456"), "sk-...3456");
+    }
+}
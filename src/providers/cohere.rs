@@ -0,0 +1,331 @@
+use crate::config::{parse_model_string, ModelConfig};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::providers::{embeddings, send_with_key};
+use crate::types::embeddings::{EmbeddingRequest, EmbeddingResponse};
+use crate::types::rerank::{RerankRequest, RerankResponse, RerankUsage};
+use crate::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 获取全局 HTTP 客户端
+fn get_http_client() -> &'static Client {
+    use once_cell::sync::Lazy;
+    static CLIENT: Lazy<Client> = Lazy::new(|| {
+        Client::builder()
+            .timeout(Duration::from_secs(60))
+            .pool_max_idle_per_host(10)
+            .build()
+            .unwrap()
+    });
+    &CLIENT
+}
+
+/// Cohere API 错误格式
+#[derive(Debug, Deserialize)]
+struct CohereErrorResponse {
+    message: String,
+}
+
+/// 解析 Cohere 错误响应并归一化
+pub(crate) fn parse_error(status: u16, body: &str) -> FeatherGateError {
+    let message = serde_json::from_str::<CohereErrorResponse>(body)
+        .map(|resp| resp.message)
+        .unwrap_or_else(|_| body.to_string());
+
+    let kind = match status {
+        // 试用 key 的调用额度耗尽
+        402 => UpstreamErrorKind::QuotaExceeded,
+        _ => UpstreamErrorKind::from_status(status).refine_by_message(&message),
+    };
+
+    FeatherGateError::upstream_with_kind(status, kind, format!("Cohere: {}", message))
+}
+
+/// 发送 JSON 请求到 Cohere，非 2xx 响应归一化为错误
+async fn post_json<T: Serialize + ?Sized>(
+    config: &ModelConfig,
+    path: &str,
+    body: &T,
+) -> Result<reqwest::Response> {
+    let client = get_http_client();
+
+    let api_base = if config.litellm_params.api_base.is_empty() {
+        "https://api.cohere.com"
+    } else {
+        &config.litellm_params.api_base
+    };
+    let url = format!("{}/{}", api_base.trim_end_matches('/'), path);
+
+    // 发送请求
+    send_with_key(config, parse_error, |api_key| {
+        client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("Content-Type", "application/json")
+            .json(body)
+    })
+    .await
+}
+
+/// embed 单次请求的最大条数
+const EMBED_BATCH_SIZE: usize = 96;
+
+/// Cohere v2 embed 请求
+#[derive(Debug, Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    texts: &'a [&'a str],
+    input_type: &'static str,
+    embedding_types: [&'static str; 1],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimension: Option<u32>,
+}
+
+/// Cohere v2 embed 响应
+#[derive(Debug, Deserialize)]
+struct EmbedResponse {
+    embeddings: EmbedVectors,
+    #[serde(default)]
+    meta: Option<EmbedMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedVectors {
+    #[serde(default)]
+    float: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedMeta {
+    billed_units: Option<BilledUnits>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BilledUnits {
+    #[serde(default)]
+    input_tokens: u32,
+}
+
+/// 转发向量嵌入请求到 Cohere（v2 embed，超过上限时自动分批）
+///
+/// OpenAI 接口没有 `input_type` 的概念，统一按 `search_document` 处理。
+pub async fn forward_embedding(
+    config: &ModelConfig,
+    req: &EmbeddingRequest,
+) -> Result<EmbeddingResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let texts = embeddings::input_texts(req)?;
+
+    let (vectors, prompt_tokens) =
+        embeddings::embed_in_batches(&texts, EMBED_BATCH_SIZE, |chunk| {
+            let embed_req = EmbedRequest {
+                model: &model_id,
+                texts: chunk,
+                input_type: "search_document",
+                embedding_types: ["float"],
+                output_dimension: req.dimensions,
+            };
+            async move {
+                let response = post_json(config, "v2/embed", &embed_req).await?;
+                let resp: EmbedResponse = response.json().await?;
+                let tokens = resp
+                    .meta
+                    .and_then(|meta| meta.billed_units)
+                    .map(|units| units.input_tokens)
+                    .unwrap_or_default();
+                Ok((resp.embeddings.float, tokens))
+            }
+        })
+        .await?;
+
+    Ok(EmbeddingResponse::new(
+        model_id,
+        vectors,
+        req.encoding_format.unwrap_or_default(),
+        prompt_tokens,
+    ))
+}
+
+/// Cohere v2 rerank 请求
+#[derive(Debug, Serialize)]
+struct CohereRerankRequest<'a> {
+    model: &'a str,
+    query: &'a str,
+    documents: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_n: Option<u32>,
+}
+
+/// Cohere v2 rerank 响应
+#[derive(Debug, Deserialize)]
+struct CohereRerankResponse {
+    results: Vec<CohereRerankResult>,
+    #[serde(default)]
+    meta: Option<RerankMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CohereRerankResult {
+    index: u32,
+    relevance_score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankMeta {
+    billed_units: Option<RerankBilledUnits>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RerankBilledUnits {
+    #[serde(default)]
+    search_units: u32,
+}
+
+/// 转发重排序请求到 Cohere（v2 rerank）
+///
+/// v2 接口不再返回文档内容，`return_documents` 由网关根据请求中的文档补全。
+pub async fn forward_rerank(config: &ModelConfig, req: &RerankRequest) -> Result<RerankResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+
+    let rerank_req = CohereRerankRequest {
+        model: &model_id,
+        query: &req.query,
+        documents: req.document_texts(),
+        top_n: req.top_n,
+    };
+    let response = post_json(config, "v2/rerank", &rerank_req).await?;
+    let resp: CohereRerankResponse = response.json().await?;
+
+    let scores = resp
+        .results
+        .into_iter()
+        .map(|result| (result.index, result.relevance_score))
+        .collect();
+    let usage = RerankUsage {
+        total_tokens: 0,
+        search_units: resp
+            .meta
+            .and_then(|meta| meta.billed_units)
+            .map(|units| units.search_units),
+    };
+
+    Ok(RerankResponse::new(model_id, req, scores, usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LitellmParams;
+    use crate::types::embeddings::{EmbeddingInput, EmbeddingVector};
+    use mockito::Server;
+
+    fn create_test_config(api_base: &str) -> ModelConfig {
+        ModelConfig {
+            model_name: "cohere-embed".to_string(),
+            litellm_params: LitellmParams {
+                model: "cohere/embed-english-v3.0".to_string(),
+                api_key: "co-test-key".to_string(),
+                api_base: api_base.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forward_embedding() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v2/embed")
+            .match_header("authorization", "Bearer co-test-key")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "embed-english-v3.0",
+                "texts": ["hello", "world"],
+                "input_type": "search_document",
+                "embedding_types": ["float"]
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "emb-1",
+                "embeddings": {"float": [[0.1, 0.2], [0.3, 0.4]]},
+                "texts": ["hello", "world"],
+                "meta": {"billed_units": {"input_tokens": 4}}
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let config = create_test_config(&server.url());
+        let req = EmbeddingRequest {
+            model: "cohere-embed".to_string(),
+            input: EmbeddingInput::TextBatch(vec!["hello".to_string(), "world".to_string()]),
+            dimensions: None,
+            encoding_format: None,
+            user: None,
+        };
+
+        let response = forward_embedding(&config, &req).await.unwrap();
+        assert_eq!(response.model, "embed-english-v3.0");
+        assert_eq!(response.data[1].embedding, EmbeddingVector::Float(vec![0.3, 0.4]));
+        assert_eq!(response.data[1].index, 1);
+        assert_eq!(response.usage.total_tokens, 4);
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_forward_rerank() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v2/rerank")
+            .match_header("authorization", "Bearer co-test-key")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "rerank-v3.5",
+                "query": "capital of France",
+                "documents": ["Berlin", "Paris"],
+                "top_n": 1
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "rr-1",
+                "results": [{"index": 1, "relevance_score": 0.98}],
+                "meta": {"billed_units": {"search_units": 1}}
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.litellm_params.model = "cohere/rerank-v3.5".to_string();
+        let req: RerankRequest = serde_json::from_value(serde_json::json!({
+            "model": "cohere-rerank",
+            "query": "capital of France",
+            "documents": ["Berlin", "Paris"],
+            "top_n": 1,
+            "return_documents": true
+        }))
+        .unwrap();
+
+        let response = forward_rerank(&config, &req).await.unwrap();
+        assert_eq!(response.model, "rerank-v3.5");
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].index, 1);
+        assert_eq!(response.results[0].document.as_ref().unwrap().text, "Paris");
+        assert_eq!(response.usage.search_units, Some(1));
+
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_parse_error_classification() {
+        let err = parse_error(429, r#"{"message": "too many requests"}"#);
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::RateLimited));
+
+        let err = parse_error(402, r#"{"message": "trial key limit reached"}"#);
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::QuotaExceeded));
+        assert!(err.to_string().contains("Cohere: trial key limit reached"));
+    }
+}
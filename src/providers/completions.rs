@@ -0,0 +1,291 @@
+use crate::error::FeatherGateError;
+use crate::types::completions::{CompletionChoice, CompletionRequest, CompletionResponse};
+use crate::types::{ChatRequest, ChatResponse, ChatStreamChunk, Message, Usage, ValidationError};
+use crate::Result;
+use futures_util::{Stream, StreamExt};
+use hyper::body::Bytes;
+use std::pin::Pin;
+
+/// 将文本补全请求转换为聊天请求（每个 prompt 对应一个单条用户消息的请求）
+///
+/// `suffix` 和 `best_of` 只有 OpenAI 原生支持，转换时忽略；大于 1 的 `n` 会改变结果，无法转换时拒绝请求。
+pub fn to_chat_requests(req: &CompletionRequest) -> Result<Vec<ChatRequest>> {
+    if req.n.is_some_and(|n| n > 1) {
+        return Err(ValidationError::CompletionParameterUnsupported("n").into());
+    }
+
+    let prompts = req
+        .prompt
+        .texts()
+        .ok_or(FeatherGateError::Validation(ValidationError::TokenPromptUnsupported))?;
+
+    Ok(prompts
+        .into_iter()
+        .map(|prompt| ChatRequest {
+            model: req.model.clone(),
+            messages: vec![Message::user(prompt)],
+            temperature: req.temperature,
+            max_tokens: req.max_tokens,
+            stream: req.stream,
+            stream_options: None,
+            top_p: req.top_p,
+            stop: req.stop.clone(),
+            user: req.user.clone(),
+        })
+        .collect())
+}
+
+/// 将聊天响应合并为文本补全响应（每个 prompt 对应一个 choice）
+pub fn from_chat_responses(
+    req: &CompletionRequest,
+    responses: Vec<ChatResponse>,
+) -> CompletionResponse {
+    let prompts = req.prompt.texts().unwrap_or_default();
+    let echo = req.echo == Some(true);
+    let model = responses
+        .first()
+        .map(|resp| resp.model.clone())
+        .unwrap_or_else(|| req.model.clone());
+
+    let mut usage: Option<Usage> = None;
+    let mut choices = Vec::with_capacity(responses.len());
+
+    for (index, resp) in responses.into_iter().enumerate() {
+        if let Some(u) = resp.usage {
+            let total = usage.get_or_insert(Usage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+            });
+            total.prompt_tokens += u.prompt_tokens;
+            total.completion_tokens += u.completion_tokens;
+            total.total_tokens += u.total_tokens;
+        }
+
+        let choice = resp.choices.into_iter().next();
+        let mut text = choice
+            .as_ref()
+            .map(|c| c.message.content.clone())
+            .unwrap_or_default();
+        if echo {
+            text = format!("{}{}", prompts.get(index).copied().unwrap_or_default(), text);
+        }
+
+        choices.push(CompletionChoice {
+            text,
+            index: index as u32,
+            logprobs: None,
+            finish_reason: choice.and_then(|c| c.finish_reason),
+        });
+    }
+
+    CompletionResponse {
+        id: format!("cmpl-{}", uuid::Uuid::new_v4()),
+        object: "text_completion".to_string(),
+        created: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        model,
+        choices,
+        usage,
+    }
+}
+
+/// 将 OpenAI 聊天 SSE 流转换为文本补全 SSE 流
+///
+/// `echo` 不为空时，先输出一个包含 prompt 的数据块。
+pub fn chat_stream_to_completion(
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+    echo: Option<String>,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    let mut buffer = String::new();
+    let mut echo = echo;
+
+    let converted = stream
+        .map(move |result| {
+            let outputs = match result {
+                Ok(bytes) => {
+                    buffer.push_str(&String::from_utf8_lossy(&bytes));
+                    process_chat_buffer(&mut buffer, &mut echo)
+                }
+                Err(e) => vec![Err(e)],
+            };
+            futures_util::stream::iter(outputs)
+        })
+        .flatten();
+
+    Box::pin(converted)
+}
+
+/// 处理聊天 SSE 缓冲区，转换所有完整事件
+fn process_chat_buffer(buffer: &mut String, echo: &mut Option<String>) -> Vec<Result<Bytes>> {
+    let mut outputs = Vec::new();
+
+    while let Some(pos) = buffer.find("\n\n") {
+        let event = buffer[..pos].to_string();
+        *buffer = buffer[pos + 2..].to_string();
+
+        let Some(data) = event.lines().find_map(|line| line.strip_prefix("data: ")) else {
+            continue;
+        };
+
+        if data == "[DONE]" {
+            outputs.push(Ok(Bytes::from("data: [DONE]\n\n")));
+            continue;
+        }
+
+        let Ok(chunk) = serde_json::from_str::<ChatStreamChunk>(data) else {
+            continue;
+        };
+
+        if let Some(prompt) = echo.take() {
+            outputs.push(Ok(completion_chunk(&chunk, prompt, None)));
+        }
+
+        let choice = chunk.choices.first();
+        let text = choice
+            .and_then(|c| c.delta.content.clone())
+            .unwrap_or_default();
+        let finish_reason = choice.and_then(|c| c.finish_reason.clone());
+        if text.is_empty() && finish_reason.is_none() {
+            continue;
+        }
+        outputs.push(Ok(completion_chunk(&chunk, text, finish_reason)));
+    }
+    outputs
+}
+
+/// 创建文本补全格式的 SSE 数据块
+fn completion_chunk(chunk: &ChatStreamChunk, text: String, finish_reason: Option<String>) -> Bytes {
+    let completion = CompletionResponse {
+        id: chunk.id.clone(),
+        object: "text_completion".to_string(),
+        created: chunk.created,
+        model: chunk.model.clone(),
+        choices: vec![CompletionChoice {
+            text,
+            index: 0,
+            logprobs: None,
+            finish_reason,
+        }],
+        usage: None,
+    };
+    let json = serde_json::to_string(&completion).unwrap_or_default();
+    Bytes::from(format!("data: {}\n\n", json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::completions::Prompt;
+    use crate::types::Stop;
+
+    fn create_request(prompt: Prompt, echo: Option<bool>) -> CompletionRequest {
+        CompletionRequest {
+            model: "claude".to_string(),
+            prompt,
+            suffix: None,
+            max_tokens: Some(16),
+            temperature: Some(0.5),
+            top_p: None,
+            n: None,
+            stream: None,
+            stream_options: None,
+            echo,
+            best_of: None,
+            stop: None,
+            user: None,
+        }
+    }
+
+    #[test]
+    fn test_to_chat_requests() {
+        let req = create_request(
+            Prompt::TextBatch(vec!["a".to_string(), "b".to_string()]),
+            None,
+        );
+
+        let chat_reqs = to_chat_requests(&req).unwrap();
+        assert_eq!(chat_reqs.len(), 2);
+        assert_eq!(chat_reqs[1].messages, vec![Message::user("b")]);
+        assert_eq!(chat_reqs[0].max_tokens, Some(16));
+        assert_eq!(chat_reqs[0].temperature, Some(0.5));
+    }
+
+    #[test]
+    fn test_to_chat_requests_keeps_stop() {
+        let req: CompletionRequest =
+            serde_json::from_str(r#"{"model": "claude", "prompt": "a", "stop": "\n"}"#).unwrap();
+        let chat_reqs = to_chat_requests(&req).unwrap();
+        assert_eq!(chat_reqs[0].stop, Some(Stop::Single("\n".to_string())));
+    }
+
+    #[test]
+    fn test_to_chat_requests_rejects_tokens() {
+        let req = create_request(Prompt::Tokens(vec![1, 2]), None);
+        assert!(matches!(
+            to_chat_requests(&req),
+            Err(FeatherGateError::Validation(ValidationError::TokenPromptUnsupported))
+        ));
+    }
+
+    #[test]
+    fn test_to_chat_requests_rejects_n() {
+        let req = CompletionRequest {
+            n: Some(2),
+            ..create_request(Prompt::Text("a".to_string()), None)
+        };
+        let err = to_chat_requests(&req).unwrap_err();
+        assert!(matches!(
+            err,
+            FeatherGateError::Validation(ValidationError::CompletionParameterUnsupported("n"))
+        ));
+        assert_eq!(err.param(), Some("n"));
+
+        // n 为 1 时与默认行为相同
+        let req = CompletionRequest {
+            n: Some(1),
+            ..create_request(Prompt::Text("a".to_string()), None)
+        };
+        assert_eq!(to_chat_requests(&req).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_from_chat_responses_with_echo() {
+        let req = create_request(Prompt::Text("Hello".to_string()), Some(true));
+        let mut chat_resp = ChatResponse::simple("claude-opus-4-5", " world");
+        chat_resp.usage = Some(Usage {
+            prompt_tokens: 1,
+            completion_tokens: 2,
+            total_tokens: 3,
+        });
+
+        let resp = from_chat_responses(&req, vec![chat_resp]);
+        assert_eq!(resp.object, "text_completion");
+        assert_eq!(resp.choices[0].text, "Hello world");
+        assert_eq!(resp.choices[0].finish_reason, Some("stop".to_string()));
+        assert_eq!(resp.usage.unwrap().total_tokens, 3);
+    }
+
+    #[test]
+    fn test_process_chat_buffer() {
+        let mut buffer = String::from(
+            "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"m\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},\"finish_reason\":null}]}\n\n\
+             data: [DONE]\n\n",
+        );
+        let mut echo = Some("Say: ".to_string());
+
+        let outputs = process_chat_buffer(&mut buffer, &mut echo);
+        assert_eq!(outputs.len(), 3);
+
+        let first = String::from_utf8_lossy(outputs[0].as_ref().unwrap()).to_string();
+        assert!(first.contains("\"object\":\"text_completion\""));
+        assert!(first.contains("\"text\":\"Say: \""));
+
+        let second = String::from_utf8_lossy(outputs[1].as_ref().unwrap()).to_string();
+        assert!(second.contains("\"text\":\"Hi\""));
+        assert_eq!(outputs[2].as_ref().unwrap(), &Bytes::from("data: [DONE]\n\n"));
+        assert!(echo.is_none());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::config::RouterSettings;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// 多副本共享的冷却状态（有序集合：成员为 model_name，分数为冷却结束的 Unix 毫秒时间）
+const REDIS_KEY: &str = "feathergate:cooldowns";
+
+/// 统计失败次数的时间窗口
+const FAILURE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 部署冷却：一分钟内失败次数超过 `allowed_fails` 的部署在 `cooldown_time` 内不再接收请求
+#[derive(Default)]
+pub struct Cooldowns {
+    state: Mutex<CooldownState>,
+}
+
+#[derive(Default)]
+struct CooldownState {
+    /// 最近一分钟内的失败时间
+    failures: HashMap<String, Vec<Instant>>,
+    /// 冷却结束时间（Unix 毫秒）
+    until: HashMap<String, i64>,
+    /// 尚未同步到 Redis 的冷却
+    pending: Vec<(String, i64)>,
+}
+
+impl Cooldowns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 部署是否正在冷却
+    pub fn is_cooling_down(&self, model_name: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .until
+            .get(model_name)
+            .is_some_and(|until| *until > Utc::now().timestamp_millis())
+    }
+
+    /// 记录一次失败；超过允许的失败次数时开始冷却，返回是否开始冷却
+    pub fn record_failure(&self, model_name: &str, settings: &RouterSettings) -> bool {
+        let Some(cooldown_time) = settings.cooldown_time else {
+            return false;
+        };
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        let failures = state.failures.entry(model_name.to_string()).or_default();
+        failures.retain(|failed_at| now.duration_since(*failed_at) < FAILURE_WINDOW);
+        failures.push(now);
+        if failures.len() <= settings.allowed_fails as usize {
+            return false;
+        }
+
+        state.failures.remove(model_name);
+        let until = Utc::now().timestamp_millis() + (cooldown_time * 1000.0) as i64;
+        state.until.insert(model_name.to_string(), until);
+        state.pending.push((model_name.to_string(), until));
+        warn!("模型 {} 一分钟内连续失败，冷却 {} 秒", model_name, cooldown_time);
+        true
+    }
+
+    /// 与 Redis 同步：上报本副本开始的冷却，并读取其他副本的冷却
+    pub(crate) async fn sync(&self, conn: &mut ConnectionManager) -> redis::RedisResult<()> {
+        let pending = std::mem::take(&mut self.state.lock().unwrap().pending);
+        let now = Utc::now().timestamp_millis();
+
+        let mut pipe = redis::pipe();
+        for (model_name, until) in &pending {
+            pipe.cmd("ZADD").arg(REDIS_KEY).arg("GT").arg(until).arg(model_name).ignore();
+        }
+        pipe.cmd("ZREMRANGEBYSCORE").arg(REDIS_KEY).arg("-inf").arg(now).ignore();
+        pipe.cmd("ZRANGE").arg(REDIS_KEY).arg(0).arg(-1).arg("WITHSCORES");
+
+        let (shared,): (Vec<(String, f64)>,) = match pipe.query_async(conn).await {
+            Ok(shared) => shared,
+            Err(e) => {
+                // 下次同步时重新上报
+                let mut state = self.state.lock().unwrap();
+                state.pending.splice(0..0, pending);
+                return Err(e);
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.until.retain(|_, until| *until > now);
+        for (model_name, until) in shared {
+            let local = state.until.entry(model_name).or_default();
+            *local = (*local).max(until as i64);
+        }
+        Ok(())
+    }
+}
+
+/// 全局部署冷却状态
+pub fn cooldowns() -> &'static Cooldowns {
+    static COOLDOWNS: Lazy<Cooldowns> = Lazy::new(Cooldowns::new);
+    &COOLDOWNS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_after_allowed_fails() {
+        let cooldowns = Cooldowns::new();
+        let settings = RouterSettings {
+            cooldown_time: Some(30.0),
+            allowed_fails: 1,
+            ..Default::default()
+        };
+
+        assert!(!cooldowns.record_failure("gpt-4", &settings));
+        assert!(!cooldowns.is_cooling_down("gpt-4"));
+        assert!(cooldowns.record_failure("gpt-4", &settings));
+        assert!(cooldowns.is_cooling_down("gpt-4"));
+        assert!(!cooldowns.is_cooling_down("claude"));
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_shared_across_replicas() {
+        let Some(mut conn) = crate::cluster::test_connection().await else {
+            return;
+        };
+        let replica_a = Cooldowns::new();
+        let replica_b = Cooldowns::new();
+        let model = uuid::Uuid::new_v4().to_string();
+        let settings = RouterSettings {
+            cooldown_time: Some(30.0),
+            ..Default::default()
+        };
+
+        assert!(replica_a.record_failure(&model, &settings));
+        replica_a.sync(&mut conn).await.unwrap();
+        assert!(!replica_b.is_cooling_down(&model));
+        replica_b.sync(&mut conn).await.unwrap();
+        assert!(replica_b.is_cooling_down(&model));
+    }
+
+    #[test]
+    fn test_cooldown_disabled_without_cooldown_time() {
+        let cooldowns = Cooldowns::new();
+        let settings = RouterSettings::default();
+        for _ in 0..5 {
+            assert!(!cooldowns.record_failure("gpt-4", &settings));
+        }
+        assert!(!cooldowns.is_cooling_down("gpt-4"));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::error::FeatherGateError;
+use crate::types::embeddings::EmbeddingRequest;
+use crate::types::ValidationError;
+use crate::Result;
+use futures_util::{StreamExt, TryStreamExt};
+use std::future::Future;
+
+/// 单次上游请求返回的向量和 token 用量
+pub(crate) type EmbeddingBatch = (Vec<Vec<f32>>, u32);
+
+/// 同时进行的上游分批请求数上限（所有分批共用同一个 key）
+const MAX_CONCURRENT_BATCHES: usize = 4;
+
+/// 文本形式的 input 列表（非 OpenAI provider 不支持 token 数组）
+pub fn input_texts(req: &EmbeddingRequest) -> Result<Vec<&str>> {
+    req.input
+        .texts()
+        .ok_or(FeatherGateError::Validation(ValidationError::TokenInputUnsupported))
+}
+
+/// 按上游单次请求的上限分批请求（最多 `MAX_CONCURRENT_BATCHES` 个并发），并按输入顺序合并结果
+pub(crate) async fn embed_in_batches<'a, F, Fut>(
+    texts: &'a [&'a str],
+    batch_size: usize,
+    embed: F,
+) -> Result<EmbeddingBatch>
+where
+    F: Fn(&'a [&'a str]) -> Fut,
+    Fut: Future<Output = Result<EmbeddingBatch>>,
+{
+    let requests: Vec<_> = texts
+        .chunks(batch_size)
+        .map(|chunk| {
+            let request = embed(chunk);
+            async move {
+                let (vectors, tokens) = request.await?;
+                if vectors.len() != chunk.len() {
+                    return Err(FeatherGateError::EmbeddingCountMismatch(vectors.len(), chunk.len()));
+                }
+                Ok((vectors, tokens))
+            }
+        })
+        .collect();
+    let batches: Vec<EmbeddingBatch> = futures_util::stream::iter(requests)
+        .buffered(MAX_CONCURRENT_BATCHES)
+        .try_collect()
+        .await?;
+
+    let mut vectors = Vec::with_capacity(texts.len());
+    let mut prompt_tokens = 0;
+    for (batch, tokens) in batches {
+        vectors.extend(batch);
+        prompt_tokens += tokens;
+    }
+    Ok((vectors, prompt_tokens))
+}
+
+/// 上游不返回 token 用量时按约 4 个字符一个 token 估算，用于计费和 TPM
+pub(crate) fn estimate_tokens(texts: &[&str]) -> u32 {
+    texts
+        .iter()
+        .map(|text| text.chars().count().div_ceil(4) as u32)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_embed_in_batches_preserves_order() {
+        let texts = ["a", "bb", "ccc", "dddd", "eeeee"];
+        let calls = AtomicUsize::new(0);
+
+        let (vectors, tokens) = embed_in_batches(&texts, 2, |chunk| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let vectors = chunk.iter().map(|t| vec![t.len() as f32]).collect();
+                Ok((vectors, chunk.len() as u32))
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(vectors, vec![vec![1.0], vec![2.0], vec![3.0], vec![4.0], vec![5.0]]);
+        assert_eq!(tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn test_embed_in_batches_limits_concurrency() {
+        let texts = vec!["a"; 20];
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        embed_in_batches(&texts, 1, |chunk| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok((vec![vec![0.0]; chunk.len()], 0))
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(peak.load(Ordering::SeqCst), MAX_CONCURRENT_BATCHES);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(&["abcd", "abcde", ""]), 3);
+    }
+
+    #[tokio::test]
+    async fn test_embed_in_batches_rejects_count_mismatch() {
+        let texts = ["a", "b"];
+        let result = embed_in_batches(&texts, 10, |_| async { Ok((vec![vec![0.0]], 0)) }).await;
+        assert!(matches!(result, Err(FeatherGateError::EmbeddingCountMismatch(1, 2))));
+    }
+}
@@ -1,6 +1,9 @@
 use crate::config::{parse_model_string, ModelConfig};
-use crate::error::FeatherGateError;
-use crate::types::{ChatRequest, ChatResponse, Choice, Message, Usage};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::providers::{embeddings, send_with_key};
+use crate::types::embeddings::{EmbeddingRequest, EmbeddingResponse};
+use crate::types::images::{ImageData, ImageGenerationRequest, ImageResponse};
+use crate::types::{ChatRequest, ChatResponse, ChatStreamChunk, Choice, Message, Stop, Usage, ValidationError};
 use crate::Result;
 use futures_util::Stream;
 use hyper::body::Bytes;
@@ -22,26 +25,37 @@ fn get_http_client() -> &'static Client {
     &CLIENT
 }
 
-/// Gemini API 请求格式
-#[derive(Debug, Serialize)]
+/// Gemini API 请求格式（也用于解析原生 generateContent 请求）
+#[derive(Debug, Serialize, Deserialize)]
 struct GeminiRequest {
     contents: Vec<GeminiContent>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "generationConfig")]
     generation_config: Option<GenerationConfig>,
+    /// 原生请求中的 system 指令（出站请求把 system 合并到第一条用户消息，不使用该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "systemInstruction")]
+    system_instruction: Option<GeminiContent>,
+    /// 原生请求中的 tools（只用于判断能否转换）
+    #[serde(default, skip_serializing)]
+    tools: Option<Vec<serde_json::Value>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct GeminiContent {
+    #[serde(default)]
     role: String,
     parts: Vec<GeminiPart>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct GeminiPart {
+    #[serde(default)]
     text: String,
+    /// 原生请求中的 inlineData、functionCall 等非文本部分（只用于判断能否转换）
+    #[serde(flatten, skip_serializing)]
+    other: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -50,14 +64,25 @@ struct GenerationConfig {
     max_output_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
 }
 
 /// Gemini API 响应格式
 #[derive(Debug, Deserialize)]
 struct GeminiResponse {
+    #[serde(default)]
     candidates: Vec<Candidate>,
     #[serde(rename = "usageMetadata")]
     usage_metadata: Option<UsageMetadata>,
+    #[serde(rename = "promptFeedback")]
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PromptFeedback {
+    #[serde(rename = "blockReason")]
+    block_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -85,6 +110,97 @@ struct UsageMetadata {
     total_token_count: u32,
 }
 
+/// Gemini API 错误格式
+#[derive(Debug, Deserialize)]
+struct GeminiErrorResponse {
+    error: GeminiErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiErrorBody {
+    code: u16,
+    message: String,
+    #[serde(default)]
+    status: Option<String>,
+    /// google.rpc 错误详情，ErrorInfo 中的 `reason` 区分 key 无效等情况
+    #[serde(default)]
+    details: Vec<serde_json::Value>,
+}
+
+impl GeminiErrorBody {
+    fn has_reason(&self, reasons: &[&str]) -> bool {
+        self.details
+            .iter()
+            .filter_map(|detail| detail["reason"].as_str())
+            .any(|reason| reasons.contains(&reason))
+    }
+
+    /// RESOURCE_EXHAUSTED 既用于每分钟限流，也用于额度（计费）用完，只能按消息区分
+    fn is_quota_exhausted(&self) -> bool {
+        let message = self.message.to_lowercase();
+        ["exceeded your current quota", "check your plan and billing"]
+            .iter()
+            .any(|pattern| message.contains(pattern))
+    }
+}
+
+/// 归一化 Gemini 错误
+///
+/// 无效或过期的 API key 返回 400 INVALID_ARGUMENT（reason 为 `API_KEY_INVALID`），归为认证失败，
+/// 以便 key 池停用该 key 并换下一个 key 重试。
+fn convert_error(error: GeminiErrorBody) -> FeatherGateError {
+    let kind = match error.status.as_deref() {
+        _ if error.has_reason(&["API_KEY_INVALID", "API_KEY_EXPIRED"]) => UpstreamErrorKind::Authentication,
+        Some("RESOURCE_EXHAUSTED") if error.is_quota_exhausted() => UpstreamErrorKind::QuotaExceeded,
+        Some("INVALID_ARGUMENT") | Some("FAILED_PRECONDITION") => UpstreamErrorKind::BadRequest,
+        Some("UNAUTHENTICATED") => UpstreamErrorKind::Authentication,
+        Some("PERMISSION_DENIED") => UpstreamErrorKind::PermissionDenied,
+        Some("NOT_FOUND") => UpstreamErrorKind::NotFound,
+        Some("RESOURCE_EXHAUSTED") => UpstreamErrorKind::RateLimited,
+        Some("DEADLINE_EXCEEDED") => UpstreamErrorKind::Timeout,
+        Some("UNAVAILABLE") => UpstreamErrorKind::Overloaded,
+        Some("INTERNAL") => UpstreamErrorKind::ServerError,
+        _ => UpstreamErrorKind::from_status(error.code),
+    }
+    .refine_by_message(&error.message);
+
+    FeatherGateError::upstream_with_kind(
+        error.code,
+        kind,
+        format!("Gemini: {}", error.message),
+    )
+}
+
+/// 解析 Gemini 错误响应并归一化
+pub(crate) fn parse_error(status: u16, body: &str) -> FeatherGateError {
+    match serde_json::from_str::<GeminiErrorResponse>(body) {
+        Ok(resp) => convert_error(GeminiErrorBody {
+            code: status,
+            ..resp.error
+        }),
+        Err(_) => FeatherGateError::upstream(status, format!("Gemini: {}", body)),
+    }
+}
+
+/// 提示词被安全策略拦截时的错误
+fn blocked_error(block_reason: &str) -> FeatherGateError {
+    FeatherGateError::upstream_with_kind(
+        400,
+        UpstreamErrorKind::ContentPolicyViolation,
+        format!("Gemini: prompt blocked ({})", block_reason),
+    )
+}
+
+/// 转换 Gemini finishReason 为 OpenAI 格式
+fn convert_finish_reason(reason: &str) -> &str {
+    match reason {
+        "STOP" => "stop",
+        "MAX_TOKENS" => "length",
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => "content_filter",
+        _ => "stop",
+    }
+}
+
 /// 转换 OpenAI 请求为 Gemini 格式
 fn convert_request(req: &ChatRequest) -> GeminiRequest {
     let mut contents = Vec::new();
@@ -110,7 +226,10 @@ fn convert_request(req: &ChatRequest) -> GeminiRequest {
 
             contents.push(GeminiContent {
                 role: role.to_string(),
-                parts: vec![GeminiPart { text }],
+                parts: vec![GeminiPart {
+                    text,
+                    other: Default::default(),
+                }],
             });
         }
     }
@@ -118,11 +237,13 @@ fn convert_request(req: &ChatRequest) -> GeminiRequest {
     let generation_config = if req.temperature.is_some()
         || req.max_tokens.is_some()
         || req.top_p.is_some()
+        || req.stop.is_some()
     {
         Some(GenerationConfig {
             temperature: req.temperature,
             max_output_tokens: req.max_tokens,
             top_p: req.top_p,
+            stop_sequences: req.stop.as_ref().map(Stop::sequences),
         })
     } else {
         None
@@ -131,11 +252,21 @@ fn convert_request(req: &ChatRequest) -> GeminiRequest {
     GeminiRequest {
         contents,
         generation_config,
+        system_instruction: None,
+        tools: None,
     }
 }
 
 /// 转换 Gemini 响应为 OpenAI 格式
 fn convert_response(resp: GeminiResponse, model: &str) -> Result<ChatResponse> {
+    if let Some(reason) = resp
+        .prompt_feedback
+        .as_ref()
+        .and_then(|feedback| feedback.block_reason.as_deref())
+    {
+        return Err(blocked_error(reason));
+    }
+
     let candidate = resp
         .candidates
         .into_iter()
@@ -152,11 +283,9 @@ fn convert_response(resp: GeminiResponse, model: &str) -> Result<ChatResponse> {
         .join("");
 
     // 转换 finish_reason
-    let finish_reason = candidate.finish_reason.map(|reason| match reason.as_str() {
-        "STOP" => "stop".to_string(),
-        "MAX_TOKENS" => "length".to_string(),
-        _ => reason,
-    });
+    let finish_reason = candidate
+        .finish_reason
+        .map(|reason| convert_finish_reason(&reason).to_string());
 
     let usage = resp.usage_metadata.map(|meta| Usage {
         prompt_tokens: meta.prompt_token_count,
@@ -181,19 +310,17 @@ fn convert_response(resp: GeminiResponse, model: &str) -> Result<ChatResponse> {
     })
 }
 
-/// 转发请求到 Gemini
-pub async fn forward_request(
+/// 发送 JSON 请求到 Gemini 模型方法（如 `generateContent`），非 2xx 响应归一化为错误
+async fn post_json<T: Serialize + ?Sized>(
     config: &ModelConfig,
-    req: &ChatRequest,
-) -> Result<ChatResponse> {
+    method: &str,
+    body: &T,
+) -> Result<reqwest::Response> {
     let client = get_http_client();
 
     // 解析模型 ID（使用统一的解析函数）
     let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
 
-    // 转换请求
-    let gemini_req = convert_request(req);
-
     // 构建 URL（不在 URL 中暴露 API 密钥）
     let api_base = if config.litellm_params.api_base.is_empty() {
         "https://generativelanguage.googleapis.com"
@@ -201,36 +328,33 @@ pub async fn forward_request(
         &config.litellm_params.api_base
     };
     let url = format!(
-        "{}/v1beta/models/{}:generateContent",
+        "{}/v1beta/models/{}:{}",
         api_base.trim_end_matches('/'),
-        model_id
+        model_id,
+        method
     );
 
     // 发送请求（通过 HTTP 头传递 API 密钥）
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .header("x-goog-api-key", &config.litellm_params.api_key)
-        .json(&gemini_req)
-        .send()
-        .await?;
+    send_with_key(config, parse_error, |api_key| {
+        client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .header("x-goog-api-key", api_key)
+            .json(body)
+    })
+    .await
+}
 
-    // 检查状态码
-    let status = response.status();
-    if !status.is_success() {
-        // 限制错误响应体大小，防止 DoS 攻击
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_default()
-            .chars()
-            .take(4096)
-            .collect::<String>();
-        return Err(FeatherGateError::upstream(
-            status.as_u16(),
-            format!("Gemini API 错误: {}", error_body),
-        ));
-    }
+/// 转发请求到 Gemini
+pub async fn forward_request(
+    config: &ModelConfig,
+    req: &ChatRequest,
+) -> Result<ChatResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+
+    // 转换请求
+    let gemini_req = convert_request(req);
+    let response = post_json(config, "generateContent", &gemini_req).await?;
 
     // 解析响应
     let gemini_resp: GeminiResponse = response.json().await?;
@@ -242,58 +366,500 @@ pub async fn forward_request_stream(
     config: &ModelConfig,
     req: &ChatRequest,
 ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
-    let client = get_http_client();
-
-    // 解析模型 ID
     let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
 
     // 转换请求
     let gemini_req = convert_request(req);
+    let response = post_json(config, "streamGenerateContent?alt=sse", &gemini_req).await?;
 
-    // 构建流式 URL
-    let api_base = if config.litellm_params.api_base.is_empty() {
-        "https://generativelanguage.googleapis.com"
-    } else {
-        &config.litellm_params.api_base
-    };
-    let url = format!(
-        "{}/v1beta/models/{}:streamGenerateContent?alt=sse",
-        api_base.trim_end_matches('/'),
-        model_id
-    );
+    // 创建 SSE 转换流
+    let stream = create_gemini_stream(response, model_id);
 
-    // 发送请求
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .header("x-goog-api-key", &config.litellm_params.api_key)
-        .json(&gemini_req)
-        .send()
-        .await?;
+    Ok(Box::pin(stream))
+}
 
-    // 检查状态码
-    let status = response.status();
-    if !status.is_success() {
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_default()
-            .chars()
-            .take(4096)
-            .collect::<String>();
-        return Err(FeatherGateError::upstream(
-            status.as_u16(),
-            format!("Gemini API 错误: {}", error_body),
-        ));
+/// Gemini 原生 generateContent 请求（/v1beta/models/{model}:generateContent）
+#[derive(Debug, Clone)]
+pub struct GenerateContentRequest {
+    /// URL 中的模型名（配置中的 model_name）
+    pub model: String,
+    /// 原样保留的请求体，透传到 Gemini 时不做任何修改
+    pub body: serde_json::Value,
+}
+
+impl GenerateContentRequest {
+    /// 需要审核的用户输入（role 为 user 或省略的文本部分）
+    pub fn user_texts(&self) -> Vec<String> {
+        let Ok(native) = serde_json::from_value::<GeminiRequest>(self.body.clone()) else {
+            return Vec::new();
+        };
+        native
+            .contents
+            .into_iter()
+            .filter(|content| content.role.is_empty() || content.role == "user")
+            .map(|content| content.parts.into_iter().map(|part| part.text).collect::<String>())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+}
+
+/// 将原生 generateContent 请求转换为聊天请求（`convert_request` 的逆向转换）
+///
+/// systemInstruction 转换为 system 消息；只支持文本部分，tools 只有 Gemini 模型支持。
+pub fn native_to_chat_request(req: &GenerateContentRequest, stream: bool) -> Result<ChatRequest> {
+    let native: GeminiRequest = serde_json::from_value(req.body.clone())
+        .map_err(|e| FeatherGateError::invalid_request(e.to_string()))?;
+    if native.tools.as_ref().is_some_and(|tools| !tools.is_empty()) {
+        return Err(ValidationError::ToolsUnsupported.into());
     }
 
-    // 创建 SSE 转换流
-    let model_id_owned = model_id.clone();
-    let stream = create_gemini_stream(response, model_id_owned);
+    let mut messages = Vec::with_capacity(native.contents.len() + 1);
+    if let Some(system) = native.system_instruction {
+        messages.push(Message::system(native_text(system, "\n")?));
+    }
+    for content in native.contents {
+        let role = match content.role.as_str() {
+            "model" => "assistant",
+            _ => "user",
+        };
+        messages.push(Message {
+            role: role.to_string(),
+            content: native_text(content, "")?,
+        });
+    }
 
+    let config = native.generation_config;
+    Ok(ChatRequest {
+        model: req.model.clone(),
+        messages,
+        temperature: config.as_ref().and_then(|c| c.temperature),
+        max_tokens: config.as_ref().and_then(|c| c.max_output_tokens),
+        stream: stream.then_some(true),
+        stream_options: None,
+        top_p: config.as_ref().and_then(|c| c.top_p),
+        stop: config
+            .and_then(|c| c.stop_sequences)
+            .filter(|stops| !stops.is_empty())
+            .map(Stop::Multiple),
+        user: None,
+    })
+}
+
+/// 提取文本部分，遇到非文本部分时拒绝请求
+fn native_text(content: GeminiContent, separator: &str) -> Result<String> {
+    let texts = content
+        .parts
+        .into_iter()
+        .map(|part| match part.other.keys().next() {
+            Some(kind) => Err(FeatherGateError::Validation(
+                ValidationError::UnsupportedContentBlock(kind.clone()),
+            )),
+            None => Ok(part.text),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(texts.join(separator))
+}
+
+/// OpenAI finish_reason 转换为 Gemini finishReason
+fn native_finish_reason(reason: &str) -> &'static str {
+    match reason {
+        "length" => "MAX_TOKENS",
+        "content_filter" => "SAFETY",
+        _ => "STOP",
+    }
+}
+
+/// 构建原生格式的响应（流式数据块也使用同样的结构）
+fn native_response(
+    model: &str,
+    text: &str,
+    finish_reason: Option<&str>,
+    usage: Option<&Usage>,
+) -> serde_json::Value {
+    let mut candidate = serde_json::json!({
+        "content": {"role": "model", "parts": [{"text": text}]},
+        "index": 0,
+    });
+    if let Some(reason) = finish_reason {
+        candidate["finishReason"] = native_finish_reason(reason).into();
+    }
+
+    let mut response = serde_json::json!({
+        "candidates": [candidate],
+        "modelVersion": model,
+    });
+    if let Some(usage) = usage {
+        response["usageMetadata"] = serde_json::json!({
+            "promptTokenCount": usage.prompt_tokens,
+            "candidatesTokenCount": usage.completion_tokens,
+            "totalTokenCount": usage.total_tokens,
+        });
+    }
+    response
+}
+
+/// 将聊天响应转换为原生 generateContent 响应（`convert_response` 的逆向转换）
+pub fn chat_to_native_response(resp: &ChatResponse) -> serde_json::Value {
+    let choice = resp.choices.first();
+    native_response(
+        &resp.model,
+        choice.map(|c| c.message.content.as_str()).unwrap_or_default(),
+        Some(choice.and_then(|c| c.finish_reason.as_deref()).unwrap_or("stop")),
+        resp.usage.as_ref(),
+    )
+}
+
+/// 将聊天 SSE 流转换为原生 streamGenerateContent（alt=sse）事件流
+pub fn chat_stream_to_native(
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    use futures_util::StreamExt;
+
+    let mut buffer = String::new();
+    let converted = stream
+        .map(move |result| {
+            let outputs = match result {
+                Ok(bytes) => {
+                    buffer.push_str(&String::from_utf8_lossy(&bytes));
+                    process_chat_buffer(&mut buffer)
+                }
+                Err(e) => vec![Err(e)],
+            };
+            futures_util::stream::iter(outputs)
+        })
+        .flatten();
+
+    Box::pin(converted)
+}
+
+/// 处理聊天 SSE 缓冲区，把每个数据块转换为原生格式
+fn process_chat_buffer(buffer: &mut String) -> Vec<Result<Bytes>> {
+    let mut outputs = Vec::new();
+
+    while let Some(pos) = buffer.find("\n\n") {
+        let event = buffer[..pos].to_string();
+        *buffer = buffer[pos + 2..].to_string();
+
+        let Some(data) = event.lines().find_map(|line| line.strip_prefix("data: ")) else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+            continue;
+        };
+        let usage = value
+            .get("usage")
+            .and_then(|u| serde_json::from_value::<Usage>(u.clone()).ok());
+        let Ok(chunk) = serde_json::from_value::<ChatStreamChunk>(value) else {
+            continue;
+        };
+
+        let choice = chunk.choices.first();
+        let text = choice
+            .and_then(|c| c.delta.content.as_deref())
+            .unwrap_or_default();
+        let finish_reason = choice.and_then(|c| c.finish_reason.as_deref());
+        if text.is_empty() && finish_reason.is_none() && usage.is_none() {
+            continue;
+        }
+
+        let response = native_response(&chunk.model, text, finish_reason, usage.as_ref());
+        outputs.push(Ok(Bytes::from(format!("data: {}\r\n\r\n", response))));
+    }
+    outputs
+}
+
+/// 转发原生 generateContent 请求到 Gemini（请求体原样透传，使用部署的真实模型 ID）
+pub async fn forward_generate_content(
+    config: &ModelConfig,
+    req: &GenerateContentRequest,
+) -> Result<serde_json::Value> {
+    let response = post_json(config, "generateContent", &req.body).await?;
+
+    Ok(response.json().await?)
+}
+
+/// 转发原生 streamGenerateContent 请求到 Gemini，事件流原样返回
+pub async fn forward_generate_content_stream(
+    config: &ModelConfig,
+    req: &GenerateContentRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    use futures_util::StreamExt;
+
+    let response = post_json(config, "streamGenerateContent?alt=sse", &req.body).await?;
+
+    let stream = response
+        .bytes_stream()
+        .map(|result| result.map_err(FeatherGateError::HttpError));
     Ok(Box::pin(stream))
 }
 
+/// batchEmbedContents 单次请求的最大条数
+const EMBED_BATCH_SIZE: usize = 100;
+
+/// Gemini 向量嵌入批量请求
+#[derive(Debug, Serialize)]
+struct BatchEmbedRequest<'a> {
+    requests: Vec<EmbedContentRequest<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EmbedContentRequest<'a> {
+    model: &'a str,
+    content: EmbedContent<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimensionality: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbedContent<'a> {
+    parts: Vec<EmbedPart<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbedPart<'a> {
+    text: &'a str,
+}
+
+/// Gemini 向量嵌入批量响应
+#[derive(Debug, Deserialize)]
+struct BatchEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<ContentEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentEmbedding {
+    values: Vec<f32>,
+}
+
+/// 转发向量嵌入请求到 Gemini（batchEmbedContents，超过上限时自动分批）
+pub async fn forward_embedding(
+    config: &ModelConfig,
+    req: &EmbeddingRequest,
+) -> Result<EmbeddingResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let texts = embeddings::input_texts(req)?;
+    let model = format!("models/{}", model_id);
+
+    let (vectors, prompt_tokens) =
+        embeddings::embed_in_batches(&texts, EMBED_BATCH_SIZE, |chunk| {
+            let batch_req = BatchEmbedRequest {
+                requests: chunk
+                    .iter()
+                    .map(|text| EmbedContentRequest {
+                        model: &model,
+                        content: EmbedContent {
+                            parts: vec![EmbedPart { text }],
+                        },
+                        output_dimensionality: req.dimensions,
+                    })
+                    .collect(),
+            };
+            async move {
+                let response = post_json(config, "batchEmbedContents", &batch_req).await?;
+                let resp: BatchEmbedResponse = response.json().await?;
+                // Gemini 不返回 token 用量，按文本长度估算
+                Ok((
+                    resp.embeddings.into_iter().map(|e| e.values).collect(),
+                    embeddings::estimate_tokens(chunk),
+                ))
+            }
+        })
+        .await?;
+
+    Ok(EmbeddingResponse::new(
+        model_id,
+        vectors,
+        req.encoding_format.unwrap_or_default(),
+        prompt_tokens,
+    ))
+}
+
+/// Imagen 图像生成请求（predict）
+#[derive(Debug, Serialize)]
+struct ImagenRequest<'a> {
+    instances: [ImagenInstance<'a>; 1],
+    parameters: ImagenParameters,
+}
+
+#[derive(Debug, Serialize)]
+struct ImagenInstance<'a> {
+    prompt: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImagenParameters {
+    sample_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aspect_ratio: Option<&'static str>,
+}
+
+/// Imagen 图像生成响应
+#[derive(Debug, Deserialize)]
+struct ImagenResponse {
+    #[serde(default)]
+    predictions: Vec<ImagenPrediction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImagenPrediction {
+    bytes_base64_encoded: Option<String>,
+    mime_type: Option<String>,
+    rai_filtered_reason: Option<String>,
+}
+
+/// Gemini 原生图像生成请求（generateContent + IMAGE 输出）
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageContentRequest<'a> {
+    contents: [EmbedContent<'a>; 1],
+    generation_config: ImageGenerationConfig,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageGenerationConfig {
+    response_modalities: [&'static str; 2],
+}
+
+/// Gemini 原生图像生成响应（只关心 inlineData 部分）
+#[derive(Debug, Deserialize)]
+struct ImageContentResponse {
+    #[serde(default)]
+    candidates: Vec<ImageCandidate>,
+    #[serde(rename = "promptFeedback")]
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageCandidate {
+    content: Option<ImageCandidateContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageCandidateContent {
+    #[serde(default)]
+    parts: Vec<ImagePart>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImagePart {
+    #[serde(rename = "inlineData")]
+    inline_data: Option<InlineData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineData {
+    mime_type: String,
+    data: String,
+}
+
+/// 将 OpenAI 的 size（如 `1792x1024`）映射为 Imagen 支持的最接近的宽高比
+fn aspect_ratio(size: &str) -> Option<&'static str> {
+    let (width, height) = size.split_once('x')?;
+    let ratio = width.parse::<f64>().ok()? / height.parse::<f64>().ok()?;
+
+    [
+        ("1:1", 1.0),
+        ("3:4", 0.75),
+        ("4:3", 4.0 / 3.0),
+        ("9:16", 9.0 / 16.0),
+        ("16:9", 16.0 / 9.0),
+    ]
+    .into_iter()
+    .min_by(|a, b| (a.1 - ratio).abs().total_cmp(&(b.1 - ratio).abs()))
+    .map(|(name, _)| name)
+}
+
+/// 转发图像生成请求到 Gemini
+///
+/// `imagen-*` 模型使用 `predict` 接口，其他模型使用 `generateContent` 的图像输出
+/// （每次只生成一张，`n` 大于 1 时并发请求）。结果统一转换为 OpenAI 的
+/// `b64_json` 或 data URL 格式。
+pub async fn forward_image_generation(
+    config: &ModelConfig,
+    req: &ImageGenerationRequest,
+) -> Result<ImageResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let format = req.response_format.unwrap_or_default();
+    let n = req.n.unwrap_or(1);
+
+    let images = if model_id.starts_with("imagen") {
+        let imagen_req = ImagenRequest {
+            instances: [ImagenInstance { prompt: &req.prompt }],
+            parameters: ImagenParameters {
+                sample_count: n,
+                aspect_ratio: req.size.as_deref().and_then(aspect_ratio),
+            },
+        };
+        let response = post_json(config, "predict", &imagen_req).await?;
+        let resp: ImagenResponse = response.json().await?;
+
+        let mut images = Vec::new();
+        let mut filtered_reason = None;
+        for prediction in resp.predictions {
+            match prediction.bytes_base64_encoded {
+                Some(data) => images.push((
+                    prediction.mime_type.unwrap_or_else(|| "image/png".to_string()),
+                    data,
+                )),
+                None => filtered_reason = prediction.rai_filtered_reason.or(filtered_reason),
+            }
+        }
+        if images.is_empty() {
+            return Err(blocked_error(filtered_reason.as_deref().unwrap_or("no image generated")));
+        }
+        images
+    } else {
+        let content_req = ImageContentRequest {
+            contents: [EmbedContent {
+                parts: vec![EmbedPart { text: &req.prompt }],
+            }],
+            generation_config: ImageGenerationConfig {
+                response_modalities: ["TEXT", "IMAGE"],
+            },
+        };
+        let responses = futures_util::future::try_join_all((0..n).map(|_| async {
+            let response = post_json(config, "generateContent", &content_req).await?;
+            let resp: ImageContentResponse = response.json().await?;
+            if let Some(reason) = resp.prompt_feedback.and_then(|f| f.block_reason) {
+                return Err(blocked_error(&reason));
+            }
+            Ok(resp.candidates)
+        }))
+        .await?;
+
+        let images: Vec<_> = responses
+            .into_iter()
+            .flatten()
+            .filter_map(|candidate| candidate.content)
+            .flat_map(|content| content.parts)
+            .filter_map(|part| part.inline_data)
+            .map(|inline| (inline.mime_type, inline.data))
+            .collect();
+        if images.is_empty() {
+            return Err(blocked_error("no image generated"));
+        }
+        images
+    };
+
+    Ok(ImageResponse {
+        created: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        data: images
+            .into_iter()
+            .map(|(mime_type, data)| ImageData::from_base64(data, &mime_type, format))
+            .collect(),
+        usage: None,
+    })
+}
+
 /// 创建 Gemini SSE 转换流
 fn create_gemini_stream(
     response: reqwest::Response,
@@ -304,57 +870,81 @@ fn create_gemini_stream(
     let mut buffer = String::new();
     let chunk_id = format!("chatcmpl-{}", uuid::Uuid::new_v4());
 
-    response.bytes_stream().filter_map(move |result| {
-        let output = match result {
-            Ok(bytes) => {
-                buffer.push_str(&String::from_utf8_lossy(&bytes));
-                process_gemini_buffer(&mut buffer, &chunk_id, &model_id)
-            }
-            Err(e) => Some(Err(FeatherGateError::HttpError(e))),
-        };
-        std::future::ready(output)
-    })
+    response
+        .bytes_stream()
+        .map(move |result| {
+            let outputs = match result {
+                Ok(bytes) => {
+                    // Gemini 使用 \r\n 作为行分隔符，统一为 \n
+                    buffer.push_str(&String::from_utf8_lossy(&bytes).replace("\r\n", "\n"));
+                    process_gemini_buffer(&mut buffer, &chunk_id, &model_id)
+                }
+                Err(e) => vec![Err(FeatherGateError::HttpError(e))],
+            };
+            futures_util::stream::iter(outputs)
+        })
+        .flatten()
 }
 
-/// 处理 Gemini SSE 缓冲区
+/// 处理 Gemini SSE 缓冲区，提取所有完整事件
 fn process_gemini_buffer(
     buffer: &mut String,
     chunk_id: &str,
     model_id: &str,
-) -> Option<Result<Bytes>> {
+) -> Vec<Result<Bytes>> {
+    let mut outputs = Vec::new();
+
     // Gemini SSE 格式: data: {...}\n\n
     while let Some(pos) = buffer.find("\n\n") {
         let line = buffer[..pos].to_string();
         *buffer = buffer[pos + 2..].to_string();
 
         if let Some(data) = line.strip_prefix("data: ") {
-            if let Some(chunk) = parse_gemini_chunk(data, chunk_id, model_id) {
-                return Some(Ok(chunk));
+            if let Some(output) = parse_gemini_chunk(data, chunk_id, model_id) {
+                outputs.push(output);
             }
         }
     }
-    None
+    outputs
 }
 
 /// 解析 Gemini 响应块并转换为 OpenAI 格式
-fn parse_gemini_chunk(data: &str, chunk_id: &str, model_id: &str) -> Option<Bytes> {
+fn parse_gemini_chunk(data: &str, chunk_id: &str, model_id: &str) -> Option<Result<Bytes>> {
+    // 上游在流中返回的错误
+    if let Ok(err) = serde_json::from_str::<GeminiErrorResponse>(data) {
+        return Some(Err(convert_error(err.error)));
+    }
+
     // 解析 Gemini 响应
     let resp: GeminiResponse = serde_json::from_str(data).ok()?;
 
+    // 提示词被拦截
+    if let Some(reason) = resp
+        .prompt_feedback
+        .as_ref()
+        .and_then(|feedback| feedback.block_reason.as_deref())
+    {
+        return Some(Err(blocked_error(reason)));
+    }
+
     // 提取文本内容
     let candidate = resp.candidates.first()?;
     let text = candidate.content.parts.first()?.text.clone();
 
     // 检查是否结束
-    let finish_reason = candidate.finish_reason.as_ref().map(|r| match r.as_str() {
-        "STOP" => "stop",
-        "MAX_TOKENS" => "length",
-        _ => "stop",
-    });
+    let finish_reason = candidate
+        .finish_reason
+        .as_deref()
+        .map(convert_finish_reason);
 
     // 创建 OpenAI 格式的 chunk
-    let chunk = create_gemini_openai_chunk(chunk_id, model_id, &text, finish_reason);
-    Some(Bytes::from(chunk))
+    let mut chunk = create_gemini_openai_chunk(chunk_id, model_id, &text, finish_reason);
+
+    // usageMetadata 是累计值，在最后一个块之后输出一个 OpenAI 格式的用量数据块，供计费读取
+    if let (Some(_), Some(meta)) = (finish_reason, &resp.usage_metadata) {
+        chunk.push_str(&create_gemini_usage_chunk(chunk_id, model_id, meta));
+    }
+    Some(Ok(Bytes::from(chunk)))
 }
 
 /// 创建 OpenAI 格式的 SSE chunk
@@ -385,6 +975,26 @@ fn create_gemini_openai_chunk(
     )
 }
 
+/// 创建只包含用量的 OpenAI SSE 数据块（与 `stream_options.include_usage` 的格式一致）
+fn create_gemini_usage_chunk(id: &str, model: &str, meta: &UsageMetadata) -> String {
+    let created = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    format!(
+        r#"data: {{"id":"{}","object":"chat.completion.chunk","created":{},"model":"{}","choices":[],"usage":{{"prompt_tokens":{},"completion_tokens":{},"total_tokens":{}}}}}
+
+"#,
+        id,
+        created,
+        model,
+        meta.prompt_token_count,
+        meta.candidates_token_count,
+        meta.total_token_count
+    )
+}
+
 /// 转义 JSON 字符串
 fn escape_json_gemini(s: &str) -> String {
     s.replace('\\', "\\\\")
@@ -398,6 +1008,7 @@ fn escape_json_gemini(s: &str) -> String {
 mod tests {
     use super::*;
     use crate::config::LitellmParams;
+    use crate::types::embeddings::{EmbeddingInput, EmbeddingVector, EncodingFormat};
     use mockito::{Server, ServerGuard};
 
     async fn setup_mock_server() -> ServerGuard {
@@ -411,7 +1022,9 @@ mod tests {
                 model: "gemini/gemini-pro".to_string(),
                 api_key: "test-api-key".to_string(),
                 api_base: api_base.to_string(),
+                ..Default::default()
             },
+            ..Default::default()
         }
     }
 
@@ -423,7 +1036,10 @@ mod tests {
             temperature: Some(0.7),
             max_tokens: Some(100),
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: Some(Stop::Multiple(vec!["END".to_string(), "\n\n".to_string()])),
+            user: None,
         };
 
         let gemini_req = convert_request(&req);
@@ -436,6 +1052,12 @@ mod tests {
             gemini_req.generation_config.as_ref().unwrap().temperature,
             Some(0.7)
         );
+
+        let json = serde_json::to_value(&gemini_req).unwrap();
+        assert_eq!(
+            json["generation_config"]["stopSequences"],
+            serde_json::json!(["END", "\n\n"])
+        );
     }
 
     #[test]
@@ -449,7 +1071,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let gemini_req = convert_request(&req);
@@ -469,7 +1094,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let gemini_req = convert_request(&req);
@@ -495,6 +1123,7 @@ mod tests {
                 candidates_token_count: 20,
                 total_token_count: 30,
             }),
+            prompt_feedback: None,
         };
 
         let openai_resp = convert_response(gemini_resp, "gemini-pro").unwrap();
@@ -507,6 +1136,222 @@ mod tests {
         assert_eq!(openai_resp.usage.as_ref().unwrap().total_tokens, 30);
     }
 
+    #[test]
+    fn test_native_to_chat_request() {
+        let req = GenerateContentRequest {
+            model: "gpt-4".to_string(),
+            body: serde_json::json!({
+                "systemInstruction": {"parts": [{"text": "Be brief"}]},
+                "contents": [
+                    {"role": "user", "parts": [{"text": "Hi"}]},
+                    {"role": "model", "parts": [{"text": "Hello"}]},
+                    {"parts": [{"text": "Bye"}]}
+                ],
+                "generationConfig": {"temperature": 0.3, "maxOutputTokens": 32, "stopSequences": ["END"]}
+            }),
+        };
+
+        let chat_req = native_to_chat_request(&req, true).unwrap();
+        assert_eq!(
+            chat_req.messages,
+            vec![
+                Message::system("Be brief"),
+                Message::user("Hi"),
+                Message::assistant("Hello"),
+                Message::user("Bye"),
+            ]
+        );
+        assert_eq!(chat_req.temperature, Some(0.3));
+        assert_eq!(chat_req.max_tokens, Some(32));
+        assert_eq!(chat_req.stream, Some(true));
+        assert_eq!(chat_req.stop, Some(Stop::Multiple(vec!["END".to_string()])));
+        assert_eq!(req.user_texts(), vec!["Hi", "Bye"]);
+
+        let req = GenerateContentRequest {
+            model: "gpt-4".to_string(),
+            body: serde_json::json!({
+                "contents": [{"role": "user", "parts": [{"inlineData": {"mimeType": "image/png", "data": ""}}]}]
+            }),
+        };
+        assert!(matches!(
+            native_to_chat_request(&req, false),
+            Err(FeatherGateError::Validation(ValidationError::UnsupportedContentBlock(kind))) if kind == "inlineData"
+        ));
+    }
+
+    #[test]
+    fn test_chat_to_native_response() {
+        let mut chat_resp = ChatResponse::simple("gpt-4", "Hello");
+        chat_resp.choices[0].finish_reason = Some("length".to_string());
+        chat_resp.usage = Some(Usage {
+            prompt_tokens: 2,
+            completion_tokens: 1,
+            total_tokens: 3,
+        });
+
+        let native = chat_to_native_response(&chat_resp);
+        assert_eq!(native["candidates"][0]["content"]["role"], "model");
+        assert_eq!(native["candidates"][0]["content"]["parts"][0]["text"], "Hello");
+        assert_eq!(native["candidates"][0]["finishReason"], "MAX_TOKENS");
+        assert_eq!(native["usageMetadata"]["totalTokenCount"], 3);
+
+        // 转换后的响应可以被出站方向的结构解析
+        let parsed: GeminiResponse = serde_json::from_value(native).unwrap();
+        assert_eq!(convert_response(parsed, "gpt-4").unwrap().choices[0].message.content, "Hello");
+    }
+
+    #[test]
+    fn test_chat_stream_to_native_chunks() {
+        let mut buffer = String::from(
+            "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\"},\"finish_reason\":null}]}\n\n\
+             data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},\"finish_reason\":\"stop\"}]}\n\n\
+             data: [DONE]\n\n",
+        );
+
+        let outputs = process_chat_buffer(&mut buffer);
+        assert_eq!(outputs.len(), 1);
+        let chunk = String::from_utf8(outputs[0].as_ref().unwrap().to_vec()).unwrap();
+        assert!(chunk.ends_with("\r\n\r\n"));
+        let native: serde_json::Value =
+            serde_json::from_str(chunk.trim_start_matches("data: ").trim_end()).unwrap();
+        assert_eq!(native["candidates"][0]["content"]["parts"][0]["text"], "Hi");
+        assert_eq!(native["candidates"][0]["finishReason"], "STOP");
+    }
+
+    #[test]
+    fn test_stream_reports_usage_chunk() {
+        let mut buffer = String::from(
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hi\"}]}}],\"usageMetadata\":{\"promptTokenCount\":5,\"candidatesTokenCount\":1,\"totalTokenCount\":6}}\n\n\
+             data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"!\"}]},\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"promptTokenCount\":5,\"candidatesTokenCount\":2,\"totalTokenCount\":7}}\n\n",
+        );
+
+        let outputs = process_gemini_buffer(&mut buffer, "chatcmpl-1", "gemini-pro");
+
+        assert_eq!(outputs.len(), 2);
+        let first = String::from_utf8(outputs[0].as_ref().unwrap().to_vec()).unwrap();
+        assert!(!first.contains("usage"));
+        let last = String::from_utf8(outputs[1].as_ref().unwrap().to_vec()).unwrap();
+        let usage: serde_json::Value = last
+            .split("\n\n")
+            .filter_map(|event| event.strip_prefix("data: "))
+            .map(|data| serde_json::from_str::<serde_json::Value>(data).unwrap())
+            .find(|event| event["usage"].is_object())
+            .unwrap();
+        assert_eq!(usage["choices"], serde_json::json!([]));
+        assert_eq!(usage["usage"]["prompt_tokens"], 5);
+        assert_eq!(usage["usage"]["completion_tokens"], 2);
+    }
+
+    #[test]
+    fn test_stream_error_payload_is_surfaced() {
+        let mut buffer = String::from(
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hi\"}]}}]}\n\n\
+             data: {\"error\":{\"code\":429,\"message\":\"Quota exceeded\",\"status\":\"RESOURCE_EXHAUSTED\"}}\n\n",
+        );
+
+        let outputs = process_gemini_buffer(&mut buffer, "chatcmpl-1", "gemini-pro");
+
+        assert_eq!(outputs.len(), 2);
+        assert!(outputs[0].is_ok());
+        match &outputs[1] {
+            Err(FeatherGateError::UpstreamError { status, kind, message }) => {
+                assert_eq!(*status, 429);
+                assert_eq!(*kind, UpstreamErrorKind::RateLimited);
+                assert!(message.contains("Quota exceeded"));
+            }
+            other => panic!("Expected UpstreamError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_classification() {
+        let err = parse_error(
+            429,
+            r#"{"error": {"code": 429, "message": "Resource has been exhausted", "status": "RESOURCE_EXHAUSTED"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::RateLimited));
+
+        let err = parse_error(
+            400,
+            r#"{"error": {"code": 400, "message": "The input token count (1200000) exceeds the maximum number of tokens allowed (1048576).", "status": "INVALID_ARGUMENT"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::ContextWindowExceeded));
+
+        let err = parse_error(
+            400,
+            r#"{"error": {"code": 400, "message": "Invalid JSON payload", "status": "INVALID_ARGUMENT"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::BadRequest));
+
+        let err = parse_error(
+            400,
+            r#"{"error": {"code": 400, "message": "API key not valid. Please pass a valid API key.", "status": "INVALID_ARGUMENT", "details": [{"@type": "type.googleapis.com/google.rpc.ErrorInfo", "reason": "API_KEY_INVALID", "domain": "googleapis.com"}]}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::Authentication));
+
+        let err = parse_error(
+            429,
+            r#"{"error": {"code": 429, "message": "You exceeded your current quota, please check your plan and billing details.", "status": "RESOURCE_EXHAUSTED"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::QuotaExceeded));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_key_fails_over_to_next_key() {
+        let mut server = setup_mock_server().await;
+        let revoked = server
+            .mock("POST", "/v1beta/models/gemini-pro:generateContent")
+            .match_header("x-goog-api-key", "AIza-revoked")
+            .with_status(400)
+            .with_body(r#"{"error": {"code": 400, "message": "API key not valid. Please pass a valid API key.", "status": "INVALID_ARGUMENT", "details": [{"@type": "type.googleapis.com/google.rpc.ErrorInfo", "reason": "API_KEY_INVALID"}]}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let valid = server
+            .mock("POST", "/v1beta/models/gemini-pro:generateContent")
+            .match_header("x-goog-api-key", "AIza-valid")
+            .with_status(200)
+            .with_body(r#"{"candidates": [{"content": {"parts": [{"text": "Hi"}]}, "finishReason": "STOP"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        // mockito 会复用端口，使用独立的模型名避免与其他测试共享 key 池的轮询位置
+        config.model_name = "gemini-key-pool".to_string();
+        config.litellm_params.api_key = "AIza-revoked".to_string();
+        config.litellm_params.api_keys = vec!["AIza-valid".to_string()];
+
+        let req = ChatRequest {
+            model: "gemini".to_string(),
+            messages: vec![Message::user("Hello")],
+            temperature: None,
+            max_tokens: None,
+            stream: None,
+            stream_options: None,
+            top_p: None,
+            stop: None,
+            user: None,
+        };
+        let response = forward_request(&config, &req).await.unwrap();
+        assert_eq!(response.choices[0].message.content, "Hi");
+
+        revoked.assert_async().await;
+        valid.assert_async().await;
+    }
+
+    #[test]
+    fn test_convert_response_blocked_prompt() {
+        let gemini_resp: GeminiResponse =
+            serde_json::from_str(r#"{"promptFeedback": {"blockReason": "SAFETY"}}"#).unwrap();
+
+        let err = convert_response(gemini_resp, "gemini-pro").unwrap_err();
+        assert_eq!(
+            err.upstream_kind(),
+            Some(UpstreamErrorKind::ContentPolicyViolation)
+        );
+    }
+
     #[tokio::test]
     async fn test_forward_request_success() {
         let mut server = setup_mock_server().await;
@@ -542,7 +1387,10 @@ mod tests {
             temperature: Some(0.7),
             max_tokens: Some(100),
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let result = forward_request(&config, &req).await;
@@ -574,7 +1422,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let result = forward_request(&config, &req).await;
@@ -582,4 +1433,140 @@ mod tests {
 
         mock.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn test_forward_embedding_batches_requests() {
+        let mut server = setup_mock_server().await;
+        let body = serde_json::json!({
+            "embeddings": (0..EMBED_BATCH_SIZE).map(|_| serde_json::json!({"values": [0.5, 1.0]})).collect::<Vec<_>>()
+        });
+        let full_batch = server
+            .mock("POST", "/v1beta/models/text-embedding-004:batchEmbedContents")
+            .match_header("x-goog-api-key", "test-api-key")
+            .match_body(mockito::Matcher::Regex(r#""outputDimensionality":2"#.to_string()))
+            .match_body(mockito::Matcher::Regex(r#""text":"text-0""#.to_string()))
+            .with_status(200)
+            .with_body(body.to_string())
+            .expect(1)
+            .create_async()
+            .await;
+        let last_batch = server
+            .mock("POST", "/v1beta/models/text-embedding-004:batchEmbedContents")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "requests": [{
+                    "model": "models/text-embedding-004",
+                    "content": {"parts": [{"text": "text-100"}]},
+                    "outputDimensionality": 2
+                }]
+            })))
+            .with_status(200)
+            .with_body(r#"{"embeddings": [{"values": [1.0, -2.5]}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.litellm_params.model = "gemini/text-embedding-004".to_string();
+        let req = EmbeddingRequest {
+            model: "gemini-embed".to_string(),
+            input: EmbeddingInput::TextBatch(
+                (0..=EMBED_BATCH_SIZE).map(|i| format!("text-{}", i)).collect(),
+            ),
+            dimensions: Some(2),
+            encoding_format: Some(EncodingFormat::Base64),
+            user: None,
+        };
+
+        let response = forward_embedding(&config, &req).await.unwrap();
+        assert_eq!(response.data.len(), EMBED_BATCH_SIZE + 1);
+        assert_eq!(response.data[100].index, 100);
+        assert_eq!(
+            response.data[100].embedding,
+            EmbeddingVector::Base64("AACAPwAAIMA=".to_string())
+        );
+
+        full_batch.assert_async().await;
+        last_batch.assert_async().await;
+    }
+
+    #[test]
+    fn test_aspect_ratio() {
+        assert_eq!(aspect_ratio("1024x1024"), Some("1:1"));
+        assert_eq!(aspect_ratio("1792x1024"), Some("16:9"));
+        assert_eq!(aspect_ratio("1024x1536"), Some("3:4"));
+        assert_eq!(aspect_ratio("auto"), None);
+    }
+
+    #[tokio::test]
+    async fn test_forward_image_generation_imagen() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("POST", "/v1beta/models/imagen-3.0-generate-002:predict")
+            .match_header("x-goog-api-key", "test-api-key")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "instances": [{"prompt": "a cat"}],
+                "parameters": {"sampleCount": 2, "aspectRatio": "16:9"}
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"predictions": [
+                    {"bytesBase64Encoded": "aGk=", "mimeType": "image/png"},
+                    {"raiFilteredReason": "filtered"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.litellm_params.model = "gemini/imagen-3.0-generate-002".to_string();
+        let req: ImageGenerationRequest = serde_json::from_value(serde_json::json!({
+            "model": "imagen",
+            "prompt": "a cat",
+            "n": 2,
+            "size": "1792x1024",
+            "response_format": "b64_json"
+        }))
+        .unwrap();
+
+        let response = forward_image_generation(&config, &req).await.unwrap();
+        assert_eq!(response.data.len(), 1);
+        assert_eq!(response.data[0].b64_json.as_deref(), Some("aGk="));
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_forward_image_generation_gemini_inline_data() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("POST", "/v1beta/models/gemini-2.0-flash-exp:generateContent")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "generationConfig": {"responseModalities": ["TEXT", "IMAGE"]}
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"candidates": [{"content": {"parts": [
+                    {"text": "Here is your cat"},
+                    {"inlineData": {"mimeType": "image/jpeg", "data": "aGk="}}
+                ]}}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.litellm_params.model = "gemini/gemini-2.0-flash-exp".to_string();
+        let req: ImageGenerationRequest = serde_json::from_value(serde_json::json!({
+            "model": "gemini-image",
+            "prompt": "a cat"
+        }))
+        .unwrap();
+
+        let response = forward_image_generation(&config, &req).await.unwrap();
+        assert_eq!(
+            response.data[0].url.as_deref(),
+            Some("data:image/jpeg;base64,aGk=")
+        );
+
+        mock.assert_async().await;
+    }
 }
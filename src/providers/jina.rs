@@ -0,0 +1,181 @@
+use crate::config::{parse_model_string, ModelConfig};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::providers::send_with_key;
+use crate::types::rerank::{RerankRequest, RerankResponse, RerankUsage};
+use crate::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 获取全局 HTTP 客户端
+fn get_http_client() -> &'static Client {
+    use once_cell::sync::Lazy;
+    static CLIENT: Lazy<Client> = Lazy::new(|| {
+        Client::builder()
+            .timeout(Duration::from_secs(60))
+            .pool_max_idle_per_host(10)
+            .build()
+            .unwrap()
+    });
+    &CLIENT
+}
+
+/// Jina API 错误格式（兼容实现通常沿用 FastAPI 的 `detail` 字段）
+#[derive(Debug, Deserialize)]
+struct JinaErrorResponse {
+    detail: serde_json::Value,
+}
+
+/// 解析 Jina 错误响应并归一化
+pub(crate) fn parse_error(status: u16, body: &str) -> FeatherGateError {
+    let message = serde_json::from_str::<JinaErrorResponse>(body)
+        .map(|resp| match resp.detail {
+            serde_json::Value::String(detail) => detail,
+            detail => detail.to_string(),
+        })
+        .unwrap_or_else(|_| body.to_string());
+
+    let kind = UpstreamErrorKind::from_status(status).refine_by_message(&message);
+    FeatherGateError::upstream_with_kind(status, kind, format!("Jina: {}", message))
+}
+
+/// Jina rerank 请求
+#[derive(Debug, Serialize)]
+struct JinaRerankRequest<'a> {
+    model: &'a str,
+    query: &'a str,
+    documents: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_n: Option<u32>,
+    return_documents: bool,
+}
+
+/// Jina rerank 响应
+#[derive(Debug, Deserialize)]
+struct JinaRerankResponse {
+    results: Vec<JinaRerankResult>,
+    #[serde(default)]
+    usage: Option<JinaUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JinaRerankResult {
+    index: u32,
+    relevance_score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct JinaUsage {
+    #[serde(default)]
+    total_tokens: u32,
+}
+
+/// 转发重排序请求到 Jina 兼容的 `/rerank` 接口（Jina AI、Infinity、vLLM 等）
+pub async fn forward_rerank(config: &ModelConfig, req: &RerankRequest) -> Result<RerankResponse> {
+    let client = get_http_client();
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+
+    let api_base = if config.litellm_params.api_base.is_empty() {
+        "https://api.jina.ai/v1"
+    } else {
+        &config.litellm_params.api_base
+    };
+    let url = format!("{}/rerank", api_base.trim_end_matches('/'));
+
+    // 文档内容由网关补全，不需要上游回传
+    let rerank_req = JinaRerankRequest {
+        model: &model_id,
+        query: &req.query,
+        documents: req.document_texts(),
+        top_n: req.top_n,
+        return_documents: false,
+    };
+
+    let response = send_with_key(config, parse_error, |api_key| {
+        client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("Content-Type", "application/json")
+            .json(&rerank_req)
+    })
+    .await?;
+
+    let resp: JinaRerankResponse = response.json().await?;
+    let scores = resp
+        .results
+        .into_iter()
+        .map(|result| (result.index, result.relevance_score))
+        .collect();
+    let usage = RerankUsage {
+        total_tokens: resp.usage.map(|usage| usage.total_tokens).unwrap_or_default(),
+        search_units: None,
+    };
+
+    Ok(RerankResponse::new(model_id, req, scores, usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LitellmParams;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_forward_rerank() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/rerank")
+            .match_header("authorization", "Bearer jina-test-key")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "jina-reranker-v2-base-multilingual",
+                "documents": ["a", "b", "c"],
+                "return_documents": false
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                "model": "jina-reranker-v2-base-multilingual",
+                "usage": {"total_tokens": 12},
+                "results": [
+                    {"index": 0, "relevance_score": 0.2},
+                    {"index": 2, "relevance_score": 0.7},
+                    {"index": 1, "relevance_score": 0.4}
+                ]
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let config = ModelConfig {
+            model_name: "jina-rerank".to_string(),
+            litellm_params: LitellmParams {
+                model: "jina/jina-reranker-v2-base-multilingual".to_string(),
+                api_key: "jina-test-key".to_string(),
+                api_base: format!("{}/v1", server.url()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let req: RerankRequest = serde_json::from_value(serde_json::json!({
+            "model": "jina-rerank",
+            "query": "q",
+            "documents": ["a", "b", "c"]
+        }))
+        .unwrap();
+
+        let response = forward_rerank(&config, &req).await.unwrap();
+        let indices: Vec<u32> = response.results.iter().map(|r| r.index).collect();
+        assert_eq!(indices, vec![2, 1, 0]);
+        assert!(response.results[0].document.is_none());
+        assert_eq!(response.usage.total_tokens, 12);
+
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_parse_error_detail() {
+        let err = parse_error(422, r#"{"detail": "documents must not be empty"}"#);
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::BadRequest));
+        assert!(err.to_string().contains("Jina: documents must not be empty"));
+    }
+}
@@ -0,0 +1,458 @@
+use crate::error::FeatherGateError;
+use crate::types::messages::{
+    ContentBlock, MessageContent, MessagesRequest, MessagesResponse, MessagesUsage,
+};
+use crate::types::{
+    ChatRequest, ChatResponse, ChatStreamChunk, Message, Stop, Usage, ValidationError,
+};
+use crate::Result;
+use futures_util::{Stream, StreamExt};
+use hyper::body::Bytes;
+use serde_json::json;
+use std::pin::Pin;
+
+/// 只有 Anthropic 模型支持、无法转换为聊天请求的参数
+const NATIVE_ONLY_PARAMS: &[&str] = &[
+    "tool_choice",
+    "top_k",
+    "thinking",
+    "service_tier",
+    "container",
+    "mcp_servers",
+];
+
+/// 将 Anthropic 原生请求转换为聊天请求（`convert_request` 的逆向转换）
+///
+/// system 内容块拼接为一条 system 消息，`stop_sequences` 转换为 `stop`，`metadata.user_id` 转换为 `user`；
+/// 只支持文本内容块，tools 和 `NATIVE_ONLY_PARAMS` 中的参数只有 Anthropic 模型支持。
+pub fn to_chat_request(req: &MessagesRequest) -> Result<ChatRequest> {
+    if req.tools.as_ref().is_some_and(|tools| !tools.is_empty()) {
+        return Err(ValidationError::ToolsUnsupported.into());
+    }
+    if let Some(param) = NATIVE_ONLY_PARAMS
+        .iter()
+        .find(|param| req.extra.get(**param).is_some_and(|value| !value.is_null()))
+    {
+        return Err(ValidationError::NativeParameterUnsupported(param).into());
+    }
+    let stop = match req.extra.get("stop_sequences") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(value) => Some(
+            serde_json::from_value::<Vec<String>>(value.clone())
+                .map_err(|e| FeatherGateError::invalid_request(format!("stop_sequences: {}", e)))?,
+        ),
+    };
+
+    let mut messages = Vec::with_capacity(req.messages.len() + 1);
+    if let Some(system) = &req.system {
+        messages.push(Message::system(content_text(system, "\n")?));
+    }
+    for message in &req.messages {
+        messages.push(Message {
+            role: message.role.clone(),
+            content: content_text(&message.content, "")?,
+        });
+    }
+
+    Ok(ChatRequest {
+        model: req.model.clone(),
+        messages,
+        temperature: req.temperature,
+        max_tokens: Some(req.max_tokens),
+        stream: req.stream,
+        stream_options: None,
+        top_p: req.top_p,
+        stop: stop.filter(|stops| !stops.is_empty()).map(Stop::Multiple),
+        user: req
+            .extra
+            .get("metadata")
+            .and_then(|metadata| metadata["user_id"].as_str())
+            .map(str::to_string),
+    })
+}
+
+/// 提取文本内容，遇到非文本内容块时拒绝请求
+fn content_text(content: &MessageContent, separator: &str) -> Result<String> {
+    match content {
+        MessageContent::Text(text) => Ok(text.clone()),
+        MessageContent::Blocks(blocks) => {
+            let texts = blocks
+                .iter()
+                .map(|block| match (block.block_type.as_str(), &block.text) {
+                    ("text", Some(text)) => Ok(text.as_str()),
+                    _ => Err(FeatherGateError::Validation(
+                        ValidationError::UnsupportedContentBlock(block.block_type.clone()),
+                    )),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(texts.join(separator))
+        }
+    }
+}
+
+/// 将聊天响应转换为 Anthropic 原生响应（`convert_response` 的逆向转换）
+pub fn from_chat_response(resp: ChatResponse) -> MessagesResponse {
+    let choice = resp.choices.into_iter().next();
+    let text = choice
+        .as_ref()
+        .map(|c| c.message.content.clone())
+        .unwrap_or_default();
+    let usage = resp.usage.unwrap_or(Usage {
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        total_tokens: 0,
+    });
+
+    MessagesResponse {
+        id: resp.id,
+        response_type: "message".to_string(),
+        role: "assistant".to_string(),
+        content: vec![ContentBlock::text(text)],
+        model: resp.model,
+        stop_reason: Some(stop_reason(choice.and_then(|c| c.finish_reason).as_deref())),
+        stop_sequence: None,
+        usage: MessagesUsage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+            extra: Default::default(),
+        },
+    }
+}
+
+/// OpenAI finish_reason 转换为 Anthropic stop_reason
+fn stop_reason(finish_reason: Option<&str>) -> String {
+    match finish_reason {
+        Some("length") => "max_tokens",
+        Some("tool_calls") => "tool_use",
+        _ => "end_turn",
+    }
+    .to_string()
+}
+
+/// 将聊天 SSE 流转换为 Anthropic 事件流
+///
+/// 输出顺序：message_start、content_block_start、content_block_delta（多次）、
+/// content_block_stop、message_delta、message_stop。
+pub fn chat_stream_to_messages(
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+    req: &MessagesRequest,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    let state = StreamState::new(req);
+
+    let converted = futures_util::stream::unfold(Some((stream, state)), |current| async move {
+        let (mut stream, mut state) = current?;
+        match stream.next().await {
+            Some(Ok(bytes)) => {
+                let outputs = state.push(&bytes);
+                Some((outputs, Some((stream, state))))
+            }
+            Some(Err(e)) => Some((vec![Err(e)], None)),
+            None => Some((state.finish(), None)),
+        }
+    })
+    .map(futures_util::stream::iter)
+    .flatten();
+
+    Box::pin(converted)
+}
+
+/// 流式转换的状态
+struct StreamState {
+    /// 未完整的事件按字节缓存，避免多字节字符被拆到两个数据块时解码出错
+    buffer: Vec<u8>,
+    id: String,
+    model: String,
+    usage: Option<Usage>,
+    finish_reason: Option<String>,
+    started: bool,
+    finished: bool,
+}
+
+impl StreamState {
+    fn new(req: &MessagesRequest) -> Self {
+        StreamState {
+            buffer: Vec::new(),
+            id: format!("msg_{}", uuid::Uuid::new_v4().simple()),
+            model: req.model.clone(),
+            usage: None,
+            finish_reason: None,
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// 处理上游字节，转换所有完整的聊天事件
+    fn push(&mut self, bytes: &[u8]) -> Vec<Result<Bytes>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut outputs = Vec::new();
+
+        while let Some(pos) = self.buffer.windows(2).position(|window| window == b"\n\n") {
+            let raw: Vec<u8> = self.buffer.drain(..pos + 2).collect();
+            if self.finished {
+                continue;
+            }
+
+            let raw = String::from_utf8_lossy(&raw);
+            let Some(data) = raw.lines().find_map(|line| line.strip_prefix("data: ")) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                outputs.extend(self.finish());
+                continue;
+            }
+
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+                continue;
+            };
+            if let Some(usage) = value
+                .get("usage")
+                .and_then(|u| serde_json::from_value::<Usage>(u.clone()).ok())
+            {
+                self.usage = Some(usage);
+            }
+            let Ok(chunk) = serde_json::from_value::<ChatStreamChunk>(value) else {
+                continue;
+            };
+
+            if !self.started {
+                self.id = chunk.id.clone();
+                self.model = chunk.model.clone();
+                outputs.extend(self.start());
+            }
+
+            let Some(choice) = chunk.choices.first() else {
+                continue;
+            };
+            if let Some(reason) = &choice.finish_reason {
+                self.finish_reason = Some(reason.clone());
+            }
+            if let Some(text) = choice.delta.content.as_deref().filter(|t| !t.is_empty()) {
+                outputs.push(Ok(event(
+                    "content_block_delta",
+                    json!({
+                        "index": 0,
+                        "delta": {"type": "text_delta", "text": text},
+                    }),
+                )));
+            }
+        }
+        outputs
+    }
+
+    /// 消息开始时的事件
+    fn start(&mut self) -> Vec<Result<Bytes>> {
+        self.started = true;
+        let input_tokens = self.usage.as_ref().map_or(0, |u| u.prompt_tokens);
+
+        vec![
+            Ok(event(
+                "message_start",
+                json!({
+                    "message": {
+                        "id": self.id,
+                        "type": "message",
+                        "role": "assistant",
+                        "content": [],
+                        "model": self.model,
+                        "stop_reason": null,
+                        "stop_sequence": null,
+                        "usage": {"input_tokens": input_tokens, "output_tokens": 0},
+                    }
+                }),
+            )),
+            Ok(event(
+                "content_block_start",
+                json!({
+                    "index": 0,
+                    "content_block": {"type": "text", "text": ""},
+                }),
+            )),
+        ]
+    }
+
+    /// 消息结束时的事件
+    fn finish(&mut self) -> Vec<Result<Bytes>> {
+        if self.finished {
+            return Vec::new();
+        }
+        let mut outputs = if self.started { Vec::new() } else { self.start() };
+        self.finished = true;
+
+        let output_tokens = self.usage.as_ref().map_or(0, |u| u.completion_tokens);
+        outputs.push(Ok(event("content_block_stop", json!({ "index": 0 }))));
+        outputs.push(Ok(event(
+            "message_delta",
+            json!({
+                "delta": {
+                    "stop_reason": stop_reason(self.finish_reason.as_deref()),
+                    "stop_sequence": null,
+                },
+                "usage": {"output_tokens": output_tokens},
+            }),
+        )));
+        outputs.push(Ok(event("message_stop", json!({}))));
+        outputs
+    }
+}
+
+/// 格式化一个 Anthropic SSE 事件
+fn event(event_type: &str, payload: serde_json::Value) -> Bytes {
+    let mut data = json!({ "type": event_type });
+    if let (Some(data), serde_json::Value::Object(payload)) = (data.as_object_mut(), payload) {
+        data.extend(payload);
+    }
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event_type, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_request(body: serde_json::Value) -> MessagesRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_to_chat_request() {
+        let req = create_request(json!({
+            "model": "gpt-4",
+            "max_tokens": 128,
+            "system": [{"type": "text", "text": "Be brief"}, {"type": "text", "text": "Use English"}],
+            "messages": [
+                {"role": "user", "content": [{"type": "text", "text": "Hello "}, {"type": "text", "text": "there"}]},
+                {"role": "assistant", "content": "Hi"},
+                {"role": "user", "content": "Bye"}
+            ],
+            "stop_sequences": ["END"],
+            "metadata": {"user_id": "u-1"}
+        }));
+
+        let chat_req = to_chat_request(&req).unwrap();
+        assert_eq!(
+            chat_req.messages,
+            vec![
+                Message::system("Be brief\nUse English"),
+                Message::user("Hello there"),
+                Message::assistant("Hi"),
+                Message::user("Bye"),
+            ]
+        );
+        assert_eq!(chat_req.max_tokens, Some(128));
+        assert_eq!(chat_req.stop, Some(Stop::Multiple(vec!["END".to_string()])));
+        assert_eq!(chat_req.user.as_deref(), Some("u-1"));
+    }
+
+    #[test]
+    fn test_to_chat_request_rejects_native_only_fields() {
+        let req = create_request(json!({
+            "model": "gpt-4",
+            "max_tokens": 128,
+            "messages": [{"role": "user", "content": "Hi"}],
+            "tools": [{"name": "f", "input_schema": {"type": "object"}}]
+        }));
+        assert!(matches!(
+            to_chat_request(&req),
+            Err(FeatherGateError::Validation(ValidationError::ToolsUnsupported))
+        ));
+
+        let req = create_request(json!({
+            "model": "gpt-4",
+            "max_tokens": 128,
+            "messages": [{"role": "user", "content": [{"type": "image", "source": {}}]}]
+        }));
+        assert!(matches!(
+            to_chat_request(&req),
+            Err(FeatherGateError::Validation(ValidationError::UnsupportedContentBlock(t))) if t == "image"
+        ));
+
+        let req = create_request(json!({
+            "model": "gpt-4",
+            "max_tokens": 128,
+            "messages": [{"role": "user", "content": "Hi"}],
+            "tool_choice": {"type": "auto"}
+        }));
+        let err = to_chat_request(&req).unwrap_err();
+        assert!(matches!(
+            err,
+            FeatherGateError::Validation(ValidationError::NativeParameterUnsupported("tool_choice"))
+        ));
+        assert_eq!(err.param(), Some("tool_choice"));
+    }
+
+    #[test]
+    fn test_from_chat_response() {
+        let mut chat_resp = ChatResponse::simple("gpt-4", "Hello");
+        chat_resp.choices[0].finish_reason = Some("length".to_string());
+        chat_resp.usage = Some(Usage {
+            prompt_tokens: 5,
+            completion_tokens: 1,
+            total_tokens: 6,
+        });
+
+        let resp = from_chat_response(chat_resp);
+        assert_eq!(resp.response_type, "message");
+        assert_eq!(resp.content, vec![ContentBlock::text("Hello")]);
+        assert_eq!(resp.stop_reason.as_deref(), Some("max_tokens"));
+        assert_eq!(resp.usage.input_tokens, 5);
+        assert_eq!(resp.usage.output_tokens, 1);
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_to_messages() {
+        let chunks = vec![
+            Ok(Bytes::from(
+                "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},\"finish_reason\":null}]}\n\n",
+            )),
+            Ok(Bytes::from(
+                "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\ndata: [DONE]\n\n",
+            )),
+        ];
+        let req = create_request(json!({
+            "model": "gpt-4",
+            "max_tokens": 16,
+            "messages": [{"role": "user", "content": "Hi"}]
+        }));
+        let stream =
+            chat_stream_to_messages(Box::pin(futures_util::stream::iter(chunks)), &req);
+
+        let events: Vec<String> = stream
+            .map(|bytes| String::from_utf8(bytes.unwrap().to_vec()).unwrap())
+            .collect()
+            .await;
+        let types: Vec<&str> = events
+            .iter()
+            .map(|e| e.lines().next().unwrap().trim_start_matches("event: "))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert!(events[0].contains("\"id\":\"c1\""));
+        assert!(events[2].contains("\"text_delta\""));
+        assert!(events[4].contains("\"stop_reason\":\"end_turn\""));
+    }
+
+    #[test]
+    fn test_stream_decodes_characters_split_across_chunks() {
+        let req = create_request(json!({
+            "model": "gpt-4",
+            "max_tokens": 16,
+            "messages": [{"role": "user", "content": "Hi"}]
+        }));
+        let mut state = StreamState::new(&req);
+        let chunk = "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"你好\"},\"finish_reason\":null}]}\n\n";
+        let split = chunk.find("你").unwrap() + 1;
+
+        assert!(state.push(&chunk.as_bytes()[..split]).is_empty());
+        let outputs = state.push(&chunk.as_bytes()[split..]);
+        let delta = String::from_utf8(outputs.last().unwrap().as_ref().unwrap().to_vec()).unwrap();
+        assert!(delta.contains("\"text\":\"你好\""));
+    }
+}
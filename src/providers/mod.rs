@@ -1,11 +1,25 @@
 pub mod routing;
+pub mod api_keys;
+pub mod cooldown;
 pub mod openai;
 pub mod anthropic;
 pub mod gemini;
+pub mod completions;
+pub mod embeddings;
+pub mod messages;
+pub mod cohere;
+pub mod jina;
+pub mod tei;
+pub mod passthrough;
+pub mod responses;
 
 use crate::config::ModelConfig;
+use crate::error::FeatherGateError;
 use crate::types::{ChatRequest, ChatResponse};
 use crate::Result;
+use futures_util::{Stream, StreamExt};
+use hyper::body::Bytes;
+use std::pin::Pin;
 
 /// Provider trait - 所有 provider 必须实现
 #[allow(async_fn_in_trait)]
@@ -15,3 +29,99 @@ pub trait Provider {
         req: &ChatRequest,
     ) -> Result<ChatResponse>;
 }
+
+/// 读取上游错误响应体（限制大小，防止 DoS 攻击）
+pub(crate) async fn read_error_body(response: reqwest::Response) -> String {
+    response
+        .text()
+        .await
+        .unwrap_or_default()
+        .chars()
+        .take(4096)
+        .collect()
+}
+
+/// 从部署的 key 池选择上游 key 并发送请求，非 2xx 响应归一化为错误
+///
+/// 上游报告的额度和认证失败回报给 key 池，供后续请求选择 key；选中的 key 因此被停用时，
+/// 用池中的下一个 key 重新构造请求并重试一次。
+pub(crate) async fn send_with_key(
+    config: &ModelConfig,
+    parse_error: fn(u16, &str) -> FeatherGateError,
+    build: impl Fn(&str) -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    let pool = api_keys::key_pool();
+    let lease = pool.acquire(config)?;
+    let err = match send_with_lease(&lease, parse_error, build(lease.key())).await {
+        Err(err) if lease.record_error(&err) => err,
+        result => return result,
+    };
+
+    // 其他 key 也都已停用时返回原来的错误
+    let Ok(lease) = pool.acquire(config) else {
+        return Err(err);
+    };
+    let result = send_with_lease(&lease, parse_error, build(lease.key())).await;
+    if let Err(err) = &result {
+        lease.record_error(err);
+    }
+    result
+}
+
+/// 与 `send_with_key` 相同，但请求体只能发送一次（如边接收边转发的上传），key 失效时不重试
+pub(crate) async fn send_once_with_key(
+    config: &ModelConfig,
+    parse_error: fn(u16, &str) -> FeatherGateError,
+    build: impl FnOnce(&str) -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    let lease = api_keys::key_pool().acquire(config)?;
+    let result = send_with_lease(&lease, parse_error, build(lease.key())).await;
+    if let Err(err) = &result {
+        lease.record_error(err);
+    }
+    result
+}
+
+/// 使用选中的 key 发送请求，非 2xx 响应归一化为错误
+async fn send_with_lease(
+    lease: &api_keys::KeyLease<'_>,
+    parse_error: fn(u16, &str) -> FeatherGateError,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response> {
+    let response = request.send().await?;
+    lease.observe(response.headers());
+
+    // 检查状态码
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = read_error_body(response).await;
+        return Err(parse_error(status.as_u16(), &error_body));
+    }
+
+    Ok(response)
+}
+
+/// 上游的原始响应（音频等非 JSON 内容，原样流式返回）
+pub struct RawResponse {
+    pub content_type: Option<String>,
+    pub body: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+}
+
+impl RawResponse {
+    /// 从上游响应构造，保留 Content-Type
+    pub(crate) fn from_upstream(response: reqwest::Response) -> Self {
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response
+            .bytes_stream()
+            .map(|result| result.map_err(FeatherGateError::HttpError));
+
+        RawResponse {
+            content_type,
+            body: Box::pin(body),
+        }
+    }
+}
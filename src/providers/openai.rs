@@ -1,10 +1,18 @@
-use crate::config::ModelConfig;
-use crate::error::FeatherGateError;
-use crate::types::{ChatRequest, ChatResponse};
+use crate::config::{parse_model_string, ModelConfig};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::providers::{send_once_with_key, send_with_key, RawResponse};
+use crate::types::audio::{AudioBody, SpeechRequest, TranscriptionRequest};
+use crate::types::completions::{CompletionRequest, CompletionResponse};
+use crate::types::embeddings::{EmbeddingRequest, EmbeddingResponse};
+use crate::types::images::{ImageGenerationRequest, ImageResponse};
+use crate::types::moderations::{ModerationRequest, ModerationResponse};
+use crate::types::responses::{ResponseObject, ResponsesRequest};
+use crate::types::{ChatRequest, ChatResponse, StreamOptions};
 use crate::Result;
 use futures_util::Stream;
 use hyper::body::Bytes;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 use std::time::Duration;
 
@@ -21,46 +29,94 @@ fn get_http_client() -> &'static Client {
     &CLIENT
 }
 
-/// 转发请求到 OpenAI（直接 passthrough）
-pub async fn forward_request(
-    config: &ModelConfig,
-    req: &ChatRequest,
-) -> Result<ChatResponse> {
-    let client = get_http_client();
+/// OpenAI API 错误格式
+#[derive(Debug, Deserialize)]
+struct OpenAIErrorResponse {
+    error: OpenAIErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIErrorBody {
+    message: String,
+    #[serde(rename = "type", default)]
+    error_type: Option<String>,
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+}
+
+/// 解析 OpenAI 错误响应并归一化
+pub(crate) fn parse_error(status: u16, body: &str) -> FeatherGateError {
+    let Ok(resp) = serde_json::from_str::<OpenAIErrorResponse>(body) else {
+        return FeatherGateError::upstream(status, format!("OpenAI: {}", body));
+    };
+
+    let code = resp.error.code.as_ref().and_then(|c| c.as_str()).unwrap_or_default();
+    let error_type = resp.error.error_type.as_deref().unwrap_or_default();
 
-    // 构建 URL
+    let kind = match (code, error_type) {
+        ("context_length_exceeded", _) => UpstreamErrorKind::ContextWindowExceeded,
+        ("content_policy_violation" | "content_filter", _) => {
+            UpstreamErrorKind::ContentPolicyViolation
+        }
+        ("insufficient_quota", _) | (_, "insufficient_quota") => UpstreamErrorKind::QuotaExceeded,
+        ("rate_limit_exceeded", _) => UpstreamErrorKind::RateLimited,
+        _ => UpstreamErrorKind::from_status(status).refine_by_message(&resp.error.message),
+    };
+
+    FeatherGateError::upstream_with_kind(
+        status,
+        kind,
+        format!("OpenAI: {}", resp.error.message),
+    )
+}
+
+/// 构建 OpenAI API URL
+fn build_url(config: &ModelConfig, path: &str) -> String {
     let api_base = if config.litellm_params.api_base.is_empty() {
         "https://api.openai.com/v1"
     } else {
         &config.litellm_params.api_base
     };
-    let url = format!("{}/chat/completions", api_base.trim_end_matches('/'));
+    format!("{}/{}", api_base.trim_end_matches('/'), path)
+}
+
+/// 发送 JSON 请求到 OpenAI 兼容接口，非 2xx 响应归一化为错误
+pub(crate) async fn post_json<T: Serialize + ?Sized>(
+    config: &ModelConfig,
+    path: &str,
+    body: &T,
+) -> Result<reqwest::Response> {
+    let client = get_http_client();
 
     // 发送请求
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", config.litellm_params.api_key))
-        .header("Content-Type", "application/json")
-        .json(req)
-        .send()
-        .await?;
-
-    // 检查状态码
-    let status = response.status();
-    if !status.is_success() {
-        // 限制错误响应体大小，防止 DoS 攻击
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_default()
-            .chars()
-            .take(4096)
-            .collect::<String>();
-        return Err(FeatherGateError::upstream(
-            status.as_u16(),
-            format!("OpenAI API 错误: {}", error_body),
-        ));
-    }
+    send_with_key(config, parse_error, |api_key| {
+        client
+            .post(build_url(config, path))
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("Content-Type", "application/json")
+            .json(body)
+    })
+    .await
+}
+
+/// 将响应体转换为字节流
+fn into_byte_stream(
+    response: reqwest::Response,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    use futures_util::StreamExt;
+    let stream = response.bytes_stream().map(|result| {
+        result.map_err(FeatherGateError::HttpError)
+    });
+
+    Box::pin(stream)
+}
+
+/// 转发请求到 OpenAI（直接 passthrough）
+pub async fn forward_request(
+    config: &ModelConfig,
+    req: &ChatRequest,
+) -> Result<ChatResponse> {
+    let response = post_json(config, "chat/completions", req).await?;
 
     // 解析响应
     let chat_response: ChatResponse = response.json().await?;
@@ -72,49 +128,207 @@ pub async fn forward_request_stream(
     config: &ModelConfig,
     req: &ChatRequest,
 ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    // 总是向上游请求用量，以便流式请求也能计费
+    let upstream_req = ChatRequest {
+        stream_options: Some(StreamOptions::with_usage(req.stream_options.as_ref())),
+        ..req.clone()
+    };
+    let response = post_json(config, "chat/completions", &upstream_req).await?;
+
+    // 返回字节流
+    Ok(into_byte_stream(response))
+}
+
+/// 构建发往上游的文本补全请求（使用部署的真实模型 ID）
+fn upstream_completion_request(
+    config: &ModelConfig,
+    req: &CompletionRequest,
+) -> Result<CompletionRequest> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    Ok(CompletionRequest {
+        model: model_id,
+        ..req.clone()
+    })
+}
+
+/// 转发文本补全请求到 OpenAI（直接 passthrough）
+pub async fn forward_completion(
+    config: &ModelConfig,
+    req: &CompletionRequest,
+) -> Result<CompletionResponse> {
+    let upstream_req = upstream_completion_request(config, req)?;
+    let response = post_json(config, "completions", &upstream_req).await?;
+
+    Ok(response.json().await?)
+}
+
+/// 转发流式文本补全请求到 OpenAI
+pub async fn forward_completion_stream(
+    config: &ModelConfig,
+    req: &CompletionRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let upstream_req = CompletionRequest {
+        stream_options: Some(StreamOptions::with_usage(req.stream_options.as_ref())),
+        ..upstream_completion_request(config, req)?
+    };
+    let response = post_json(config, "completions", &upstream_req).await?;
+
+    Ok(into_byte_stream(response))
+}
+
+/// 转发向量嵌入请求到 OpenAI（直接 passthrough，使用部署的真实模型 ID）
+pub async fn forward_embedding(
+    config: &ModelConfig,
+    req: &EmbeddingRequest,
+) -> Result<EmbeddingResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = EmbeddingRequest {
+        model: model_id,
+        ..req.clone()
+    };
+    let response = post_json(config, "embeddings", &upstream_req).await?;
+
+    Ok(response.json().await?)
+}
+
+/// 转发图像生成请求到 OpenAI（直接 passthrough，使用部署的真实模型 ID）
+pub async fn forward_image_generation(
+    config: &ModelConfig,
+    req: &ImageGenerationRequest,
+) -> Result<ImageResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = ImageGenerationRequest {
+        model: model_id,
+        ..req.clone()
+    };
+    let response = post_json(config, "images/generations", &upstream_req).await?;
+
+    Ok(response.json().await?)
+}
+
+/// 转发内容审核请求到 OpenAI（直接 passthrough，使用部署的真实模型 ID）
+pub async fn forward_moderation(
+    config: &ModelConfig,
+    req: &ModerationRequest,
+) -> Result<ModerationResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = ModerationRequest {
+        model: Some(model_id),
+        ..req.clone()
+    };
+    let response = post_json(config, "moderations", &upstream_req).await?;
+
+    Ok(response.json().await?)
+}
+
+/// 转发 Responses API 请求到 OpenAI（直接 passthrough，使用部署的真实模型 ID）
+pub async fn forward_responses(
+    config: &ModelConfig,
+    req: &ResponsesRequest,
+) -> Result<ResponseObject> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = ResponsesRequest {
+        model: model_id,
+        ..req.clone()
+    };
+    let response = post_json(config, "responses", &upstream_req).await?;
+
+    Ok(response.json().await?)
+}
+
+/// 转发流式 Responses API 请求到 OpenAI，事件流原样返回
+pub async fn forward_responses_stream(
+    config: &ModelConfig,
+    req: &ResponsesRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = ResponsesRequest {
+        model: model_id,
+        ..req.clone()
+    };
+    let response = post_json(config, "responses", &upstream_req).await?;
+
+    Ok(into_byte_stream(response))
+}
+
+/// 转发文本转语音请求到 OpenAI，音频内容原样流式返回
+pub async fn forward_speech(config: &ModelConfig, req: &SpeechRequest) -> Result<RawResponse> {
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+    let upstream_req = SpeechRequest {
+        model: model_id,
+        ..req.clone()
+    };
+    let response = post_json(config, "audio/speech", &upstream_req).await?;
+
+    Ok(RawResponse::from_upstream(response))
+}
+
+/// 转发语音转文本请求到 OpenAI Whisper 兼容接口（包括自建的 faster-whisper）
+///
+/// 音频文件以流式 multipart 上传，响应格式由 `response_format` 决定，原样返回。
+/// 已缓冲的文件在上游 key 失效时换 key 重试，边接收边转发的文件只能上传一次。
+pub async fn forward_transcription(
+    config: &ModelConfig,
+    req: TranscriptionRequest,
+) -> Result<RawResponse> {
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+    use reqwest::multipart::{Form, Part};
+
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
     let client = get_http_client();
 
-    // 构建 URL
-    let api_base = if config.litellm_params.api_base.is_empty() {
-        "https://api.openai.com/v1"
-    } else {
-        &config.litellm_params.api_base
+    let file = req.file;
+    let mut part_headers = HeaderMap::new();
+    if let Some(content_type) = &file.content_type {
+        let value = HeaderValue::from_str(content_type)
+            .map_err(|e| FeatherGateError::invalid_request(e.to_string()))?;
+        part_headers.insert(CONTENT_TYPE, value);
+    }
+    let form = |body: reqwest::Body| {
+        let part = Part::stream(body)
+            .file_name(file.filename.clone())
+            .headers(part_headers.clone());
+        let mut form = Form::new().text("model", model_id.clone());
+        for (name, value) in &req.fields {
+            form = form.text(name.clone(), value.clone());
+        }
+        form.part("file", part)
+    };
+    // 发送请求（multipart 自带 Content-Type）
+    let upload = |api_key: &str, form: Form| {
+        client
+            .post(build_url(config, "audio/transcriptions"))
+            .header("Authorization", format!("Bearer {}", api_key))
+            .multipart(form)
     };
-    let url = format!("{}/chat/completions", api_base.trim_end_matches('/'));
 
-    // 发送请求
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", config.litellm_params.api_key))
-        .header("Content-Type", "application/json")
-        .json(req)
-        .send()
-        .await?;
-
-    // 检查状态码
-    let status = response.status();
-    if !status.is_success() {
-        // 限制错误响应体大小，防止 DoS 攻击
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_default()
-            .chars()
-            .take(4096)
-            .collect::<String>();
-        return Err(FeatherGateError::upstream(
-            status.as_u16(),
-            format!("OpenAI API 错误: {}", error_body),
-        ));
+    let response = match file.body {
+        AudioBody::Buffered(bytes) => {
+            send_with_key(config, parse_error, |api_key| upload(api_key, form(bytes.clone().into())))
+                .await
+        }
+        AudioBody::Streaming(stream) => {
+            let body = reqwest::Body::wrap_stream(stream);
+            send_once_with_key(config, parse_error, |api_key| upload(api_key, form(body))).await
+        }
     }
+    .map_err(upload_error)?;
 
-    // 返回字节流
-    use futures_util::StreamExt;
-    let stream = response.bytes_stream().map(|result| {
-        result.map_err(FeatherGateError::HttpError)
-    });
+    Ok(RawResponse::from_upstream(response))
+}
 
-    Ok(Box::pin(stream))
+/// 上传被音频流中的表单错误（如 file 之后的字段）中止时，返回该错误而不是连接错误
+fn upload_error(err: FeatherGateError) -> FeatherGateError {
+    if let FeatherGateError::HttpError(e) = &err {
+        let mut source = std::error::Error::source(e);
+        while let Some(inner) = source {
+            if let Some(FeatherGateError::Validation(validation)) = inner.downcast_ref() {
+                return validation.clone().into();
+            }
+            source = inner.source();
+        }
+    }
+    err
 }
 
 #[cfg(test)]
@@ -135,7 +349,9 @@ mod tests {
                 model: "openai/gpt-4".to_string(),
                 api_key: "sk-test-key".to_string(),
                 api_base: api_base.to_string(),
+                ..Default::default()
             },
+            ..Default::default()
         }
     }
 
@@ -146,7 +362,10 @@ mod tests {
             temperature: Some(0.7),
             max_tokens: Some(100),
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         }
     }
 
@@ -199,6 +418,55 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn test_key_pool_skips_rejected_key() {
+        let mut server = setup_mock_server().await;
+
+        let rejected = server
+            .mock("POST", "/chat/completions")
+            .match_header("authorization", "Bearer sk-revoked-0001")
+            .with_status(401)
+            .with_body(r#"{"error": {"message": "Incorrect API key provided", "type": "invalid_request_error"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("POST", "/chat/completions")
+            .match_header("authorization", "Bearer sk-working-0002")
+            .with_status(200)
+            .with_header("x-ratelimit-remaining-requests", "99")
+            .with_body(
+                r#"{
+                "id": "chatcmpl-pool",
+                "object": "chat.completion",
+                "created": 1677652288,
+                "model": "gpt-4",
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": "ok"},
+                    "finish_reason": "stop"
+                }]
+            }"#,
+            )
+            .expect(4)
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.model_name = "gpt-4-key-pool".to_string();
+        config.litellm_params.api_key = "sk-revoked-0001".to_string();
+        config.litellm_params.api_keys = vec!["sk-working-0002".to_string()];
+        let req = create_test_request();
+
+        // 第一个 key 返回 401 后被停用，同一个请求换第二个 key 重试，之后的请求都使用第二个 key
+        for _ in 0..4 {
+            assert_eq!(forward_request(&config, &req).await.unwrap().id, "chatcmpl-pool");
+        }
+
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_forward_request_preserves_parameters() {
         let mut server = setup_mock_server().await;
@@ -264,6 +532,326 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[test]
+    fn test_parse_error_classification() {
+        let err = parse_error(
+            400,
+            r#"{"error": {"message": "This model's maximum context length is 8192 tokens", "type": "invalid_request_error", "param": "messages", "code": "context_length_exceeded"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::ContextWindowExceeded));
+        assert!(err.to_string().contains("maximum context length"));
+
+        let err = parse_error(
+            429,
+            r#"{"error": {"message": "You exceeded your current quota", "type": "insufficient_quota", "param": null, "code": "insufficient_quota"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::QuotaExceeded));
+
+        let err = parse_error(
+            400,
+            r#"{"error": {"message": "Your request was rejected by our safety system", "type": "invalid_request_error", "code": "content_policy_violation"}}"#,
+        );
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::ContentPolicyViolation));
+
+        let err = parse_error(502, "<html>Bad Gateway</html>");
+        assert_eq!(err.upstream_kind(), Some(UpstreamErrorKind::ServerError));
+        assert!(err.to_string().contains("Bad Gateway"));
+    }
+
+    #[tokio::test]
+    async fn test_forward_completion_uses_model_id() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("POST", "/completions")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "gpt-3.5-turbo-instruct",
+                "prompt": "Say hi",
+                "suffix": "!",
+                "echo": true,
+                "best_of": 2
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "cmpl-1",
+                "object": "text_completion",
+                "created": 1234567890,
+                "model": "gpt-3.5-turbo-instruct",
+                "choices": [{"text": "Say hi there", "index": 0, "logprobs": null, "finish_reason": "stop"}]
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.model_name = "instruct".to_string();
+        config.litellm_params.model = "openai/gpt-3.5-turbo-instruct".to_string();
+
+        let req: CompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "instruct",
+            "prompt": "Say hi",
+            "suffix": "!",
+            "echo": true,
+            "best_of": 2
+        }))
+        .unwrap();
+
+        let response = forward_completion(&config, &req).await.unwrap();
+        assert_eq!(response.object, "text_completion");
+        assert_eq!(response.choices[0].text, "Say hi there");
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_forward_embedding_passthrough() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("POST", "/embeddings")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "text-embedding-3-small",
+                "input": ["a", "b"],
+                "dimensions": 2,
+                "encoding_format": "base64"
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                "object": "list",
+                "data": [
+                    {"object": "embedding", "embedding": "AACAPwAAIMA=", "index": 0},
+                    {"object": "embedding", "embedding": "AAAAAAAAAAA=", "index": 1}
+                ],
+                "model": "text-embedding-3-small",
+                "usage": {"prompt_tokens": 2, "total_tokens": 2}
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.model_name = "embed".to_string();
+        config.litellm_params.model = "openai/text-embedding-3-small".to_string();
+
+        let req: EmbeddingRequest = serde_json::from_value(serde_json::json!({
+            "model": "embed",
+            "input": ["a", "b"],
+            "dimensions": 2,
+            "encoding_format": "base64"
+        }))
+        .unwrap();
+
+        let response = forward_embedding(&config, &req).await.unwrap();
+        assert_eq!(response.data.len(), 2);
+        assert_eq!(response.usage.prompt_tokens, 2);
+
+        mock.assert_async().await;
+    }
+
+    async fn collect_body(response: RawResponse) -> Vec<u8> {
+        use futures_util::StreamExt;
+        let mut body = Vec::new();
+        let mut stream = response.body;
+        while let Some(chunk) = stream.next().await {
+            body.extend_from_slice(&chunk.unwrap());
+        }
+        body
+    }
+
+    #[tokio::test]
+    async fn test_forward_speech_returns_binary() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("POST", "/audio/speech")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "tts-1",
+                "input": "Hello",
+                "voice": "alloy"
+            })))
+            .with_status(200)
+            .with_header("content-type", "audio/mpeg")
+            .with_body(b"\xFF\xFBaudio")
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.litellm_params.model = "openai/tts-1".to_string();
+        let req: SpeechRequest = serde_json::from_value(serde_json::json!({
+            "model": "tts",
+            "input": "Hello",
+            "voice": "alloy"
+        }))
+        .unwrap();
+
+        let response = forward_speech(&config, &req).await.unwrap();
+        assert_eq!(response.content_type.as_deref(), Some("audio/mpeg"));
+        assert_eq!(collect_body(response).await, b"\xFF\xFBaudio");
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_forward_transcription_streams_multipart() {
+        use crate::types::audio::AudioFile;
+
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_header(
+                "content-type",
+                mockito::Matcher::Regex("^multipart/form-data; boundary=".to_string()),
+            )
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"model\"\r\n\r\nwhisper-1".to_string()),
+                mockito::Matcher::Regex("name=\"language\"\r\n\r\nen".to_string()),
+                mockito::Matcher::Regex("filename=\"a.wav\"".to_string()),
+                mockito::Matcher::Regex("RIFF-part1-part2".to_string()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"text": "hello"}"#)
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.litellm_params.model = "openai/whisper-1".to_string();
+        let chunks = vec![Ok(Bytes::from("RIFF-part1")), Ok(Bytes::from("-part2"))];
+        let req = TranscriptionRequest {
+            model: "whisper".to_string(),
+            fields: vec![("language".to_string(), "en".to_string())],
+            file: AudioFile {
+                filename: "a.wav".to_string(),
+                content_type: Some("audio/wav".to_string()),
+                body: AudioBody::Streaming(Box::pin(futures_util::stream::iter(chunks))),
+            },
+        };
+
+        let response = forward_transcription(&config, req).await.unwrap();
+        assert_eq!(response.content_type.as_deref(), Some("application/json"));
+        assert_eq!(collect_body(response).await, br#"{"text": "hello"}"#);
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_buffered_transcription_fails_over_to_next_key() {
+        use crate::types::audio::AudioFile;
+
+        let mut server = setup_mock_server().await;
+        let revoked = server
+            .mock("POST", mockito::Matcher::Any)
+            .match_header("authorization", "Bearer sk-revoked")
+            .with_status(401)
+            .with_body(r#"{"error": {"message": "Incorrect API key provided", "type": "invalid_request_error", "code": "invalid_api_key"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let transcription = server
+            .mock("POST", "/audio/transcriptions")
+            .match_header("authorization", "Bearer sk-valid")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"model\"\r\n\r\nwhisper-1".to_string()),
+                mockito::Matcher::Regex("(?i)content-type: audio/wav\r\n\r\nRIFF".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"text": "hello"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.model_name = "whisper-key-pool".to_string();
+        config.litellm_params.model = "openai/whisper-1".to_string();
+        config.litellm_params.api_key = "sk-revoked".to_string();
+        config.litellm_params.api_keys = vec!["sk-valid".to_string()];
+
+        // 已缓冲的音频文件在第一个 key 返回 401 后重新构造表单，用下一个 key 上传
+        let req = TranscriptionRequest {
+            model: "whisper".to_string(),
+            fields: Vec::new(),
+            file: AudioFile {
+                filename: "a.wav".to_string(),
+                content_type: Some("audio/wav".to_string()),
+                body: AudioBody::Buffered(Bytes::from("RIFF")),
+            },
+        };
+        let response = forward_transcription(&config, req).await.unwrap();
+        assert_eq!(collect_body(response).await, br#"{"text": "hello"}"#);
+
+        revoked.assert_async().await;
+        transcription.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_forward_transcription_rejects_field_after_file() {
+        use crate::types::audio::AudioFile;
+        use crate::types::ValidationError;
+
+        let server = setup_mock_server().await;
+        let mut config = create_test_config(&server.url());
+        config.litellm_params.model = "openai/whisper-1".to_string();
+
+        let chunks = vec![
+            Ok(Bytes::from("RIFF-part1")),
+            Err(ValidationError::FieldAfterFile("language".to_string()).into()),
+        ];
+        let req = TranscriptionRequest {
+            model: "whisper".to_string(),
+            fields: Vec::new(),
+            file: AudioFile {
+                filename: "a.wav".to_string(),
+                content_type: Some("audio/wav".to_string()),
+                body: AudioBody::Streaming(Box::pin(futures_util::stream::iter(chunks))),
+            },
+        };
+
+        let err = forward_transcription(&config, req).await.err().unwrap();
+        assert!(matches!(
+            err,
+            FeatherGateError::Validation(ValidationError::FieldAfterFile(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forward_image_generation_passthrough() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("POST", "/images/generations")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "dall-e-3",
+                "prompt": "a cat",
+                "size": "1024x1024",
+                "background": "transparent",
+                "output_format": "webp"
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"created": 1, "data": [{"url": "https://example.com/cat.png", "revised_prompt": "a cute cat"}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut config = create_test_config(&server.url());
+        config.model_name = "image".to_string();
+        config.litellm_params.model = "openai/dall-e-3".to_string();
+
+        let req: ImageGenerationRequest = serde_json::from_value(serde_json::json!({
+            "model": "image",
+            "prompt": "a cat",
+            "size": "1024x1024",
+            "background": "transparent",
+            "output_format": "webp"
+        }))
+        .unwrap();
+
+        let response = forward_image_generation(&config, &req).await.unwrap();
+        assert_eq!(response.data[0].url.as_deref(), Some("https://example.com/cat.png"));
+        assert_eq!(response.data[0].revised_prompt.as_deref(), Some("a cute cat"));
+
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_forward_request_with_empty_api_base() {
         // 当 api_base 为空时，应使用默认 OpenAI URL
@@ -273,7 +861,9 @@ mod tests {
                 model: "openai/gpt-4".to_string(),
                 api_key: "sk-test-key".to_string(),
                 api_base: String::new(), // 空字符串
+                ..Default::default()
             },
+            ..Default::default()
         };
 
         let req = create_test_request();
@@ -0,0 +1,285 @@
+use crate::config::{parse_model_string, Config, ModelConfig};
+use crate::error::FeatherGateError;
+use crate::providers::api_keys::key_pool;
+use crate::Result;
+use futures_util::{Stream, StreamExt};
+use hyper::body::Bytes;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use reqwest::{Client, Method, StatusCode};
+use std::pin::Pin;
+use std::time::Duration;
+
+/// 支持透传的提供商及其默认 API 地址（与各 provider 模块的默认值一致）
+const PROVIDERS: &[(&str, &str)] = &[
+    ("openai", "https://api.openai.com/v1"),
+    ("anthropic", "https://api.anthropic.com"),
+    ("gemini", "https://generativelanguage.googleapis.com"),
+];
+
+/// 调用方的凭据头，转发前全部移除
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "x-api-key", "x-goog-api-key"];
+
+/// 逐跳头，不能原样转发
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "host",
+    "connection",
+    "keep-alive",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// 获取透传专用的 HTTP 客户端（不设置总超时，批处理结果下载等长响应需要持续推流）
+fn get_http_client() -> &'static Client {
+    use once_cell::sync::Lazy;
+    static CLIENT: Lazy<Client> = Lazy::new(|| {
+        Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .pool_max_idle_per_host(10)
+            .build()
+            .unwrap()
+    });
+    &CLIENT
+}
+
+/// 原样透传的请求
+pub struct PassthroughRequest {
+    pub provider: String,
+    pub method: Method,
+    /// 去掉 `/{provider}` 前缀后的路径和查询字符串
+    pub path_and_query: String,
+    pub headers: HeaderMap,
+    pub body: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+}
+
+/// 上游的原样响应（包括非 2xx 响应）
+pub struct PassthroughResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+}
+
+/// 拆分透传路径：`/anthropic/v1/messages/batches` → (`anthropic`, `/v1/messages/batches`)
+pub fn split_path(path: &str) -> Option<(&'static str, &str)> {
+    PROVIDERS.iter().find_map(|(provider, _)| {
+        let rest = path.strip_prefix('/')?.strip_prefix(provider)?;
+        (rest.is_empty() || rest.starts_with('/')).then_some((*provider, rest))
+    })
+}
+
+/// 查找提供凭据的部署（该提供商在 model_list 中的第一个部署）
+fn find_deployment<'a>(config: &'a Config, provider: &str) -> Result<&'a ModelConfig> {
+    config
+        .model_list
+        .iter()
+        .find(|m| {
+            parse_model_string(&m.litellm_params.model).is_ok_and(|(p, _)| p == provider)
+        })
+        .ok_or_else(|| FeatherGateError::UnsupportedProvider(provider.to_string()))
+}
+
+/// 构建上游 URL（路径原样拼接到 api_base 之后，移除调用方通过 `key=` 传递的凭据）
+fn build_url(api_base: &str, path_and_query: &str) -> String {
+    let (path, query) = match path_and_query.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path_and_query, None),
+    };
+    let mut url = format!("{}{}", api_base.trim_end_matches('/'), path);
+
+    let query: Vec<&str> = query
+        .into_iter()
+        .flat_map(|q| q.split('&'))
+        .filter(|pair| !pair.is_empty() && *pair != "key" && !pair.starts_with("key="))
+        .collect();
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(&query.join("&"));
+    }
+    url
+}
+
+/// 转发请求头：移除逐跳头和调用方凭据，注入部署的上游凭据
+fn upstream_headers(provider: &str, headers: &HeaderMap, api_key: &str) -> Result<HeaderMap> {
+    let mut upstream = headers.clone();
+    for name in HOP_BY_HOP_HEADERS.iter().chain(CREDENTIAL_HEADERS) {
+        upstream.remove(*name);
+    }
+
+    let invalid_key = |_| FeatherGateError::InvalidUpstreamApiKey(provider.to_string());
+    match provider {
+        "openai" => {
+            let value = HeaderValue::from_str(&format!("Bearer {}", api_key)).map_err(invalid_key)?;
+            upstream.insert(header::AUTHORIZATION, value);
+        }
+        "anthropic" => {
+            upstream.insert("x-api-key", HeaderValue::from_str(api_key).map_err(invalid_key)?);
+            if !upstream.contains_key("anthropic-version") {
+                upstream.insert("anthropic-version", HeaderValue::from_static("2023-06-01"));
+            }
+        }
+        _ => {
+            upstream.insert("x-goog-api-key", HeaderValue::from_str(api_key).map_err(invalid_key)?);
+        }
+    }
+    Ok(upstream)
+}
+
+/// 原样转发请求到提供商，请求体和响应体都以流的方式传输
+///
+/// 上游的非 2xx 响应同样原样返回，不做归一化。
+pub async fn forward(config: &Config, req: PassthroughRequest) -> Result<PassthroughResponse> {
+    let deployment = find_deployment(config, &req.provider)?;
+    let params = &deployment.litellm_params;
+    let api_base = match params.api_base.as_str() {
+        "" => PROVIDERS
+            .iter()
+            .find(|(provider, _)| *provider == req.provider)
+            .map(|(_, base)| *base)
+            .unwrap_or_default(),
+        api_base => api_base,
+    };
+
+    let lease = key_pool().acquire(deployment)?;
+    let response = get_http_client()
+        .request(req.method, build_url(api_base, &req.path_and_query))
+        .headers(upstream_headers(&req.provider, &req.headers, lease.key())?)
+        .body(reqwest::Body::wrap_stream(req.body))
+        .send()
+        .await?;
+
+    // 响应体原样返回，只按状态码判断 key 是否失效
+    let status = response.status();
+    lease.observe(response.headers());
+    if !status.is_success() {
+        lease.record_error(&FeatherGateError::upstream(status.as_u16(), ""));
+    }
+    let mut headers = response.headers().clone();
+    for name in HOP_BY_HOP_HEADERS {
+        headers.remove(*name);
+    }
+    let body = response
+        .bytes_stream()
+        .map(|result| result.map_err(FeatherGateError::HttpError));
+
+    Ok(PassthroughResponse {
+        status,
+        headers,
+        body: Box::pin(body),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LitellmParams;
+
+    #[test]
+    fn test_split_path() {
+        assert_eq!(
+            split_path("/anthropic/v1/messages/batches"),
+            Some(("anthropic", "/v1/messages/batches"))
+        );
+        assert_eq!(split_path("/openai"), Some(("openai", "")));
+        assert_eq!(split_path("/openai-compatible/v1"), None);
+        assert_eq!(split_path("/v1/chat/completions"), None);
+    }
+
+    #[test]
+    fn test_build_url_strips_caller_key() {
+        assert_eq!(
+            build_url("https://generativelanguage.googleapis.com/", "/v1beta/cachedContents?key=AIza&pageSize=10"),
+            "https://generativelanguage.googleapis.com/v1beta/cachedContents?pageSize=10"
+        );
+        assert_eq!(
+            build_url("https://api.openai.com/v1", "/fine_tuning/jobs"),
+            "https://api.openai.com/v1/fine_tuning/jobs"
+        );
+    }
+
+    #[test]
+    fn test_upstream_headers_swap_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer sk-fg-caller"));
+        headers.insert(header::HOST, HeaderValue::from_static("gateway.local"));
+        headers.insert("anthropic-beta", HeaderValue::from_static("message-batches-2024-09-24"));
+
+        let upstream = upstream_headers("anthropic", &headers, "sk-ant-real").unwrap();
+        assert!(!upstream.contains_key(header::AUTHORIZATION));
+        assert!(!upstream.contains_key(header::HOST));
+        assert_eq!(upstream["x-api-key"], "sk-ant-real");
+        assert_eq!(upstream["anthropic-version"], "2023-06-01");
+        assert_eq!(upstream["anthropic-beta"], "message-batches-2024-09-24");
+
+        let result = upstream_headers("openai", &headers, "sk-bad\nkey");
+        assert!(matches!(result, Err(FeatherGateError::InvalidUpstreamApiKey(p)) if p == "openai"));
+    }
+
+    #[tokio::test]
+    async fn test_forward_streams_request_and_response() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/files")
+            .match_query(mockito::Matcher::UrlEncoded("purpose".into(), "batch".into()))
+            .match_header("authorization", "Bearer sk-real")
+            .match_body("line-1\nline-2\n")
+            .with_status(404)
+            .with_header("x-request-id", "req_1")
+            .with_body(r#"{"error": {"message": "not found"}}"#)
+            .create_async()
+            .await;
+
+        let config = Config {
+            model_list: vec![ModelConfig {
+                model_name: "gpt-4".to_string(),
+                litellm_params: LitellmParams {
+                    model: "openai/gpt-4".to_string(),
+                    api_key: "sk-real".to_string(),
+                    api_base: format!("{}/v1", server.url()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let chunks = vec![Ok(Bytes::from("line-1\n")), Ok(Bytes::from("line-2\n"))];
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer sk-fg-caller"));
+
+        let response = forward(
+            &config,
+            PassthroughRequest {
+                provider: "openai".to_string(),
+                method: Method::POST,
+                path_and_query: "/files?purpose=batch".to_string(),
+                headers,
+                body: Box::pin(futures_util::stream::iter(chunks)),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        assert_eq!(response.headers["x-request-id"], "req_1");
+        let body: Vec<Bytes> = response.body.map(|chunk| chunk.unwrap()).collect().await;
+        assert_eq!(body.concat(), br#"{"error": {"message": "not found"}}"#);
+        mock.assert_async().await;
+
+        let err = forward(
+            &config,
+            PassthroughRequest {
+                provider: "gemini".to_string(),
+                method: Method::GET,
+                path_and_query: "/v1beta/cachedContents".to_string(),
+                headers: HeaderMap::new(),
+                body: Box::pin(futures_util::stream::empty()),
+            },
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(err, FeatherGateError::UnsupportedProvider(p) if p == "gemini"));
+    }
+}
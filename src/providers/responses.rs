@@ -0,0 +1,755 @@
+use crate::auth;
+use crate::error::FeatherGateError;
+use crate::types::responses::{
+    ContentPart, ResponseItem, ResponseObject, ResponseUsage, ResponsesInput, ResponsesRequest,
+};
+use crate::types::{ChatRequest, ChatResponse, ChatStreamChunk, Message, Usage, ValidationError};
+use crate::Result;
+use futures_util::{Stream, StreamExt};
+use hyper::body::Bytes;
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 本地保存的响应数量上限（超出后淘汰最早的响应）
+const MAX_STORED_RESPONSES: usize = 1000;
+/// 本地保存的响应总大小上限（按序列化后的字节数估算，超出后淘汰最早的响应）
+const MAX_STORED_BYTES: usize = 64 * 1024 * 1024;
+/// 本地保存的响应的有效期
+const STORED_RESPONSE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 本地保存的对话（用于 previous_response_id 重建上下文）
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    /// 截至该响应的完整对话：历史、本次输入和本次输出
+    pub items: Vec<ResponseItem>,
+    /// 响应来自 OpenAI，上游同样保存了该响应，可以直接透传 previous_response_id
+    pub native: bool,
+}
+
+/// 待保存的对话：历史和本次输入，以及发起请求的调用方
+pub struct Conversation {
+    owner: String,
+    items: Vec<ResponseItem>,
+}
+
+struct StoredEntry {
+    /// 保存响应的调用方，其他调用方查找时视为不存在
+    owner: String,
+    response: StoredResponse,
+    size: usize,
+    stored_at: Instant,
+}
+
+/// 按保存顺序淘汰：超过数量或总大小上限时淘汰最早的响应，过期的响应同样被淘汰
+struct ResponseStore {
+    entries: HashMap<String, StoredEntry>,
+    order: VecDeque<String>,
+    bytes: usize,
+    max_entries: usize,
+    max_bytes: usize,
+    ttl: Duration,
+}
+
+impl ResponseStore {
+    fn new(max_entries: usize, max_bytes: usize, ttl: Duration) -> Self {
+        ResponseStore {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            bytes: 0,
+            max_entries,
+            max_bytes,
+            ttl,
+        }
+    }
+
+    fn get(&self, owner: &str, id: &str) -> Option<StoredResponse> {
+        self.entries
+            .get(id)
+            .filter(|entry| entry.owner == owner && entry.stored_at.elapsed() < self.ttl)
+            .map(|entry| entry.response.clone())
+    }
+
+    fn insert(&mut self, id: String, owner: String, response: StoredResponse) {
+        let size = serde_json::to_vec(&response.items).map(|json| json.len()).unwrap_or_default();
+        let entry = StoredEntry {
+            owner,
+            response,
+            size,
+            stored_at: Instant::now(),
+        };
+        self.bytes += size;
+        match self.entries.insert(id.clone(), entry) {
+            Some(replaced) => self.bytes -= replaced.size,
+            None => self.order.push_back(id),
+        }
+
+        while let Some(oldest) = self.order.front() {
+            let expired = self
+                .entries
+                .get(oldest)
+                .is_none_or(|entry| entry.stored_at.elapsed() >= self.ttl);
+            if !expired && self.order.len() <= self.max_entries && self.bytes <= self.max_bytes {
+                break;
+            }
+            if let Some(entry) = self.order.pop_front().and_then(|oldest| self.entries.remove(&oldest)) {
+                self.bytes -= entry.size;
+            }
+        }
+    }
+}
+
+fn response_store() -> &'static Mutex<ResponseStore> {
+    static STORE: Lazy<Mutex<ResponseStore>> =
+        Lazy::new(|| Mutex::new(ResponseStore::new(MAX_STORED_RESPONSES, MAX_STORED_BYTES, STORED_RESPONSE_TTL)));
+    &STORE
+}
+
+/// 查找当前调用方保存的响应（其他调用方保存的和已过期的响应视为不存在）
+pub fn lookup(id: &str) -> Option<StoredResponse> {
+    let owner = auth::current_caller().owner_id();
+    response_store().lock().unwrap().get(&owner, id)
+}
+
+/// 保存响应及其完整对话
+///
+/// `conversation` 为历史和本次输入（见 [`conversation`]），输出项会追加在其后。
+pub fn remember(conversation: Conversation, response: &ResponseObject, native: bool) {
+    let Conversation { owner, mut items } = conversation;
+    items.extend(response.output.iter().cloned());
+
+    let mut store = response_store().lock().unwrap();
+    store.insert(response.id.clone(), owner, StoredResponse { items, native });
+}
+
+/// 历史对话加上本次请求的输入项，归属于当前调用方
+///
+/// 在请求上下文中创建，流式响应结束时（可能已离开请求上下文）按创建时的调用方保存。
+pub fn conversation(req: &ResponsesRequest, previous: Option<&StoredResponse>) -> Conversation {
+    let mut items = previous.map(|p| p.items.clone()).unwrap_or_default();
+    items.extend(req.input.items());
+    Conversation {
+        owner: auth::current_caller().owner_id(),
+        items,
+    }
+}
+
+/// 构建发往 OpenAI 的请求
+///
+/// 上一个响应来自其他 provider 时，OpenAI 无法识别其 ID，需要把本地保存的消息展开为输入项。
+pub fn openai_request(req: &ResponsesRequest, previous: Option<&StoredResponse>) -> ResponsesRequest {
+    match previous {
+        Some(previous) if !previous.native => {
+            let mut input: Vec<ResponseItem> = previous
+                .items
+                .iter()
+                .filter(|item| item.is_message())
+                .map(|item| ResponseItem {
+                    id: None,
+                    status: None,
+                    ..item.clone()
+                })
+                .collect();
+            input.extend(req.input.items());
+            ResponsesRequest {
+                input: ResponsesInput::Items(input),
+                previous_response_id: None,
+                ..req.clone()
+            }
+        }
+        _ => req.clone(),
+    }
+}
+
+/// 将 Responses API 请求转换为聊天请求
+///
+/// `instructions` 转换为 system 消息，developer 角色按 system 处理；历史中的非消息项
+/// （函数调用、推理等）会被跳过，本次输入中出现非消息项则拒绝请求。
+pub fn to_chat_request(req: &ResponsesRequest, history: &[ResponseItem]) -> Result<ChatRequest> {
+    let mut messages = Vec::new();
+    if let Some(instructions) = &req.instructions {
+        messages.push(Message::system(instructions.clone()));
+    }
+
+    messages.extend(history.iter().filter(|item| item.is_message()).map(to_message));
+
+    for item in req.input.items() {
+        if !item.is_message() {
+            let item_type = item.item_type.unwrap_or_default();
+            return Err(FeatherGateError::Validation(
+                ValidationError::UnsupportedInputItem(item_type),
+            ));
+        }
+        messages.push(to_message(&item));
+    }
+
+    Ok(ChatRequest {
+        model: req.model.clone(),
+        messages,
+        temperature: req.temperature,
+        max_tokens: req.max_output_tokens,
+        stream: req.stream,
+        stream_options: None,
+        top_p: req.top_p,
+        stop: None,
+        user: None,
+    })
+}
+
+/// 消息项转换为聊天消息
+fn to_message(item: &ResponseItem) -> Message {
+    let role = match item.role.as_deref() {
+        Some("developer") => "system",
+        Some(role) => role,
+        None => "user",
+    };
+    Message {
+        role: role.to_string(),
+        content: item.text(),
+    }
+}
+
+/// 将聊天响应转换为 Responses API 响应
+pub fn from_chat_response(req: &ResponsesRequest, resp: ChatResponse) -> ResponseObject {
+    let choice = resp.choices.into_iter().next();
+    let text = choice
+        .as_ref()
+        .map(|c| c.message.content.clone())
+        .unwrap_or_default();
+
+    let mut response = new_response(req, resp.model);
+    response.output = vec![ResponseItem::output_message(new_id("msg"), text)];
+    response.usage = resp.usage.map(response_usage);
+    finalize(&mut response, choice.and_then(|c| c.finish_reason).as_deref());
+    response
+}
+
+/// 状态为 in_progress 的空响应
+fn new_response(req: &ResponsesRequest, model: String) -> ResponseObject {
+    ResponseObject {
+        id: new_id("resp"),
+        object: "response".to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        status: "in_progress".to_string(),
+        model,
+        output: Vec::new(),
+        usage: None,
+        previous_response_id: req.previous_response_id.clone(),
+        extra: Default::default(),
+    }
+}
+
+/// 根据聊天的 finish_reason 设置响应状态（因长度截断时为 incomplete）
+fn finalize(response: &mut ResponseObject, finish_reason: Option<&str>) {
+    if finish_reason == Some("length") {
+        response.status = "incomplete".to_string();
+        response.extra.insert(
+            "incomplete_details".to_string(),
+            json!({"reason": "max_output_tokens"}),
+        );
+    } else {
+        response.status = "completed".to_string();
+    }
+}
+
+fn response_usage(usage: Usage) -> ResponseUsage {
+    ResponseUsage {
+        input_tokens: usage.prompt_tokens,
+        output_tokens: usage.completion_tokens,
+        total_tokens: usage.total_tokens,
+    }
+}
+
+fn new_id(prefix: &str) -> String {
+    format!("{}_{}", prefix, uuid::Uuid::new_v4().simple())
+}
+
+/// 将聊天 SSE 流转换为 Responses API 的类型化事件流
+///
+/// 流结束（`[DONE]` 或上游关闭连接）时补齐 done 事件并发送 `response.completed`，
+/// `conversation` 不为空时同时把响应保存到本地。
+pub fn chat_stream_to_responses(
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+    req: &ResponsesRequest,
+    conversation: Option<Conversation>,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    let state = StreamState::new(req, conversation);
+
+    let converted = futures_util::stream::unfold(Some((stream, state)), |current| async move {
+        let (mut stream, mut state) = current?;
+        match stream.next().await {
+            Some(Ok(bytes)) => {
+                let outputs = state.push(&bytes);
+                Some((outputs, Some((stream, state))))
+            }
+            Some(Err(e)) => Some((vec![Err(e)], None)),
+            None => Some((state.finish(), None)),
+        }
+    })
+    .map(futures_util::stream::iter)
+    .flatten();
+
+    Box::pin(converted)
+}
+
+/// 流式转换的状态
+struct StreamState {
+    /// 未完整的事件按字节缓存，避免多字节字符被拆到两个数据块时解码出错
+    buffer: Vec<u8>,
+    response: ResponseObject,
+    item_id: String,
+    text: String,
+    finish_reason: Option<String>,
+    sequence: u64,
+    started: bool,
+    finished: bool,
+    conversation: Option<Conversation>,
+}
+
+impl StreamState {
+    fn new(req: &ResponsesRequest, conversation: Option<Conversation>) -> Self {
+        StreamState {
+            buffer: Vec::new(),
+            response: new_response(req, req.model.clone()),
+            item_id: new_id("msg"),
+            text: String::new(),
+            finish_reason: None,
+            sequence: 0,
+            started: false,
+            finished: false,
+            conversation,
+        }
+    }
+
+    /// 处理上游字节，转换所有完整的聊天事件
+    fn push(&mut self, bytes: &[u8]) -> Vec<Result<Bytes>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut outputs = Vec::new();
+
+        while let Some(pos) = self.buffer.windows(2).position(|window| window == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..pos + 2).collect();
+            if self.finished {
+                continue;
+            }
+
+            let event = String::from_utf8_lossy(&event);
+            let Some(data) = event.lines().find_map(|line| line.strip_prefix("data: ")) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                outputs.extend(self.finish());
+                continue;
+            }
+
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+                continue;
+            };
+            if let Some(usage) = value
+                .get("usage")
+                .and_then(|u| serde_json::from_value::<Usage>(u.clone()).ok())
+            {
+                self.response.usage = Some(response_usage(usage));
+            }
+            let Ok(chunk) = serde_json::from_value::<ChatStreamChunk>(value) else {
+                continue;
+            };
+
+            if !self.started {
+                self.response.model = chunk.model.clone();
+                outputs.extend(self.start());
+            }
+
+            let Some(choice) = chunk.choices.first() else {
+                continue;
+            };
+            if let Some(reason) = &choice.finish_reason {
+                self.finish_reason = Some(reason.clone());
+            }
+            if let Some(delta) = choice.delta.content.as_deref().filter(|d| !d.is_empty()) {
+                self.text.push_str(delta);
+                outputs.push(Ok(self.event(
+                    "response.output_text.delta",
+                    json!({
+                        "item_id": self.item_id,
+                        "output_index": 0,
+                        "content_index": 0,
+                        "delta": delta,
+                    }),
+                )));
+            }
+        }
+        outputs
+    }
+
+    /// 响应开始时的事件：created、in_progress，以及空的输出项和内容块
+    fn start(&mut self) -> Vec<Result<Bytes>> {
+        self.started = true;
+        let response = serde_json::to_value(&self.response).unwrap_or_default();
+        let item = json!({
+            "id": self.item_id,
+            "type": "message",
+            "status": "in_progress",
+            "role": "assistant",
+            "content": [],
+        });
+
+        vec![
+            Ok(self.event("response.created", json!({ "response": response }))),
+            Ok(self.event("response.in_progress", json!({ "response": response }))),
+            Ok(self.event(
+                "response.output_item.added",
+                json!({ "output_index": 0, "item": item }),
+            )),
+            Ok(self.event(
+                "response.content_part.added",
+                json!({
+                    "item_id": self.item_id,
+                    "output_index": 0,
+                    "content_index": 0,
+                    "part": ContentPart::output_text(""),
+                }),
+            )),
+        ]
+    }
+
+    /// 响应结束时的事件：补齐 done 事件，最后发送完整响应
+    fn finish(&mut self) -> Vec<Result<Bytes>> {
+        if self.finished {
+            return Vec::new();
+        }
+        let mut outputs = if self.started { Vec::new() } else { self.start() };
+        self.finished = true;
+
+        let item = ResponseItem::output_message(self.item_id.clone(), self.text.clone());
+        self.response.output = vec![item.clone()];
+        finalize(&mut self.response, self.finish_reason.as_deref());
+
+        outputs.push(Ok(self.event(
+            "response.output_text.done",
+            json!({
+                "item_id": self.item_id,
+                "output_index": 0,
+                "content_index": 0,
+                "text": self.text,
+            }),
+        )));
+        outputs.push(Ok(self.event(
+            "response.content_part.done",
+            json!({
+                "item_id": self.item_id,
+                "output_index": 0,
+                "content_index": 0,
+                "part": ContentPart::output_text(self.text.clone()),
+            }),
+        )));
+        outputs.push(Ok(self.event(
+            "response.output_item.done",
+            json!({ "output_index": 0, "item": item }),
+        )));
+
+        let event_type = match self.response.status.as_str() {
+            "incomplete" => "response.incomplete",
+            _ => "response.completed",
+        };
+        let response = serde_json::to_value(&self.response).unwrap_or_default();
+        outputs.push(Ok(self.event(event_type, json!({ "response": response }))));
+
+        if let Some(conversation) = self.conversation.take() {
+            remember(conversation, &self.response, false);
+        }
+        outputs
+    }
+
+    /// 格式化一个类型化 SSE 事件
+    fn event(&mut self, event_type: &str, payload: serde_json::Value) -> Bytes {
+        let mut data = json!({
+            "type": event_type,
+            "sequence_number": self.sequence,
+        });
+        if let (Some(data), serde_json::Value::Object(payload)) = (data.as_object_mut(), payload) {
+            data.extend(payload);
+        }
+        self.sequence += 1;
+        Bytes::from(format!("event: {}\ndata: {}\n\n", event_type, data))
+    }
+}
+
+/// 透传 OpenAI 的事件流，同时在 `response.completed` 时把响应保存到本地
+pub fn record_openai_stream(
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+    conversation: Conversation,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    // 按字节缓存未完整的事件，只解码完整的事件
+    let mut buffer = Vec::new();
+    let mut conversation = Some(conversation);
+
+    let recorded = stream.inspect(move |result| {
+        let Ok(bytes) = result else {
+            return;
+        };
+        buffer.extend_from_slice(bytes);
+        while let Some(pos) = buffer.windows(2).position(|window| window == b"\n\n") {
+            let event: Vec<u8> = buffer.drain(..pos + 2).collect();
+            let event = String::from_utf8_lossy(&event);
+            let Some(data) = event.lines().find_map(|line| line.strip_prefix("data: ")) else {
+                continue;
+            };
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+                continue;
+            };
+            let done = matches!(
+                value["type"].as_str(),
+                Some("response.completed" | "response.incomplete")
+            );
+            if !done {
+                continue;
+            }
+            if let (Some(items), Ok(response)) = (
+                conversation.take(),
+                serde_json::from_value::<ResponseObject>(value["response"].clone()),
+            ) {
+                remember(items, &response, true);
+            }
+        }
+    });
+
+    Box::pin(recorded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::responses::ItemContent;
+
+    fn create_request(input: ResponsesInput) -> ResponsesRequest {
+        ResponsesRequest {
+            model: "claude".to_string(),
+            input,
+            instructions: Some("Be brief".to_string()),
+            previous_response_id: None,
+            max_output_tokens: Some(64),
+            temperature: None,
+            top_p: None,
+            stream: None,
+            store: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_to_chat_request() {
+        let req = create_request(ResponsesInput::Items(vec![
+            ResponseItem::message("developer", "Answer in English"),
+            ResponseItem::message("user", "Hi"),
+        ]));
+        let history = vec![
+            ResponseItem::message("user", "Earlier"),
+            ResponseItem {
+                item_type: Some("reasoning".to_string()),
+                ..Default::default()
+            },
+            ResponseItem::output_message("msg_1", "Reply"),
+        ];
+
+        let chat_req = to_chat_request(&req, &history).unwrap();
+        assert_eq!(
+            chat_req.messages,
+            vec![
+                Message::system("Be brief"),
+                Message::user("Earlier"),
+                Message::assistant("Reply"),
+                Message::system("Answer in English"),
+                Message::user("Hi"),
+            ]
+        );
+        assert_eq!(chat_req.max_tokens, Some(64));
+    }
+
+    #[test]
+    fn test_to_chat_request_rejects_function_calls() {
+        let req = create_request(ResponsesInput::Items(vec![ResponseItem {
+            item_type: Some("function_call_output".to_string()),
+            ..Default::default()
+        }]));
+        assert!(matches!(
+            to_chat_request(&req, &[]),
+            Err(FeatherGateError::Validation(ValidationError::UnsupportedInputItem(t)))
+                if t == "function_call_output"
+        ));
+    }
+
+    #[test]
+    fn test_from_chat_response() {
+        let req = create_request(ResponsesInput::Text("Hi".to_string()));
+        let mut chat_resp = ChatResponse::simple("claude-opus-4-5", "Hello");
+        chat_resp.usage = Some(Usage {
+            prompt_tokens: 3,
+            completion_tokens: 1,
+            total_tokens: 4,
+        });
+
+        let resp = from_chat_response(&req, chat_resp.clone());
+        assert!(resp.id.starts_with("resp_"));
+        assert_eq!(resp.object, "response");
+        assert_eq!(resp.status, "completed");
+        assert_eq!(resp.output[0].text(), "Hello");
+        assert_eq!(resp.usage.unwrap().total_tokens, 4);
+
+        chat_resp.choices[0].finish_reason = Some("length".to_string());
+        let resp = from_chat_response(&req, chat_resp);
+        assert_eq!(resp.status, "incomplete");
+        assert_eq!(resp.extra["incomplete_details"]["reason"], "max_output_tokens");
+    }
+
+    #[test]
+    fn test_store_and_openai_expansion() {
+        let req = create_request(ResponsesInput::Text("Hi".to_string()));
+        let resp = from_chat_response(&req, ChatResponse::simple("claude-opus-4-5", "Hello"));
+        remember(conversation(&req, None), &resp, false);
+
+        let stored = lookup(&resp.id).unwrap();
+        assert_eq!(stored.items.len(), 2);
+        assert!(!stored.native);
+
+        let mut next = create_request(ResponsesInput::Text("Again".to_string()));
+        next.previous_response_id = Some(resp.id.clone());
+        let upstream = openai_request(&next, Some(&stored));
+        assert_eq!(upstream.previous_response_id, None);
+        let ResponsesInput::Items(items) = upstream.input else {
+            panic!("expected items");
+        };
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].id, None);
+        assert_eq!(items[1].text(), "Hello");
+        assert_eq!(items[2].content, Some(ItemContent::Text("Again".to_string())));
+
+        // 上一个响应来自 OpenAI 时原样透传
+        let native = StoredResponse {
+            items: Vec::new(),
+            native: true,
+        };
+        let upstream = openai_request(&next, Some(&native));
+        assert_eq!(upstream.previous_response_id, Some(resp.id));
+    }
+
+    #[tokio::test]
+    async fn test_stored_responses_are_scoped_to_caller() {
+        let req = create_request(ResponsesInput::Text("Hi".to_string()));
+        let resp = from_chat_response(&req, ChatResponse::simple("claude-opus-4-5", "Hello"));
+        let conversation = auth::with_caller(auth::Caller::Master, async { conversation(&req, None) }).await;
+        remember(conversation, &resp, false);
+
+        assert!(auth::with_caller(auth::Caller::Master, async { lookup(&resp.id) }).await.is_some());
+        // 其他调用方看不到该响应
+        assert!(lookup(&resp.id).is_none());
+    }
+
+    #[test]
+    fn test_response_store_limits() {
+        let stored = |text: &str| StoredResponse {
+            items: vec![ResponseItem::message("user", text)],
+            native: false,
+        };
+        let size = serde_json::to_vec(&stored("aaaa").items).unwrap().len();
+
+        // 总大小超出上限时淘汰最早的响应
+        let mut store = ResponseStore::new(10, size * 2, Duration::from_secs(60));
+        for id in ["r1", "r2", "r3"] {
+            store.insert(id.to_string(), "owner".to_string(), stored("aaaa"));
+        }
+        assert!(store.get("owner", "r1").is_none());
+        assert!(store.get("owner", "r3").is_some());
+        assert_eq!(store.bytes, size * 2);
+
+        // 过期的响应查不到，并在下次保存时淘汰
+        let mut store = ResponseStore::new(10, usize::MAX, Duration::ZERO);
+        store.insert("r1".to_string(), "owner".to_string(), stored("aaaa"));
+        assert!(store.get("owner", "r1").is_none());
+        assert!(store.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_to_responses() {
+        let chunks = vec![
+            Ok(Bytes::from(
+                "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"m\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hel\"},\"finish_reason\":null}]}\n\ndata: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"m\",",
+            )),
+            Ok(Bytes::from(
+                "\"choices\":[{\"index\":0,\"delta\":{\"content\":\"lo\"},\"finish_reason\":\"stop\"}]}\n\n",
+            )),
+        ];
+        let req = create_request(ResponsesInput::Text("Hi".to_string()));
+        let stream = chat_stream_to_responses(
+            Box::pin(futures_util::stream::iter(chunks)),
+            &req,
+            Some(conversation(&req, None)),
+        );
+
+        let events: Vec<serde_json::Value> = stream
+            .map(|bytes| {
+                let text = String::from_utf8(bytes.unwrap().to_vec()).unwrap();
+                let data = text.lines().find_map(|l| l.strip_prefix("data: ")).unwrap();
+                serde_json::from_str(data).unwrap()
+            })
+            .collect()
+            .await;
+
+        let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            vec![
+                "response.created",
+                "response.in_progress",
+                "response.output_item.added",
+                "response.content_part.added",
+                "response.output_text.delta",
+                "response.output_text.delta",
+                "response.output_text.done",
+                "response.content_part.done",
+                "response.output_item.done",
+                "response.completed",
+            ]
+        );
+        assert_eq!(events[5]["delta"], "lo");
+        assert_eq!(events[6]["text"], "Hello");
+        assert_eq!(events[9]["sequence_number"], 9);
+
+        let completed = &events[9]["response"];
+        assert_eq!(completed["status"], "completed");
+        assert_eq!(completed["output"][0]["content"][0]["text"], "Hello");
+
+        // 流结束后响应已保存到本地
+        let stored = lookup(completed["id"].as_str().unwrap()).unwrap();
+        assert_eq!(stored.items.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_openai_stream_split_inside_character_is_recorded() {
+        let req = create_request(ResponsesInput::Text("Hi".to_string()));
+        let response = from_chat_response(&req, ChatResponse::simple("gpt-4o", "你好"));
+        let event = format!(
+            "event: response.completed\ndata: {}\n\n",
+            json!({"type": "response.completed", "response": response})
+        );
+        let split = event.find("你").unwrap() + 1;
+        let chunks = vec![
+            Ok(Bytes::copy_from_slice(&event.as_bytes()[..split])),
+            Ok(Bytes::copy_from_slice(&event.as_bytes()[split..])),
+        ];
+
+        let stream = record_openai_stream(
+            Box::pin(futures_util::stream::iter(chunks)),
+            conversation(&req, None),
+        );
+        stream.for_each(|_| async {}).await;
+
+        let stored = lookup(&response.id).unwrap();
+        assert_eq!(stored.items.last().unwrap().text(), "你好");
+    }
+}
@@ -1,55 +1,948 @@
-use crate::config::{parse_model_string, Config};
-use crate::error::FeatherGateError;
-use crate::providers::{anthropic, gemini, openai};
-use crate::types::{ChatRequest, ChatResponse};
+use crate::auth;
+use crate::config::{parse_model_string, Config, ModelConfig, ModelMode, RouterSettings};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::metrics;
+use crate::ratelimit;
+use crate::spend;
+use crate::providers::gemini::GenerateContentRequest;
+use crate::providers::{
+    anthropic, cohere, completions, cooldown, gemini, jina, messages, openai, responses, tei,
+    RawResponse,
+};
+use crate::types::audio::{SpeechRequest, TranscriptionRequest};
+use crate::types::completions::{CompletionRequest, CompletionResponse};
+use crate::types::embeddings::{EmbeddingRequest, EmbeddingResponse};
+use crate::types::images::{ImageGenerationRequest, ImageResponse};
+use crate::types::messages::{MessagesRequest, MessagesResponse};
+use crate::types::moderations::{ModerationInput, ModerationRequest, ModerationResponse};
+use crate::types::rerank::{RerankRequest, RerankResponse};
+use crate::types::responses::{ResponseObject, ResponsesRequest};
+use crate::types::{ChatRequest, ChatResponse, StreamOptions, ValidationError};
 use crate::Result;
-use futures_util::Stream;
+use futures_util::{Stream, StreamExt};
 use hyper::body::Bytes;
+use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
 
 /// 路由请求到正确的 provider
 pub async fn route_request(
     config: Arc<Config>,
     req: ChatRequest,
 ) -> Result<ChatResponse> {
-    // 查找模型配置
+    let model = req.model.clone();
+    authorize(&config, &model, req.user.as_deref())?;
+    moderate(&config, &model, user_inputs(&req)).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch(config, ChatRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由流式请求到正确的 provider（仅在开始推流前重试和回退）
+pub async fn route_request_stream(
+    config: Arc<Config>,
+    req: ChatRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model = req.model.clone();
+    authorize(&config, &model, req.user.as_deref())?;
+    moderate(&config, &model, user_inputs(&req)).await?;
+    let stream = route_with_fallbacks(config, &model, |config, model| {
+        dispatch_stream(config, ChatRequest { model, ..req.clone() })
+    })
+    .await?;
+    Ok(usage_as_requested(stream, req.stream_options.as_ref()))
+}
+
+/// 路由文本补全请求：OpenAI 直接透传，其他 provider 转换为聊天请求
+pub async fn route_completion(
+    config: Arc<Config>,
+    req: CompletionRequest,
+) -> Result<CompletionResponse> {
+    let model = req.model.clone();
+    authorize(&config, &model, req.user.as_deref())?;
+    moderate(&config, &model, prompt_inputs(&req)).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_completion(config, CompletionRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由流式文本补全请求
+pub async fn route_completion_stream(
+    config: Arc<Config>,
+    req: CompletionRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model = req.model.clone();
+    authorize(&config, &model, req.user.as_deref())?;
+    moderate(&config, &model, prompt_inputs(&req)).await?;
+    let stream = route_with_fallbacks(config, &model, |config, model| {
+        dispatch_completion_stream(config, CompletionRequest { model, ..req.clone() })
+    })
+    .await?;
+    Ok(usage_as_requested(stream, req.stream_options.as_ref()))
+}
+
+/// 路由 Responses API 请求：OpenAI 直接透传，其他 provider 转换为聊天请求
+pub async fn route_responses(
+    config: Arc<Config>,
+    req: ResponsesRequest,
+) -> Result<ResponseObject> {
+    let model = req.model.clone();
+    authorize(&config, &model, response_user(&req))?;
+    moderate(&config, &model, response_inputs(&req)).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_responses(config, ResponsesRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由流式 Responses API 请求
+pub async fn route_responses_stream(
+    config: Arc<Config>,
+    req: ResponsesRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model = req.model.clone();
+    authorize(&config, &model, response_user(&req))?;
+    moderate(&config, &model, response_inputs(&req)).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_responses_stream(config, ResponsesRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由 Anthropic 原生请求：Anthropic 直接透传，其他 provider 逆向转换为聊天请求
+pub async fn route_messages(
+    config: Arc<Config>,
+    req: MessagesRequest,
+) -> Result<MessagesResponse> {
+    let model = req.model.clone();
+    authorize(&config, &model, message_user(&req))?;
+    moderate(&config, &model, message_inputs(&req)).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_messages(config, MessagesRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由流式 Anthropic 原生请求
+pub async fn route_messages_stream(
+    config: Arc<Config>,
+    req: MessagesRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model = req.model.clone();
+    authorize(&config, &model, message_user(&req))?;
+    moderate(&config, &model, message_inputs(&req)).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_messages_stream(config, MessagesRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由 Gemini 原生请求：Gemini 直接透传，其他 provider 逆向转换为聊天请求
+pub async fn route_generate_content(
+    config: Arc<Config>,
+    req: GenerateContentRequest,
+) -> Result<serde_json::Value> {
+    let model = req.model.clone();
+    authorize(&config, &model, None)?;
+    moderate(&config, &model, req.user_texts()).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_generate_content(config, GenerateContentRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由流式 Gemini 原生请求
+pub async fn route_generate_content_stream(
+    config: Arc<Config>,
+    req: GenerateContentRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model = req.model.clone();
+    authorize(&config, &model, None)?;
+    moderate(&config, &model, req.user_texts()).await?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_generate_content_stream(config, GenerateContentRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由向量嵌入请求到正确的 provider
+pub async fn route_embedding(
+    config: Arc<Config>,
+    req: EmbeddingRequest,
+) -> Result<EmbeddingResponse> {
+    let model = req.model.clone();
+    authorize(&config, &model, req.user.as_deref())?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_embedding(config, EmbeddingRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由重排序请求到正确的 provider
+pub async fn route_rerank(config: Arc<Config>, req: RerankRequest) -> Result<RerankResponse> {
+    let model = req.model.clone();
+    authorize(&config, &model, None)?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_rerank(config, RerankRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由图像生成请求到正确的 provider
+pub async fn route_image_generation(
+    config: Arc<Config>,
+    req: ImageGenerationRequest,
+) -> Result<ImageResponse> {
+    let model = req.model.clone();
+    authorize(&config, &model, req.user.as_deref())?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_image_generation(config, ImageGenerationRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由文本转语音请求到正确的 provider
+pub async fn route_speech(config: Arc<Config>, req: SpeechRequest) -> Result<RawResponse> {
+    let model = req.model.clone();
+    authorize(&config, &model, None)?;
+    route_with_fallbacks(config, &model, |config, model| {
+        dispatch_speech(config, SpeechRequest { model, ..req.clone() })
+    })
+    .await
+}
+
+/// 路由语音转文本请求到正确的 provider
+///
+/// 上传的音频是流式转发的，请求体无法重放，因此不重试也不回退。
+pub async fn route_transcription(
+    config: Arc<Config>,
+    req: TranscriptionRequest,
+) -> Result<RawResponse> {
+    authorize(&config, &req.model, None)?;
+    admit_deployment(&config, &req.model, ModelAccess::Caller)?;
+    let model_config = find_deployment(&config, &req.model, ModelMode::AudioTranscription)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+
+    let response = match provider.as_str() {
+        "openai" => openai::forward_transcription(model_config, req).await,
+        _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }?;
+
+    Ok(spend::track_transcription(response, &config, model_config))
+}
+
+/// 路由内容审核请求，未指定模型时使用配置的审核模型
+pub async fn route_moderation(
+    config: Arc<Config>,
+    req: ModerationRequest,
+) -> Result<ModerationResponse> {
+    let model = match &req.model {
+        Some(model) => model.clone(),
+        None => config
+            .moderation_model()
+            .map(|m| m.model_name.clone())
+            .ok_or(ValidationError::MissingField("model"))?,
+    };
+    authorize(&config, &model, None)?;
+    moderation(config, model, req, ModelAccess::Caller).await
+}
+
+/// 转发前的检查：调用方的模型权限、预算和 RPM/TPM（请求中的终端用户同样计入）
+fn authorize(config: &Config, model: &str, user: Option<&str>) -> Result<()> {
+    auth::check_model_access(config, model)?;
+    auth::set_end_user(user);
+    spend::check_budgets(config)?;
+    ratelimit::check(config)
+}
+
+/// Responses API 请求中的 `user` 字段
+fn response_user(req: &ResponsesRequest) -> Option<&str> {
+    req.extra.get("user").and_then(|user| user.as_str())
+}
+
+/// Anthropic 原生请求中的 `metadata.user_id`
+fn message_user(req: &MessagesRequest) -> Option<&str> {
+    req.extra
+        .get("metadata")
+        .and_then(|metadata| metadata["user_id"].as_str())
+}
+
+/// 发送内容审核请求（网关侧审核同样使用，此时不检查调用方的模型权限）
+async fn moderation(
+    config: Arc<Config>,
+    model: String,
+    req: ModerationRequest,
+    access: ModelAccess,
+) -> Result<ModerationResponse> {
+    route_with_access(config, &model, access, |config, model| {
+        dispatch_moderation(
+            config,
+            ModerationRequest {
+                model: Some(model),
+                ..req.clone()
+            },
+        )
+    })
+    .await
+}
+
+/// 请求的模型或其任一回退模型开启 moderation 时，先用审核模型检查用户输入，被标记则拒绝请求
+///
+/// 不论最终由哪个 provider 处理，都使用同一个审核模型；审核请求失败时同样拒绝（fail closed）。
+async fn moderate(config: &Arc<Config>, model: &str, inputs: Vec<String>) -> Result<()> {
+    let enabled = reachable_models(&config.router_settings, model)
+        .any(|name| config.find_model(name).is_some_and(|m| m.moderation));
+    if !enabled || inputs.is_empty() {
+        return Ok(());
+    }
+
+    let moderation_model = config
+        .moderation_model()
+        .map(|m| m.model_name.clone())
+        .ok_or(ValidationError::MissingField("model"))?;
+    let req = ModerationRequest {
+        model: None,
+        input: ModerationInput::TextBatch(inputs),
+    };
+    let response = moderation(Arc::clone(config), moderation_model, req, ModelAccess::Gateway).await?;
+    if response.flagged() {
+        let categories = response.flagged_categories().join(", ");
+        warn!("模型 {} 的请求未通过内容审核: {}", model, categories);
+        return Err(FeatherGateError::ModerationFlagged(categories));
+    }
+
+    Ok(())
+}
+
+/// 请求可能到达的模型：请求的模型及其在各回退列表中的候选模型
+fn reachable_models<'a>(
+    settings: &'a RouterSettings,
+    model: &'a str,
+) -> impl Iterator<Item = &'a str> {
+    let fallbacks = [
+        &settings.fallbacks,
+        &settings.context_window_fallbacks,
+        &settings.content_policy_fallbacks,
+    ];
+    std::iter::once(model).chain(
+        fallbacks
+            .into_iter()
+            .flat_map(move |fallbacks| RouterSettings::lookup(fallbacks, model))
+            .map(String::as_str),
+    )
+}
+
+/// 聊天请求中需要审核的用户消息
+fn user_inputs(req: &ChatRequest) -> Vec<String> {
+    req.messages
+        .iter()
+        .filter(|m| m.role == "user")
+        .map(|m| m.content.clone())
+        .collect()
+}
+
+/// 文本补全请求中需要审核的 prompt（token 数组无法审核）
+fn prompt_inputs(req: &CompletionRequest) -> Vec<String> {
+    req.prompt
+        .texts()
+        .unwrap_or_default()
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Responses API 请求中需要审核的用户消息（历史消息在上一轮已经审核过）
+fn response_inputs(req: &ResponsesRequest) -> Vec<String> {
+    req.input
+        .items()
+        .iter()
+        .filter(|item| item.is_message() && item.role.as_deref().is_none_or(|r| r == "user"))
+        .map(|item| item.text())
+        .collect()
+}
+
+/// Anthropic 原生请求中需要审核的用户消息（只审核文本内容块）
+fn message_inputs(req: &MessagesRequest) -> Vec<String> {
+    use crate::types::messages::MessageContent;
+
+    req.messages
+        .iter()
+        .filter(|m| m.role == "user")
+        .map(|m| match &m.content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|block| block.text.as_deref())
+                .collect(),
+        })
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+/// 查找模型配置，检查模型声明的 mode 是否支持该类型的请求，以及选定部署的 RPM/TPM
+fn find_deployment<'a>(
+    config: &'a Config,
+    model: &str,
+    mode: ModelMode,
+) -> Result<&'a ModelConfig> {
     let model_config = config
-        .find_model(&req.model)
-        .ok_or_else(|| FeatherGateError::ModelNotFound(req.model.clone()))?;
+        .find_model(model)
+        .ok_or_else(|| FeatherGateError::ModelNotFound(model.to_string()))?;
+
+    // 聊天模型和文本补全模型可以互相转换
+    let accepted: &[ModelMode] = match mode {
+        ModelMode::Chat | ModelMode::Completion => &[ModelMode::Chat, ModelMode::Completion],
+        _ => std::slice::from_ref(&mode),
+    };
+    if !model_config.supports(accepted) {
+        return Err(FeatherGateError::ModelModeMismatch(model.to_string(), mode));
+    }
+    ratelimit::check_deployment(model_config)?;
+
+    Ok(model_config)
+}
+
+/// 将请求发送到模型对应的 provider
+async fn dispatch(config: Arc<Config>, req: ChatRequest) -> Result<ChatResponse> {
+    // 查找模型配置
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
 
     // 解析 provider
     let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
 
     // 路由到对应 provider
-    match provider.as_str() {
+    let response = match provider.as_str() {
         "openai" => openai::forward_request(model_config, &req).await,
         "anthropic" => anthropic::forward_request(model_config, &req).await,
         "gemini" => gemini::forward_request(model_config, &req).await,
         _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }?;
+
+    // 与图像成本相同，按实际使用的部署的单价计费
+    if let Some(usage) = &response.usage {
+        let (input, output) = (usage.prompt_tokens.into(), usage.completion_tokens.into());
+        spend::record_usage(&config, model_config, input, output);
     }
+    Ok(response)
 }
 
-/// 路由流式请求到正确的 provider
-pub async fn route_request_stream(
+/// 客户端没有要求 `stream_options.include_usage` 时，去掉为计费向上游请求的用量数据块
+fn usage_as_requested(
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+    options: Option<&StreamOptions>,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    if StreamOptions::includes_usage(options) {
+        return stream;
+    }
+
+    // 按字节缓存未完整的事件，其余事件原样转发；流结束时输出剩余内容
+    let mut buffer = Vec::new();
+    let stripped = stream
+        .map(Some)
+        .chain(futures_util::stream::once(async { None }))
+        .map(move |item| {
+            let outputs = match item {
+                Some(Ok(bytes)) => {
+                    buffer.extend_from_slice(&bytes);
+                    drain_non_usage_events(&mut buffer)
+                }
+                Some(Err(e)) => vec![Err(e)],
+                None if buffer.is_empty() => Vec::new(),
+                None => vec![Ok(Bytes::from(std::mem::take(&mut buffer)))],
+            };
+            futures_util::stream::iter(outputs)
+        })
+        .flatten();
+
+    Box::pin(stripped)
+}
+
+/// 取出缓冲区中所有完整的 SSE 事件，丢弃只包含用量（`choices` 为空）的数据块
+fn drain_non_usage_events(buffer: &mut Vec<u8>) -> Vec<Result<Bytes>> {
+    let mut outputs = Vec::new();
+    while let Some(pos) = buffer.windows(2).position(|window| window == b"\n\n") {
+        let event: Vec<u8> = buffer.drain(..pos + 2).collect();
+        if !is_usage_chunk(&event) {
+            outputs.push(Ok(Bytes::from(event)));
+        }
+    }
+    outputs
+}
+
+fn is_usage_chunk(event: &[u8]) -> bool {
+    let event = String::from_utf8_lossy(event);
+    let Some(data) = event.lines().find_map(|line| line.strip_prefix("data: ")) else {
+        return false;
+    };
+    serde_json::from_str::<serde_json::Value>(data).is_ok_and(|value| {
+        value["usage"].is_object() && value["choices"].as_array().is_some_and(Vec::is_empty)
+    })
+}
+
+/// 将流式请求发送到模型对应的 provider
+async fn dispatch_stream(
     config: Arc<Config>,
     req: ChatRequest,
 ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
     // 查找模型配置
-    let model_config = config
-        .find_model(&req.model)
-        .ok_or_else(|| FeatherGateError::ModelNotFound(req.model.clone()))?;
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
 
     // 解析 provider
     let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
 
     // 路由到对应 provider（支持所有提供商流式）
-    match provider.as_str() {
+    let stream = match provider.as_str() {
         "openai" => openai::forward_request_stream(model_config, &req).await,
         "anthropic" => anthropic::forward_request_stream(model_config, &req).await,
         "gemini" => gemini::forward_request_stream(model_config, &req).await,
         _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }?;
+
+    Ok(spend::track_stream(stream, &config, model_config))
+}
+
+/// 将文本补全请求发送到模型对应的 provider
+async fn dispatch_completion(
+    config: Arc<Config>,
+    req: CompletionRequest,
+) -> Result<CompletionResponse> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Completion)?;
+
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    if provider == "openai" {
+        let response = openai::forward_completion(model_config, &req).await?;
+        if let Some(usage) = &response.usage {
+            let (input, output) = (usage.prompt_tokens.into(), usage.completion_tokens.into());
+            spend::record_usage(&config, model_config, input, output);
+        }
+        return Ok(response);
     }
+
+    // 每个 prompt 转换为一个聊天请求
+    let chat_reqs = completions::to_chat_requests(&req)?;
+    let responses = futures_util::future::try_join_all(
+        chat_reqs
+            .into_iter()
+            .map(|chat_req| dispatch(Arc::clone(&config), chat_req)),
+    )
+    .await?;
+
+    Ok(completions::from_chat_responses(&req, responses))
+}
+
+/// 将流式文本补全请求发送到模型对应的 provider
+async fn dispatch_completion_stream(
+    config: Arc<Config>,
+    req: CompletionRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Completion)?;
+
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    if provider == "openai" {
+        let stream = openai::forward_completion_stream(model_config, &req).await?;
+        return Ok(spend::track_stream(stream, &config, model_config));
+    }
+
+    let mut chat_reqs = completions::to_chat_requests(&req)?;
+    if chat_reqs.len() != 1 {
+        return Err(ValidationError::BatchStreamUnsupported.into());
+    }
+    let chat_req = chat_reqs.remove(0);
+
+    let echo = match req.echo {
+        Some(true) => req.prompt.texts().map(|prompts| prompts.concat()),
+        _ => None,
+    };
+    let stream = dispatch_stream(config, chat_req).await?;
+
+    Ok(completions::chat_stream_to_completion(stream, echo))
+}
+
+/// 将 Responses API 请求发送到模型对应的 provider
+async fn dispatch_responses(config: Arc<Config>, req: ResponsesRequest) -> Result<ResponseObject> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    let previous = previous_response(&req, &provider)?;
+    let conversation = responses::conversation(&req, previous.as_ref());
+
+    let response = if provider == "openai" {
+        let upstream_req = responses::openai_request(&req, previous.as_ref());
+        let response = openai::forward_responses(model_config, &upstream_req).await?;
+        if let Some(usage) = &response.usage {
+            let (input, output) = (usage.input_tokens.into(), usage.output_tokens.into());
+            spend::record_usage(&config, model_config, input, output);
+        }
+        response
+    } else {
+        let chat_req = responses::to_chat_request(&req, history(&previous))?;
+        let chat_resp = dispatch(Arc::clone(&config), chat_req).await?;
+        responses::from_chat_response(&req, chat_resp)
+    };
+
+    if req.store != Some(false) {
+        responses::remember(conversation, &response, provider == "openai");
+    }
+    Ok(response)
+}
+
+/// 将流式 Responses API 请求发送到模型对应的 provider
+async fn dispatch_responses_stream(
+    config: Arc<Config>,
+    req: ResponsesRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    let previous = previous_response(&req, &provider)?;
+    let conversation = responses::conversation(&req, previous.as_ref());
+    let store = req.store != Some(false);
+
+    if provider == "openai" {
+        let upstream_req = responses::openai_request(&req, previous.as_ref());
+        let stream = openai::forward_responses_stream(model_config, &upstream_req).await?;
+        let stream = spend::track_stream(stream, &config, model_config);
+        if !store {
+            return Ok(stream);
+        }
+        return Ok(responses::record_openai_stream(stream, conversation));
+    }
+
+    let chat_req = responses::to_chat_request(&req, history(&previous))?;
+    let stream = dispatch_stream(config, chat_req).await?;
+
+    Ok(responses::chat_stream_to_responses(
+        stream,
+        &req,
+        store.then_some(conversation),
+    ))
+}
+
+/// 从本地存储加载 previous_response_id 对应的对话
+///
+/// OpenAI 自己也保存响应，本地找不到时交给上游处理；其他 provider 只能依赖本地存储。
+fn previous_response(
+    req: &ResponsesRequest,
+    provider: &str,
+) -> Result<Option<responses::StoredResponse>> {
+    let Some(id) = &req.previous_response_id else {
+        return Ok(None);
+    };
+    match responses::lookup(id) {
+        Some(previous) => Ok(Some(previous)),
+        None if provider == "openai" => Ok(None),
+        None => Err(ValidationError::PreviousResponseNotFound(id.clone()).into()),
+    }
+}
+
+fn history(previous: &Option<responses::StoredResponse>) -> &[crate::types::responses::ResponseItem] {
+    previous.as_ref().map(|p| p.items.as_slice()).unwrap_or_default()
+}
+
+/// 将 Anthropic 原生请求发送到模型对应的 provider
+async fn dispatch_messages(config: Arc<Config>, req: MessagesRequest) -> Result<MessagesResponse> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
+
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    if provider == "anthropic" {
+        let response = anthropic::forward_messages(model_config, &req).await?;
+        let (input, output) = (response.usage.input_tokens.into(), response.usage.output_tokens.into());
+        spend::record_usage(&config, model_config, input, output);
+        return Ok(response);
+    }
+
+    let chat_req = messages::to_chat_request(&req)?;
+    let chat_resp = dispatch(Arc::clone(&config), chat_req).await?;
+    Ok(messages::from_chat_response(chat_resp))
+}
+
+/// 将流式 Anthropic 原生请求发送到模型对应的 provider
+async fn dispatch_messages_stream(
+    config: Arc<Config>,
+    req: MessagesRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
+
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    if provider == "anthropic" {
+        let stream = anthropic::forward_messages_stream(model_config, &req).await?;
+        return Ok(spend::track_stream(stream, &config, model_config));
+    }
+
+    let chat_req = messages::to_chat_request(&req)?;
+    let stream = dispatch_stream(config, chat_req).await?;
+    Ok(messages::chat_stream_to_messages(stream, &req))
+}
+
+/// 将 Gemini 原生请求发送到模型对应的 provider
+async fn dispatch_generate_content(
+    config: Arc<Config>,
+    req: GenerateContentRequest,
+) -> Result<serde_json::Value> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
+
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    if provider == "gemini" {
+        let response = gemini::forward_generate_content(model_config, &req).await?;
+        let usage = &response["usageMetadata"];
+        let input = usage["promptTokenCount"].as_u64().unwrap_or_default();
+        let output = usage["candidatesTokenCount"].as_u64().unwrap_or_default();
+        spend::record_usage(&config, model_config, input, output);
+        return Ok(response);
+    }
+
+    let chat_req = gemini::native_to_chat_request(&req, false)?;
+    let chat_resp = dispatch(Arc::clone(&config), chat_req).await?;
+    Ok(gemini::chat_to_native_response(&chat_resp))
+}
+
+/// 将流式 Gemini 原生请求发送到模型对应的 provider
+async fn dispatch_generate_content_stream(
+    config: Arc<Config>,
+    req: GenerateContentRequest,
+) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Chat)?;
+
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+    if provider == "gemini" {
+        let stream = gemini::forward_generate_content_stream(model_config, &req).await?;
+        return Ok(spend::track_stream(stream, &config, model_config));
+    }
+
+    let chat_req = gemini::native_to_chat_request(&req, true)?;
+    let stream = dispatch_stream(config, chat_req).await?;
+    Ok(gemini::chat_stream_to_native(stream))
+}
+
+/// 将向量嵌入请求发送到模型对应的 provider
+async fn dispatch_embedding(
+    config: Arc<Config>,
+    req: EmbeddingRequest,
+) -> Result<EmbeddingResponse> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Embedding)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+
+    let response = match provider.as_str() {
+        "openai" => openai::forward_embedding(model_config, &req).await,
+        "gemini" => gemini::forward_embedding(model_config, &req).await,
+        "cohere" => cohere::forward_embedding(model_config, &req).await,
+        _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }?;
+
+    spend::record_usage(&config, model_config, response.usage.prompt_tokens.into(), 0);
+    Ok(response)
+}
+
+/// 将重排序请求发送到模型对应的 provider
+async fn dispatch_rerank(config: Arc<Config>, req: RerankRequest) -> Result<RerankResponse> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::Rerank)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+
+    let response = match provider.as_str() {
+        "cohere" => cohere::forward_rerank(model_config, &req).await,
+        "jina" => jina::forward_rerank(model_config, &req).await,
+        "tei" => tei::forward_rerank(model_config, &req).await,
+        _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }?;
+
+    spend::record_cost(&config, spend::rerank_cost(model_config, &response.usage));
+    Ok(response)
+}
+
+/// 将图像生成请求发送到模型对应的 provider
+async fn dispatch_image_generation(
+    config: Arc<Config>,
+    req: ImageGenerationRequest,
+) -> Result<ImageResponse> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::ImageGeneration)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+
+    let response = match provider.as_str() {
+        "openai" => openai::forward_image_generation(model_config, &req).await,
+        "gemini" => gemini::forward_image_generation(model_config, &req).await,
+        _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }?;
+
+    // 回退后实际使用的部署可能不同，因此在这里按部署的单价记录成本
+    let count = response.data.len() as u64;
+    let cost = spend::image_cost(model_config, count);
+    metrics::global_metrics().record_images(count, cost);
+    spend::record_cost(&config, cost);
+
+    Ok(response)
+}
+
+/// 将内容审核请求发送到模型对应的 provider
+async fn dispatch_moderation(
+    config: Arc<Config>,
+    req: ModerationRequest,
+) -> Result<ModerationResponse> {
+    let model = req.model.clone().unwrap_or_default();
+    let model_config = find_deployment(&config, &model, ModelMode::Moderation)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+
+    match provider.as_str() {
+        "openai" => openai::forward_moderation(model_config, &req).await,
+        _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }
+}
+
+/// 将文本转语音请求发送到模型对应的 provider
+async fn dispatch_speech(config: Arc<Config>, req: SpeechRequest) -> Result<RawResponse> {
+    let model_config = find_deployment(&config, &req.model, ModelMode::AudioSpeech)?;
+    let (provider, _model_id) = parse_model_string(&model_config.litellm_params.model)?;
+
+    let response = match provider.as_str() {
+        "openai" => openai::forward_speech(model_config, &req).await,
+        _ => Err(FeatherGateError::UnsupportedProvider(provider)),
+    }?;
+
+    spend::record_cost(&config, spend::speech_cost(model_config, req.input.chars().count()));
+    Ok(response)
+}
+
+/// 部署的模型权限检查方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelAccess {
+    /// 按当前调用方（虚拟 key、JWT、团队和组织）的模型权限检查
+    Caller,
+    /// 网关自身发起的请求（网关侧内容审核），不检查调用方的模型权限
+    Gateway,
+}
+
+/// 按 router_settings 对可重试错误重试，并根据错误分类选择回退模型
+///
+/// 回退模型同样检查调用方的模型权限，调用方无权使用的回退模型直接跳过。
+async fn route_with_fallbacks<T, F, Fut>(
+    config: Arc<Config>,
+    model: &str,
+    dispatch: F,
+) -> Result<T>
+where
+    F: Fn(Arc<Config>, String) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    route_with_access(config, model, ModelAccess::Caller, dispatch).await
+}
+
+async fn route_with_access<T, F, Fut>(
+    config: Arc<Config>,
+    model: &str,
+    access: ModelAccess,
+    dispatch: F,
+) -> Result<T>
+where
+    F: Fn(Arc<Config>, String) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let settings = &config.router_settings;
+    let mut attempted = Vec::new();
+    let mut model_name = model.to_string();
+
+    loop {
+        let mut retries = 0;
+        // 部署正在冷却时不转发，直接回退；选定的部署 RPM/TPM 已用尽时 dispatch 同样在转发前返回错误
+        let result = match admit_deployment(&config, &model_name, access) {
+            Err(e) => Err(e),
+            Ok(()) => loop {
+                match dispatch(Arc::clone(&config), model_name.clone()).await {
+                    Err(e)
+                        if retries < settings.num_retries
+                            && e.upstream_kind().is_some_and(UpstreamErrorKind::is_retryable) =>
+                    {
+                        retries += 1;
+                        let delay = retry_delay(retries);
+                        warn!(
+                            "模型 {} 请求失败，{}ms 后第 {} 次重试: {}",
+                            model_name,
+                            delay.as_millis(),
+                            retries,
+                            e
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    result => break result,
+                }
+            },
+        };
+
+        let err = match result {
+            Ok(response) => return Ok(response),
+            Err(e) => e,
+        };
+        if err.upstream_kind().is_some_and(UpstreamErrorKind::is_retryable) {
+            cooldown::cooldowns().record_failure(&model_name, settings);
+        }
+
+        attempted.push(model_name.clone());
+        let next = loop {
+            match next_fallback(settings, model, &err, &attempted) {
+                Some(next) if !may_use(&config, &next, access) => {
+                    warn!("调用方无权使用回退模型 {}，跳过", next);
+                    attempted.push(next);
+                }
+                next => break next,
+            }
+        };
+        match next {
+            Some(next) => {
+                warn!("模型 {} 请求失败，回退到 {}: {}", model_name, next, err);
+                model_name = next;
+            }
+            None => return Err(err),
+        }
+    }
+}
+
+/// 转发到某个模型前的检查：调用方的模型权限和冷却（部署的 RPM/TPM 在选定部署后检查）
+fn admit_deployment(config: &Config, model_name: &str, access: ModelAccess) -> Result<()> {
+    if access == ModelAccess::Caller {
+        auth::check_model_access(config, model_name)?;
+    }
+    if cooldown::cooldowns().is_cooling_down(model_name) {
+        return Err(FeatherGateError::DeploymentCoolingDown(model_name.to_string()));
+    }
+    Ok(())
+}
+
+/// 调用方是否可以使用该模型（查询团队失败时视为不可用）
+fn may_use(config: &Config, model_name: &str, access: ModelAccess) -> bool {
+    access == ModelAccess::Gateway || auth::check_model_access(config, model_name).is_ok()
+}
+
+/// 根据错误分类选择下一个回退模型
+fn next_fallback(
+    settings: &RouterSettings,
+    model_name: &str,
+    err: &FeatherGateError,
+    attempted: &[String],
+) -> Option<String> {
+    let kind = match err {
+        // 部署正在冷却或 RPM/TPM 已用尽，与上游限流一样回退到其他模型
+        FeatherGateError::RateLimitExceeded(..) | FeatherGateError::DeploymentCoolingDown(_) => {
+            UpstreamErrorKind::RateLimited
+        }
+        _ => err.upstream_kind()?,
+    };
+    let fallbacks = match kind {
+        UpstreamErrorKind::ContextWindowExceeded => &settings.context_window_fallbacks,
+        UpstreamErrorKind::ContentPolicyViolation => &settings.content_policy_fallbacks,
+        // 请求本身有误，换模型也无济于事
+        UpstreamErrorKind::BadRequest => return None,
+        _ => &settings.fallbacks,
+    };
+
+    RouterSettings::lookup(fallbacks, model_name)
+        .find(|candidate| !attempted.contains(candidate))
+        .cloned()
+}
+
+/// 重试退避时间（指数退避，最长 5 秒）
+fn retry_delay(attempt: u32) -> Duration {
+    let millis = 200u64.saturating_mul(1 << attempt.min(5).saturating_sub(1));
+    Duration::from_millis(millis.min(5000))
 }
 
 /// 根据模型字符串判断 provider
@@ -73,7 +966,9 @@ mod tests {
                         model: "openai/gpt-4".to_string(),
                         api_key: "sk-test".to_string(),
                         api_base: "https://api.openai.com".to_string(),
+                        ..Default::default()
                     },
+                    ..Default::default()
                 },
                 ModelConfig {
                     model_name: "claude".to_string(),
@@ -81,7 +976,9 @@ mod tests {
                         model: "anthropic/claude-opus-4-5".to_string(),
                         api_key: "sk-ant-test".to_string(),
                         api_base: "https://api.anthropic.com".to_string(),
+                        ..Default::default()
                     },
+                    ..Default::default()
                 },
                 ModelConfig {
                     model_name: "gemini".to_string(),
@@ -89,9 +986,12 @@ mod tests {
                         model: "gemini/gemini-pro".to_string(),
                         api_key: "AIza-test".to_string(),
                         api_base: "https://generativelanguage.googleapis.com".to_string(),
+                        ..Default::default()
                     },
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         }
     }
 
@@ -110,6 +1010,1217 @@ mod tests {
         assert!(determine_provider("invalid").is_err());
     }
 
+    fn create_openai_model(model_name: &str, api_base: &str) -> ModelConfig {
+        ModelConfig {
+            model_name: model_name.to_string(),
+            litellm_params: LitellmParams {
+                model: format!("openai/{}", model_name),
+                api_key: "sk-test".to_string(),
+                api_base: api_base.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn create_chat_request(model: &str) -> ChatRequest {
+        ChatRequest {
+            model: model.to_string(),
+            messages: vec![Message::user("test")],
+            temperature: None,
+            max_tokens: None,
+            stream: None,
+            stream_options: None,
+            top_p: None,
+            stop: None,
+            user: None,
+        }
+    }
+
+    const OK_BODY: &str = r#"{
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1234567890,
+        "model": "gpt-4",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "ok"},
+            "finish_reason": "stop"
+        }]
+    }"#;
+
+    #[tokio::test]
+    async fn test_route_request_retries_retryable_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let overloaded = server
+            .mock("POST", "/chat/completions")
+            .with_status(503)
+            .with_body(r#"{"error": {"message": "overloaded", "type": "server_error"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_body(OK_BODY)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = Config {
+            model_list: vec![create_openai_model("gpt-4", &server.url())],
+            ..Default::default()
+        };
+        config.router_settings.num_retries = 1;
+
+        let result = route_request(Arc::new(config), create_chat_request("gpt-4")).await;
+        assert!(result.is_ok());
+
+        overloaded.assert_async().await;
+        ok.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_request_does_not_retry_bad_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/chat/completions")
+            .with_status(400)
+            .with_body(r#"{"error": {"message": "bad", "type": "invalid_request_error"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = Config {
+            model_list: vec![create_openai_model("gpt-4", &server.url())],
+            ..Default::default()
+        };
+        config.router_settings.num_retries = 3;
+
+        let result = route_request(Arc::new(config), create_chat_request("gpt-4")).await;
+        assert!(result.is_err());
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_request_context_window_fallback() {
+        let mut server = mockito::Server::new_async().await;
+        let too_long = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"model": "gpt-4"})))
+            .with_status(400)
+            .with_body(
+                r#"{"error": {"message": "maximum context length exceeded", "type": "invalid_request_error", "code": "context_length_exceeded"}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let fallback = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({"model": "gpt-4-32k"}),
+            ))
+            .with_status(200)
+            .with_body(OK_BODY)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut config = Config {
+            model_list: vec![
+                create_openai_model("gpt-4", &server.url()),
+                create_openai_model("gpt-4-32k", &server.url()),
+            ],
+            ..Default::default()
+        };
+        config.router_settings.context_window_fallbacks = vec![
+            [("gpt-4".to_string(), vec!["gpt-4-32k".to_string()])].into(),
+        ];
+
+        let result = route_request(Arc::new(config), create_chat_request("gpt-4")).await;
+        assert!(result.is_ok());
+
+        too_long.assert_async().await;
+        fallback.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_completion_translates_for_anthropic() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/messages")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "claude-opus-4-5",
+                "messages": [{"role": "user", "content": "Once upon"}]
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "text", "text": " a time"}],
+                "model": "claude-opus-4-5",
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 2, "output_tokens": 3}
+            }"#,
+            )
+            .create_async()
+            .await;
+
+        let config = Arc::new(Config {
+            model_list: vec![ModelConfig {
+                model_name: "claude".to_string(),
+                litellm_params: LitellmParams {
+                    model: "anthropic/claude-opus-4-5".to_string(),
+                    api_key: "sk-ant-test".to_string(),
+                    api_base: server.url(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let req: CompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude",
+            "prompt": "Once upon",
+            "echo": true
+        }))
+        .unwrap();
+
+        let response = route_completion(config, req).await.unwrap();
+        assert_eq!(response.object, "text_completion");
+        assert_eq!(response.choices[0].text, "Once upon a time");
+        assert_eq!(response.usage.unwrap().total_tokens, 5);
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_responses_rebuilds_previous_conversation() {
+        let mut server = mockito::Server::new_async().await;
+        let anthropic_reply = r#"{
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Paris"}],
+            "model": "claude-opus-4-5",
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 2, "output_tokens": 1}
+        }"#;
+        let first = server
+            .mock("POST", "/v1/messages")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "system": "Be brief",
+                "messages": [{"role": "user", "content": "Capital of France?"}]
+            })))
+            .with_status(200)
+            .with_body(anthropic_reply)
+            .create_async()
+            .await;
+        let second = server
+            .mock("POST", "/v1/messages")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "messages": [
+                    {"role": "user", "content": "Capital of France?"},
+                    {"role": "assistant", "content": "Paris"},
+                    {"role": "user", "content": "And Italy?"}
+                ]
+            })))
+            .with_status(200)
+            .with_body(anthropic_reply)
+            .create_async()
+            .await;
+
+        let config = Arc::new(Config {
+            model_list: vec![ModelConfig {
+                model_name: "claude".to_string(),
+                litellm_params: LitellmParams {
+                    model: "anthropic/claude-opus-4-5".to_string(),
+                    api_key: "sk-ant-test".to_string(),
+                    api_base: server.url(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+
+        let req: ResponsesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude",
+            "instructions": "Be brief",
+            "input": "Capital of France?"
+        }))
+        .unwrap();
+        let response = route_responses(Arc::clone(&config), req).await.unwrap();
+        assert_eq!(response.status, "completed");
+        assert_eq!(response.output[0].text(), "Paris");
+        first.assert_async().await;
+
+        let first_id = response.id;
+        let req: ResponsesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude",
+            "previous_response_id": first_id,
+            "input": [{"role": "user", "content": "And Italy?"}]
+        }))
+        .unwrap();
+        let response = route_responses(Arc::clone(&config), req).await.unwrap();
+        assert_eq!(response.previous_response_id, Some(first_id));
+        second.assert_async().await;
+
+        let req: ResponsesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude",
+            "previous_response_id": "resp_unknown",
+            "input": "Hi"
+        }))
+        .unwrap();
+        let err = route_responses(config, req).await.unwrap_err();
+        assert!(matches!(
+            err,
+            FeatherGateError::Validation(ValidationError::PreviousResponseNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_route_messages_passthrough_and_translation() {
+        let mut server = mockito::Server::new_async().await;
+        let anthropic = server
+            .mock("POST", "/v1/messages")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "model": "claude-opus-4-5",
+                "tools": [{"name": "get_weather", "input_schema": {"type": "object"}}],
+                "tool_choice": {"type": "auto"}
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {"city": "Paris"}}],
+                "model": "claude-opus-4-5",
+                "stop_reason": "tool_use",
+                "stop_sequence": null,
+                "usage": {"input_tokens": 10, "output_tokens": 5, "cache_read_input_tokens": 0}
+            }"#,
+            )
+            .create_async()
+            .await;
+        let openai = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "messages": [
+                    {"role": "system", "content": "Be brief"},
+                    {"role": "user", "content": "Hi"}
+                ],
+                "max_tokens": 64
+            })))
+            .with_status(200)
+            .with_body(serde_json::to_string(&ChatResponse::simple("gpt-4", "Hello")).unwrap())
+            .create_async()
+            .await;
+
+        let config = Arc::new(Config {
+            model_list: vec![
+                ModelConfig {
+                    model_name: "claude".to_string(),
+                    litellm_params: LitellmParams {
+                        model: "anthropic/claude-opus-4-5".to_string(),
+                        api_key: "sk-ant-test".to_string(),
+                        api_base: server.url(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                create_openai_model("gpt-4", &server.url()),
+            ],
+            ..Default::default()
+        });
+
+        let req: MessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "claude",
+            "max_tokens": 64,
+            "messages": [{"role": "user", "content": "Weather in Paris?"}],
+            "tools": [{"name": "get_weather", "input_schema": {"type": "object"}}],
+            "tool_choice": {"type": "auto"}
+        }))
+        .unwrap();
+        let response = route_messages(Arc::clone(&config), req).await.unwrap();
+        assert_eq!(response.stop_reason.as_deref(), Some("tool_use"));
+        assert_eq!(response.content[0].extra["input"]["city"], "Paris");
+        anthropic.assert_async().await;
+
+        let req: MessagesRequest = serde_json::from_value(serde_json::json!({
+            "model": "gpt-4",
+            "max_tokens": 64,
+            "system": "Be brief",
+            "messages": [{"role": "user", "content": [{"type": "text", "text": "Hi"}]}]
+        }))
+        .unwrap();
+        let response = route_messages(config, req).await.unwrap();
+        assert_eq!(response.content[0].text.as_deref(), Some("Hello"));
+        assert_eq!(response.stop_reason.as_deref(), Some("end_turn"));
+        openai.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_generate_content_passthrough() {
+        let mut server = mockito::Server::new_async().await;
+        let body = serde_json::json!({
+            "contents": [{"role": "user", "parts": [{"text": "Hi"}]}],
+            "tools": [{"googleSearch": {}}],
+            "safetySettings": [{"category": "HARM_CATEGORY_HARASSMENT", "threshold": "BLOCK_NONE"}]
+        });
+        let mock = server
+            .mock("POST", "/v1beta/models/gemini-2.5-flash:generateContent")
+            .match_header("x-goog-api-key", "gm-test")
+            .match_body(mockito::Matcher::Json(body.clone()))
+            .with_status(200)
+            .with_body(r#"{"candidates": [{"content": {"role": "model", "parts": [{"text": "Hello"}]}, "finishReason": "STOP", "groundingMetadata": {}}]}"#)
+            .create_async()
+            .await;
+
+        let config = Arc::new(Config {
+            model_list: vec![ModelConfig {
+                model_name: "flash".to_string(),
+                litellm_params: LitellmParams {
+                    model: "gemini/gemini-2.5-flash".to_string(),
+                    api_key: "gm-test".to_string(),
+                    api_base: server.url(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let req = GenerateContentRequest {
+            model: "flash".to_string(),
+            body,
+        };
+
+        let response = route_generate_content(config, req).await.unwrap();
+        assert_eq!(response["candidates"][0]["content"]["parts"][0]["text"], "Hello");
+        assert!(response["candidates"][0]["groundingMetadata"].is_object());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_embedding_falls_back_to_cohere() {
+        let mut server = mockito::Server::new_async().await;
+        let overloaded = server
+            .mock("POST", "/embeddings")
+            .with_status(503)
+            .with_body(r#"{"error": {"message": "overloaded", "type": "server_error"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let cohere = server
+            .mock("POST", "/v2/embed")
+            .with_status(200)
+            .with_body(r#"{"embeddings": {"float": [[0.1, 0.2]]}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut openai_embed = create_openai_model("openai-embed", &server.url());
+        openai_embed.model_info.mode = Some(ModelMode::Embedding);
+        let mut config = Config {
+            model_list: vec![
+                openai_embed,
+                ModelConfig {
+                    model_name: "cohere-embed".to_string(),
+                    litellm_params: LitellmParams {
+                        model: "cohere/embed-english-v3.0".to_string(),
+                        api_key: "co-test".to_string(),
+                        api_base: server.url(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        config.router_settings.fallbacks = vec![
+            [("openai-embed".to_string(), vec!["cohere-embed".to_string()])].into(),
+        ];
+
+        let req: EmbeddingRequest = serde_json::from_value(serde_json::json!({
+            "model": "openai-embed",
+            "input": "hello"
+        }))
+        .unwrap();
+
+        let response = route_embedding(Arc::new(config), req).await.unwrap();
+        assert_eq!(response.data.len(), 1);
+
+        overloaded.assert_async().await;
+        cohere.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_rerank_falls_back_to_tei() {
+        let mut server = mockito::Server::new_async().await;
+        let rate_limited = server
+            .mock("POST", "/v2/rerank")
+            .with_status(429)
+            .with_body(r#"{"message": "too many requests"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let tei = server
+            .mock("POST", "/tei/rerank")
+            .with_status(200)
+            .with_body(r#"[{"index": 0, "score": 0.4}, {"index": 1, "score": 0.8}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let rerank_model = |name: &str, model: &str, api_base: String| {
+            let mut config = ModelConfig {
+                model_name: name.to_string(),
+                litellm_params: LitellmParams {
+                    model: model.to_string(),
+                    api_key: "test".to_string(),
+                    api_base,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            config.model_info.mode = Some(ModelMode::Rerank);
+            config
+        };
+        let mut config = Config {
+            model_list: vec![
+                rerank_model("cohere-rerank", "cohere/rerank-v3.5", server.url()),
+                rerank_model("local-rerank", "tei/bge-reranker-base", format!("{}/tei", server.url())),
+                create_openai_model("gpt-4", &server.url()),
+            ],
+            ..Default::default()
+        };
+        config.router_settings.fallbacks = vec![
+            [("cohere-rerank".to_string(), vec!["local-rerank".to_string()])].into(),
+        ];
+        config.model_list[2].model_info.mode = Some(ModelMode::Chat);
+        let config = Arc::new(config);
+
+        let req: RerankRequest = serde_json::from_value(serde_json::json!({
+            "model": "cohere-rerank",
+            "query": "q",
+            "documents": ["a", "b"]
+        }))
+        .unwrap();
+        let response = route_rerank(Arc::clone(&config), req.clone()).await.unwrap();
+        assert_eq!(response.model, "bge-reranker-base");
+        assert_eq!(response.results[0].index, 1);
+
+        rate_limited.assert_async().await;
+        tei.assert_async().await;
+
+        // 聊天模型不能处理重排序请求
+        let req = RerankRequest { model: "gpt-4".to_string(), ..req };
+        let result = route_rerank(config, req).await;
+        assert!(matches!(
+            result,
+            Err(FeatherGateError::ModelModeMismatch(_, ModelMode::Rerank))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_route_request_enforces_key_allowlist() {
+        let store = auth::keys::test_store();
+        let (api_key, _) = store
+            .generate(auth::keys::NewKey {
+                models: vec!["gpt-4".to_string()],
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let caller = auth::Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+        let config = Arc::new(Config {
+            model_list: vec![
+                create_openai_model("gpt-4", "http://127.0.0.1:1"),
+                create_openai_model("gpt-4o", "http://127.0.0.1:1"),
+            ],
+            ..Default::default()
+        });
+
+        let result = auth::with_caller(
+            caller,
+            route_request(Arc::clone(&config), create_chat_request("gpt-4o")),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(FeatherGateError::ModelNotAllowed(model)) if model == "gpt-4o"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fallbacks_skip_models_the_key_may_not_use() {
+        let mut server = mockito::Server::new_async().await;
+        let overloaded = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"model": "gpt-4"})))
+            .with_status(529)
+            .with_body(r#"{"error": {"message": "Overloaded"}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let forbidden = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"model": "gpt-4o"})))
+            .with_status(200)
+            .with_body(USAGE_BODY)
+            .expect(0)
+            .create_async()
+            .await;
+        let allowed = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"model": "gpt-4o-mini"})))
+            .with_status(200)
+            .with_body(USAGE_BODY)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let store = auth::keys::test_store();
+        let mut callers = Vec::new();
+        for models in [vec!["gpt-4", "gpt-4o-mini"], vec!["gpt-4"]] {
+            let (api_key, _) = store
+                .generate(auth::keys::NewKey {
+                    models: models.into_iter().map(str::to_string).collect(),
+                    ..Default::default()
+                }, "master_key")
+                .unwrap();
+            callers.push(auth::Caller::Key(store.lookup(&api_key).unwrap().unwrap()));
+        }
+        let config = Arc::new(Config {
+            model_list: vec![
+                create_openai_model("gpt-4", &server.url()),
+                create_openai_model("gpt-4o", &server.url()),
+                create_openai_model("gpt-4o-mini", &server.url()),
+            ],
+            router_settings: RouterSettings {
+                fallbacks: vec![[(
+                    "gpt-4".to_string(),
+                    vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()],
+                )]
+                .into()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        // 跳过 key 无权使用的 gpt-4o，回退到 gpt-4o-mini
+        let mut callers = callers.into_iter();
+        let response = auth::with_caller(
+            callers.next().unwrap(),
+            route_request(Arc::clone(&config), create_chat_request("gpt-4")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.choices[0].message.content, "ok");
+
+        // 没有可用的回退时返回原始错误
+        let result = auth::with_caller(
+            callers.next().unwrap(),
+            route_request(Arc::clone(&config), create_chat_request("gpt-4")),
+        )
+        .await;
+        assert!(matches!(result, Err(FeatherGateError::UpstreamError { status: 529, .. })));
+
+        overloaded.assert_async().await;
+        forbidden.assert_async().await;
+        allowed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_fallbacks_respect_team_access_groups() {
+        use crate::auth::teams::{self, EntitySettings, NewTeam};
+
+        let mut server = mockito::Server::new_async().await;
+        let overloaded = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"model": "gpt-4"})))
+            .with_status(529)
+            .with_body(r#"{"error": {"message": "Overloaded"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let outside_group = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({"model": "gpt-4o"})))
+            .with_status(200)
+            .with_body(USAGE_BODY)
+            .expect(0)
+            .create_async()
+            .await;
+
+        // 团队只能使用 beta-models 访问组，key 本身不限制模型
+        let team = teams::team_store()
+            .create_team(NewTeam {
+                settings: EntitySettings {
+                    models: vec!["beta-models".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let store = auth::keys::test_store();
+        let (api_key, _) = store
+            .generate(auth::keys::NewKey {
+                team_id: Some(team.team_id.clone()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let caller = auth::Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+
+        let mut model = create_openai_model("gpt-4", &server.url());
+        model.model_info.access_groups = vec!["beta-models".to_string()];
+        let config = Arc::new(Config {
+            model_list: vec![model, create_openai_model("gpt-4o", &server.url())],
+            router_settings: RouterSettings {
+                fallbacks: vec![[("gpt-4".to_string(), vec!["gpt-4o".to_string()])].into()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let result = auth::with_caller(caller, route_request(config, create_chat_request("gpt-4"))).await;
+        assert!(matches!(result, Err(FeatherGateError::UpstreamError { status: 529, .. })));
+
+        overloaded.assert_async().await;
+        outside_group.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_request_tracks_spend_and_enforces_budget() {
+        let mut server = mockito::Server::new_async().await;
+        let upstream = server
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_body(USAGE_BODY)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let store = auth::keys::test_store();
+        let (api_key, _) = store
+            .generate(
+                auth::keys::NewKey {
+                    team_id: Some("routing-budget-team".to_string()),
+                    ..Default::default()
+                },
+                "master_key",
+            )
+            .unwrap();
+        let caller = auth::Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+
+        let mut model = create_openai_model("gpt-4", &server.url());
+        model.model_info.input_cost_per_token = Some(0.00001);
+        model.model_info.output_cost_per_token = Some(0.00002);
+        let mut config = Config {
+            model_list: vec![model],
+            ..Default::default()
+        };
+        config.general_settings.team_budgets.insert(
+            "routing-budget-team".to_string(),
+            crate::config::BudgetConfig {
+                max_budget: Some(0.001),
+                budget_duration: Some("1d".to_string()),
+            },
+        );
+        config
+            .general_settings
+            .user_budgets
+            .insert("routing-budget-user".to_string(), crate::config::BudgetConfig::default());
+        let config = Arc::new(config);
+        let req = ChatRequest {
+            user: Some("routing-budget-user".to_string()),
+            ..create_chat_request("gpt-4")
+        };
+
+        // 第一次请求未超预算，按部署单价计费：10 * 0.00001 + 100 * 0.00002
+        auth::with_caller(caller.clone(), route_request(Arc::clone(&config), req.clone()))
+            .await
+            .unwrap();
+        let team_spend = spend::tracker()
+            .summary(spend::EntityType::Team, "routing-budget-team")
+            .unwrap()
+            .spend;
+        assert!((team_spend - 0.0021).abs() < 1e-9);
+        let user_spend = spend::tracker()
+            .summary(spend::EntityType::User, "routing-budget-user")
+            .unwrap()
+            .spend;
+        assert!((user_spend - 0.0021).abs() < 1e-9);
+
+        // 团队预算用尽后，请求在调用上游之前被拒绝
+        let result = auth::with_caller(caller, route_request(Arc::clone(&config), req)).await;
+        assert!(matches!(
+            result,
+            Err(FeatherGateError::BudgetExceeded(name, _, _)) if name == "team routing-budget-team"
+        ));
+        upstream.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_request_stream_tracks_openai_usage() {
+        use futures_util::TryStreamExt;
+
+        let mut server = mockito::Server::new_async().await;
+        let body = concat!(
+            "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},\"finish_reason\":\"stop\"}],\"usage\":null}\n\n",
+            "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4\",\"choices\":[],\"usage\":{\"prompt_tokens\":10,\"completion_tokens\":100,\"total_tokens\":110}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let upstream = server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "stream": true,
+                "stream_options": {"include_usage": true}
+            })))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let store = auth::keys::test_store();
+        let (api_key, _) = store
+            .generate(
+                auth::keys::NewKey {
+                    team_id: Some("routing-stream-team".to_string()),
+                    ..Default::default()
+                },
+                "master_key",
+            )
+            .unwrap();
+        let caller = auth::Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+
+        let mut model = create_openai_model("gpt-4", &server.url());
+        model.model_info.input_cost_per_token = Some(0.00001);
+        model.model_info.output_cost_per_token = Some(0.00002);
+        let mut config = Config {
+            model_list: vec![model],
+            ..Default::default()
+        };
+        config.general_settings.team_budgets.insert(
+            "routing-stream-team".to_string(),
+            crate::config::BudgetConfig {
+                max_budget: Some(1.0),
+                budget_duration: Some("1d".to_string()),
+            },
+        );
+        let config = Arc::new(config);
+        let req = ChatRequest {
+            stream: Some(true),
+            ..create_chat_request("gpt-4")
+        };
+        let team_spend = || {
+            spend::tracker()
+                .summary(spend::EntityType::Team, "routing-stream-team")
+                .unwrap()
+                .spend
+        };
+
+        // 客户端没有要求用量：仍然计费，但用量数据块不转发给客户端
+        let stream = auth::with_caller(caller.clone(), route_request_stream(Arc::clone(&config), req.clone()))
+            .await
+            .unwrap();
+        let body: Vec<Bytes> = stream.try_collect().await.unwrap();
+        let body = String::from_utf8(body.concat()).unwrap();
+        assert!(body.contains("\"content\":\"Hi\""));
+        assert!(!body.contains("\"prompt_tokens\""));
+        assert!(body.ends_with("data: [DONE]\n\n"));
+        assert!((team_spend() - 0.0021).abs() < 1e-9);
+
+        // 客户端要求用量时原样返回
+        let req = ChatRequest {
+            stream_options: Some(StreamOptions {
+                include_usage: Some(true),
+                ..Default::default()
+            }),
+            ..req
+        };
+        let stream = auth::with_caller(caller, route_request_stream(Arc::clone(&config), req))
+            .await
+            .unwrap();
+        let body: Vec<Bytes> = stream.try_collect().await.unwrap();
+        assert!(String::from_utf8(body.concat()).unwrap().contains("\"prompt_tokens\":10"));
+        assert!((team_spend() - 0.0042).abs() < 1e-9);
+        upstream.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_speech_tracks_spend_and_enforces_budget() {
+        let mut server = mockito::Server::new_async().await;
+        let upstream = server
+            .mock("POST", "/audio/speech")
+            .with_status(200)
+            .with_header("content-type", "audio/mpeg")
+            .with_body("ID3-audio")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let store = auth::keys::test_store();
+        let (api_key, _) = store
+            .generate(
+                auth::keys::NewKey {
+                    team_id: Some("routing-speech-team".to_string()),
+                    ..Default::default()
+                },
+                "master_key",
+            )
+            .unwrap();
+        let caller = auth::Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+
+        let mut model = create_openai_model("tts", &server.url());
+        model.litellm_params.model = "openai/tts-1".to_string();
+        model.model_info.mode = Some(ModelMode::AudioSpeech);
+        model.model_info.input_cost_per_character = Some(0.0001);
+        let mut config = Config {
+            model_list: vec![model],
+            ..Default::default()
+        };
+        config.general_settings.team_budgets.insert(
+            "routing-speech-team".to_string(),
+            crate::config::BudgetConfig {
+                max_budget: Some(0.001),
+                budget_duration: None,
+            },
+        );
+        let config = Arc::new(config);
+        let req = SpeechRequest {
+            model: "tts".to_string(),
+            input: "你好，世界！".to_string(),
+            voice: "alloy".to_string(),
+            response_format: None,
+            speed: None,
+            instructions: None,
+        };
+
+        // 按输入字符数计费：6 * 0.0001
+        auth::with_caller(caller.clone(), route_speech(Arc::clone(&config), req.clone()))
+            .await
+            .unwrap();
+        let team_spend = spend::tracker()
+            .summary(spend::EntityType::Team, "routing-speech-team")
+            .unwrap()
+            .spend;
+        assert!((team_spend - 0.0006).abs() < 1e-9);
+
+        // 第二次请求计费后超出预算，第三次在调用上游之前被拒绝
+        let upstream_again = server
+            .mock("POST", "/audio/speech")
+            .with_status(200)
+            .with_body("ID3-audio")
+            .expect(1)
+            .create_async()
+            .await;
+        auth::with_caller(caller.clone(), route_speech(Arc::clone(&config), req.clone()))
+            .await
+            .unwrap();
+        let result = auth::with_caller(caller, route_speech(config, req)).await;
+        assert!(matches!(
+            result,
+            Err(FeatherGateError::BudgetExceeded(name, _, _)) if name == "team routing-speech-team"
+        ));
+        upstream.assert_async().await;
+        upstream_again.assert_async().await;
+    }
+
+    const USAGE_BODY: &str = r#"{
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1234567890,
+        "model": "gpt-4",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "ok"},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 100, "total_tokens": 110}
+    }"#;
+
+    #[tokio::test]
+    async fn test_route_request_enforces_key_rpm_limit() {
+        let mut server = mockito::Server::new_async().await;
+        let upstream = server
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_body(OK_BODY)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let store = auth::keys::test_store();
+        let (api_key, _) = store
+            .generate(
+                auth::keys::NewKey {
+                    rpm_limit: Some(1),
+                    ..Default::default()
+                },
+                "master_key",
+            )
+            .unwrap();
+        let caller = auth::Caller::Key(store.lookup(&api_key).unwrap().unwrap());
+        let config = Arc::new(Config {
+            model_list: vec![create_openai_model("gpt-4", &server.url())],
+            ..Default::default()
+        });
+
+        auth::with_caller(
+            caller.clone(),
+            route_request(Arc::clone(&config), create_chat_request("gpt-4")),
+        )
+        .await
+        .unwrap();
+
+        // 限额用尽后在调用上游之前拒绝
+        let result =
+            auth::with_caller(caller, route_request(config, create_chat_request("gpt-4"))).await;
+        let err = result.unwrap_err();
+        assert!(matches!(
+            &err,
+            FeatherGateError::RateLimitExceeded(_, ratelimit::LimitKind::Requests, 1)
+        ));
+        assert_eq!(err.status_code(), 429);
+        upstream.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_deployment_tpm_exhaustion_falls_back() {
+        let mut primary = mockito::Server::new_async().await;
+        let primary_mock = primary
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_body(USAGE_BODY)
+            .expect(1)
+            .create_async()
+            .await;
+        let mut backup = mockito::Server::new_async().await;
+        let backup_mock = backup
+            .mock("POST", "/chat/completions")
+            .with_status(200)
+            .with_body(USAGE_BODY)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut limited = create_openai_model("tpm-primary", &primary.url());
+        limited.litellm_params.tpm = Some(100);
+        let mut config = Config {
+            model_list: vec![limited, create_openai_model("tpm-backup", &backup.url())],
+            ..Default::default()
+        };
+        config.router_settings.fallbacks = vec![
+            [("tpm-primary".to_string(), vec!["tpm-backup".to_string()])].into(),
+        ];
+        let config = Arc::new(config);
+
+        // 第一次请求实际用了 110 个 token，超出部署的 TPM，下一次请求回退到备用部署
+        route_request(Arc::clone(&config), create_chat_request("tpm-primary"))
+            .await
+            .unwrap();
+        route_request(config, create_chat_request("tpm-primary"))
+            .await
+            .unwrap();
+
+        primary_mock.assert_async().await;
+        backup_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_route_rejects_mode_mismatch() {
+        let mut embed = create_openai_model("embed", "http://127.0.0.1:1");
+        embed.model_info.mode = Some(ModelMode::Embedding);
+        let config = Arc::new(Config {
+            model_list: vec![embed, create_openai_model("gpt-4", "http://127.0.0.1:1")],
+            ..Default::default()
+        });
+
+        let result = route_request(Arc::clone(&config), create_chat_request("embed")).await;
+        assert!(matches!(
+            result,
+            Err(FeatherGateError::ModelModeMismatch(_, ModelMode::Chat))
+        ));
+
+        // 声明 mode 的聊天模型同样不能处理向量嵌入请求
+        let mut config = (*config).clone();
+        config.model_list[1].model_info.mode = Some(ModelMode::Chat);
+        let req: EmbeddingRequest =
+            serde_json::from_value(serde_json::json!({"model": "gpt-4", "input": "hi"})).unwrap();
+        let result = route_embedding(Arc::new(config), req).await;
+        assert!(matches!(
+            result,
+            Err(FeatherGateError::ModelModeMismatch(_, ModelMode::Embedding))
+        ));
+    }
+
+    fn create_moderated_config(server_url: &str) -> Config {
+        let mut moderation = create_openai_model("omni-moderation-latest", server_url);
+        moderation.model_info.mode = Some(ModelMode::Moderation);
+        Config {
+            model_list: vec![
+                ModelConfig {
+                    model_name: "claude".to_string(),
+                    litellm_params: LitellmParams {
+                        model: "anthropic/claude-opus-4-5".to_string(),
+                        api_key: "sk-ant-test".to_string(),
+                        api_base: server_url.to_string(),
+                        ..Default::default()
+                    },
+                    moderation: true,
+                    ..Default::default()
+                },
+                moderation,
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_moderation_rejects_flagged_input() {
+        let mut server = mockito::Server::new_async().await;
+        let moderation = server
+            .mock("POST", "/moderations")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "model": "omni-moderation-latest",
+                "input": ["test"]
+            })))
+            .with_status(200)
+            .with_body(
+                r#"{"id": "modr-1", "model": "omni-moderation-latest", "results": [
+                    {"flagged": true, "categories": {"violence": true, "hate": false}}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let anthropic = server
+            .mock("POST", "/v1/messages")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let config = Arc::new(create_moderated_config(&server.url()));
+        let err = route_request(config, create_chat_request("claude"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(&err, FeatherGateError::ModerationFlagged(c) if c == "violence"));
+        let json = err.to_json(crate::i18n::Locale::En);
+        assert_eq!(json["error"]["code"], "content_policy_violation");
+        assert_eq!(err.status_code(), hyper::StatusCode::BAD_REQUEST);
+
+        moderation.assert_async().await;
+        anthropic.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_moderation_allows_clean_input() {
+        let mut server = mockito::Server::new_async().await;
+        let moderation = server
+            .mock("POST", "/moderations")
+            .with_status(200)
+            .with_body(
+                r#"{"id": "modr-1", "model": "omni-moderation-latest", "results": [
+                    {"flagged": false, "categories": {"violence": false}}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let anthropic = server
+            .mock("POST", "/v1/messages")
+            .with_status(200)
+            .with_body(
+                r#"{
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "content": [{"type": "text", "text": "hi"}],
+                "model": "claude-opus-4-5",
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 1, "output_tokens": 1}
+            }"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = Arc::new(create_moderated_config(&server.url()));
+        let response = route_request(config, create_chat_request("claude")).await.unwrap();
+        assert_eq!(response.choices[0].message.content, "hi");
+
+        moderation.assert_async().await;
+        anthropic.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_moderation_covers_fallback_models() {
+        let mut server = mockito::Server::new_async().await;
+        let moderation = server
+            .mock("POST", "/moderations")
+            .with_status(200)
+            .with_body(
+                r#"{"id": "modr-1", "model": "omni-moderation-latest", "results": [
+                    {"flagged": true, "categories": {"violence": true}}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let openai = server
+            .mock("POST", "/chat/completions")
+            .expect(0)
+            .create_async()
+            .await;
+
+        // 请求的模型未开启 moderation，但回退模型开启
+        let mut config = create_moderated_config(&server.url());
+        config.model_list.push(create_openai_model("gpt-4", &server.url()));
+        config.router_settings.fallbacks =
+            vec![[("gpt-4".to_string(), vec!["claude".to_string()])].into()];
+        let err = route_request(Arc::new(config), create_chat_request("gpt-4"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, FeatherGateError::ModerationFlagged(_)));
+        moderation.assert_async().await;
+        openai.assert_async().await;
+    }
+
+    #[test]
+    fn test_next_fallback_by_kind() {
+        let settings = RouterSettings {
+            fallbacks: vec![[("gpt-4".to_string(), vec!["claude".to_string()])].into()],
+            ..Default::default()
+        };
+
+        let overloaded = FeatherGateError::upstream(529, "Overloaded");
+        assert_eq!(
+            next_fallback(&settings, "gpt-4", &overloaded, &["gpt-4".to_string()]),
+            Some("claude".to_string())
+        );
+
+        // 已尝试过的模型不再回退
+        let attempted = ["gpt-4".to_string(), "claude".to_string()];
+        assert_eq!(next_fallback(&settings, "gpt-4", &overloaded, &attempted), None);
+
+        // 请求参数错误不回退
+        let bad_request = FeatherGateError::upstream(400, "bad");
+        assert_eq!(next_fallback(&settings, "gpt-4", &bad_request, &[]), None);
+
+        // 上下文超限只使用 context_window_fallbacks
+        let too_long =
+            FeatherGateError::upstream_with_kind(400, UpstreamErrorKind::ContextWindowExceeded, "");
+        assert_eq!(next_fallback(&settings, "gpt-4", &too_long, &[]), None);
+    }
+
     #[tokio::test]
     async fn test_route_request_model_not_found() {
         let config = Arc::new(create_test_config());
@@ -119,7 +2230,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let result = route_request(config, req).await;
@@ -139,8 +2253,11 @@ mod tests {
                     model: "unknown-provider/model".to_string(),
                     api_key: "test".to_string(),
                     api_base: String::new(),
+                    ..Default::default()
                 },
+                ..Default::default()
             }],
+            ..Default::default()
         });
 
         let req = ChatRequest {
@@ -149,7 +2266,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let result = route_request(config, req).await;
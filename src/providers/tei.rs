@@ -0,0 +1,142 @@
+use crate::config::{parse_model_string, ModelConfig};
+use crate::error::{FeatherGateError, UpstreamErrorKind};
+use crate::providers::send_with_key;
+use crate::types::rerank::{RerankRequest, RerankResponse, RerankUsage};
+use crate::Result;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 获取全局 HTTP 客户端
+fn get_http_client() -> &'static Client {
+    use once_cell::sync::Lazy;
+    static CLIENT: Lazy<Client> = Lazy::new(|| {
+        Client::builder()
+            .timeout(Duration::from_secs(60))
+            .pool_max_idle_per_host(10)
+            .build()
+            .unwrap()
+    });
+    &CLIENT
+}
+
+/// TEI 错误格式
+#[derive(Debug, Deserialize)]
+struct TeiErrorResponse {
+    error: String,
+}
+
+/// 解析 TEI 错误响应并归一化
+pub(crate) fn parse_error(status: u16, body: &str) -> FeatherGateError {
+    let message = serde_json::from_str::<TeiErrorResponse>(body)
+        .map(|resp| resp.error)
+        .unwrap_or_else(|_| body.to_string());
+
+    let kind = UpstreamErrorKind::from_status(status).refine_by_message(&message);
+    FeatherGateError::upstream_with_kind(status, kind, format!("TEI: {}", message))
+}
+
+/// TEI `/rerank` 请求
+#[derive(Debug, Serialize)]
+struct TeiRerankRequest<'a> {
+    query: &'a str,
+    texts: Vec<&'a str>,
+    /// 超过模型最大长度时截断，而不是返回 413
+    truncate: bool,
+}
+
+/// TEI `/rerank` 响应中的单条结果
+#[derive(Debug, Deserialize)]
+struct TeiRerankResult {
+    index: u32,
+    score: f64,
+}
+
+/// 转发重排序请求到自托管的 Text Embeddings Inference（`/rerank`）
+///
+/// TEI 部署只服务一个模型，必须配置 api_base；TEI 不支持 top_n，由网关排序后截取。
+pub async fn forward_rerank(config: &ModelConfig, req: &RerankRequest) -> Result<RerankResponse> {
+    let client = get_http_client();
+    let (_, model_id) = parse_model_string(&config.litellm_params.model)?;
+
+    if config.litellm_params.api_base.is_empty() {
+        return Err(FeatherGateError::config(format!(
+            "TEI 模型 {} 需要配置 api_base",
+            config.model_name
+        )));
+    }
+    let url = format!("{}/rerank", config.litellm_params.api_base.trim_end_matches('/'));
+
+    let rerank_req = TeiRerankRequest {
+        query: &req.query,
+        texts: req.document_texts(),
+        truncate: true,
+    };
+
+    let response = send_with_key(config, parse_error, |api_key| {
+        client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", api_key))
+            .header("Content-Type", "application/json")
+            .json(&rerank_req)
+    })
+    .await?;
+
+    let results: Vec<TeiRerankResult> = response.json().await?;
+    let scores = results
+        .into_iter()
+        .map(|result| (result.index, result.score))
+        .collect();
+
+    Ok(RerankResponse::new(model_id, req, scores, RerankUsage::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LitellmParams;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_forward_rerank_applies_top_n() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/rerank")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "query": "q",
+                "texts": ["a", "b", "c"],
+                "truncate": true
+            })))
+            .with_status(200)
+            .with_body(r#"[{"index": 1, "score": 0.9}, {"index": 2, "score": 0.3}, {"index": 0, "score": 0.1}]"#)
+            .create_async()
+            .await;
+
+        let config = ModelConfig {
+            model_name: "bge-rerank".to_string(),
+            litellm_params: LitellmParams {
+                model: "tei/bge-reranker-base".to_string(),
+                api_key: "none".to_string(),
+                api_base: server.url(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let req: RerankRequest = serde_json::from_value(serde_json::json!({
+            "model": "bge-rerank",
+            "query": "q",
+            "documents": ["a", "b", "c"],
+            "top_n": 2,
+            "return_documents": true
+        }))
+        .unwrap();
+
+        let response = forward_rerank(&config, &req).await.unwrap();
+        assert_eq!(response.model, "bge-reranker-base");
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(response.results[0].document.as_ref().unwrap().text, "b");
+        assert_eq!(response.results[1].index, 2);
+
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,689 @@
+use crate::auth::{self, Caller};
+use crate::config::{Config, ModelConfig, RateLimitConfig};
+use crate::error::FeatherGateError;
+use crate::metrics;
+use crate::Result;
+use hyper::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use once_cell::sync::Lazy;
+use redis::aio::ConnectionManager;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// 限流主体类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// 虚拟 key（按 token）
+    Key,
+    /// 用户（虚拟 key 的 user_id 或请求中的 `user` 字段）
+    User,
+    /// 团队（虚拟 key 的 team_id）
+    Team,
+    /// 组织（团队所属的组织）
+    Organization,
+    /// 模型部署（按 model_name、上游模型和 api_base 区分同名的多个部署）
+    Deployment,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Key => "key",
+            Scope::User => "user",
+            Scope::Team => "team",
+            Scope::Organization => "organization",
+            Scope::Deployment => "deployment",
+        }
+    }
+}
+
+/// 限流维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    /// 每分钟请求数（RPM）
+    Requests,
+    /// 每分钟 token 数（TPM）
+    Tokens,
+}
+
+impl LimitKind {
+    /// OpenAI 风格响应头中的后缀
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LimitKind::Requests => "requests",
+            LimitKind::Tokens => "tokens",
+        }
+    }
+}
+
+/// 某个限额的当前状态，用于 `x-ratelimit-*` 响应头
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitStatus {
+    pub kind: LimitKind,
+    /// 每分钟限额
+    pub limit: u64,
+    /// 当前剩余
+    pub remaining: u64,
+    /// 恢复到满额还需要的时间
+    pub reset: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    scope: Scope,
+    id: String,
+    kind: LimitKind,
+}
+
+impl BucketKey {
+    /// Redis 中累计消耗的计数器
+    fn redis_key(&self) -> String {
+        format!(
+            "feathergate:ratelimit:{}:{}:{}",
+            self.scope.as_str(),
+            self.id,
+            self.kind.as_str()
+        )
+    }
+}
+
+/// Redis 计数器闲置多久后过期（秒）
+const COUNTER_TTL_SECS: i64 = 600;
+
+/// 本地的桶闲置多久后可以清除（与 Redis 计数器同时过期）
+const BUCKET_IDLE: Duration = Duration::from_secs(COUNTER_TTL_SECS as u64);
+
+/// 一个请求需要遵守的限额
+#[derive(Debug, Clone)]
+struct Limit {
+    key: BucketKey,
+    /// 错误消息中使用的名称（key 使用脱敏后的 key）
+    display_name: String,
+    limit: u64,
+}
+
+/// 令牌桶：容量为每分钟限额，按每秒限额 / 60 的速度匀速补充
+///
+/// TPM 桶在响应后按实际用量扣除，可以扣成负数，欠下的部分补足之前拒绝新请求。
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    /// 上次补充时的每分钟限额
+    limit: u64,
+    updated: Instant,
+    /// 尚未同步到 Redis 的消耗
+    pending: u64,
+    /// 上次同步时 Redis 中的累计消耗（包括所有副本）
+    seen: Option<u64>,
+}
+
+impl Bucket {
+    fn new(limit: u64, now: Instant) -> Self {
+        Bucket {
+            tokens: limit as f64,
+            limit,
+            updated: now,
+            pending: 0,
+            seen: None,
+        }
+    }
+
+    /// 补充自上次更新以来的令牌（限额修改后按新的容量计算）
+    fn refill(&mut self, limit: u64, now: Instant) {
+        let capacity = limit as f64;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity / 60.0).min(capacity);
+        self.limit = limit;
+        self.updated = now;
+    }
+
+    /// 闲置足够久、已经补满且没有待同步的消耗：清除后重新创建的桶与它等价
+    fn is_idle(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated);
+        let capacity = self.limit as f64;
+        elapsed >= BUCKET_IDLE
+            && self.pending == 0
+            && self.tokens + elapsed.as_secs_f64() * capacity / 60.0 >= capacity
+    }
+
+    /// 令牌补充到 target 需要的时间
+    fn time_until(&self, limit: u64, target: f64) -> Duration {
+        if self.tokens >= target {
+            return Duration::ZERO;
+        }
+        if limit == 0 {
+            return Duration::from_secs(60);
+        }
+        Duration::from_secs_f64((target - self.tokens) * 60.0 / limit as f64)
+    }
+
+    fn status(&self, limit: &Limit) -> LimitStatus {
+        LimitStatus {
+            kind: limit.key.kind,
+            limit: limit.limit,
+            remaining: self.tokens.max(0.0) as u64,
+            reset: self.time_until(limit.limit, limit.limit as f64),
+        }
+    }
+}
+
+/// 进程内的 RPM/TPM 令牌桶
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+    /// 上次清除闲置的桶的时间（已删除的 key、团队和配置中移除的部署不再使用它们的桶）
+    swept: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 所有限额都至少剩余 1 时放行，并从每个 RPM 桶中扣除一个请求
+    ///
+    /// 返回各限额的状态；被拒绝时同时返回第一个耗尽的限额和需要等待的时间。
+    /// 只有全部通过才扣除，被拒绝的请求不占用其他限额。
+    fn acquire<'a>(&self, limits: &'a [Limit]) -> (Vec<LimitStatus>, Option<(&'a Limit, Duration)>) {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        self.evict_idle(&mut buckets, now);
+
+        let mut rejected = None;
+        for limit in limits {
+            let bucket = bucket(&mut buckets, limit, now);
+            if rejected.is_none() && bucket.tokens < 1.0 {
+                rejected = Some((limit, bucket.time_until(limit.limit, 1.0)));
+            }
+        }
+
+        let statuses = limits
+            .iter()
+            .map(|limit| {
+                let bucket = bucket(&mut buckets, limit, now);
+                if rejected.is_none() && limit.key.kind == LimitKind::Requests {
+                    bucket.tokens -= 1.0;
+                    bucket.pending += 1;
+                }
+                bucket.status(limit)
+            })
+            .collect();
+        (statuses, rejected)
+    }
+
+    /// 每隔 [`BUCKET_IDLE`] 清除一次闲置的桶
+    fn evict_idle(&self, buckets: &mut HashMap<BucketKey, Bucket>, now: Instant) {
+        let mut swept = self.swept.lock().unwrap();
+        match *swept {
+            Some(at) if now.saturating_duration_since(at) < BUCKET_IDLE => {}
+            Some(_) => {
+                buckets.retain(|_, bucket| !bucket.is_idle(now));
+                *swept = Some(now);
+            }
+            None => *swept = Some(now),
+        }
+    }
+
+    /// 按实际用量从 TPM 桶中扣除 token
+    fn consume(&self, limits: &[Limit], tokens: u64) {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        for limit in limits.iter().filter(|l| l.key.kind == LimitKind::Tokens) {
+            let bucket = bucket(&mut buckets, limit, now);
+            bucket.tokens -= tokens as f64;
+            bucket.pending += tokens;
+        }
+    }
+
+    /// 与 Redis 同步：上报本副本的消耗，并扣除其他副本在上次同步之后的消耗
+    ///
+    /// 同步失败时保留本副本的消耗，下次同步时一起上报。
+    pub(crate) async fn sync(&self, conn: &mut ConnectionManager) -> redis::RedisResult<()> {
+        let counters: Vec<(BucketKey, u64)> = self
+            .buckets
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .map(|(key, bucket)| (key.clone(), std::mem::take(&mut bucket.pending)))
+            .collect();
+        if counters.is_empty() {
+            return Ok(());
+        }
+
+        let mut pipe = redis::pipe();
+        for (key, pending) in &counters {
+            let name = key.redis_key();
+            pipe.incr(&name, *pending).expire(&name, COUNTER_TTL_SECS).ignore();
+        }
+        let result: redis::RedisResult<Vec<u64>> = pipe.query_async(conn).await;
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let totals = match result {
+            Ok(totals) => totals,
+            Err(e) => {
+                for (key, pending) in counters {
+                    if let Some(bucket) = buckets.get_mut(&key) {
+                        bucket.pending += pending;
+                    }
+                }
+                return Err(e);
+            }
+        };
+        for ((key, pending), total) in counters.iter().zip(totals) {
+            let Some(bucket) = buckets.get_mut(key) else {
+                continue;
+            };
+            // 第一次同步时无法区分历史消耗，只记录基准；计数器过期重建后同样重新开始
+            if let Some(seen) = bucket.seen {
+                let others = total.saturating_sub(seen + pending);
+                bucket.tokens -= others as f64;
+            }
+            bucket.seen = Some(total);
+        }
+        Ok(())
+    }
+}
+
+/// 取出（或以满额创建）限额对应的桶，并补充令牌
+fn bucket<'a>(buckets: &'a mut HashMap<BucketKey, Bucket>, limit: &Limit, now: Instant) -> &'a mut Bucket {
+    let bucket = buckets
+        .entry(limit.key.clone())
+        .or_insert_with(|| Bucket::new(limit.limit, now));
+    bucket.refill(limit.limit, now);
+    bucket
+}
+
+/// 全局限流器
+pub fn limiter() -> &'static RateLimiter {
+    static LIMITER: Lazy<RateLimiter> = Lazy::new(RateLimiter::new);
+    &LIMITER
+}
+
+/// 一个请求经过的限额状态，用于生成响应头
+#[derive(Debug, Default)]
+struct RequestLimits {
+    statuses: Vec<LimitStatus>,
+    retry_after: Option<Duration>,
+}
+
+tokio::task_local! {
+    static REQUEST_LIMITS: RefCell<RequestLimits>;
+}
+
+/// 在请求处理期间收集限额状态，供 [`apply_headers`] 使用
+pub async fn scope<F: Future>(f: F) -> F::Output {
+    REQUEST_LIMITS.scope(RefCell::new(RequestLimits::default()), f).await
+}
+
+/// 把一个配置展开为 RPM 和 TPM 两个限额
+fn push_limits(limits: &mut Vec<Limit>, scope: Scope, id: &str, display_name: String, config: &RateLimitConfig) {
+    let kinds = [
+        (LimitKind::Requests, config.rpm_limit),
+        (LimitKind::Tokens, config.tpm_limit),
+    ];
+    for (kind, limit) in kinds {
+        if let Some(limit) = limit {
+            limits.push(Limit {
+                key: BucketKey {
+                    scope,
+                    id: id.to_string(),
+                    kind,
+                },
+                display_name: display_name.clone(),
+                limit,
+            });
+        }
+    }
+}
+
+/// 当前调用方的限额：虚拟 key、调用方的用户、团队和组织（来自虚拟 key 或 JWT），以及请求中的 `user`
+///
+/// 团队记录中的限额优先于配置文件中的 `team_rate_limits`。
+fn caller_limits(config: &Config) -> Result<Vec<Limit>> {
+    let settings = &config.general_settings;
+    let mut limits = Vec::new();
+    let mut users = Vec::new();
+
+    let caller = auth::current_caller();
+    let membership = auth::membership(&caller)?;
+    if let Caller::Key(key) = &caller {
+        let key_limits = RateLimitConfig {
+            rpm_limit: key.rpm_limit,
+            tpm_limit: key.tpm_limit,
+        };
+        push_limits(&mut limits, Scope::Key, &key.token, format!("key {}", key.key_name), &key_limits);
+    }
+    users.extend(caller.user_id().map(str::to_string));
+    if let Some(team_id) = caller.team_id() {
+        let configured = settings.team_rate_limits.get(team_id).cloned().unwrap_or_default();
+        let team_limits = match &membership.team {
+            Some(team) => team.settings.rate_limits_or(configured),
+            None => configured,
+        };
+        push_limits(&mut limits, Scope::Team, team_id, format!("team {}", team_id), &team_limits);
+    }
+    if let Some(org) = &membership.organization {
+        let org_id = &org.organization_id;
+        let org_limits = org.settings.rate_limits();
+        push_limits(&mut limits, Scope::Organization, org_id, format!("organization {}", org_id), &org_limits);
+    }
+    if let Some(end_user) = auth::end_user() {
+        if !users.contains(&end_user) {
+            users.push(end_user);
+        }
+    }
+    for user in users {
+        if let Some(user_limits) = settings.user_rate_limits.get(&user) {
+            push_limits(&mut limits, Scope::User, &user, format!("user {}", user), user_limits);
+        }
+    }
+    Ok(limits)
+}
+
+/// 部署自身的限额（`litellm_params.rpm` / `tpm`），同名的每个部署各用一组桶
+fn deployment_limits(model_config: &ModelConfig) -> Vec<Limit> {
+    let params = &model_config.litellm_params;
+    let mut limits = Vec::new();
+    let deployment_limits = RateLimitConfig {
+        rpm_limit: params.rpm,
+        tpm_limit: params.tpm,
+    };
+    let name = &model_config.model_name;
+    let id = format!("{}/{}@{}", name, params.model, params.api_base);
+    push_limits(&mut limits, Scope::Deployment, &id, format!("deployment {}", name), &deployment_limits);
+    limits
+}
+
+/// 检查限额并记录状态，超限时返回 RateLimitExceeded
+fn enforce(limits: &[Limit]) -> Result<()> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    let (statuses, rejected) = limiter().acquire(limits);
+    let _ = REQUEST_LIMITS.try_with(|state| {
+        let mut state = state.borrow_mut();
+        state.statuses.extend(statuses);
+        if let Some((_, retry_after)) = rejected {
+            state.retry_after = Some(retry_after);
+        }
+    });
+
+    match rejected {
+        Some((limit, retry_after)) => {
+            metrics::global_metrics().record_rate_limited();
+            warn!(
+                "{} 超出每分钟 {} 限制 {}，{}ms 后恢复",
+                limit.display_name,
+                limit.key.kind.as_str(),
+                limit.limit,
+                retry_after.as_millis()
+            );
+            Err(FeatherGateError::RateLimitExceeded(
+                limit.display_name.clone(),
+                limit.key.kind,
+                limit.limit,
+            ))
+        }
+        None => Ok(()),
+    }
+}
+
+/// 转发前检查当前调用方（key、用户、团队、组织）的 RPM/TPM
+pub fn check(config: &Config) -> Result<()> {
+    enforce(&caller_limits(config)?)
+}
+
+/// 路由选定部署后、转发前检查它的 RPM/TPM（回退到其他部署时分别检查）
+pub fn check_deployment(model_config: &ModelConfig) -> Result<()> {
+    enforce(&deployment_limits(model_config))
+}
+
+/// 需要按实际用量扣除的 TPM 限额
+///
+/// 流式响应在推流结束后才知道用量，此时已不在请求上下文中，需要提前取出。
+#[derive(Debug, Clone, Default)]
+pub struct TokenLimits(Vec<Limit>);
+
+impl TokenLimits {
+    /// 当前请求的调用方和实际使用的部署的 TPM 限额
+    pub fn for_request(config: &Config, model_config: &ModelConfig) -> Self {
+        let mut limits = caller_limits(config).unwrap_or_else(|e| {
+            warn!("查找调用方的限额失败，本次用量只计入部署: {}", e);
+            Vec::new()
+        });
+        limits.extend(deployment_limits(model_config));
+        limits.retain(|limit| limit.key.kind == LimitKind::Tokens);
+        TokenLimits(limits)
+    }
+
+    /// 扣除实际使用的 token
+    pub fn consume(&self, tokens: u64) {
+        if !self.0.is_empty() && tokens > 0 {
+            limiter().consume(&self.0, tokens);
+        }
+    }
+}
+
+/// 按限额状态添加 OpenAI 风格的 `x-ratelimit-*` 响应头（多个限额时取剩余最少的），
+/// 被限流时还会添加 `retry-after`
+pub fn apply_headers(headers: &mut HeaderMap) {
+    let _ = REQUEST_LIMITS.try_with(|state| {
+        let state = state.borrow();
+        for kind in [LimitKind::Requests, LimitKind::Tokens] {
+            let Some(status) = state
+                .statuses
+                .iter()
+                .filter(|status| status.kind == kind)
+                .min_by_key(|status| status.remaining)
+            else {
+                continue;
+            };
+            let suffix = kind.as_str();
+            let values = [
+                ("limit", status.limit.to_string()),
+                ("remaining", status.remaining.to_string()),
+                ("reset", format_reset(status.reset)),
+            ];
+            for (name, value) in values {
+                if let (Ok(name), Ok(value)) = (
+                    format!("x-ratelimit-{}-{}", name, suffix).parse::<hyper::header::HeaderName>(),
+                    HeaderValue::from_str(&value),
+                ) {
+                    headers.insert(name, value);
+                }
+            }
+        }
+        if let Some(retry_after) = state.retry_after {
+            let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            headers.insert(RETRY_AFTER, HeaderValue::from(seconds));
+        }
+    });
+}
+
+/// OpenAI 格式的恢复时间，如 `120ms`、`6s`、`1m30s`
+fn format_reset(reset: Duration) -> String {
+    if reset < Duration::from_secs(1) {
+        return format!("{}ms", reset.as_millis());
+    }
+    let seconds = reset.as_secs_f64().ceil() as u64;
+    match seconds / 60 {
+        0 => format!("{}s", seconds),
+        minutes => format!("{}m{}s", minutes, seconds % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(kind: LimitKind, limit: u64) -> Limit {
+        Limit {
+            key: BucketKey {
+                scope: Scope::Key,
+                id: "test".to_string(),
+                kind,
+            },
+            display_name: "key test".to_string(),
+            limit,
+        }
+    }
+
+    #[test]
+    fn test_requests_bucket_rejects_when_empty() {
+        let limiter = RateLimiter::new();
+        let limits = [limit(LimitKind::Requests, 2)];
+
+        let (statuses, rejected) = limiter.acquire(&limits);
+        assert!(rejected.is_none());
+        assert_eq!(statuses[0].remaining, 1);
+        assert!(limiter.acquire(&limits).1.is_none());
+
+        let (statuses, rejected) = limiter.acquire(&limits);
+        let (rejected, retry_after) = rejected.unwrap();
+        assert_eq!(rejected.display_name, "key test");
+        assert_eq!(statuses[0].remaining, 0);
+        // 每 30 秒补充一个请求
+        assert!(retry_after > Duration::from_secs(29) && retry_after <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_tokens_reconciled_after_response() {
+        let limiter = RateLimiter::new();
+        let limits = [limit(LimitKind::Requests, 100), limit(LimitKind::Tokens, 1000)];
+
+        assert!(limiter.acquire(&limits).1.is_none());
+        // 实际用量超出剩余额度后，欠下的部分补足之前拒绝新请求
+        limiter.consume(&limits, 1500);
+        let (statuses, rejected) = limiter.acquire(&limits);
+        let (rejected, retry_after) = rejected.unwrap();
+        assert_eq!(rejected.key.kind, LimitKind::Tokens);
+        assert!(retry_after > Duration::from_secs(29));
+        // 被拒绝的请求不扣除 RPM
+        assert_eq!(statuses[0].remaining, 99);
+        assert_eq!(statuses[1].remaining, 0);
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let now = Instant::now();
+        let mut bucket = Bucket::new(60, now);
+        bucket.tokens = 0.0;
+        bucket.refill(60, now + Duration::from_secs(10));
+        assert!((bucket.tokens - 10.0).abs() < 1e-9);
+        bucket.refill(60, now + Duration::from_secs(600));
+        assert_eq!(bucket.tokens, 60.0);
+    }
+
+    #[test]
+    fn test_idle_buckets_are_evicted() {
+        let limiter = RateLimiter::new();
+        let limits = [limit(LimitKind::Requests, 60), limit(LimitKind::Tokens, 1000)];
+        assert!(limiter.acquire(&limits).1.is_none());
+        limiter.consume(&limits, 100_000);
+
+        let mut buckets = limiter.buckets.lock().unwrap();
+        limiter.evict_idle(&mut buckets, Instant::now());
+        assert_eq!(buckets.len(), 2);
+
+        // 已补满的桶被清除；欠下的 token 还没补足的桶保留
+        buckets.values_mut().for_each(|bucket| bucket.pending = 0);
+        limiter.evict_idle(&mut buckets, Instant::now() + BUCKET_IDLE);
+        assert_eq!(buckets.len(), 1);
+        assert!(buckets.keys().all(|key| key.kind == LimitKind::Tokens));
+    }
+
+    #[test]
+    fn test_deployments_with_same_name_have_separate_buckets() {
+        let deployment = |api_base: &str| ModelConfig {
+            model_name: "ratelimit-shared-name".to_string(),
+            litellm_params: crate::config::LitellmParams {
+                model: "openai/gpt-4o".to_string(),
+                api_base: api_base.to_string(),
+                rpm: Some(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (east, west) = (deployment("https://east.example.com"), deployment("https://west.example.com"));
+
+        assert!(check_deployment(&east).is_ok());
+        assert!(check_deployment(&west).is_ok());
+        assert!(matches!(
+            check_deployment(&east),
+            Err(FeatherGateError::RateLimitExceeded(name, LimitKind::Requests, 1))
+                if name == "deployment ratelimit-shared-name"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apply_headers_uses_most_restrictive_limit() {
+        let limits = [
+            limit(LimitKind::Requests, 10),
+            Limit {
+                key: BucketKey {
+                    scope: Scope::Team,
+                    id: "headers-team".to_string(),
+                    kind: LimitKind::Requests,
+                },
+                display_name: "team headers-team".to_string(),
+                limit: 1,
+            },
+        ];
+        let mut headers = HeaderMap::new();
+        scope(async {
+            assert!(enforce(&limits).is_ok());
+            assert!(matches!(
+                enforce(&limits),
+                Err(FeatherGateError::RateLimitExceeded(name, LimitKind::Requests, 1)) if name == "team headers-team"
+            ));
+            apply_headers(&mut headers);
+        })
+        .await;
+
+        assert_eq!(headers["x-ratelimit-limit-requests"], "1");
+        assert_eq!(headers["x-ratelimit-remaining-requests"], "0");
+        assert!(headers.contains_key("x-ratelimit-reset-requests"));
+        assert!(!headers.contains_key("x-ratelimit-limit-tokens"));
+        assert_eq!(headers[RETRY_AFTER], "60");
+    }
+
+    #[tokio::test]
+    async fn test_sync_shares_consumption_across_replicas() {
+        let Some(mut conn) = crate::cluster::test_connection().await else {
+            return;
+        };
+        let replica_a = RateLimiter::new();
+        let replica_b = RateLimiter::new();
+        let limits = [Limit {
+            key: BucketKey {
+                scope: Scope::Team,
+                id: uuid::Uuid::new_v4().to_string(),
+                kind: LimitKind::Requests,
+            },
+            display_name: "team shared".to_string(),
+            limit: 2,
+        }];
+
+        // 两个副本先各同步一次，建立基准
+        assert!(replica_a.acquire(&limits).1.is_none());
+        replica_a.sync(&mut conn).await.unwrap();
+        assert!(replica_b.acquire(&limits).1.is_none());
+        replica_b.sync(&mut conn).await.unwrap();
+
+        // 副本 A 再发一个请求后，副本 B 同步时扣除 A 的消耗，额度用尽
+        assert!(replica_a.acquire(&limits).1.is_none());
+        replica_a.sync(&mut conn).await.unwrap();
+        replica_b.sync(&mut conn).await.unwrap();
+        assert!(replica_b.acquire(&limits).1.is_some());
+    }
+
+    #[test]
+    fn test_format_reset() {
+        assert_eq!(format_reset(Duration::from_millis(120)), "120ms");
+        assert_eq!(format_reset(Duration::from_millis(5200)), "6s");
+        assert_eq!(format_reset(Duration::from_secs(90)), "1m30s");
+    }
+}
@@ -0,0 +1,752 @@
+use super::handlers::{error_response, json_response, not_found, read_json_body, BoxBody, BoxError};
+use crate::auth::keys::{self, KeyFilter, KeyStore, KeyUpdate, NewKey, VirtualKey};
+use crate::auth::teams::{self, NewOrganization, NewTeam, OrganizationUpdate, TeamStore, TeamUpdate};
+use crate::auth::{self, Caller};
+use crate::config::Config;
+use crate::error::FeatherGateError;
+use crate::providers::api_keys::{self, KeyPool};
+use crate::spend::{self, EntityType, SpendTracker};
+use crate::types::keys::{DeleteKeyRequest, GenerateKeyRequest, ListKeysQuery, UpdateKeyRequest};
+use crate::types::teams::{
+    DeleteOrganizationRequest, DeleteTeamRequest, NewOrganizationRequest, NewTeamRequest, TeamMemberAddRequest,
+    TeamMemberDeleteRequest, UpdateOrganizationRequest, UpdateTeamRequest,
+};
+use crate::types::ValidationError;
+use hyper::header::HeaderMap;
+use hyper::{Method, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// /key/list 每页最多返回的数量
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// key 信息加上请求中的 key（生成和轮换时为新的明文 key，只返回这一次）
+#[derive(Serialize)]
+struct KeyResponse {
+    key: String,
+    #[serde(flatten)]
+    info: VirtualKey,
+}
+
+#[derive(Deserialize)]
+struct KeyInfoQuery {
+    key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UserInfoQuery {
+    user_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TeamInfoQuery {
+    team_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TeamListQuery {
+    organization_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OrganizationInfoQuery {
+    organization_id: Option<String>,
+}
+
+/// 管理 API 的路径前缀
+const ADMIN_PREFIXES: &[&str] = &["/key/", "/user/", "/team/", "/organization/", "/model/keys"];
+
+/// 是否为管理 API 路径
+pub(super) fn is_admin_path(path: &str) -> bool {
+    ADMIN_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+/// 管理 API（虚拟 key、团队和组织管理，以及花费查询），只允许 master key 调用
+///
+/// 请求和响应格式与 litellm 的管理接口兼容；所有修改都会写入审计日志。
+pub(super) async fn admin_api(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let locale = config.general_settings.api_locale;
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    if !matches!(auth::current_caller(), Caller::Master) {
+        warn!("拒绝非 master key 调用管理 API: {} {}", method, path);
+        let err = FeatherGateError::RouteNotAllowed(path);
+        return Ok(error_response(&err, locale));
+    }
+    let changed_by = changed_by(req.headers());
+
+    let result = match (&method, path.as_str()) {
+        (&Method::POST, "/key/generate") => generate_key(req, &changed_by).await,
+        (&Method::POST, "/key/update") => update_key(req, &changed_by).await,
+        (&Method::POST, "/key/delete") => delete_keys(req, &changed_by).await,
+        (&Method::POST, "/key/regenerate") => regenerate_key(req, None, &changed_by).await,
+        (&Method::POST, path) if path.ends_with("/regenerate") => {
+            // litellm 的 /key/{key}/regenerate 形式
+            let key = path
+                .trim_start_matches("/key/")
+                .trim_end_matches("/regenerate")
+                .to_string();
+            regenerate_key(req, Some(key), &changed_by).await
+        }
+        (&Method::GET, "/key/info") => {
+            key_info(keys::key_store(), spend::tracker(), req.uri().query())
+        }
+        (&Method::GET, "/key/list") => list_keys(keys::key_store(), req.uri().query()),
+        (&Method::GET, "/user/info") => user_info(&config, spend::tracker(), req.uri().query()),
+        (&Method::POST, "/team/new") => new_team(req, &changed_by).await,
+        (&Method::POST, "/team/update") => update_team(req, &changed_by).await,
+        (&Method::POST, "/team/delete") => delete_teams(req, &changed_by).await,
+        (&Method::POST, "/team/member_add") => add_team_members(req, &changed_by).await,
+        (&Method::POST, "/team/member_delete") => remove_team_member(req, &changed_by).await,
+        (&Method::GET, "/team/info") => team_info(
+            &config,
+            teams::team_store(),
+            keys::key_store(),
+            spend::tracker(),
+            req.uri().query(),
+        ),
+        (&Method::GET, "/team/list") => list_teams(teams::team_store(), spend::tracker(), req.uri().query()),
+        (&Method::POST, "/organization/new") => new_organization(req, &changed_by).await,
+        (&Method::POST, "/organization/update") => update_organization(req, &changed_by).await,
+        (&Method::POST, "/organization/delete") => delete_organizations(req, &changed_by).await,
+        (&Method::GET, "/organization/info") => {
+            organization_info(teams::team_store(), spend::tracker(), req.uri().query())
+        }
+        (&Method::GET, "/organization/list") => list_organizations(teams::team_store(), spend::tracker()),
+        (&Method::GET, "/model/keys") => model_keys(&config, api_keys::key_pool()),
+        _ => return Ok(not_found()),
+    };
+
+    match result {
+        Ok(body) => Ok(json_response(StatusCode::OK, &body)),
+        Err(e) => {
+            warn!("管理 API {} {} 失败: {}", method, path, e);
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 审计日志中的操作人：优先使用 litellm 的 `litellm-changed-by` 请求头
+fn changed_by(headers: &HeaderMap) -> String {
+    headers
+        .get("litellm-changed-by")
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .unwrap_or("master_key")
+        .to_string()
+}
+
+async fn generate_key(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let generate_req: GenerateKeyRequest = read_json_body(req).await?;
+    generate(keys::key_store(), generate_req, changed_by)
+}
+
+fn generate(
+    store: &KeyStore,
+    req: GenerateKeyRequest,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+    let expires = req.expires();
+    let (key, info) = store.generate(
+        NewKey {
+            key_alias: req.key_alias,
+            models: req.models,
+            expires,
+            metadata: req.metadata,
+            blocked: req.blocked,
+            user_id: req.user_id,
+            team_id: req.team_id,
+            max_budget: req.max_budget,
+            budget_duration: req.budget_duration,
+            rpm_limit: req.rpm_limit,
+            tpm_limit: req.tpm_limit,
+        },
+        changed_by,
+    )?;
+    info!("生成虚拟 key {}（操作人: {}）", info.key_name, changed_by);
+    Ok(serde_json::to_value(KeyResponse { key, info })?)
+}
+
+async fn update_key(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let update_req: UpdateKeyRequest = read_json_body(req).await?;
+    update(keys::key_store(), update_req, changed_by)
+}
+
+fn update(
+    store: &KeyStore,
+    req: UpdateKeyRequest,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+    let token = keys::resolve_token(&req.key);
+    let info = store.update(&token, key_update(&req), changed_by)?;
+    info!("修改虚拟 key {}（操作人: {}）", info.key_name, changed_by);
+    Ok(serde_json::to_value(KeyResponse { key: req.key, info })?)
+}
+
+async fn delete_keys(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let delete_req: DeleteKeyRequest = read_json_body(req).await?;
+    delete(keys::key_store(), spend::tracker(), delete_req, changed_by)
+}
+
+fn delete(
+    store: &KeyStore,
+    tracker: &SpendTracker,
+    req: DeleteKeyRequest,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+
+    let mut tokens: Vec<String> = req.keys.iter().map(|key| keys::resolve_token(key)).collect();
+    for alias in &req.key_aliases {
+        let key = store
+            .get_by_alias(alias)?
+            .ok_or_else(|| FeatherGateError::KeyNotFound(alias.clone()))?;
+        tokens.push(key.token);
+    }
+    // 同一个 key 可能同时以明文、token 和别名给出
+    tokens.sort_unstable();
+    tokens.dedup();
+
+    let deleted = store.delete(&tokens, tracker, changed_by)?;
+    for key in &deleted {
+        info!("删除虚拟 key {}（操作人: {}）", key.key_name, changed_by);
+    }
+    // 只返回调用方给出的 key 和别名，与 /key/list 一样不暴露 token
+    Ok(json!({ "deleted_keys": req.keys, "deleted_key_aliases": req.key_aliases }))
+}
+
+async fn regenerate_key(
+    req: Request<hyper::body::Incoming>,
+    path_key: Option<String>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    // /key/{key}/regenerate 的请求体可以为空
+    let mut regenerate_req: UpdateKeyRequest = match path_key {
+        Some(_) => read_json_body(req).await.unwrap_or_default(),
+        None => read_json_body(req).await?,
+    };
+    if let Some(key) = path_key {
+        regenerate_req.key = key;
+    }
+    regenerate(keys::key_store(), spend::tracker(), regenerate_req, changed_by)
+}
+
+fn regenerate(
+    store: &KeyStore,
+    tracker: &SpendTracker,
+    req: UpdateKeyRequest,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+    let token = keys::resolve_token(&req.key);
+    let (key, info) = store.regenerate(&token, key_update(&req), tracker, changed_by)?;
+    info!("轮换虚拟 key {}（操作人: {}）", info.key_name, changed_by);
+    Ok(serde_json::to_value(KeyResponse { key, info })?)
+}
+
+fn key_info(
+    store: &KeyStore,
+    tracker: &SpendTracker,
+    query: Option<&str>,
+) -> crate::Result<serde_json::Value> {
+    let query: KeyInfoQuery = parse_query(query)?;
+    let key = query.key.ok_or(ValidationError::MissingField("key"))?;
+    let info = store
+        .get(&keys::resolve_token(&key))?
+        .ok_or_else(|| FeatherGateError::KeyNotFound(key.clone()))?;
+
+    let summary = tracker.summary(EntityType::Key, &info.token)?;
+    let mut info = serde_json::to_value(info)?;
+    info["spend"] = json!(summary.spend);
+    info["budget_reset_at"] = json!(summary.budget_reset_at);
+    Ok(json!({ "key": key, "info": info }))
+}
+
+/// 用户的花费和预算
+fn user_info(
+    config: &Config,
+    tracker: &SpendTracker,
+    query: Option<&str>,
+) -> crate::Result<serde_json::Value> {
+    let query: UserInfoQuery = parse_query(query)?;
+    let user_id = query.user_id.ok_or(ValidationError::MissingField("user_id"))?;
+    let budget = config.general_settings.user_budgets.get(&user_id).cloned().unwrap_or_default();
+    let limits = config.general_settings.user_rate_limits.get(&user_id).cloned().unwrap_or_default();
+    let summary = tracker.summary(EntityType::User, &user_id)?;
+    Ok(json!({
+        "user_id": user_id,
+        "spend": summary.spend,
+        "max_budget": budget.max_budget,
+        "budget_duration": budget.budget_duration,
+        "budget_reset_at": summary.budget_reset_at,
+        "rpm_limit": limits.rpm_limit,
+        "tpm_limit": limits.tpm_limit,
+    }))
+}
+
+/// 团队的花费、预算和限额（团队记录中的设置优先于配置文件），以及团队记录和 key
+fn team_info(
+    config: &Config,
+    teams: &TeamStore,
+    keys: &KeyStore,
+    tracker: &SpendTracker,
+    query: Option<&str>,
+) -> crate::Result<serde_json::Value> {
+    let query: TeamInfoQuery = parse_query(query)?;
+    let team_id = query.team_id.ok_or(ValidationError::MissingField("team_id"))?;
+    let mut budget = config.general_settings.team_budgets.get(&team_id).cloned().unwrap_or_default();
+    let mut limits = config.general_settings.team_rate_limits.get(&team_id).cloned().unwrap_or_default();
+    let team = teams.get_team(&team_id)?;
+    if let Some(team) = &team {
+        budget = team.settings.budget_or(budget);
+        limits = team.settings.rate_limits_or(limits);
+    }
+    let filter = KeyFilter {
+        team_id: Some(team_id.clone()),
+        ..Default::default()
+    };
+    let (team_keys, _) = keys.list(&filter, u32::MAX, 0)?;
+    let summary = tracker.summary(EntityType::Team, &team_id)?;
+    Ok(json!({
+        "team_id": team_id,
+        "spend": summary.spend,
+        "max_budget": budget.max_budget,
+        "budget_duration": budget.budget_duration,
+        "budget_reset_at": summary.budget_reset_at,
+        "rpm_limit": limits.rpm_limit,
+        "tpm_limit": limits.tpm_limit,
+        "team_info": team,
+        "keys": team_keys,
+    }))
+}
+
+async fn new_team(req: Request<hyper::body::Incoming>, changed_by: &str) -> crate::Result<serde_json::Value> {
+    let new_req: NewTeamRequest = read_json_body(req).await?;
+    create_team(teams::team_store(), new_req, changed_by)
+}
+
+fn create_team(store: &TeamStore, req: NewTeamRequest, changed_by: &str) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+    let team = store.create_team(
+        NewTeam {
+            team_id: req.team_id,
+            team_alias: req.team_alias,
+            organization_id: req.organization_id,
+            settings: req.settings.into(),
+            members: req.members_with_roles,
+        },
+        changed_by,
+    )?;
+    info!("创建团队 {}（操作人: {}）", team.team_id, changed_by);
+    Ok(serde_json::to_value(team)?)
+}
+
+async fn update_team(req: Request<hyper::body::Incoming>, changed_by: &str) -> crate::Result<serde_json::Value> {
+    let update_req: UpdateTeamRequest = read_json_body(req).await?;
+    modify_team(teams::team_store(), update_req, changed_by)
+}
+
+fn modify_team(store: &TeamStore, req: UpdateTeamRequest, changed_by: &str) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+    let update = TeamUpdate {
+        team_alias: req.team_alias,
+        organization_id: req.organization_id,
+        settings: req.settings.into(),
+    };
+    let team = store.update_team(&req.team_id, update, changed_by)?;
+    info!("修改团队 {}（操作人: {}）", team.team_id, changed_by);
+    Ok(serde_json::to_value(team)?)
+}
+
+async fn delete_teams(req: Request<hyper::body::Incoming>, changed_by: &str) -> crate::Result<serde_json::Value> {
+    let delete_req: DeleteTeamRequest = read_json_body(req).await?;
+    remove_teams(teams::team_store(), delete_req, changed_by)
+}
+
+fn remove_teams(store: &TeamStore, req: DeleteTeamRequest, changed_by: &str) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+    let deleted = store.delete_teams(&req.team_ids, changed_by)?;
+    for team in &deleted {
+        info!("删除团队 {}（操作人: {}）", team.team_id, changed_by);
+    }
+    Ok(json!({ "deleted_teams": req.team_ids }))
+}
+
+async fn add_team_members(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let add_req: TeamMemberAddRequest = read_json_body(req).await?;
+    add_req.validate()?;
+    let team = teams::team_store().add_members(&add_req.team_id, add_req.member.into_vec(), changed_by)?;
+    info!("修改团队 {} 的成员（操作人: {}）", team.team_id, changed_by);
+    Ok(serde_json::to_value(team)?)
+}
+
+async fn remove_team_member(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let delete_req: TeamMemberDeleteRequest = read_json_body(req).await?;
+    delete_req.validate()?;
+    let user_ids: Vec<String> = delete_req.user_id.into_iter().collect();
+    let team = teams::team_store().remove_members(&delete_req.team_id, &user_ids, changed_by)?;
+    info!("修改团队 {} 的成员（操作人: {}）", team.team_id, changed_by);
+    Ok(serde_json::to_value(team)?)
+}
+
+/// 团队列表，附带各团队的花费
+fn list_teams(store: &TeamStore, tracker: &SpendTracker, query: Option<&str>) -> crate::Result<serde_json::Value> {
+    let query: TeamListQuery = parse_query(query)?;
+    let teams = store.list_teams(query.organization_id.as_deref())?;
+    let mut result = Vec::with_capacity(teams.len());
+    for team in teams {
+        let spend = tracker.summary(EntityType::Team, &team.team_id)?.spend;
+        let mut team = serde_json::to_value(team)?;
+        team["spend"] = json!(spend);
+        result.push(team);
+    }
+    Ok(json!(result))
+}
+
+async fn new_organization(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let new_req: NewOrganizationRequest = read_json_body(req).await?;
+    create_organization(teams::team_store(), new_req, changed_by)
+}
+
+fn create_organization(
+    store: &TeamStore,
+    req: NewOrganizationRequest,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    req.validate()?;
+    let org = store.create_organization(
+        NewOrganization {
+            organization_id: req.organization_id,
+            organization_alias: req.organization_alias,
+            settings: req.settings.into(),
+        },
+        changed_by,
+    )?;
+    info!("创建组织 {}（操作人: {}）", org.organization_id, changed_by);
+    Ok(serde_json::to_value(org)?)
+}
+
+async fn update_organization(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let update_req: UpdateOrganizationRequest = read_json_body(req).await?;
+    update_req.validate()?;
+    let update = OrganizationUpdate {
+        organization_alias: update_req.organization_alias,
+        settings: update_req.settings.into(),
+    };
+    let org = teams::team_store().update_organization(&update_req.organization_id, update, changed_by)?;
+    info!("修改组织 {}（操作人: {}）", org.organization_id, changed_by);
+    Ok(serde_json::to_value(org)?)
+}
+
+async fn delete_organizations(
+    req: Request<hyper::body::Incoming>,
+    changed_by: &str,
+) -> crate::Result<serde_json::Value> {
+    let delete_req: DeleteOrganizationRequest = read_json_body(req).await?;
+    delete_req.validate()?;
+    let deleted = teams::team_store().delete_organizations(&delete_req.organization_ids, changed_by)?;
+    for org in &deleted {
+        info!("删除组织 {}（操作人: {}）", org.organization_id, changed_by);
+    }
+    Ok(json!({ "deleted_organizations": delete_req.organization_ids }))
+}
+
+/// 组织的花费和设置，以及下属团队
+fn organization_info(
+    store: &TeamStore,
+    tracker: &SpendTracker,
+    query: Option<&str>,
+) -> crate::Result<serde_json::Value> {
+    let query: OrganizationInfoQuery = parse_query(query)?;
+    let organization_id = query
+        .organization_id
+        .ok_or(ValidationError::MissingField("organization_id"))?;
+    let org = store
+        .get_organization(&organization_id)?
+        .ok_or_else(|| FeatherGateError::OrganizationNotFound(organization_id.clone()))?;
+    let summary = tracker.summary(EntityType::Organization, &organization_id)?;
+    let mut info = serde_json::to_value(org)?;
+    info["spend"] = json!(summary.spend);
+    info["budget_reset_at"] = json!(summary.budget_reset_at);
+    info["teams"] = json!(store.list_teams(Some(&organization_id))?);
+    Ok(info)
+}
+
+/// 组织列表，附带各组织的花费
+fn list_organizations(store: &TeamStore, tracker: &SpendTracker) -> crate::Result<serde_json::Value> {
+    let mut result = Vec::new();
+    for org in store.list_organizations()? {
+        let spend = tracker.summary(EntityType::Organization, &org.organization_id)?.spend;
+        let mut org = serde_json::to_value(org)?;
+        org["spend"] = json!(spend);
+        result.push(org);
+    }
+    Ok(json!(result))
+}
+
+/// 各部署的上游 key 池状态（key 已脱敏）
+fn model_keys(config: &Config, pool: &KeyPool) -> crate::Result<serde_json::Value> {
+    Ok(json!({ "data": pool.status(config) }))
+}
+
+fn list_keys(store: &KeyStore, query: Option<&str>) -> crate::Result<serde_json::Value> {
+    let query: ListKeysQuery = parse_query(query)?;
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, MAX_PAGE_SIZE);
+    let filter = KeyFilter {
+        key_alias: query.key_alias,
+        user_id: query.user_id,
+        team_id: query.team_id,
+    };
+
+    let (keys, total) = store.list(&filter, size, (page - 1) * size)?;
+    let keys: Vec<serde_json::Value> = match query.return_full_object {
+        true => keys.iter().map(|key| json!(key)).collect(),
+        false => keys.into_iter().map(|key| json!(key.token)).collect(),
+    };
+    Ok(json!({
+        "keys": keys,
+        "total_count": total,
+        "current_page": page,
+        "total_pages": total.div_ceil(u64::from(size)),
+    }))
+}
+
+fn key_update(req: &UpdateKeyRequest) -> KeyUpdate {
+    KeyUpdate {
+        key_alias: req.key_alias.clone(),
+        models: req.models.clone(),
+        expires: req.expires(),
+        metadata: req.metadata.clone(),
+        blocked: req.blocked,
+        user_id: req.user_id.clone(),
+        team_id: req.team_id.clone(),
+        max_budget: req.max_budget,
+        budget_duration: req.budget_duration.clone(),
+        rpm_limit: req.rpm_limit,
+        tpm_limit: req.tpm_limit,
+    }
+}
+
+/// 解析查询字符串
+fn parse_query<T: serde::de::DeserializeOwned>(query: Option<&str>) -> crate::Result<T> {
+    serde_urlencoded::from_str(query.unwrap_or_default())
+        .map_err(|e| FeatherGateError::invalid_request(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate_request(body: serde_json::Value) -> GenerateKeyRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_key_lifecycle() {
+        let store = keys::test_store();
+        let generated = generate(
+            &store,
+            generate_request(json!({
+                "key_alias": "ci",
+                "models": ["gpt-4o"],
+                "duration": "30d",
+                "metadata": {"owner": "ci"},
+                "user_id": "alice"
+            })),
+            "master_key",
+        )
+        .unwrap();
+        let api_key = generated["key"].as_str().unwrap().to_string();
+        assert!(api_key.starts_with(keys::KEY_PREFIX));
+        assert_eq!(generated["models"], json!(["gpt-4o"]));
+        assert_eq!(generated["user_id"], "alice");
+        assert!(generated["expires"].is_string());
+
+        // 明文 key 和 token 都可以用来指定 key
+        let token = generated["token"].as_str().unwrap().to_string();
+        let info = key_info(&store, spend::tracker(), Some(&format!("key={}", token))).unwrap();
+        assert_eq!(info["info"]["key_alias"], "ci");
+
+        let update_req: UpdateKeyRequest =
+            serde_json::from_value(json!({"key": api_key, "models": ["gpt-4o", "claude-*"]})).unwrap();
+        let updated = update(&store, update_req, "alice").unwrap();
+        assert_eq!(updated["models"], json!(["gpt-4o", "claude-*"]));
+        assert_eq!(updated["metadata"]["owner"], "ci");
+
+        let rotate_req = UpdateKeyRequest {
+            key: api_key.clone(),
+            ..Default::default()
+        };
+        let rotated = regenerate(&store, spend::tracker(), rotate_req, "alice").unwrap();
+        assert_ne!(rotated["key"], api_key.as_str());
+        assert!(matches!(
+            key_info(&store, spend::tracker(), Some(&format!("key={}", api_key))),
+            Err(FeatherGateError::KeyNotFound(_))
+        ));
+
+        let delete_req = DeleteKeyRequest {
+            key_aliases: vec!["ci".to_string()],
+            ..Default::default()
+        };
+        let deleted = delete(&store, spend::tracker(), delete_req, "alice").unwrap();
+        assert_eq!(deleted, json!({"deleted_keys": [], "deleted_key_aliases": ["ci"]}));
+        assert!(store.get_by_alias("ci").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_delete_same_key_given_twice() {
+        let store = keys::test_store();
+        let first = generate(&store, generate_request(json!({"key_alias": "a"})), "master_key").unwrap();
+        let second = generate(&store, generate_request(json!({})), "master_key").unwrap();
+
+        // 第一个 key 以明文和别名各给出一次，中间夹着另一个 key
+        let delete_req = DeleteKeyRequest {
+            keys: vec![
+                first["key"].as_str().unwrap().to_string(),
+                second["token"].as_str().unwrap().to_string(),
+            ],
+            key_aliases: vec!["a".to_string()],
+        };
+        let deleted = delete(&store, spend::tracker(), delete_req.clone(), "alice").unwrap();
+        assert_eq!(deleted["deleted_keys"], json!(delete_req.keys));
+        assert_eq!(deleted["deleted_key_aliases"], json!(["a"]));
+        assert!(store.get(first["token"].as_str().unwrap()).unwrap().is_none());
+        assert!(store.get(second["token"].as_str().unwrap()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_keys_pagination() {
+        let store = keys::test_store();
+        for team in ["search", "search", "ads"] {
+            generate(&store, generate_request(json!({"team_id": team})), "master_key").unwrap();
+        }
+
+        let page = list_keys(&store, Some("team_id=search&size=1&page=2")).unwrap();
+        assert_eq!(page["total_count"], 2);
+        assert_eq!(page["total_pages"], 2);
+        assert_eq!(page["current_page"], 2);
+        assert!(page["keys"][0].is_string());
+
+        let page = list_keys(&store, Some("return_full_object=true")).unwrap();
+        assert_eq!(page["total_count"], 3);
+        assert!(page["keys"][0]["key_name"].is_string());
+
+        assert!(matches!(
+            key_info(&store, spend::tracker(), None),
+            Err(FeatherGateError::Validation(ValidationError::MissingField("key")))
+        ));
+    }
+
+    #[test]
+    fn test_team_and_organization_lifecycle() {
+        // 团队和 key 需要在同一个数据库中
+        let db = Box::leak(Box::new(crate::db::Database::open_in_memory().unwrap()));
+        let teams = TeamStore::new(db);
+        let keys = KeyStore::new(db);
+        let org_req: NewOrganizationRequest =
+            serde_json::from_value(json!({"organization_id": "acme", "models": ["gpt-*"]})).unwrap();
+        create_organization(&teams, org_req, "master_key").unwrap();
+
+        let team_req: NewTeamRequest = serde_json::from_value(json!({
+            "team_id": "search",
+            "organization_id": "acme",
+            "max_budget": 5,
+            "members_with_roles": [{"user_id": "alice", "role": "admin"}]
+        }))
+        .unwrap();
+        let team = create_team(&teams, team_req, "master_key").unwrap();
+        assert_eq!(team["members"], json!([{"user_id": "alice", "role": "admin"}]));
+        assert_eq!(team["max_budget"], 5.0);
+
+        let update_req: UpdateTeamRequest =
+            serde_json::from_value(json!({"team_id": "search", "rpm_limit": 10})).unwrap();
+        modify_team(&teams, update_req, "alice").unwrap();
+        // 已登记团队的 key 只能发给团队成员
+        let err = generate(&keys, generate_request(json!({"team_id": "search", "user_id": "bob"})), "master_key")
+            .unwrap_err();
+        assert!(matches!(err, FeatherGateError::NotTeamMember(..)));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(matches!(
+            generate(&keys, generate_request(json!({"team_id": "search"})), "master_key"),
+            Err(FeatherGateError::Validation(ValidationError::MissingField("user_id")))
+        ));
+        let key = generate(&keys, generate_request(json!({"team_id": "search", "user_id": "alice"})), "master_key")
+            .unwrap();
+        let move_req = UpdateKeyRequest {
+            key: key["key"].as_str().unwrap().to_string(),
+            user_id: Some("bob".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(update(&keys, move_req, "master_key"), Err(FeatherGateError::NotTeamMember(..))));
+
+        // 团队记录中的设置优先于配置文件
+        let mut config = Config::default();
+        config.general_settings.team_budgets.insert(
+            "search".to_string(),
+            crate::config::BudgetConfig {
+                max_budget: Some(1.0),
+                budget_duration: Some("30d".to_string()),
+            },
+        );
+        let info = team_info(&config, &teams, &keys, spend::tracker(), Some("team_id=search")).unwrap();
+        assert_eq!(info["max_budget"], 5.0);
+        assert_eq!(info["budget_duration"], "30d");
+        assert_eq!(info["rpm_limit"], 10);
+        assert_eq!(info["team_info"]["organization_id"], "acme");
+        assert_eq!(info["keys"].as_array().unwrap().len(), 1);
+
+        let org = organization_info(&teams, spend::tracker(), Some("organization_id=acme")).unwrap();
+        assert_eq!(org["teams"][0]["team_id"], "search");
+        assert_eq!(list_teams(&teams, spend::tracker(), Some("organization_id=other")).unwrap(), json!([]));
+
+        // 还有 key 的团队不能删除
+        let delete_req = DeleteTeamRequest {
+            team_ids: vec!["search".to_string()],
+        };
+        let err = remove_teams(&teams, delete_req, "alice").unwrap_err();
+        assert!(matches!(err, FeatherGateError::TeamInUse(_, 1)));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(matches!(
+            organization_info(&teams, spend::tracker(), Some("organization_id=missing")),
+            Err(FeatherGateError::OrganizationNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_changed_by_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(changed_by(&headers), "master_key");
+        headers.insert("litellm-changed-by", "alice".parse().unwrap());
+        assert_eq!(changed_by(&headers), "alice");
+    }
+}
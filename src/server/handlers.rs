@@ -1,27 +1,90 @@
-use crate::config::Config;
+use super::{admin, multipart, streaming};
+use crate::auth;
+use crate::config::{Config, ModelConfig};
+use crate::error::FeatherGateError;
+use crate::i18n::Locale;
 use crate::metrics;
-use crate::providers::routing;
+use crate::providers::gemini::GenerateContentRequest;
+use crate::providers::passthrough::{self, PassthroughRequest};
+use crate::providers::{routing, RawResponse};
+use crate::ratelimit;
+use crate::spend;
+use crate::types::audio::SpeechRequest;
+use crate::types::completions::CompletionRequest;
+use crate::types::embeddings::EmbeddingRequest;
+use crate::types::images::ImageGenerationRequest;
+use crate::types::messages::MessagesRequest;
+use crate::types::moderations::ModerationRequest;
+use crate::types::rerank::RerankRequest;
+use crate::types::responses::ResponsesRequest;
 use crate::types::ChatRequest;
+use futures_util::{Stream, StreamExt};
 use http_body_util::{BodyExt, Full, StreamBody};
 use hyper::body::{Bytes, Frame};
 use hyper::{Method, Request, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::json;
+use std::pin::Pin;
 use std::sync::Arc;
+use tracing::{info, warn};
 
 // 统一的 Body 类型，可以处理普通响应和流式响应
-type BoxError = Box<dyn std::error::Error + Send + Sync>;
-type BoxBody = http_body_util::combinators::BoxBody<Bytes, BoxError>;
+pub(super) type BoxError = Box<dyn std::error::Error + Send + Sync>;
+pub(super) type BoxBody = http_body_util::combinators::BoxBody<Bytes, BoxError>;
 
 /// 处理 HTTP 请求的主路由
 pub async fn handle_request(
     req: Request<hyper::body::Incoming>,
     config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let caller = match auth::authenticate(&config, req.uri().path(), req.headers()).await {
+        Ok(caller) => caller,
+        Err(e) => {
+            metrics::global_metrics().record_auth_failure();
+            warn!("认证失败 {} {}: {}", req.method(), req.uri().path(), e);
+            return Ok(auth_error_response(req.uri().path(), &e, config.general_settings.api_locale));
+        }
+    };
+
+    let response = async move {
+        let mut response = route(req, config).await?;
+        ratelimit::apply_headers(response.headers_mut());
+        Ok(response)
+    };
+    auth::with_caller(caller, ratelimit::scope(response)).await
+}
+
+/// 按方法和路径分发到各端点
+async fn route(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
 ) -> Result<Response<BoxBody>, BoxError> {
     match (req.method(), req.uri().path()) {
         (&Method::GET, "/health") => Ok(health_check()),
         (&Method::GET, "/v1/models") => Ok(list_models(config)),
+        (&Method::GET, "/model/info" | "/v1/model/info") => Ok(model_info(config)),
         (&Method::GET, "/metrics") => Ok(metrics_endpoint()),
         (&Method::POST, "/v1/chat/completions") => chat_completions(req, config).await,
+        (&Method::POST, "/v1/completions") => completions(req, config).await,
+        (&Method::POST, "/v1/responses") => responses(req, config).await,
+        (&Method::POST, "/v1/messages") => messages(req, config).await,
+        (&Method::POST, "/v1/embeddings") => embeddings(req, config).await,
+        (&Method::POST, "/v1/rerank") => rerank(req, config).await,
+        (&Method::POST, "/v1/images/generations") => image_generations(req, config).await,
+        (&Method::POST, "/v1/audio/transcriptions") => audio_transcriptions(req, config).await,
+        (&Method::POST, "/v1/audio/speech") => audio_speech(req, config).await,
+        (&Method::POST, "/v1/moderations") => moderations(req, config).await,
+        (&Method::GET, path) if path.starts_with("/v1/models/") => {
+            Ok(retrieve_model(&path["/v1/models/".len()..], config))
+        }
+        (&Method::POST, path) if path.starts_with("/v1beta/models/") => {
+            generate_content(req, config).await
+        }
+        (_, path) if admin::is_admin_path(path) => admin::admin_api(req, config).await,
+        (_, path) if passthrough::split_path(path).is_some() => {
+            provider_passthrough(req, config).await
+        }
         _ => Ok(not_found()),
     }
 }
@@ -44,34 +107,68 @@ fn health_check() -> Response<BoxBody> {
         .unwrap()
 }
 
-/// 列出可用模型
-fn list_models(config: Arc<Config>) -> Response<BoxBody> {
-    let models: Vec<_> = config
+/// OpenAI 格式的模型对象，附带 model_info 元数据
+fn model_object(model: &ModelConfig) -> serde_json::Value {
+    json!({
+        "id": model.model_name,
+        "object": "model",
+        "created": 0,
+        "owned_by": "feathergate",
+        "model_info": model.resolved_model_info()
+    })
+}
+
+/// 当前调用方可以访问的模型（同时受所属团队和组织的 models 限制）
+fn visible_models(config: &Config) -> impl Iterator<Item = &ModelConfig> {
+    config
         .model_list
         .iter()
-        .map(|m| {
-            json!({
-                "id": m.model_name,
-                "object": "model",
-                "owned_by": "feathergate"
-            })
-        })
-        .collect();
+        .filter(move |m| auth::check_model_access(config, &m.model_name).is_ok())
+}
+
+/// 列出可用模型（虚拟 key 只能看到允许访问的模型）
+fn list_models(config: Arc<Config>) -> Response<BoxBody> {
+    let models: Vec<_> = visible_models(&config).map(model_object).collect();
 
     let body = json!({
         "object": "list",
         "data": models
     });
 
-    Response::builder()
-        .status(StatusCode::OK)
-        .header("Content-Type", "application/json")
-        .body(
-            Full::new(Bytes::from(body.to_string()))
-                .map_err(|e| Box::new(e) as BoxError)
-                .boxed(),
-        )
-        .unwrap()
+    json_response(StatusCode::OK, &body)
+}
+
+/// 查询单个模型（GET /v1/models/{id}）
+fn retrieve_model(model_name: &str, config: Arc<Config>) -> Response<BoxBody> {
+    let model = config
+        .find_model(model_name)
+        .filter(|m| auth::check_model_access(&config, &m.model_name).is_ok());
+    match model {
+        Some(model) => json_response(StatusCode::OK, &model_object(model)),
+        None => error_response(
+            &FeatherGateError::ModelNotFound(model_name.to_string()),
+            config.general_settings.api_locale,
+        ),
+    }
+}
+
+/// litellm 兼容的模型详情（/model/info），不包含 api_key
+fn model_info(config: Arc<Config>) -> Response<BoxBody> {
+    let models: Vec<_> = visible_models(&config)
+        .map(|m| {
+            let mut litellm_params = json!({ "model": m.litellm_params.model });
+            if !m.litellm_params.api_base.is_empty() {
+                litellm_params["api_base"] = json!(m.litellm_params.api_base);
+            }
+            json!({
+                "model_name": m.model_name,
+                "litellm_params": litellm_params,
+                "model_info": m.resolved_model_info()
+            })
+        })
+        .collect();
+
+    json_response(StatusCode::OK, &json!({ "data": models }))
 }
 
 /// 指标端点
@@ -96,28 +193,17 @@ async fn chat_completions(
     config: Arc<Config>,
 ) -> Result<Response<BoxBody>, BoxError> {
     let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
 
-    // 读取请求体
-    let whole_body = req.collect().await?.to_bytes();
-    let chat_req: ChatRequest = serde_json::from_slice(&whole_body)?;
+    // 读取并解析请求体
+    let chat_req: ChatRequest = match read_json_body(req).await {
+        Ok(chat_req) => chat_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
 
     // 验证请求参数
     if let Err(e) = chat_req.validate() {
-        let error_body = json!({
-            "error": {
-                "message": e,
-                "type": "invalid_request_error"
-            }
-        });
-        return Ok(Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .header("Content-Type", "application/json")
-            .body(
-                Full::new(Bytes::from(error_body.to_string()))
-                    .map_err(|e| Box::new(e) as BoxError)
-                    .boxed(),
-            )
-            .unwrap());
+        return Ok(error_response(&e.into(), locale));
     }
 
     // 检查是否为流式请求
@@ -142,31 +228,7 @@ async fn chat_completions(
         }
         Err(e) => {
             metrics.record_failure();
-            let error_body = json!({
-                "error": {
-                    "message": e.to_string(),
-                    "type": "feathergate_error"
-                }
-            });
-
-            let status = match e {
-                crate::FeatherGateError::ModelNotFound(_) => StatusCode::NOT_FOUND,
-                crate::FeatherGateError::UnsupportedProvider(_) => StatusCode::BAD_REQUEST,
-                crate::FeatherGateError::UpstreamError { status, .. } => {
-                    StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
-                }
-                _ => StatusCode::INTERNAL_SERVER_ERROR,
-            };
-
-            Ok(Response::builder()
-                .status(status)
-                .header("Content-Type", "application/json")
-                .body(
-                    Full::new(Bytes::from(error_body.to_string()))
-                        .map_err(|e| Box::new(e) as BoxError)
-                        .boxed(),
-                )
-                .unwrap())
+            Ok(error_response(&e, locale))
         }
     }
 }
@@ -177,59 +239,582 @@ async fn chat_completions_stream(
     config: Arc<Config>,
 ) -> Result<Response<BoxBody>, BoxError> {
     let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
 
-    // 路由流式请求
+    // 路由流式请求：开始推流前的错误按非流式路径返回正确的状态码
     match routing::route_request_stream(config, chat_req).await {
         Ok(stream) => {
             metrics.record_success();
+            Ok(sse_response(stream, locale))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 旧版文本补全端点
+async fn completions(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
 
-            // 将字节流转换为 Frame 流
-            use futures_util::StreamExt;
-            let frame_stream = stream.map(|result| {
-                result.map(Frame::data).map_err(|e| Box::new(e) as BoxError)
-            });
+    let completion_req: CompletionRequest = match read_json_body(req).await {
+        Ok(completion_req) => completion_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
 
-            // 创建 StreamBody 并转换为 BoxBody
-            let body = StreamBody::new(frame_stream);
-            let boxed_body = BodyExt::boxed(body);
+    if let Err(e) = completion_req.validate() {
+        return Ok(error_response(&e.into(), locale));
+    }
 
-            Ok(Response::builder()
-                .status(StatusCode::OK)
-                .header("Content-Type", "text/event-stream")
-                .header("Cache-Control", "no-cache")
-                .header("Connection", "keep-alive")
-                .header("X-Accel-Buffering", "no") // 禁用 Nginx 缓冲
-                .body(boxed_body)
-                .unwrap())
+    if completion_req.stream == Some(true) {
+        return match routing::route_completion_stream(config, completion_req).await {
+            Ok(stream) => {
+                metrics.record_success();
+                Ok(sse_response(stream, locale))
+            }
+            Err(e) => {
+                metrics.record_failure();
+                Ok(error_response(&e, locale))
+            }
+        };
+    }
+
+    match routing::route_completion(config, completion_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// Responses API 端点
+async fn responses(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let responses_req: ResponsesRequest = match read_json_body(req).await {
+        Ok(responses_req) => responses_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
+
+    if let Err(e) = responses_req.validate() {
+        return Ok(error_response(&e.into(), locale));
+    }
+
+    if responses_req.stream == Some(true) {
+        return match routing::route_responses_stream(config, responses_req).await {
+            Ok(stream) => {
+                metrics.record_success();
+                Ok(sse_response(stream, locale))
+            }
+            Err(e) => {
+                metrics.record_failure();
+                Ok(error_response(&e, locale))
+            }
+        };
+    }
+
+    match routing::route_responses(config, responses_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
         }
         Err(e) => {
             metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// Anthropic 原生消息端点（错误使用 Anthropic 格式返回）
+async fn messages(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
 
-            // 返回 SSE 格式的错误消息
-            let error_data = json!({
-                "error": {
-                    "message": e.to_string(),
-                    "type": "feathergate_error"
-                }
-            });
-            let sse_error = format!("data: {}\n\ndata: [DONE]\n\n", error_data);
+    let messages_req: MessagesRequest = match read_json_body(req).await {
+        Ok(messages_req) => messages_req,
+        Err(e) => return Ok(anthropic_error_response(&e, locale)),
+    };
 
-            Ok(Response::builder()
-                .status(StatusCode::OK)  // SSE 需要 200 状态码
-                .header("Content-Type", "text/event-stream")
-                .header("Cache-Control", "no-cache")
-                .body(
-                    Full::new(Bytes::from(sse_error))
-                        .map_err(|e| Box::new(e) as BoxError)
-                        .boxed(),
-                )
+    if let Err(e) = messages_req.validate() {
+        return Ok(anthropic_error_response(&e.into(), locale));
+    }
+
+    if messages_req.stream == Some(true) {
+        return match routing::route_messages_stream(config, messages_req).await {
+            Ok(stream) => {
+                metrics.record_success();
+                Ok(anthropic_sse_response(stream, locale))
+            }
+            Err(e) => {
+                metrics.record_failure();
+                Ok(anthropic_error_response(&e, locale))
+            }
+        };
+    }
+
+    match routing::route_messages(config, messages_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(anthropic_error_response(&e, locale))
+        }
+    }
+}
+
+/// Gemini 原生端点（`/v1beta/models/{model}:generateContent` 和 `:streamGenerateContent`）
+///
+/// 错误使用 Gemini 格式返回；流式响应总是使用 SSE（等同于 `alt=sse`）。
+async fn generate_content(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let target = req.uri().path().trim_start_matches("/v1beta/models/");
+    let Some((model, method)) = target.rsplit_once(':') else {
+        return Ok(not_found());
+    };
+    let stream = match method {
+        "generateContent" => false,
+        "streamGenerateContent" => true,
+        _ => return Ok(not_found()),
+    };
+    let model = model.to_string();
+
+    let body: serde_json::Value = match read_json_body(req).await {
+        Ok(body) => body,
+        Err(e) => return Ok(gemini_error_response(&e, locale)),
+    };
+    let native_req = GenerateContentRequest { model, body };
+
+    if stream {
+        return match routing::route_generate_content_stream(config, native_req).await {
+            Ok(stream) => {
+                metrics.record_success();
+                Ok(event_stream_response(streaming::sse_frames_with(
+                    stream,
+                    locale,
+                    streaming::format_sse_gemini_error,
+                )))
+            }
+            Err(e) => {
+                metrics.record_failure();
+                Ok(gemini_error_response(&e, locale))
+            }
+        };
+    }
+
+    match routing::route_generate_content(config, native_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(gemini_error_response(&e, locale))
+        }
+    }
+}
+
+/// 提供商原生接口透传（`/openai/*`、`/anthropic/*`、`/gemini/*`）
+///
+/// 路径、查询字符串和请求体原样转发，调用方的凭据替换为部署的上游凭据；
+/// 上游响应（包括错误响应）原样流式返回。
+async fn provider_passthrough(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let path = req.uri().path().to_string();
+    let Some((provider, rest)) = passthrough::split_path(&path) else {
+        return Ok(not_found());
+    };
+    // 透传请求无法按模型检查权限，受模型限制的虚拟 key（或所属团队、组织）不能使用；
+    // 预算和调用方的速率限制与其他端点一样在转发前检查
+    let admitted = auth::is_unrestricted()
+        .and_then(|unrestricted| match unrestricted {
+            true => Ok(()),
+            false => Err(FeatherGateError::RouteNotAllowed(format!("/{}/*", provider))),
+        })
+        .and_then(|_| spend::check_budgets(&config))
+        .and_then(|_| ratelimit::check(&config));
+    if let Err(err) = admitted {
+        metrics.record_failure();
+        return Ok(error_response(&err, locale));
+    }
+    let path_and_query = match req.uri().query() {
+        Some(query) => format!("{}?{}", rest, query),
+        None => rest.to_string(),
+    };
+    let method = req.method().clone();
+    let (parts, body) = req.into_parts();
+    let body = body
+        .into_data_stream()
+        .map(|chunk| chunk.map_err(|e| FeatherGateError::invalid_request(e.to_string())));
+
+    let started = std::time::Instant::now();
+    let passthrough_req = PassthroughRequest {
+        provider: provider.to_string(),
+        method: method.clone(),
+        path_and_query,
+        headers: parts.headers,
+        body: Box::pin(body),
+    };
+
+    match passthrough::forward(&config, passthrough_req).await {
+        Ok(response) => {
+            if response.status.is_success() {
+                metrics.record_success();
+            } else {
+                metrics.record_failure();
+            }
+            info!(
+                "透传 {} {} {} -> {} ({} ms)",
+                provider,
+                method,
+                rest,
+                response.status.as_u16(),
+                started.elapsed().as_millis()
+            );
+
+            let frame_stream = response
+                .body
+                .map(|result| result.map(Frame::data).map_err(|e| Box::new(e) as BoxError));
+            let mut builder = Response::builder().status(response.status);
+            if let Some(headers) = builder.headers_mut() {
+                headers.extend(response.headers);
+            }
+            Ok(builder
+                .body(BodyExt::boxed(StreamBody::new(frame_stream)))
                 .unwrap())
         }
+        Err(e) => {
+            metrics.record_failure();
+            warn!("透传 {} {} {} 失败: {}", provider, method, rest, e);
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 向量嵌入端点
+async fn embeddings(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let embedding_req: EmbeddingRequest = match read_json_body(req).await {
+        Ok(embedding_req) => embedding_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
+
+    if let Err(e) = embedding_req.validate() {
+        return Ok(error_response(&e.into(), locale));
+    }
+
+    match routing::route_embedding(config, embedding_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 重排序端点
+async fn rerank(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let rerank_req: RerankRequest = match read_json_body(req).await {
+        Ok(rerank_req) => rerank_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
+
+    if let Err(e) = rerank_req.validate() {
+        return Ok(error_response(&e.into(), locale));
+    }
+
+    match routing::route_rerank(config, rerank_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 图像生成端点
+async fn image_generations(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let image_req: ImageGenerationRequest = match read_json_body(req).await {
+        Ok(image_req) => image_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
+
+    if let Err(e) = image_req.validate() {
+        return Ok(error_response(&e.into(), locale));
+    }
+
+    match routing::route_image_generation(config, image_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 语音转文本端点（multipart 上传，音频流式转发到上游）
+async fn audio_transcriptions(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let content_type = req
+        .headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = req.into_body().into_data_stream();
+
+    let transcription_req = match multipart::parse_transcription_form(&content_type, body).await {
+        Ok(transcription_req) => transcription_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
+
+    match routing::route_transcription(config, transcription_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(raw_response(response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 文本转语音端点（返回二进制音频流）
+async fn audio_speech(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let speech_req: SpeechRequest = match read_json_body(req).await {
+        Ok(speech_req) => speech_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
+
+    if let Err(e) = speech_req.validate() {
+        return Ok(error_response(&e.into(), locale));
+    }
+
+    match routing::route_speech(config, speech_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(raw_response(response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 内容审核端点
+async fn moderations(
+    req: Request<hyper::body::Incoming>,
+    config: Arc<Config>,
+) -> Result<Response<BoxBody>, BoxError> {
+    let metrics = metrics::global_metrics();
+    let locale = config.general_settings.api_locale;
+
+    let moderation_req: ModerationRequest = match read_json_body(req).await {
+        Ok(moderation_req) => moderation_req,
+        Err(e) => return Ok(error_response(&e, locale)),
+    };
+
+    if let Err(e) = moderation_req.validate() {
+        return Ok(error_response(&e.into(), locale));
+    }
+
+    match routing::route_moderation(config, moderation_req).await {
+        Ok(response) => {
+            metrics.record_success();
+            Ok(json_response(StatusCode::OK, &response))
+        }
+        Err(e) => {
+            metrics.record_failure();
+            Ok(error_response(&e, locale))
+        }
+    }
+}
+
+/// 读取并解析 JSON 请求体，无效的请求体返回 invalid_request 错误
+pub(super) async fn read_json_body<T: DeserializeOwned>(
+    req: Request<hyper::body::Incoming>,
+) -> crate::Result<T> {
+    let whole_body = req
+        .collect()
+        .await
+        .map_err(|e| FeatherGateError::invalid_request(e.to_string()))?
+        .to_bytes();
+    serde_json::from_slice(&whole_body).map_err(|e| FeatherGateError::invalid_request(e.to_string()))
+}
+
+/// JSON 响应
+pub(super) fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<BoxBody> {
+    let body = serde_json::to_string(body).unwrap_or_default();
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(
+            Full::new(Bytes::from(body))
+                .map_err(|e| Box::new(e) as BoxError)
+                .boxed(),
+        )
+        .unwrap()
+}
+
+/// SSE 流式响应（中途错误转为 error 事件）
+fn sse_response(
+    stream: Pin<Box<dyn Stream<Item = crate::Result<Bytes>> + Send + Sync>>,
+    locale: Locale,
+) -> Response<BoxBody> {
+    event_stream_response(streaming::sse_frames(stream, locale))
+}
+
+/// Anthropic 格式的 SSE 流式响应（中途错误转为 Anthropic 的 error 事件）
+fn anthropic_sse_response(
+    stream: Pin<Box<dyn Stream<Item = crate::Result<Bytes>> + Send + Sync>>,
+    locale: Locale,
+) -> Response<BoxBody> {
+    event_stream_response(streaming::sse_frames_with(
+        stream,
+        locale,
+        streaming::format_sse_anthropic_error,
+    ))
+}
+
+/// text/event-stream 响应
+fn event_stream_response(
+    frames: impl Stream<Item = Bytes> + Send + Sync + 'static,
+) -> Response<BoxBody> {
+    // 将字节流转换为 Frame 流
+    let frame_stream = frames.map(|bytes| Ok::<_, BoxError>(Frame::data(bytes)));
+
+    // 创建 StreamBody 并转换为 BoxBody
+    let body = StreamBody::new(frame_stream);
+    let boxed_body = BodyExt::boxed(body);
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/event-stream")
+        .header("Cache-Control", "no-cache")
+        .header("Connection", "keep-alive")
+        .header("X-Accel-Buffering", "no") // 禁用 Nginx 缓冲
+        .body(boxed_body)
+        .unwrap()
+}
+
+/// 原样流式返回上游响应体（中途出错时直接中断连接）
+fn raw_response(response: RawResponse) -> Response<BoxBody> {
+    let frame_stream = response
+        .body
+        .map(|result| result.map(Frame::data).map_err(|e| Box::new(e) as BoxError));
+    let content_type = response
+        .content_type
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", content_type)
+        .body(BodyExt::boxed(StreamBody::new(frame_stream)))
+        .unwrap()
+}
+
+/// 错误响应（Anthropic 错误格式，/v1/messages 使用）
+fn anthropic_error_response(err: &FeatherGateError, locale: Locale) -> Response<BoxBody> {
+    json_response(err.status_code(), &err.to_anthropic_json(locale))
+}
+
+/// 错误响应（Gemini 错误格式，/v1beta 原生端点使用）
+fn gemini_error_response(err: &FeatherGateError, locale: Locale) -> Response<BoxBody> {
+    json_response(err.status_code(), &err.to_gemini_json(locale))
+}
+
+/// 认证失败响应（原生格式端点使用各自的错误格式，便于对应 SDK 解析）
+fn auth_error_response(path: &str, err: &FeatherGateError, locale: Locale) -> Response<BoxBody> {
+    if path == "/v1/messages" {
+        anthropic_error_response(err, locale)
+    } else if path.starts_with("/v1beta/") {
+        gemini_error_response(err, locale)
+    } else {
+        error_response(err, locale)
     }
 }
 
+/// 错误响应（OpenAI 错误格式）
+pub(super) fn error_response(err: &FeatherGateError, locale: Locale) -> Response<BoxBody> {
+    Response::builder()
+        .status(err.status_code())
+        .header("Content-Type", "application/json")
+        .body(
+            Full::new(Bytes::from(err.to_json(locale).to_string()))
+                .map_err(|e| Box::new(e) as BoxError)
+                .boxed(),
+        )
+        .unwrap()
+}
+
 /// 404 响应
-fn not_found() -> Response<BoxBody> {
+pub(super) fn not_found() -> Response<BoxBody> {
     Response::builder()
         .status(StatusCode::NOT_FOUND)
         .body(
@@ -243,7 +828,7 @@ fn not_found() -> Response<BoxBody> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, LitellmParams, ModelConfig};
+    use crate::config::LitellmParams;
 
     fn create_test_config() -> Config {
         Config {
@@ -254,7 +839,9 @@ mod tests {
                         model: "openai/gpt-4".to_string(),
                         api_key: "sk-test".to_string(),
                         api_base: "https://api.openai.com".to_string(),
+                        ..Default::default()
                     },
+                    ..Default::default()
                 },
                 ModelConfig {
                     model_name: "claude".to_string(),
@@ -262,9 +849,12 @@ mod tests {
                         model: "anthropic/claude-opus-4-5".to_string(),
                         api_key: "sk-ant-test".to_string(),
                         api_base: "https://api.anthropic.com".to_string(),
+                        ..Default::default()
                     },
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         }
     }
 
@@ -281,6 +871,47 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[test]
+    fn test_model_object_includes_defaults() {
+        let config = create_test_config();
+        let model = model_object(&config.model_list[1]);
+        assert_eq!(model["id"], "claude");
+        assert_eq!(model["model_info"]["max_input_tokens"], 200_000);
+        assert_eq!(model["model_info"]["supports_function_calling"], true);
+
+        let response = retrieve_model("gpt-5", Arc::new(config));
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_model_info_hides_api_keys() {
+        let response = model_info(Arc::new(create_test_config()));
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("anthropic/claude-opus-4-5"));
+        assert!(!body.contains("sk-test") && !body.contains("sk-ant-test"));
+    }
+
+    #[test]
+    fn test_error_response_status() {
+        let err = FeatherGateError::ModelNotFound("gpt-5".to_string());
+        let response = error_response(&err, Locale::En);
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = error_response(&FeatherGateError::upstream(429, "rate limited"), Locale::En);
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+
+        let err = FeatherGateError::invalid_request("malformed body");
+        let response = error_response(&err, Locale::En);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[test]
     fn test_not_found() {
         let response = not_found();
@@ -1,4 +1,6 @@
+mod admin;
 pub mod handlers;
+pub mod multipart;
 pub mod streaming;
 
 use crate::config::Config;
@@ -143,8 +145,11 @@ mod tests {
                     model: "openai/gpt-4".to_string(),
                     api_key: "sk-test".to_string(),
                     api_base: "https://api.openai.com".to_string(),
+                    ..Default::default()
                 },
+                ..Default::default()
             }],
+            ..Default::default()
         }
     }
 
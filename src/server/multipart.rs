@@ -0,0 +1,221 @@
+use crate::error::FeatherGateError;
+use crate::types::audio::{AudioBody, AudioFile, TranscriptionRequest};
+use crate::types::ValidationError;
+use crate::Result;
+use futures_util::{Stream, StreamExt};
+use hyper::body::Bytes;
+use std::pin::Pin;
+
+/// 需要缓冲的音频文件大小上限（与 OpenAI 的 25 MB 限制一致）
+const MAX_BUFFERED_FILE_MB: usize = 25;
+
+/// 解析语音转文本的 multipart 表单
+///
+/// `model` 字段出现在 `file` 之前时（OpenAI Python SDK 的默认顺序），文件内容不经缓冲，
+/// 直接流式转发到上游，此时 `file` 之后不能再有其他字段（上游请求已经开始，无法再追加），
+/// 出现时以 400 中止请求；否则先把文件缓冲到内存，读完整个表单后再转发。
+pub async fn parse_transcription_form<S, E>(
+    content_type: &str,
+    body: S,
+) -> Result<TranscriptionRequest>
+where
+    S: Stream<Item = std::result::Result<Bytes, E>> + Send + 'static,
+    E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+{
+    let boundary = multer::parse_boundary(content_type).map_err(invalid_form)?;
+    let mut multipart = multer::Multipart::new(body, boundary);
+
+    let mut model = None;
+    let mut fields = Vec::new();
+    let mut file = None;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(invalid_form)? {
+        let name = field.name().unwrap_or_default().to_string();
+
+        if name != "file" {
+            let value = field.text().await.map_err(invalid_form)?;
+            match name.as_str() {
+                "model" => model = Some(value),
+                _ => fields.push((name, value)),
+            }
+            continue;
+        }
+
+        let filename = field.file_name().unwrap_or("audio").to_string();
+        let content_type = field.content_type().map(|mime| mime.to_string());
+
+        if model.is_some() {
+            file = Some(AudioFile {
+                filename,
+                content_type,
+                body: AudioBody::Streaming(stream_file(field, multipart)),
+            });
+            break;
+        }
+
+        let mut buffer = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(invalid_form)? {
+            if buffer.len() + chunk.len() > MAX_BUFFERED_FILE_MB * 1024 * 1024 {
+                return Err(ValidationError::AudioFileTooLarge(MAX_BUFFERED_FILE_MB).into());
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+        file = Some(AudioFile {
+            filename,
+            content_type,
+            body: AudioBody::Buffered(Bytes::from(buffer)),
+        });
+    }
+
+    Ok(TranscriptionRequest {
+        model: model.ok_or(ValidationError::MissingField("model"))?,
+        fields,
+        file: file.ok_or(ValidationError::MissingField("file"))?,
+    })
+}
+
+/// 流式输出文件内容，文件结束后表单中还有字段时输出错误
+fn stream_file(
+    field: multer::Field<'static>,
+    mut multipart: multer::Multipart<'static>,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    let trailing = futures_util::stream::once(async move {
+        match multipart.next_field().await {
+            Ok(Some(field)) => {
+                let name = field.name().unwrap_or_default().to_string();
+                Some(Err(ValidationError::FieldAfterFile(name).into()))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(invalid_form(e))),
+        }
+    })
+    .filter_map(std::future::ready);
+
+    Box::pin(field.map(|chunk| chunk.map_err(invalid_form)).chain(trailing))
+}
+
+/// 无效的 multipart 表单
+fn invalid_form(err: multer::Error) -> FeatherGateError {
+    FeatherGateError::invalid_request(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT_TYPE: &str = "multipart/form-data; boundary=X-BOUNDARY";
+
+    fn form_body(parts: &[(&str, Option<&str>, &str)]) -> String {
+        let mut body = String::new();
+        for (name, filename, value) in parts {
+            body.push_str("--X-BOUNDARY\r\n");
+            match filename {
+                Some(filename) => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: audio/mpeg\r\n\r\n",
+                    name, filename
+                )),
+                None => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                    name
+                )),
+            }
+            body.push_str(value);
+            body.push_str("\r\n");
+        }
+        body.push_str("--X-BOUNDARY--\r\n");
+        body
+    }
+
+    /// 把请求体拆成多个小块，模拟网络分片
+    fn chunked(body: String) -> impl Stream<Item = std::result::Result<Bytes, std::io::Error>> {
+        let chunks: Vec<_> = body
+            .into_bytes()
+            .chunks(7)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        futures_util::stream::iter(chunks)
+    }
+
+    async fn collect(body: AudioBody) -> Vec<u8> {
+        match body {
+            AudioBody::Buffered(bytes) => bytes.to_vec(),
+            AudioBody::Streaming(mut stream) => {
+                let mut output = Vec::new();
+                while let Some(chunk) = stream.next().await {
+                    output.extend_from_slice(&chunk.unwrap());
+                }
+                output
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_model_before_file_streams_file() {
+        let body = form_body(&[
+            ("model", None, "whisper"),
+            ("language", None, "en"),
+            ("file", Some("speech.mp3"), "ID3-audio-bytes"),
+        ]);
+
+        let req = parse_transcription_form(CONTENT_TYPE, chunked(body)).await.unwrap();
+        assert_eq!(req.model, "whisper");
+        assert_eq!(req.fields, vec![("language".to_string(), "en".to_string())]);
+        assert_eq!(req.file.filename, "speech.mp3");
+        assert_eq!(req.file.content_type.as_deref(), Some("audio/mpeg"));
+        assert!(matches!(req.file.body, AudioBody::Streaming(_)));
+        assert_eq!(collect(req.file.body).await, b"ID3-audio-bytes");
+    }
+
+    #[tokio::test]
+    async fn test_field_after_streamed_file_is_rejected() {
+        let body = form_body(&[
+            ("model", None, "whisper"),
+            ("file", Some("speech.mp3"), "ID3-audio-bytes"),
+            ("language", None, "en"),
+        ]);
+
+        let req = parse_transcription_form(CONTENT_TYPE, chunked(body)).await.unwrap();
+        let AudioBody::Streaming(mut stream) = req.file.body else {
+            panic!("expected a streaming body");
+        };
+        let mut last = None;
+        while let Some(chunk) = stream.next().await {
+            last = Some(chunk);
+        }
+        let err = last.unwrap().unwrap_err();
+        assert!(matches!(
+            &err,
+            FeatherGateError::Validation(ValidationError::FieldAfterFile(name)) if name == "language"
+        ));
+        assert_eq!(err.status_code(), hyper::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_file_before_model_is_buffered() {
+        let body = form_body(&[
+            ("file", Some("speech.mp3"), "ID3-audio-bytes"),
+            ("model", None, "whisper"),
+        ]);
+
+        let req = parse_transcription_form(CONTENT_TYPE, chunked(body)).await.unwrap();
+        assert_eq!(req.model, "whisper");
+        assert!(matches!(req.file.body, AudioBody::Buffered(_)));
+        assert_eq!(collect(req.file.body).await, b"ID3-audio-bytes");
+    }
+
+    #[tokio::test]
+    async fn test_missing_fields() {
+        let body = form_body(&[("model", None, "whisper")]);
+        let err = parse_transcription_form(CONTENT_TYPE, chunked(body)).await.err().unwrap();
+        assert!(matches!(
+            err,
+            FeatherGateError::Validation(ValidationError::MissingField("file"))
+        ));
+
+        let err = parse_transcription_form("application/json", chunked(String::new()))
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, FeatherGateError::InvalidRequest(_)));
+    }
+}
@@ -1,5 +1,10 @@
+use crate::error::FeatherGateError;
+use crate::i18n::Locale;
 use crate::types::ChatStreamChunk;
+use crate::Result;
+use futures_util::{Stream, StreamExt};
 use hyper::body::Bytes;
+use tracing::warn;
 
 /// 格式化 SSE 数据块
 pub fn format_sse_chunk(chunk: &ChatStreamChunk) -> String {
@@ -12,6 +17,56 @@ pub fn format_sse_done() -> String {
     "data: [DONE]\n\n".to_string()
 }
 
+/// 格式化 SSE 错误事件（OpenAI SDK 会将其识别为 APIError）
+pub fn format_sse_error(err: &FeatherGateError, locale: Locale) -> String {
+    format!("event: error\ndata: {}\n\n", err.to_json(locale))
+}
+
+/// 格式化 Anthropic 格式的 SSE 错误事件（/v1/messages 使用）
+pub fn format_sse_anthropic_error(err: &FeatherGateError, locale: Locale) -> String {
+    format!("event: error\ndata: {}\n\n", err.to_anthropic_json(locale))
+}
+
+/// 格式化 Gemini 格式的 SSE 错误数据块（/v1beta 原生端点使用）
+pub fn format_sse_gemini_error(err: &FeatherGateError, locale: Locale) -> String {
+    format!("data: {}\r\n\r\n", err.to_gemini_json(locale))
+}
+
+/// 将 provider 字节流转换为 SSE 输出流
+///
+/// 流中途出现的错误会被转换为一个 `error` 事件，随后结束流。
+pub fn sse_frames<S>(stream: S, locale: Locale) -> impl Stream<Item = Bytes>
+where
+    S: Stream<Item = Result<Bytes>>,
+{
+    sse_frames_with(stream, locale, format_sse_error)
+}
+
+/// 同 [`sse_frames`]，使用指定的错误事件格式
+pub fn sse_frames_with<S>(
+    stream: S,
+    locale: Locale,
+    format_error: fn(&FeatherGateError, Locale) -> String,
+) -> impl Stream<Item = Bytes>
+where
+    S: Stream<Item = Result<Bytes>>,
+{
+    stream.scan(false, move |failed, item| {
+        if *failed {
+            return std::future::ready(None);
+        }
+        let bytes = match item {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("流式响应中途出错: {}", e);
+                *failed = true;
+                Bytes::from(format_error(&e, locale))
+            }
+        };
+        std::future::ready(Some(bytes))
+    })
+}
+
 /// 将字符串转换为 SSE Bytes
 pub fn to_sse_bytes(data: &str) -> Bytes {
     Bytes::from(data.to_string())
@@ -51,6 +106,35 @@ mod tests {
         assert_eq!(done, "data: [DONE]\n\n");
     }
 
+    #[test]
+    fn test_format_sse_error() {
+        let err = FeatherGateError::upstream(529, "Overloaded");
+        let sse = format_sse_error(&err, Locale::En);
+        assert!(sse.starts_with("event: error\ndata: "));
+        assert!(sse.ends_with("\n\n"));
+
+        let data = sse.lines().nth(1).unwrap().strip_prefix("data: ").unwrap();
+        let json: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert!(json["error"]["message"].as_str().unwrap().contains("Overloaded"));
+    }
+
+    #[tokio::test]
+    async fn test_sse_frames_stops_after_error() {
+        let items: Vec<Result<Bytes>> = vec![
+            Ok(Bytes::from("data: first\n\n")),
+            Err(FeatherGateError::upstream(500, "boom")),
+            Ok(Bytes::from("data: never\n\n")),
+        ];
+
+        let frames: Vec<Bytes> = sse_frames(futures_util::stream::iter(items), Locale::En)
+            .collect()
+            .await;
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0], Bytes::from("data: first\n\n"));
+        assert!(String::from_utf8_lossy(&frames[1]).starts_with("event: error\n"));
+    }
+
     #[test]
     fn test_to_sse_bytes() {
         let data = "test data";
@@ -0,0 +1,1011 @@
+use crate::auth::{self, Caller};
+use crate::config::{BudgetConfig, Config, ModelConfig};
+use crate::db::{self, Database};
+use crate::error::FeatherGateError;
+use crate::metrics;
+use crate::providers::RawResponse;
+use crate::ratelimit::TokenLimits;
+use crate::types::keys::parse_duration;
+use crate::types::rerank::RerankUsage;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use hyper::body::Bytes;
+use once_cell::sync::Lazy;
+use redis::aio::ConnectionManager;
+use rusqlite::{params, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+/// 计费主体类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityType {
+    /// 虚拟 key（按 token）
+    Key,
+    /// 用户（虚拟 key 的 user_id 或请求中的 `user` 字段）
+    User,
+    /// 团队（虚拟 key 的 team_id）
+    Team,
+    /// 组织（团队所属的组织）
+    Organization,
+}
+
+impl EntityType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntityType::Key => "key",
+            EntityType::User => "user",
+            EntityType::Team => "team",
+            EntityType::Organization => "organization",
+        }
+    }
+}
+
+/// 某个主体在当前预算周期内的花费
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SpendSummary {
+    /// 花费（美元）
+    pub spend: f64,
+    /// 下次清零的时间，未配置预算周期时为空
+    pub budget_reset_at: Option<DateTime<Utc>>,
+}
+
+/// 在 Redis 中累加当前预算周期的花费，周期结束时先清零（ARGV: 当前时间, 增量, 下次清零时间或 0）
+const SHARED_SPEND_SCRIPT: &str = r"
+local reset_at = tonumber(redis.call('HGET', KEYS[1], 'reset_at') or '0')
+if reset_at > 0 and reset_at <= tonumber(ARGV[1]) then
+    redis.call('DEL', KEYS[1])
+    reset_at = 0
+end
+local spend = redis.call('HINCRBYFLOAT', KEYS[1], 'spend', ARGV[2])
+if reset_at == 0 and tonumber(ARGV[3]) > 0 then
+    redis.call('HSET', KEYS[1], 'reset_at', ARGV[3])
+end
+return spend
+";
+
+/// 多副本共享的花费（只登记设置了 `max_budget` 的主体）
+#[derive(Debug, Default)]
+struct SharedSpend {
+    /// 尚未同步到 Redis 的花费
+    pending: f64,
+    /// 上次同步时 Redis 中当前预算周期的花费（包括所有副本）
+    total: Option<f64>,
+    budget: BudgetConfig,
+    /// 本副本登记时的预算周期结束时间，之后没有新花费的条目会被移除
+    budget_reset_at: Option<DateTime<Utc>>,
+}
+
+impl SharedSpend {
+    /// 登记主体的预算，开始本副本的预算周期
+    fn register(&mut self, budget: &BudgetConfig, now: DateTime<Utc>) {
+        self.budget = budget.clone();
+        if self.budget_reset_at.is_none() {
+            self.budget_reset_at = next_reset(budget, now);
+        }
+    }
+}
+
+/// 从现在开始的预算周期的结束时间，未配置预算周期时为空
+fn next_reset(budget: &BudgetConfig, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    budget
+        .budget_duration
+        .as_deref()
+        .and_then(parse_duration)
+        .and_then(|duration| now.checked_add_signed(duration))
+}
+
+/// 本副本记录的花费（当前预算周期）
+#[derive(Debug, Clone, Default)]
+struct LocalSpend {
+    spend: f64,
+    budget_reset_at: Option<DateTime<Utc>>,
+    /// 有尚未写入数据库的变化
+    dirty: bool,
+}
+
+impl LocalSpend {
+    /// 预算周期已结束时清零
+    fn expire(&mut self, now: DateTime<Utc>) {
+        if self.budget_reset_at.is_some_and(|reset_at| reset_at <= now) {
+            self.spend = 0.0;
+            self.budget_reset_at = None;
+            self.dirty = true;
+        }
+    }
+}
+
+/// 花费存储（SQLite `spend` 表）
+///
+/// 花费在内存中累加（每个主体第一次使用时从数据库读取），由后台任务批量写入数据库，请求路径不等待写入；
+/// 预算周期结束且没有未写入变化的主体从内存中移除，下次使用时重新读取。
+/// 配置 Redis 后，各副本的花费定期汇总到 Redis，检查预算时取本地和汇总值中较大的一个。
+pub struct SpendTracker {
+    db: &'static Database,
+    local: Mutex<HashMap<(EntityType, String), LocalSpend>>,
+    shared: Mutex<HashMap<(EntityType, String), SharedSpend>>,
+    /// 写入数据库期间持有，转移或删除主体的花费时不会与写入交错（否则已删除的行会被重新写入）
+    flush_lock: Mutex<()>,
+}
+
+impl SpendTracker {
+    pub fn new(db: &'static Database) -> Self {
+        SpendTracker {
+            db,
+            local: Mutex::new(HashMap::new()),
+            shared: Mutex::new(HashMap::new()),
+            flush_lock: Mutex::new(()),
+        }
+    }
+
+    /// 当前预算周期内的花费（周期已结束时为 0）
+    pub fn summary(&self, entity: EntityType, id: &str) -> Result<SpendSummary> {
+        self.with_local(entity, id, |local| SpendSummary {
+            spend: local.spend,
+            budget_reset_at: local.budget_reset_at,
+        })
+    }
+
+    /// 累加花费；预算周期已结束时先清零并开始新的周期
+    pub fn add(&self, entity: EntityType, id: &str, cost: f64, budget: &BudgetConfig) -> Result<()> {
+        let now = Utc::now();
+        self.with_local(entity, id, |local| {
+            local.spend += cost;
+            if local.budget_reset_at.is_none() {
+                local.budget_reset_at = next_reset(budget, now);
+            }
+            local.dirty = true;
+        })?;
+
+        // 没有预算上限的主体不需要在副本间汇总
+        if budget.max_budget.is_some() {
+            let mut shared = self.shared.lock().unwrap();
+            let entry = shared.entry((entity, id.to_string())).or_default();
+            entry.pending += cost;
+            entry.register(budget, now);
+        }
+        Ok(())
+    }
+
+    /// 在主体的本地花费上执行操作，第一次使用时从数据库读取
+    fn with_local<T>(&self, entity: EntityType, id: &str, f: impl FnOnce(&mut LocalSpend) -> T) -> Result<T> {
+        let key = (entity, id.to_string());
+        let now = Utc::now();
+        if let Some(local) = self.local.lock().unwrap().get_mut(&key) {
+            local.expire(now);
+            return Ok(f(local));
+        }
+
+        let row: Option<(f64, Option<i64>)> = self.db.with_conn(|conn| {
+            conn.query_row(
+                "SELECT spend, budget_reset_at FROM spend WHERE entity_type = ?1 AND entity_id = ?2",
+                params![entity.as_str(), id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+        })?;
+        let loaded = row.map_or_else(LocalSpend::default, |(spend, reset_at)| LocalSpend {
+            spend,
+            budget_reset_at: reset_at.map(db::timestamp),
+            dirty: false,
+        });
+
+        // 读取期间其他请求可能已经加载过，以先加载的为准
+        let mut locals = self.local.lock().unwrap();
+        let local = locals.entry(key).or_insert(loaded);
+        local.expire(now);
+        Ok(f(local))
+    }
+
+    /// 把有变化的花费批量写入数据库；写入失败时保留变化，下次重试
+    pub fn flush(&self) -> Result<()> {
+        let _flushing = self.flush_lock.lock().unwrap();
+        self.evict(Utc::now());
+
+        let dirty: Vec<((EntityType, String), LocalSpend)> = self
+            .local
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter(|(_, local)| local.dirty)
+            .map(|(key, local)| {
+                local.dirty = false;
+                (key.clone(), local.clone())
+            })
+            .collect();
+        if dirty.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now().timestamp();
+        let result = self.db.transaction(|tx| {
+            let mut stmt = tx
+                .prepare_cached(
+                    "INSERT INTO spend (entity_type, entity_id, spend, budget_reset_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT (entity_type, entity_id) DO UPDATE SET
+                        spend = excluded.spend,
+                        budget_reset_at = excluded.budget_reset_at,
+                        updated_at = excluded.updated_at",
+                )
+                .map_err(db::db_error)?;
+            for ((entity, id), local) in &dirty {
+                let reset_at = local.budget_reset_at.map(|reset_at| reset_at.timestamp());
+                stmt.execute(params![entity.as_str(), id, local.spend, reset_at, now])
+                    .map_err(db::db_error)?;
+            }
+            Ok(())
+        });
+
+        if result.is_err() {
+            let mut locals = self.local.lock().unwrap();
+            for (key, _) in dirty {
+                if let Some(local) = locals.get_mut(&key) {
+                    local.dirty = true;
+                }
+            }
+        }
+        result
+    }
+
+    /// 把主体的花费（包括尚未写入数据库的部分）转移到新的 ID，用于轮换虚拟 key
+    pub fn transfer(&self, entity: EntityType, from: &str, to: &str) -> Result<()> {
+        let _flushing = self.flush_lock.lock().unwrap();
+        self.db.with_conn(|conn| {
+            conn.execute(
+                "UPDATE spend SET entity_id = ?1 WHERE entity_type = ?2 AND entity_id = ?3",
+                params![to, entity.as_str(), from],
+            )
+        })?;
+
+        let (from, to) = ((entity, from.to_string()), (entity, to.to_string()));
+        let mut locals = self.local.lock().unwrap();
+        if let Some(mut local) = locals.remove(&from) {
+            local.dirty = true;
+            locals.insert(to.clone(), local);
+        }
+        drop(locals);
+        let mut shared = self.shared.lock().unwrap();
+        if let Some(entry) = shared.remove(&from) {
+            shared.insert(to, entry);
+        }
+        Ok(())
+    }
+
+    /// 删除主体的花费（包括尚未写入数据库的部分），用于删除虚拟 key
+    pub fn remove(&self, entity: EntityType, ids: &[String]) -> Result<()> {
+        let _flushing = self.flush_lock.lock().unwrap();
+        self.db.transaction(|tx| {
+            for id in ids {
+                tx.execute(
+                    "DELETE FROM spend WHERE entity_type = ?1 AND entity_id = ?2",
+                    params![entity.as_str(), id],
+                )
+                .map_err(db::db_error)?;
+            }
+            Ok(())
+        })?;
+
+        let mut locals = self.local.lock().unwrap();
+        let mut shared = self.shared.lock().unwrap();
+        for id in ids {
+            let key = (entity, id.clone());
+            locals.remove(&key);
+            shared.remove(&key);
+        }
+        Ok(())
+    }
+
+    /// 移除预算周期已结束、且没有尚未写入数据库或同步到 Redis 的花费的主体
+    fn evict(&self, now: DateTime<Utc>) {
+        let ended = |reset_at: Option<DateTime<Utc>>| reset_at.is_some_and(|reset_at| reset_at <= now);
+        self.local
+            .lock()
+            .unwrap()
+            .retain(|_, local| local.dirty || !ended(local.budget_reset_at));
+        self.shared
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.pending != 0.0 || !ended(entry.budget_reset_at));
+    }
+
+    /// 所有副本在当前预算周期内的花费（尚未同步过时为空），并登记该主体以便下次同步
+    fn shared_spend(&self, entity: EntityType, id: &str, budget: &BudgetConfig) -> Option<f64> {
+        let mut shared = self.shared.lock().unwrap();
+        let entry = shared.entry((entity, id.to_string())).or_default();
+        entry.register(budget, Utc::now());
+        entry.total.map(|total| total + entry.pending)
+    }
+
+    /// 与 Redis 同步：上报本副本的花费，并读取所有副本的汇总
+    ///
+    /// 同步失败时保留本副本的花费，下次同步时一起上报。
+    pub(crate) async fn sync(&self, conn: &mut ConnectionManager) -> redis::RedisResult<()> {
+        let now = Utc::now();
+        let entries: Vec<((EntityType, String), f64, i64)> = self
+            .shared
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .map(|(key, entry)| {
+                let next_reset = next_reset(&entry.budget, now).map_or(0, |reset_at| reset_at.timestamp());
+                (key.clone(), std::mem::take(&mut entry.pending), next_reset)
+            })
+            .collect();
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut pipe = redis::pipe();
+        for ((entity, id), pending, next_reset) in &entries {
+            pipe.cmd("EVAL")
+                .arg(SHARED_SPEND_SCRIPT)
+                .arg(1)
+                .arg(format!("feathergate:spend:{}:{}", entity.as_str(), id))
+                .arg(now.timestamp())
+                .arg(pending)
+                .arg(next_reset);
+        }
+        let result: redis::RedisResult<Vec<f64>> = pipe.query_async(conn).await;
+
+        let mut shared = self.shared.lock().unwrap();
+        let totals = match result {
+            Ok(totals) => totals,
+            Err(e) => {
+                for (key, pending, _) in entries {
+                    shared.entry(key).or_default().pending += pending;
+                }
+                return Err(e);
+            }
+        };
+        for ((key, _, _), total) in entries.into_iter().zip(totals) {
+            shared.entry(key).or_default().total = Some(total);
+        }
+        Ok(())
+    }
+
+    /// 花费达到预算上限时返回 BudgetExceeded
+    fn check(&self, subject: &Subject) -> Result<()> {
+        let Some(max_budget) = subject.budget.max_budget else {
+            return Ok(());
+        };
+        let mut spend = self.summary(subject.entity, &subject.id)?.spend;
+        if let Some(shared) = self.shared_spend(subject.entity, &subject.id, &subject.budget) {
+            spend = spend.max(shared);
+        }
+        if spend >= max_budget {
+            return Err(FeatherGateError::BudgetExceeded(
+                subject.display_name.clone(),
+                spend,
+                max_budget,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 全局花费存储
+pub fn tracker() -> &'static SpendTracker {
+    static TRACKER: Lazy<SpendTracker> = Lazy::new(|| SpendTracker::new(db::global()));
+    &TRACKER
+}
+
+/// 花费写入数据库的间隔
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 启动后台任务，定期把花费批量写入数据库
+pub fn start() {
+    tokio::spawn(async {
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            flush().await;
+        }
+    });
+}
+
+/// 把尚未写入的花费写入数据库（关闭前调用一次）
+pub async fn flush() {
+    match tokio::task::spawn_blocking(|| tracker().flush()).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("写入花费失败，稍后重试: {}", e),
+        Err(e) => warn!("写入花费的任务异常退出: {}", e),
+    }
+}
+
+/// 一个请求需要计费的主体
+#[derive(Debug, Clone)]
+struct Subject {
+    entity: EntityType,
+    id: String,
+    /// 错误消息中使用的名称（key 使用脱敏后的 key）
+    display_name: String,
+    budget: BudgetConfig,
+}
+
+/// 当前请求的计费主体：虚拟 key、调用方的用户、团队和组织（来自虚拟 key 或 JWT），以及请求中配置了预算的 `user`
+///
+/// 团队记录中的预算优先于配置文件中的 `team_budgets`。
+fn subjects(config: &Config) -> Result<Vec<Subject>> {
+    let settings = &config.general_settings;
+    let user = |id: &str| Subject {
+        entity: EntityType::User,
+        id: id.to_string(),
+        display_name: format!("user {}", id),
+        budget: settings.user_budgets.get(id).cloned().unwrap_or_default(),
+    };
+
+    let caller = auth::current_caller();
+    let membership = auth::membership(&caller)?;
+    let mut subjects = Vec::new();
+    if let Caller::Key(key) = &caller {
+        subjects.push(Subject {
+            entity: EntityType::Key,
+            id: key.token.clone(),
+            display_name: format!("key {}", key.key_name),
+            budget: BudgetConfig {
+                max_budget: key.max_budget,
+                budget_duration: key.budget_duration.clone(),
+            },
+        });
+    }
+    if let Some(user_id) = caller.user_id() {
+        subjects.push(user(user_id));
+    }
+    if let Some(team_id) = caller.team_id() {
+        let configured = settings.team_budgets.get(team_id).cloned().unwrap_or_default();
+        let budget = match &membership.team {
+            Some(team) => team.settings.budget_or(configured),
+            None => configured,
+        };
+        subjects.push(Subject {
+            entity: EntityType::Team,
+            id: team_id.to_string(),
+            display_name: format!("team {}", team_id),
+            budget,
+        });
+    }
+    if let Some(org) = &membership.organization {
+        subjects.push(Subject {
+            entity: EntityType::Organization,
+            id: org.organization_id.clone(),
+            display_name: format!("organization {}", org.organization_id),
+            budget: org.settings.budget(),
+        });
+    }
+    // 终端用户 ID 由客户端任意填写，只记录配置了预算的终端用户，避免花费记录无限增长
+    if let Some(end_user) = auth::end_user().filter(|id| settings.user_budgets.contains_key(id)) {
+        if !subjects.iter().any(|s| s.entity == EntityType::User && s.id == end_user) {
+            subjects.push(user(&end_user));
+        }
+    }
+    Ok(subjects)
+}
+
+/// 记录花费时使用的计费主体；查不到团队或组织时只记录日志，不影响已经完成的请求
+fn recorded_subjects(config: &Config) -> Vec<Subject> {
+    subjects(config).unwrap_or_else(|e| {
+        warn!("查找计费主体失败，本次花费未记录: {}", e);
+        Vec::new()
+    })
+}
+
+/// 转发前检查当前请求所有计费主体的预算
+pub fn check_budgets(config: &Config) -> Result<()> {
+    let tracker = tracker();
+    subjects(config)?
+        .iter()
+        .try_for_each(|subject| tracker.check(subject))
+}
+
+/// 按部署的单价计算 token 成本（美元）
+pub fn token_cost(model_config: &ModelConfig, input_tokens: u64, output_tokens: u64) -> f64 {
+    let info = model_config.resolved_model_info();
+    info.input_cost_per_token.unwrap_or_default() * input_tokens as f64
+        + info.output_cost_per_token.unwrap_or_default() * output_tokens as f64
+}
+
+/// 图像生成的成本：按生成的图像数量计算
+pub fn image_cost(model_config: &ModelConfig, count: u64) -> f64 {
+    model_config.resolved_model_info().output_cost_per_image.unwrap_or_default() * count as f64
+}
+
+/// 重排序的成本：按 search unit（上游未报告时每次请求计 1 个）和 token 计算
+pub fn rerank_cost(model_config: &ModelConfig, usage: &RerankUsage) -> f64 {
+    let info = model_config.resolved_model_info();
+    info.input_cost_per_request.unwrap_or_default()
+        + info.input_cost_per_query.unwrap_or_default() * usage.search_units.unwrap_or(1) as f64
+        + info.input_cost_per_token.unwrap_or_default() * usage.total_tokens as f64
+}
+
+/// 文本转语音的成本：按输入字符数计算
+pub fn speech_cost(model_config: &ModelConfig, characters: usize) -> f64 {
+    let info = model_config.resolved_model_info();
+    info.input_cost_per_request.unwrap_or_default()
+        + info.input_cost_per_character.unwrap_or_default() * characters as f64
+}
+
+/// 语音转文本的成本：按音频时长（秒）计算
+pub fn transcription_cost(model_config: &ModelConfig, seconds: f64) -> f64 {
+    let info = model_config.resolved_model_info();
+    info.input_cost_per_request.unwrap_or_default() + info.input_cost_per_second.unwrap_or_default() * seconds
+}
+
+/// 记录当前请求的花费
+pub fn record_cost(config: &Config, cost: f64) {
+    record_for(&recorded_subjects(config), cost);
+}
+
+/// 按部署的单价记录当前请求的 token 花费，并按实际用量扣除 TPM
+pub fn record_usage(config: &Config, model_config: &ModelConfig, input_tokens: u64, output_tokens: u64) {
+    record_cost(config, token_cost(model_config, input_tokens, output_tokens));
+    TokenLimits::for_request(config, model_config).consume(input_tokens + output_tokens);
+}
+
+/// 花费计入各主体；写入失败只记录日志，不影响已经完成的请求
+fn record_for(subjects: &[Subject], cost: f64) {
+    if cost <= 0.0 {
+        return;
+    }
+    metrics::global_metrics().record_spend(cost);
+
+    let tracker = tracker();
+    for subject in subjects {
+        if let Err(e) = tracker.add(subject.entity, &subject.id, cost, &subject.budget) {
+            warn!("记录 {} 的花费失败: {}", subject.display_name, e);
+        }
+    }
+}
+
+/// 流式响应：从 SSE 事件中读取上游报告的用量，流结束（或客户端断开）时记录花费并扣除 TPM
+///
+/// 支持 OpenAI（chat/completions/responses）、Anthropic 和 Gemini 的流式事件格式；
+/// 上游没有报告用量时不计费。
+pub fn track_stream(
+    stream: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>,
+    config: &Config,
+    model_config: &ModelConfig,
+) -> Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>> {
+    let mut usage = StreamUsage {
+        buffer: Vec::new(),
+        input_tokens: 0,
+        output_tokens: 0,
+        model_config: model_config.clone(),
+        subjects: recorded_subjects(config),
+        token_limits: TokenLimits::for_request(config, model_config),
+    };
+
+    Box::pin(stream.inspect(move |result| {
+        if let Ok(bytes) = result {
+            usage.feed(bytes);
+        }
+    }))
+}
+
+/// 语音转文本响应：读取响应中的音频时长（`verbose_json` 的 `duration` 或 `usage.seconds`），响应结束时记录花费
+///
+/// 响应不是 JSON 或没有报告时长时只计每次请求的固定成本。
+pub fn track_transcription(response: RawResponse, config: &Config, model_config: &ModelConfig) -> RawResponse {
+    let mut usage = TranscriptionUsage {
+        buffer: Vec::new(),
+        model_config: model_config.clone(),
+        subjects: recorded_subjects(config),
+    };
+
+    RawResponse {
+        content_type: response.content_type,
+        body: Box::pin(response.body.inspect(move |result| {
+            if let Ok(bytes) = result {
+                usage.feed(bytes);
+            }
+        })),
+    }
+}
+
+/// 语音转文本响应体（只保留前 TRANSCRIPTION_BUFFER_LIMIT 字节），drop 时记录花费
+struct TranscriptionUsage {
+    buffer: Vec<u8>,
+    model_config: ModelConfig,
+    subjects: Vec<Subject>,
+}
+
+/// 为读取时长最多缓存的响应体大小
+const TRANSCRIPTION_BUFFER_LIMIT: usize = 1 << 20;
+
+impl TranscriptionUsage {
+    fn feed(&mut self, bytes: &Bytes) {
+        let room = TRANSCRIPTION_BUFFER_LIMIT.saturating_sub(self.buffer.len());
+        self.buffer.extend_from_slice(&bytes[..bytes.len().min(room)]);
+    }
+}
+
+impl Drop for TranscriptionUsage {
+    fn drop(&mut self) {
+        let seconds = serde_json::from_slice::<Value>(&self.buffer)
+            .ok()
+            .and_then(|value| value["duration"].as_f64().or_else(|| value["usage"]["seconds"].as_f64()))
+            .unwrap_or_default();
+        record_for(&self.subjects, transcription_cost(&self.model_config, seconds));
+    }
+}
+
+/// 流式响应中累计的用量，drop 时记录花费并扣除 TPM
+struct StreamUsage {
+    /// 未完整的事件按字节缓存，只解码完整的事件
+    buffer: Vec<u8>,
+    input_tokens: u64,
+    output_tokens: u64,
+    model_config: ModelConfig,
+    subjects: Vec<Subject>,
+    token_limits: TokenLimits,
+}
+
+impl StreamUsage {
+    fn feed(&mut self, bytes: &Bytes) {
+        self.buffer.extend_from_slice(bytes);
+        while let Some(pos) = self.buffer.windows(2).position(|window| window == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..pos + 2).collect();
+            let event = String::from_utf8_lossy(&event);
+            let Some(data) = event.lines().find_map(|line| line.strip_prefix("data: ")) else {
+                continue;
+            };
+            if let Ok(value) = serde_json::from_str::<Value>(data) {
+                let (input, output) = event_usage(&value);
+                self.input_tokens = input.unwrap_or(self.input_tokens);
+                self.output_tokens = output.unwrap_or(self.output_tokens);
+            }
+        }
+    }
+}
+
+impl Drop for StreamUsage {
+    fn drop(&mut self) {
+        let cost = token_cost(&self.model_config, self.input_tokens, self.output_tokens);
+        record_for(&self.subjects, cost);
+        self.token_limits.consume(self.input_tokens + self.output_tokens);
+    }
+}
+
+/// 从一个流式事件中读取用量（输入 token, 输出 token）
+///
+/// Gemini 每个事件报告累计用量，Anthropic 在 message_start 中报告输入、在 message_delta 中报告输出，
+/// OpenAI 在最后一个事件中报告，因此总是取最新出现的值。
+fn event_usage(value: &Value) -> (Option<u64>, Option<u64>) {
+    let usage = [
+        &value["usage"],
+        &value["response"]["usage"],
+        &value["message"]["usage"],
+        &value["usageMetadata"],
+    ]
+    .into_iter()
+    .find(|usage| usage.is_object());
+    let Some(usage) = usage else {
+        return (None, None);
+    };
+
+    let count = |fields: [&str; 3]| fields.iter().find_map(|field| usage[field].as_u64());
+    (
+        count(["prompt_tokens", "input_tokens", "promptTokenCount"]),
+        count(["completion_tokens", "output_tokens", "candidatesTokenCount"]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LitellmParams, ModelInfo};
+    use serde_json::json;
+
+    fn test_tracker() -> SpendTracker {
+        SpendTracker::new(Box::leak(Box::new(Database::open_in_memory().unwrap())))
+    }
+
+    fn priced_model() -> ModelConfig {
+        ModelConfig {
+            model_name: "gpt-4o".to_string(),
+            litellm_params: LitellmParams {
+                model: "openai/gpt-4o".to_string(),
+                api_key: "sk-test".to_string(),
+                api_base: String::new(),
+                ..Default::default()
+            },
+            model_info: ModelInfo {
+                input_cost_per_token: Some(0.000002),
+                output_cost_per_token: Some(0.00001),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spend_rolls_up_to_team_and_organization() {
+        use crate::auth::teams::{self, EntitySettings, NewOrganization, NewTeam};
+
+        let org = teams::team_store()
+            .create_organization(NewOrganization {
+                settings: EntitySettings {
+                    max_budget: Some(0.5),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let team = teams::team_store()
+            .create_team(NewTeam {
+                organization_id: Some(org.organization_id.clone()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let keys = auth::keys::test_store();
+        let (api_key, _) = keys
+            .generate(auth::keys::NewKey {
+                team_id: Some(team.team_id.clone()),
+                ..Default::default()
+            }, "master_key")
+            .unwrap();
+        let caller = Caller::Key(keys.lookup(&api_key).unwrap().unwrap());
+        let config = Config::default();
+
+        auth::with_caller(caller, async {
+            assert!(check_budgets(&config).is_ok());
+            record_cost(&config, 0.6);
+            // 组织预算约束其下所有团队和 key
+            assert!(matches!(
+                check_budgets(&config),
+                Err(FeatherGateError::BudgetExceeded(name, _, _)) if name == format!("organization {}", org.organization_id)
+            ));
+        })
+        .await;
+        let team_spend = tracker().summary(EntityType::Team, &team.team_id).unwrap().spend;
+        assert!((team_spend - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_token_cost_uses_deployment_pricing() {
+        let cost = token_cost(&priced_model(), 1000, 100);
+        assert!((cost - 0.003).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rerank_and_audio_cost() {
+        let mut model = priced_model();
+        model.model_info.input_cost_per_request = Some(0.001);
+        model.model_info.input_cost_per_query = Some(0.002);
+        model.model_info.input_cost_per_character = Some(0.00001);
+        model.model_info.input_cost_per_second = Some(0.0001);
+
+        // 未报告 search unit 时按 1 个计：0.001 + 0.002 + 100 * 0.000002
+        let usage = RerankUsage {
+            total_tokens: 100,
+            search_units: None,
+        };
+        assert!((rerank_cost(&model, &usage) - 0.0032).abs() < 1e-12);
+        assert!((speech_cost(&model, 100) - 0.002).abs() < 1e-12);
+        assert!((transcription_cost(&model, 60.0) - 0.007).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_image_cost_uses_deployment_pricing() {
+        let mut model = priced_model();
+        assert_eq!(image_cost(&model, 2), 0.0);
+        model.model_info.output_cost_per_image = Some(0.04);
+        assert!((image_cost(&model, 2) - 0.08).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_track_transcription_records_duration() {
+        let mut model = priced_model();
+        model.model_info.input_cost_per_second = Some(0.0001);
+        let mut config = Config::default();
+        config
+            .general_settings
+            .user_budgets
+            .insert("transcription-user".to_string(), BudgetConfig::default());
+        let chunks = vec![
+            Ok(Bytes::from(r#"{"text": "hi", "dura"#)),
+            Ok(Bytes::from(r#"tion": 12.5}"#)),
+        ];
+        let response = RawResponse {
+            content_type: Some("application/json".to_string()),
+            body: Box::pin(futures_util::stream::iter(chunks)),
+        };
+
+        // 计费主体在创建时确定，响应体在请求上下文之外读完也会记到该用户
+        let response = auth::with_caller(Caller::Anonymous, async {
+            auth::set_end_user(Some("transcription-user"));
+            track_transcription(response, &config, &model)
+        })
+        .await;
+        let body: Vec<_> = response.body.collect().await;
+        assert_eq!(body.len(), 2);
+
+        let spend = tracker().summary(EntityType::User, "transcription-user").unwrap().spend;
+        assert!((spend - 0.00125).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_only_budgeted_end_users_are_tracked() {
+        let mut config = Config::default();
+        config
+            .general_settings
+            .user_budgets
+            .insert("budgeted-end-user".to_string(), BudgetConfig::default());
+
+        let end_users = |user: &'static str| {
+            let config = &config;
+            auth::with_caller(Caller::Anonymous, async move {
+                auth::set_end_user(Some(user));
+                subjects(config).unwrap().into_iter().map(|s| s.id).collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(end_users("budgeted-end-user").await, ["budgeted-end-user"]);
+        assert!(end_users("random-end-user").await.is_empty());
+    }
+
+    #[test]
+    fn test_spend_accumulates_and_resets() {
+        let tracker = test_tracker();
+        let daily = BudgetConfig {
+            max_budget: Some(1.0),
+            budget_duration: Some("1d".to_string()),
+        };
+
+        tracker.add(EntityType::Team, "search", 0.4, &daily).unwrap();
+        tracker.add(EntityType::Team, "search", 0.4, &daily).unwrap();
+        let summary = tracker.summary(EntityType::Team, "search").unwrap();
+        assert!((summary.spend - 0.8).abs() < 1e-9);
+        assert!(summary.budget_reset_at.unwrap() > Utc::now());
+
+        let subject = Subject {
+            entity: EntityType::Team,
+            id: "search".to_string(),
+            display_name: "team search".to_string(),
+            budget: daily.clone(),
+        };
+        assert!(tracker.check(&subject).is_ok());
+        tracker.add(EntityType::Team, "search", 0.3, &daily).unwrap();
+        assert!(matches!(
+            tracker.check(&subject),
+            Err(FeatherGateError::BudgetExceeded(name, _, max)) if name == "team search" && max == 1.0
+        ));
+
+        // 花费批量写入数据库，重启后从数据库恢复
+        tracker.flush().unwrap();
+        let restarted = SpendTracker::new(tracker.db);
+        let summary = restarted.summary(EntityType::Team, "search").unwrap();
+        assert!((summary.spend - 1.1).abs() < 1e-9);
+
+        // 预算周期结束后花费清零
+        tracker
+            .local
+            .lock()
+            .unwrap()
+            .get_mut(&(EntityType::Team, "search".to_string()))
+            .unwrap()
+            .budget_reset_at = Some(Utc::now());
+        assert_eq!(tracker.summary(EntityType::Team, "search").unwrap().spend, 0.0);
+        tracker.add(EntityType::Team, "search", 0.2, &daily).unwrap();
+        let summary = tracker.summary(EntityType::Team, "search").unwrap();
+        assert!((summary.spend - 0.2).abs() < 1e-9);
+        assert!(summary.budget_reset_at.unwrap() > Utc::now());
+    }
+
+    #[test]
+    fn test_only_budgeted_spend_is_shared_and_ended_periods_are_evicted() {
+        let tracker = test_tracker();
+        let unlimited = BudgetConfig {
+            max_budget: None,
+            budget_duration: Some("1d".to_string()),
+        };
+        let daily = BudgetConfig {
+            max_budget: Some(1.0),
+            ..unlimited.clone()
+        };
+        let team = (EntityType::Team, "evicted".to_string());
+        let user = (EntityType::User, "evicted".to_string());
+
+        tracker.add(EntityType::User, "evicted", 0.1, &unlimited).unwrap();
+        tracker.add(EntityType::Team, "evicted", 0.2, &daily).unwrap();
+        assert!(!tracker.shared.lock().unwrap().contains_key(&user));
+        assert!(tracker.shared.lock().unwrap().contains_key(&team));
+
+        // 周期未结束时保留
+        tracker.flush().unwrap();
+        assert_eq!(tracker.local.lock().unwrap().len(), 2);
+
+        // 周期结束后，已写入数据库的本地花费被移除；还有未同步花费的共享条目保留到同步之后
+        let ended = Some(Utc::now());
+        for local in tracker.local.lock().unwrap().values_mut() {
+            local.budget_reset_at = ended;
+        }
+        tracker.shared.lock().unwrap().get_mut(&team).unwrap().budget_reset_at = ended;
+        tracker.flush().unwrap();
+        assert!(tracker.local.lock().unwrap().is_empty());
+        assert!(tracker.shared.lock().unwrap().contains_key(&team));
+
+        tracker.shared.lock().unwrap().get_mut(&team).unwrap().pending = 0.0;
+        tracker.flush().unwrap();
+        assert!(tracker.shared.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shared_spend_across_replicas() {
+        let Some(mut conn) = crate::cluster::test_connection().await else {
+            return;
+        };
+        let replica_a = test_tracker();
+        let replica_b = test_tracker();
+        let team = uuid::Uuid::new_v4().to_string();
+        let budget = BudgetConfig {
+            max_budget: Some(1.0),
+            budget_duration: Some("1d".to_string()),
+        };
+        let subject = Subject {
+            entity: EntityType::Team,
+            id: team.clone(),
+            display_name: format!("team {}", team),
+            budget: budget.clone(),
+        };
+
+        replica_a.add(EntityType::Team, &team, 0.6, &budget).unwrap();
+        replica_b.add(EntityType::Team, &team, 0.5, &budget).unwrap();
+        // 每个副本单独都没有超出预算
+        assert!(replica_a.check(&subject).is_ok());
+        assert!(replica_b.check(&subject).is_ok());
+
+        replica_a.sync(&mut conn).await.unwrap();
+        replica_b.sync(&mut conn).await.unwrap();
+        assert!(matches!(
+            replica_b.check(&subject),
+            Err(FeatherGateError::BudgetExceeded(_, spend, _)) if (spend - 1.1).abs() < 1e-9
+        ));
+        replica_a.sync(&mut conn).await.unwrap();
+        assert!(replica_a.check(&subject).is_err());
+    }
+
+    #[test]
+    fn test_event_usage_formats() {
+        let openai = json!({"choices": [], "usage": {"prompt_tokens": 10, "completion_tokens": 5}});
+        assert_eq!(event_usage(&openai), (Some(10), Some(5)));
+
+        let anthropic_start = json!({"type": "message_start", "message": {"usage": {"input_tokens": 12, "output_tokens": 1}}});
+        assert_eq!(event_usage(&anthropic_start), (Some(12), Some(1)));
+        let anthropic_delta = json!({"type": "message_delta", "usage": {"output_tokens": 30}});
+        assert_eq!(event_usage(&anthropic_delta), (None, Some(30)));
+
+        let responses = json!({"type": "response.completed", "response": {"usage": {"input_tokens": 7, "output_tokens": 3}}});
+        assert_eq!(event_usage(&responses), (Some(7), Some(3)));
+
+        let gemini = json!({"usageMetadata": {"promptTokenCount": 4, "candidatesTokenCount": 9}});
+        assert_eq!(event_usage(&gemini), (Some(4), Some(9)));
+
+        assert_eq!(event_usage(&json!({"choices": []})), (None, None));
+    }
+
+    #[test]
+    fn test_stream_usage_reads_events_split_inside_characters() {
+        let mut usage = StreamUsage {
+            buffer: Vec::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            model_config: priced_model(),
+            subjects: Vec::new(),
+            token_limits: TokenLimits::default(),
+        };
+        let event = "data: {\"choices\":[{\"delta\":{\"content\":\"你好\"}}],\"usage\":{\"prompt_tokens\":10,\"completion_tokens\":5}}\n\n";
+        let split = event.find("你").unwrap() + 1;
+
+        usage.feed(&Bytes::copy_from_slice(&event.as_bytes()[..split]));
+        assert_eq!(usage.buffer.len(), split);
+        usage.feed(&Bytes::copy_from_slice(&event.as_bytes()[split..]));
+        assert!(usage.buffer.is_empty());
+        assert_eq!((usage.input_tokens, usage.output_tokens), (10, 5));
+    }
+}
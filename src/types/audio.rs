@@ -0,0 +1,54 @@
+use super::ValidationError;
+use crate::Result;
+use futures_util::Stream;
+use hyper::body::Bytes;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+/// OpenAI 兼容的文本转语音请求（/v1/audio/speech）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechRequest {
+    pub model: String,
+    pub input: String,
+    pub voice: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+impl SpeechRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> std::result::Result<(), ValidationError> {
+        if self.input.is_empty() {
+            return Err(ValidationError::EmptyInput);
+        }
+
+        Ok(())
+    }
+}
+
+/// 语音转文本请求（/v1/audio/transcriptions 的 multipart 表单）
+pub struct TranscriptionRequest {
+    pub model: String,
+    /// 除 model 和 file 以外的表单字段（language、prompt、response_format 等），原样转发
+    pub fields: Vec<(String, String)>,
+    pub file: AudioFile,
+}
+
+/// 上传的音频文件
+pub struct AudioFile {
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub body: AudioBody,
+}
+
+/// 音频文件内容
+pub enum AudioBody {
+    /// 文件在 model 字段之前上传时，需要先缓冲才能确定路由
+    Buffered(Bytes),
+    /// 边接收边转发到上游
+    Streaming(Pin<Box<dyn Stream<Item = Result<Bytes>> + Send + Sync>>),
+}
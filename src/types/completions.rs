@@ -0,0 +1,134 @@
+use super::{Stop, StreamOptions, Usage, ValidationError};
+use serde::{Deserialize, Serialize};
+
+/// 旧版 OpenAI 文本补全请求（/v1/completions）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionRequest {
+    pub model: String,
+    pub prompt: Prompt,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub echo: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_of: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Stop>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// prompt 可以是字符串、字符串数组或 token 数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Prompt {
+    Text(String),
+    TextBatch(Vec<String>),
+    Tokens(Vec<u32>),
+    TokenBatch(Vec<Vec<u32>>),
+}
+
+impl Prompt {
+    /// 文本形式的 prompt 列表（token 数组返回 None）
+    pub fn texts(&self) -> Option<Vec<&str>> {
+        match self {
+            Prompt::Text(text) => Some(vec![text.as_str()]),
+            Prompt::TextBatch(texts) => Some(texts.iter().map(String::as_str).collect()),
+            Prompt::Tokens(_) | Prompt::TokenBatch(_) => None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Prompt::Text(_) => false,
+            Prompt::TextBatch(texts) => texts.is_empty(),
+            Prompt::Tokens(tokens) => tokens.is_empty(),
+            Prompt::TokenBatch(batch) => batch.is_empty(),
+        }
+    }
+}
+
+impl CompletionRequest {
+    /// 验证请求参数范围
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(temp) = self.temperature {
+            if !(0.0..=2.0).contains(&temp) {
+                return Err(ValidationError::TemperatureOutOfRange(temp));
+            }
+        }
+
+        if let Some(top_p) = self.top_p {
+            if !(0.0..=1.0).contains(&top_p) {
+                return Err(ValidationError::TopPOutOfRange(top_p));
+            }
+        }
+
+        if self.prompt.is_empty() {
+            return Err(ValidationError::EmptyPrompt);
+        }
+
+        Ok(())
+    }
+}
+
+/// 文本补全响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionResponse {
+    pub id: String,
+    pub object: String,
+    pub created: u64,
+    pub model: String,
+    pub choices: Vec<CompletionChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+/// 文本补全选择
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletionChoice {
+    pub text: String,
+    pub index: u32,
+    pub logprobs: Option<serde_json::Value>,
+    pub finish_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_variants() {
+        let req: CompletionRequest =
+            serde_json::from_str(r#"{"model": "m", "prompt": "Say hi"}"#).unwrap();
+        assert_eq!(req.prompt.texts(), Some(vec!["Say hi"]));
+
+        let req: CompletionRequest =
+            serde_json::from_str(r#"{"model": "m", "prompt": ["a", "b"], "echo": true}"#).unwrap();
+        assert_eq!(req.prompt.texts(), Some(vec!["a", "b"]));
+        assert_eq!(req.echo, Some(true));
+
+        let req: CompletionRequest =
+            serde_json::from_str(r#"{"model": "m", "prompt": [1, 2, 3]}"#).unwrap();
+        assert_eq!(req.prompt, Prompt::Tokens(vec![1, 2, 3]));
+        assert_eq!(req.prompt.texts(), None);
+    }
+
+    #[test]
+    fn test_validate_empty_prompt() {
+        let req: CompletionRequest =
+            serde_json::from_str(r#"{"model": "m", "prompt": []}"#).unwrap();
+        assert_eq!(req.validate().unwrap_err(), ValidationError::EmptyPrompt);
+    }
+}
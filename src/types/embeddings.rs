@@ -0,0 +1,186 @@
+use super::ValidationError;
+use serde::{Deserialize, Serialize};
+
+/// OpenAI 兼容的向量嵌入请求（/v1/embeddings）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRequest {
+    pub model: String,
+    pub input: EmbeddingInput,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<EncodingFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// input 可以是字符串、字符串数组或 token 数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    Text(String),
+    TextBatch(Vec<String>),
+    Tokens(Vec<u32>),
+    TokenBatch(Vec<Vec<u32>>),
+}
+
+impl EmbeddingInput {
+    /// 文本形式的 input 列表（token 数组返回 None）
+    pub fn texts(&self) -> Option<Vec<&str>> {
+        match self {
+            EmbeddingInput::Text(text) => Some(vec![text.as_str()]),
+            EmbeddingInput::TextBatch(texts) => Some(texts.iter().map(String::as_str).collect()),
+            EmbeddingInput::Tokens(_) | EmbeddingInput::TokenBatch(_) => None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            EmbeddingInput::Text(_) => false,
+            EmbeddingInput::TextBatch(texts) => texts.is_empty(),
+            EmbeddingInput::Tokens(tokens) => tokens.is_empty(),
+            EmbeddingInput::TokenBatch(batch) => batch.is_empty(),
+        }
+    }
+}
+
+/// 向量编码格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodingFormat {
+    #[default]
+    Float,
+    /// 小端 f32 字节序列的 base64 编码
+    Base64,
+}
+
+impl EmbeddingRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.input.is_empty() {
+            return Err(ValidationError::EmptyInput);
+        }
+
+        Ok(())
+    }
+}
+
+/// 向量嵌入响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingResponse {
+    pub object: String,
+    pub data: Vec<Embedding>,
+    pub model: String,
+    pub usage: EmbeddingUsage,
+}
+
+/// 单条向量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Embedding {
+    pub object: String,
+    pub embedding: EmbeddingVector,
+    pub index: u32,
+}
+
+/// 向量数据（float 数组或 base64 字符串）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingVector {
+    Float(Vec<f32>),
+    Base64(String),
+}
+
+impl EmbeddingVector {
+    /// 按请求的编码格式构造向量
+    pub fn encode(values: Vec<f32>, format: EncodingFormat) -> Self {
+        use base64::Engine;
+        match format {
+            EncodingFormat::Float => EmbeddingVector::Float(values),
+            EncodingFormat::Base64 => {
+                let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+                EmbeddingVector::Base64(base64::engine::general_purpose::STANDARD.encode(bytes))
+            }
+        }
+    }
+}
+
+/// 向量嵌入的 token 用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    pub prompt_tokens: u32,
+    pub total_tokens: u32,
+}
+
+impl EmbeddingResponse {
+    /// 由按输入顺序排列的向量构造响应
+    pub fn new(
+        model: impl Into<String>,
+        vectors: Vec<Vec<f32>>,
+        format: EncodingFormat,
+        prompt_tokens: u32,
+    ) -> Self {
+        EmbeddingResponse {
+            object: "list".to_string(),
+            data: vectors
+                .into_iter()
+                .enumerate()
+                .map(|(index, values)| Embedding {
+                    object: "embedding".to_string(),
+                    embedding: EmbeddingVector::encode(values, format),
+                    index: index as u32,
+                })
+                .collect(),
+            model: model.into(),
+            usage: EmbeddingUsage {
+                prompt_tokens,
+                total_tokens: prompt_tokens,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_variants() {
+        let req: EmbeddingRequest =
+            serde_json::from_str(r#"{"model": "m", "input": "hello"}"#).unwrap();
+        assert_eq!(req.input.texts(), Some(vec!["hello"]));
+        assert_eq!(req.encoding_format, None);
+
+        let req: EmbeddingRequest = serde_json::from_str(
+            r#"{"model": "m", "input": ["a", "b"], "dimensions": 256, "encoding_format": "base64"}"#,
+        )
+        .unwrap();
+        assert_eq!(req.input.texts(), Some(vec!["a", "b"]));
+        assert_eq!(req.dimensions, Some(256));
+        assert_eq!(req.encoding_format, Some(EncodingFormat::Base64));
+
+        let req: EmbeddingRequest =
+            serde_json::from_str(r#"{"model": "m", "input": [[1, 2], [3]]}"#).unwrap();
+        assert_eq!(req.input, EmbeddingInput::TokenBatch(vec![vec![1, 2], vec![3]]));
+        assert_eq!(req.input.texts(), None);
+    }
+
+    #[test]
+    fn test_validate_empty_input() {
+        let req: EmbeddingRequest =
+            serde_json::from_str(r#"{"model": "m", "input": []}"#).unwrap();
+        assert_eq!(req.validate().unwrap_err(), ValidationError::EmptyInput);
+    }
+
+    #[test]
+    fn test_base64_encoding() {
+        let vector = EmbeddingVector::encode(vec![1.0, -2.5], EncodingFormat::Base64);
+        // 1.0 = 0x3F800000, -2.5 = 0xC0200000（小端）
+        assert_eq!(vector, EmbeddingVector::Base64("AACAPwAAIMA=".to_string()));
+
+        let resp = EmbeddingResponse::new("m", vec![vec![0.5]], EncodingFormat::Float, 3);
+        let json = serde_json::to_value(&resp).unwrap();
+        assert_eq!(json["object"], "list");
+        assert_eq!(json["data"][0]["embedding"], serde_json::json!([0.5]));
+        assert_eq!(json["usage"]["total_tokens"], 3);
+    }
+}
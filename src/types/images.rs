@@ -0,0 +1,123 @@
+use super::ValidationError;
+use serde::{Deserialize, Serialize};
+
+/// OpenAI 兼容的图像生成请求（/v1/images/generations）
+///
+/// 未建模的字段（background、output_format、moderation 等）保存在 `extra` 中，透传到 OpenAI 时原样保留。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageGenerationRequest {
+    pub model: String,
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ImageResponseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 图像返回格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageResponseFormat {
+    #[default]
+    Url,
+    B64Json,
+}
+
+impl ImageGenerationRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.prompt.is_empty() {
+            return Err(ValidationError::EmptyPrompt);
+        }
+
+        if let Some(n) = self.n {
+            if !(1..=10).contains(&n) {
+                return Err(ValidationError::ImageCountOutOfRange(n));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 图像生成响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageResponse {
+    pub created: u64,
+    pub data: Vec<ImageData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<serde_json::Value>,
+}
+
+/// 单张图像
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub b64_json: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revised_prompt: Option<String>,
+}
+
+impl ImageData {
+    /// 由 base64 图像数据构造，`url` 格式返回 data URL
+    pub fn from_base64(
+        b64_json: String,
+        mime_type: &str,
+        format: ImageResponseFormat,
+    ) -> Self {
+        match format {
+            ImageResponseFormat::B64Json => ImageData {
+                b64_json: Some(b64_json),
+                ..Default::default()
+            },
+            ImageResponseFormat::Url => ImageData {
+                url: Some(format!("data:{};base64,{}", mime_type, b64_json)),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_image_request() {
+        let req: ImageGenerationRequest = serde_json::from_str(
+            r#"{"model": "dall-e-3", "prompt": "a cat", "response_format": "b64_json"}"#,
+        )
+        .unwrap();
+        assert!(req.validate().is_ok());
+        assert_eq!(req.response_format, Some(ImageResponseFormat::B64Json));
+
+        let req: ImageGenerationRequest =
+            serde_json::from_str(r#"{"model": "dall-e-3", "prompt": "a cat", "n": 11}"#).unwrap();
+        assert_eq!(
+            req.validate().unwrap_err(),
+            ValidationError::ImageCountOutOfRange(11)
+        );
+    }
+
+    #[test]
+    fn test_image_data_from_base64() {
+        let data = ImageData::from_base64("aGk=".to_string(), "image/png", ImageResponseFormat::Url);
+        assert_eq!(data.url.as_deref(), Some("data:image/png;base64,aGk="));
+        assert!(data.b64_json.is_none());
+
+        let json = serde_json::to_value(&data).unwrap();
+        assert!(json.get("b64_json").is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use super::ValidationError;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// litellm 兼容的生成 key 请求（/key/generate）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenerateKeyRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_alias: Option<String>,
+    /// 允许使用的 model_name，空列表表示不限制
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// 有效期（如 `30d`），未指定时不过期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub blocked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<String>,
+    /// 预算上限（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_budget: Option<f64>,
+    /// 预算周期（如 `30d`），到期后花费清零
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_duration: Option<String>,
+    /// 每分钟请求数限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm_limit: Option<u64>,
+    /// 每分钟 token 数限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm_limit: Option<u64>,
+}
+
+impl GenerateKeyRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        expires_after(self.duration.as_deref())?;
+        validate_budget_duration(self.budget_duration.as_deref())
+    }
+
+    /// 按 duration 计算的过期时间
+    pub fn expires(&self) -> Option<DateTime<Utc>> {
+        expires_after(self.duration.as_deref()).ok().flatten()
+    }
+}
+
+/// litellm 兼容的更新 key 请求（/key/update、/key/regenerate）
+///
+/// 只修改请求中出现的字段。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateKeyRequest {
+    /// 明文 key 或 token（哈希）
+    #[serde(default)]
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models: Option<Vec<String>>,
+    /// 从现在起的有效期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<String>,
+    /// 预算上限（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_budget: Option<f64>,
+    /// 预算周期（如 `30d`），到期后花费清零
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_duration: Option<String>,
+    /// 每分钟请求数限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm_limit: Option<u64>,
+    /// 每分钟 token 数限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm_limit: Option<u64>,
+}
+
+impl UpdateKeyRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.key.is_empty() {
+            return Err(ValidationError::MissingField("key"));
+        }
+        expires_after(self.duration.as_deref())?;
+        validate_budget_duration(self.budget_duration.as_deref())
+    }
+
+    /// 按 duration 计算的新过期时间（未指定 duration 时为 None，表示不修改）
+    pub fn expires(&self) -> Option<DateTime<Utc>> {
+        expires_after(self.duration.as_deref()).ok().flatten()
+    }
+}
+
+/// litellm 兼容的删除 key 请求（/key/delete），按 key 或别名删除
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteKeyRequest {
+    /// 明文 key 或 token（哈希）
+    #[serde(default)]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub key_aliases: Vec<String>,
+}
+
+impl DeleteKeyRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.keys.is_empty() && self.key_aliases.is_empty() {
+            return Err(ValidationError::MissingField("keys"));
+        }
+        Ok(())
+    }
+}
+
+/// /key/list 的查询参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListKeysQuery {
+    #[serde(default = "default_page")]
+    pub page: u32,
+    #[serde(default = "default_page_size")]
+    pub size: u32,
+    pub key_alias: Option<String>,
+    pub user_id: Option<String>,
+    pub team_id: Option<String>,
+    /// 返回完整的 key 信息，默认只返回 token
+    #[serde(default)]
+    pub return_full_object: bool,
+}
+
+fn default_page() -> u32 {
+    1
+}
+
+fn default_page_size() -> u32 {
+    10
+}
+
+/// 解析 litellm 风格的时长：`30s`、`30m`、`30h`、`30d`、`2w`、`1mo`（按 30 天计）
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().ok()?;
+
+    match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        "mo" => Duration::try_days(amount.checked_mul(30)?),
+        _ => None,
+    }
+}
+
+/// budget_duration 必须是合法的时长
+pub(super) fn validate_budget_duration(duration: Option<&str>) -> Result<(), ValidationError> {
+    match duration {
+        Some(duration) if parse_duration(duration).is_none() => Err(
+            ValidationError::InvalidDuration("budget_duration", duration.to_string()),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// 从现在起经过 duration 后的时间
+fn expires_after(duration: Option<&str>) -> Result<Option<DateTime<Utc>>, ValidationError> {
+    let Some(duration) = duration else {
+        return Ok(None);
+    };
+    parse_duration(duration)
+        .and_then(|duration| Utc::now().checked_add_signed(duration))
+        .map(Some)
+        .ok_or_else(|| ValidationError::InvalidDuration("duration", duration.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(Duration::seconds(30)));
+        assert_eq!(parse_duration("15m"), Some(Duration::minutes(15)));
+        assert_eq!(parse_duration("1d"), Some(Duration::days(1)));
+        assert_eq!(parse_duration("1mo"), Some(Duration::days(30)));
+        assert_eq!(parse_duration("30"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("3y"), None);
+    }
+
+    #[test]
+    fn test_update_request_validation() {
+        let req: UpdateKeyRequest = serde_json::from_str(r#"{"duration": "7d"}"#).unwrap();
+        assert_eq!(req.validate(), Err(ValidationError::MissingField("key")));
+
+        let req: UpdateKeyRequest =
+            serde_json::from_str(r#"{"key": "sk-fg-abc", "duration": "soon"}"#).unwrap();
+        assert_eq!(
+            req.validate(),
+            Err(ValidationError::InvalidDuration("duration", "soon".to_string()))
+        );
+
+        let req: DeleteKeyRequest = serde_json::from_str("{}").unwrap();
+        assert!(req.validate().is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use super::ValidationError;
+use serde::{Deserialize, Serialize};
+
+/// Anthropic 原生的消息请求（/v1/messages）
+///
+/// 未建模的字段（tool_choice、metadata、thinking 等）保存在 `extra` 中，透传到 Anthropic 时原样保留。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagesRequest {
+    pub model: String,
+    pub messages: Vec<InputMessage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<MessageContent>,
+    pub max_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<serde_json::Value>>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 输入消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMessage {
+    pub role: String,
+    pub content: MessageContent,
+}
+
+/// 消息内容（以及 system）可以是字符串或内容块数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+/// 内容块（text、image、tool_use、tool_result 等）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentBlock {
+    #[serde(rename = "type")]
+    pub block_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ContentBlock {
+    /// 文本块
+    pub fn text(text: impl Into<String>) -> Self {
+        ContentBlock {
+            block_type: "text".to_string(),
+            text: Some(text.into()),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl MessagesRequest {
+    /// 验证请求参数范围
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(temp) = self.temperature {
+            if !(0.0..=2.0).contains(&temp) {
+                return Err(ValidationError::TemperatureOutOfRange(temp));
+            }
+        }
+
+        if let Some(top_p) = self.top_p {
+            if !(0.0..=1.0).contains(&top_p) {
+                return Err(ValidationError::TopPOutOfRange(top_p));
+            }
+        }
+
+        if self.messages.is_empty() {
+            return Err(ValidationError::EmptyMessages);
+        }
+
+        Ok(())
+    }
+}
+
+/// Anthropic 原生的消息响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessagesResponse {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub response_type: String,
+    pub role: String,
+    pub content: Vec<ContentBlock>,
+    pub model: String,
+    pub stop_reason: Option<String>,
+    #[serde(default)]
+    pub stop_sequence: Option<String>,
+    pub usage: MessagesUsage,
+}
+
+/// token 用量（cache_creation_input_tokens 等字段原样保留）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessagesUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_request_blocks() {
+        let req: MessagesRequest = serde_json::from_str(
+            r#"{
+            "model": "claude",
+            "max_tokens": 256,
+            "system": [{"type": "text", "text": "Be brief", "cache_control": {"type": "ephemeral"}}],
+            "messages": [
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": [{"type": "tool_use", "id": "t1", "name": "f", "input": {}}]}
+            ],
+            "tools": [{"name": "f", "input_schema": {"type": "object"}}],
+            "tool_choice": {"type": "auto"}
+        }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(&req.system, Some(MessageContent::Blocks(blocks)) if blocks[0].text.as_deref() == Some("Be brief")));
+        assert_eq!(req.messages[0].content, MessageContent::Text("Hi".to_string()));
+        let MessageContent::Blocks(blocks) = &req.messages[1].content else {
+            panic!("expected blocks");
+        };
+        assert_eq!(blocks[0].block_type, "tool_use");
+        assert_eq!(blocks[0].extra["name"], "f");
+
+        // 透传时保留未建模的字段
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["tool_choice"]["type"], "auto");
+        assert_eq!(json["system"][0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(json["messages"][1]["content"][0]["input"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_validate_empty_messages() {
+        let req: MessagesRequest =
+            serde_json::from_str(r#"{"model": "claude", "max_tokens": 16, "messages": []}"#).unwrap();
+        assert_eq!(req.validate(), Err(ValidationError::EmptyMessages));
+    }
+}
@@ -1,4 +1,18 @@
+use crate::i18n::{self, Locale, MessageKey};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+pub mod audio;
+pub mod completions;
+pub mod embeddings;
+pub mod images;
+pub mod keys;
+pub mod messages;
+pub mod moderations;
+pub mod rerank;
+pub mod responses;
+pub mod teams;
 
 /// OpenAI 兼容的聊天请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,42 +25,264 @@ pub struct ChatRequest {
     pub max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    /// 流式选项；客户端没有要求 `include_usage` 时，网关仍向上游请求用量，再从响应中去掉用量数据块
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
+    /// 停止序列
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Stop>,
+    /// 终端用户标识，计入该用户的预算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// OpenAI 流式选项
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_usage: Option<bool>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl StreamOptions {
+    /// 客户端是否要求在流的最后返回用量
+    pub fn includes_usage(options: Option<&StreamOptions>) -> bool {
+        options.and_then(|options| options.include_usage) == Some(true)
+    }
+
+    /// 在原有选项上开启 `include_usage`，用于向上游请求用量
+    pub fn with_usage(options: Option<&StreamOptions>) -> StreamOptions {
+        StreamOptions {
+            include_usage: Some(true),
+            ..options.cloned().unwrap_or_default()
+        }
+    }
+}
+
+/// stop 可以是单个字符串或字符串数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Stop {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl Stop {
+    /// 停止序列列表（Anthropic stop_sequences、Gemini stopSequences 均为数组）
+    pub fn sequences(&self) -> Vec<String> {
+        match self {
+            Stop::Single(stop) => vec![stop.clone()],
+            Stop::Multiple(stops) => stops.clone(),
+        }
+    }
 }
 
 impl ChatRequest {
     /// 验证请求参数范围
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), ValidationError> {
         // 验证 temperature (0.0 - 2.0)
         if let Some(temp) = self.temperature {
             if !(0.0..=2.0).contains(&temp) {
-                return Err(format!(
-                    "temperature 必须在 0.0 到 2.0 之间，当前值: {}",
-                    temp
-                ));
+                return Err(ValidationError::TemperatureOutOfRange(temp));
             }
         }
 
         // 验证 top_p (0.0 - 1.0)
         if let Some(top_p) = self.top_p {
             if !(0.0..=1.0).contains(&top_p) {
-                return Err(format!(
-                    "top_p 必须在 0.0 到 1.0 之间，当前值: {}",
-                    top_p
-                ));
+                return Err(ValidationError::TopPOutOfRange(top_p));
             }
         }
 
         // 验证 messages 非空
         if self.messages.is_empty() {
-            return Err("messages 不能为空".to_string());
+            return Err(ValidationError::EmptyMessages);
         }
 
         Ok(())
     }
 }
 
+/// 请求参数验证错误
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationError {
+    TemperatureOutOfRange(f32),
+    TopPOutOfRange(f32),
+    EmptyMessages,
+    EmptyPrompt,
+    /// 非 OpenAI 模型不支持 token 数组形式的 prompt
+    TokenPromptUnsupported,
+    /// 非 OpenAI 模型的流式补全只支持单个 prompt
+    BatchStreamUnsupported,
+    /// 非 OpenAI 模型的文本补全不支持该参数（参数名）
+    CompletionParameterUnsupported(&'static str),
+    EmptyInput,
+    /// 非 OpenAI 模型不支持 token 数组形式的 input
+    TokenInputUnsupported,
+    ImageCountOutOfRange(u32),
+    /// 缺少必需的表单字段
+    MissingField(&'static str),
+    /// 需要缓冲的音频文件超过上限（MB）
+    AudioFileTooLarge(usize),
+    /// 流式转发音频时 file 之后出现的表单字段（字段名）
+    FieldAfterFile(String),
+    /// 非 OpenAI 模型的 Responses API 只支持消息类型的输入项
+    UnsupportedInputItem(String),
+    /// previous_response_id 在本地存储中不存在
+    PreviousResponseNotFound(String),
+    /// 原生格式端点（/v1/messages 等）转换到其他 provider 时只支持文本内容块
+    UnsupportedContentBlock(String),
+    /// 原生格式端点转换到其他 provider 时不支持 tools
+    ToolsUnsupported,
+    /// 原生格式端点转换到其他 provider 时无法转换的参数（参数名）
+    NativeParameterUnsupported(&'static str),
+    EmptyQuery,
+    EmptyDocuments,
+    TopNOutOfRange(u32),
+    /// 时长格式无效（参数名, 原始值）
+    InvalidDuration(&'static str, String),
+}
+
+impl ValidationError {
+    /// 按指定语言渲染错误消息
+    pub fn message(&self, locale: Locale) -> String {
+        match self {
+            ValidationError::TemperatureOutOfRange(v) => {
+                i18n::message(MessageKey::TemperatureOutOfRange, locale, &[v])
+            }
+            ValidationError::TopPOutOfRange(v) => {
+                i18n::message(MessageKey::TopPOutOfRange, locale, &[v])
+            }
+            ValidationError::EmptyMessages => i18n::message(MessageKey::EmptyMessages, locale, &[]),
+            ValidationError::EmptyPrompt => i18n::message(MessageKey::EmptyPrompt, locale, &[]),
+            ValidationError::TokenPromptUnsupported => {
+                i18n::message(MessageKey::TokenPromptUnsupported, locale, &[])
+            }
+            ValidationError::BatchStreamUnsupported => {
+                i18n::message(MessageKey::BatchStreamUnsupported, locale, &[])
+            }
+            ValidationError::CompletionParameterUnsupported(param) => {
+                i18n::message(MessageKey::CompletionParameterUnsupported, locale, &[param])
+            }
+            ValidationError::EmptyInput => i18n::message(MessageKey::EmptyInput, locale, &[]),
+            ValidationError::TokenInputUnsupported => {
+                i18n::message(MessageKey::TokenInputUnsupported, locale, &[])
+            }
+            ValidationError::ImageCountOutOfRange(n) => {
+                i18n::message(MessageKey::ImageCountOutOfRange, locale, &[n])
+            }
+            ValidationError::MissingField(field) => {
+                i18n::message(MessageKey::MissingField, locale, &[field])
+            }
+            ValidationError::AudioFileTooLarge(limit) => {
+                i18n::message(MessageKey::AudioFileTooLarge, locale, &[limit])
+            }
+            ValidationError::FieldAfterFile(field) => {
+                i18n::message(MessageKey::FieldAfterFile, locale, &[field])
+            }
+            ValidationError::UnsupportedInputItem(item_type) => {
+                i18n::message(MessageKey::UnsupportedInputItem, locale, &[item_type])
+            }
+            ValidationError::PreviousResponseNotFound(id) => {
+                i18n::message(MessageKey::PreviousResponseNotFound, locale, &[id])
+            }
+            ValidationError::UnsupportedContentBlock(block_type) => {
+                i18n::message(MessageKey::UnsupportedContentBlock, locale, &[block_type])
+            }
+            ValidationError::ToolsUnsupported => {
+                i18n::message(MessageKey::ToolsUnsupported, locale, &[])
+            }
+            ValidationError::NativeParameterUnsupported(param) => {
+                i18n::message(MessageKey::NativeParameterUnsupported, locale, &[param])
+            }
+            ValidationError::EmptyQuery => i18n::message(MessageKey::EmptyQuery, locale, &[]),
+            ValidationError::EmptyDocuments => {
+                i18n::message(MessageKey::EmptyDocuments, locale, &[])
+            }
+            ValidationError::TopNOutOfRange(n) => {
+                i18n::message(MessageKey::TopNOutOfRange, locale, &[n])
+            }
+            ValidationError::InvalidDuration(field, value) => {
+                i18n::message(MessageKey::InvalidDuration, locale, &[field, value])
+            }
+        }
+    }
+
+    /// 出错的请求参数名
+    pub fn param(&self) -> &'static str {
+        match self {
+            ValidationError::TemperatureOutOfRange(_) => "temperature",
+            ValidationError::TopPOutOfRange(_) => "top_p",
+            ValidationError::EmptyMessages | ValidationError::UnsupportedContentBlock(_) => {
+                "messages"
+            }
+            ValidationError::ToolsUnsupported => "tools",
+            ValidationError::EmptyPrompt
+            | ValidationError::TokenPromptUnsupported
+            | ValidationError::BatchStreamUnsupported => "prompt",
+            ValidationError::EmptyInput
+            | ValidationError::TokenInputUnsupported
+            | ValidationError::UnsupportedInputItem(_) => "input",
+            ValidationError::ImageCountOutOfRange(_) => "n",
+            ValidationError::MissingField(field)
+            | ValidationError::CompletionParameterUnsupported(field)
+            | ValidationError::NativeParameterUnsupported(field) => field,
+            ValidationError::AudioFileTooLarge(_) | ValidationError::FieldAfterFile(_) => "file",
+            ValidationError::PreviousResponseNotFound(_) => "previous_response_id",
+            ValidationError::EmptyQuery => "query",
+            ValidationError::EmptyDocuments => "documents",
+            ValidationError::TopNOutOfRange(_) => "top_n",
+            ValidationError::InvalidDuration(field, _) => field,
+        }
+    }
+
+    /// OpenAI 错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::TemperatureOutOfRange(v) | ValidationError::TopPOutOfRange(v)
+                if *v < 0.0 =>
+            {
+                "decimal_below_min_value"
+            }
+            ValidationError::TemperatureOutOfRange(_) | ValidationError::TopPOutOfRange(_) => {
+                "decimal_above_max_value"
+            }
+            ValidationError::EmptyMessages
+            | ValidationError::EmptyPrompt
+            | ValidationError::EmptyInput
+            | ValidationError::EmptyDocuments => "empty_array",
+            ValidationError::EmptyQuery => "empty_string",
+            ValidationError::TokenPromptUnsupported
+            | ValidationError::BatchStreamUnsupported
+            | ValidationError::CompletionParameterUnsupported(_)
+            | ValidationError::TokenInputUnsupported
+            | ValidationError::UnsupportedInputItem(_)
+            | ValidationError::UnsupportedContentBlock(_)
+            | ValidationError::ToolsUnsupported
+            | ValidationError::NativeParameterUnsupported(_) => "unsupported_value",
+            ValidationError::InvalidDuration(..) | ValidationError::FieldAfterFile(_) => {
+                "invalid_value"
+            }
+            ValidationError::ImageCountOutOfRange(0) | ValidationError::TopNOutOfRange(_) => {
+                "integer_below_min_value"
+            }
+            ValidationError::ImageCountOutOfRange(_) => "integer_above_max_value",
+            ValidationError::MissingField(_) => "missing_required_parameter",
+            ValidationError::AudioFileTooLarge(_) => "file_too_large",
+            ValidationError::PreviousResponseNotFound(_) => "previous_response_not_found",
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(i18n::log_locale()))
+    }
+}
+
 /// 聊天消息
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Message {
@@ -183,7 +419,10 @@ mod tests {
             temperature: Some(0.7),
             max_tokens: Some(100),
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -274,7 +513,10 @@ mod tests {
             temperature: Some(1.0),
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
         assert!(req.validate().is_ok());
     }
@@ -287,9 +529,14 @@ mod tests {
             temperature: Some(3.0),
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
         assert!(req.validate().is_err());
+        assert_eq!(req.validate().unwrap_err().param(), "temperature");
+        assert_eq!(req.validate().unwrap_err().code(), "decimal_above_max_value");
     }
 
     #[test]
@@ -300,7 +547,10 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: Some(1.5),
+            stop: None,
+            user: None,
         };
         assert!(req.validate().is_err());
     }
@@ -313,8 +563,13 @@ mod tests {
             temperature: None,
             max_tokens: None,
             stream: None,
+            stream_options: None,
             top_p: None,
+            stop: None,
+            user: None,
         };
         assert!(req.validate().is_err());
+        assert_eq!(req.validate().unwrap_err(), ValidationError::EmptyMessages);
+        assert_eq!(req.validate().unwrap_err().code(), "empty_array");
     }
 }
@@ -0,0 +1,119 @@
+use super::ValidationError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// OpenAI 兼容的内容审核请求（/v1/moderations）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationRequest {
+    /// 未指定时使用 general_settings.moderation_model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub input: ModerationInput,
+}
+
+/// input 可以是字符串、字符串数组或多模态输入数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ModerationInput {
+    Text(String),
+    TextBatch(Vec<String>),
+    Multimodal(Vec<serde_json::Value>),
+}
+
+impl ModerationRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let empty = match &self.input {
+            ModerationInput::Text(_) => false,
+            ModerationInput::TextBatch(texts) => texts.is_empty(),
+            ModerationInput::Multimodal(items) => items.is_empty(),
+        };
+        if empty {
+            return Err(ValidationError::EmptyInput);
+        }
+
+        Ok(())
+    }
+}
+
+/// 内容审核响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationResponse {
+    pub id: String,
+    pub model: String,
+    pub results: Vec<ModerationResult>,
+}
+
+/// 单条输入的审核结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    #[serde(default)]
+    pub categories: BTreeMap<String, serde_json::Value>,
+    /// category_scores、category_applied_input_types 等字段原样保留
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ModerationResponse {
+    /// 是否有任意输入被标记
+    pub fn flagged(&self) -> bool {
+        self.results.iter().any(|result| result.flagged)
+    }
+
+    /// 被标记的类别（去重，按名称排序）
+    pub fn flagged_categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = self
+            .results
+            .iter()
+            .flat_map(|result| &result.categories)
+            .filter(|(_, value)| value.as_bool() == Some(true))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        categories.sort_unstable();
+        categories.dedup();
+        categories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moderation_response() {
+        let resp: ModerationResponse = serde_json::from_str(
+            r#"{
+            "id": "modr-1",
+            "model": "omni-moderation-latest",
+            "results": [
+                {"flagged": false, "categories": {"violence": false}, "category_scores": {"violence": 0.01}},
+                {"flagged": true, "categories": {"violence": true, "harassment": true, "illicit": null}, "category_scores": {"violence": 0.9}}
+            ]
+        }"#,
+        )
+        .unwrap();
+
+        assert!(resp.flagged());
+        assert_eq!(resp.flagged_categories(), vec!["harassment", "violence"]);
+
+        let json = serde_json::to_value(&resp).unwrap();
+        assert_eq!(json["results"][1]["category_scores"]["violence"], 0.9);
+    }
+
+    #[test]
+    fn test_moderation_input_variants() {
+        let req: ModerationRequest = serde_json::from_str(r#"{"input": ["a", "b"]}"#).unwrap();
+        assert_eq!(req.model, None);
+        assert_eq!(
+            req.input,
+            ModerationInput::TextBatch(vec!["a".to_string(), "b".to_string()])
+        );
+
+        let req: ModerationRequest = serde_json::from_str(
+            r#"{"model": "m", "input": [{"type": "text", "text": "hi"}]}"#,
+        )
+        .unwrap();
+        assert!(matches!(req.input, ModerationInput::Multimodal(_)));
+    }
+}
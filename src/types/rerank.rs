@@ -0,0 +1,166 @@
+use super::ValidationError;
+use serde::{Deserialize, Serialize};
+
+/// Cohere 兼容的重排序请求（/v1/rerank）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerankRequest {
+    pub model: String,
+    pub query: String,
+    pub documents: Vec<RerankDocument>,
+    /// 只返回得分最高的前 N 条，未指定时返回全部
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_n: Option<u32>,
+    /// 是否在结果中附带文档内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_documents: Option<bool>,
+}
+
+/// 文档可以是字符串或 `{"text": ...}` 对象
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RerankDocument {
+    Text(String),
+    Object { text: String },
+}
+
+impl RerankDocument {
+    /// 文档文本
+    pub fn text(&self) -> &str {
+        match self {
+            RerankDocument::Text(text) | RerankDocument::Object { text } => text,
+        }
+    }
+}
+
+impl RerankRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.query.is_empty() {
+            return Err(ValidationError::EmptyQuery);
+        }
+
+        if self.documents.is_empty() {
+            return Err(ValidationError::EmptyDocuments);
+        }
+
+        if self.top_n == Some(0) {
+            return Err(ValidationError::TopNOutOfRange(0));
+        }
+
+        Ok(())
+    }
+
+    /// 文档文本列表
+    pub fn document_texts(&self) -> Vec<&str> {
+        self.documents.iter().map(RerankDocument::text).collect()
+    }
+}
+
+/// 归一化的重排序响应（与 Cohere、Jina 的格式一致）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerankResponse {
+    pub id: String,
+    pub model: String,
+    /// 按 relevance_score 从高到低排列
+    pub results: Vec<RerankResult>,
+    pub usage: RerankUsage,
+}
+
+/// 单条文档的重排序结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RerankResult {
+    /// 文档在请求 documents 中的下标
+    pub index: u32,
+    pub relevance_score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document: Option<RerankResultDocument>,
+}
+
+/// 结果中附带的文档内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RerankResultDocument {
+    pub text: String,
+}
+
+/// 重排序用量（Cohere 按 search_units 计费，Jina 和 TEI 按 token 计费）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RerankUsage {
+    pub total_tokens: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_units: Option<u32>,
+}
+
+impl RerankResponse {
+    /// 由上游返回的 (下标, 得分) 构造响应：按得分降序排列，截取 top_n，按需附带文档
+    pub fn new(
+        model: impl Into<String>,
+        req: &RerankRequest,
+        mut scores: Vec<(u32, f64)>,
+        usage: RerankUsage,
+    ) -> Self {
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        if let Some(top_n) = req.top_n {
+            scores.truncate(top_n as usize);
+        }
+
+        let results = scores
+            .into_iter()
+            .map(|(index, relevance_score)| RerankResult {
+                index,
+                relevance_score,
+                document: req
+                    .return_documents
+                    .unwrap_or_default()
+                    .then(|| req.documents.get(index as usize))
+                    .flatten()
+                    .map(|document| RerankResultDocument {
+                        text: document.text().to_string(),
+                    }),
+            })
+            .collect();
+
+        RerankResponse {
+            id: format!("rerank-{}", uuid::Uuid::new_v4()),
+            model: model.into(),
+            results,
+            usage,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_variants_and_validate() {
+        let req: RerankRequest = serde_json::from_str(
+            r#"{"model": "rerank", "query": "q", "documents": ["a", {"text": "b"}], "top_n": 0}"#,
+        )
+        .unwrap();
+        assert_eq!(req.document_texts(), vec!["a", "b"]);
+        assert_eq!(req.validate(), Err(ValidationError::TopNOutOfRange(0)));
+
+        let req: RerankRequest =
+            serde_json::from_str(r#"{"model": "rerank", "query": "q", "documents": []}"#).unwrap();
+        assert_eq!(req.validate(), Err(ValidationError::EmptyDocuments));
+    }
+
+    #[test]
+    fn test_response_sorted_and_truncated() {
+        let req: RerankRequest = serde_json::from_str(
+            r#"{"model": "rerank", "query": "q", "documents": ["a", "b", "c"], "top_n": 2, "return_documents": true}"#,
+        )
+        .unwrap();
+
+        let response = RerankResponse::new(
+            "rerank-v3",
+            &req,
+            vec![(0, 0.1), (1, 0.9), (2, 0.5)],
+            RerankUsage::default(),
+        );
+        let indices: Vec<u32> = response.results.iter().map(|r| r.index).collect();
+        assert_eq!(indices, vec![1, 2]);
+        assert_eq!(response.results[0].document.as_ref().unwrap().text, "b");
+    }
+}
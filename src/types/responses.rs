@@ -0,0 +1,229 @@
+use super::ValidationError;
+use serde::{Deserialize, Serialize};
+
+/// OpenAI Responses API 请求（/v1/responses）
+///
+/// 未建模的字段（tools、reasoning、text 等）保存在 `extra` 中，透传到 OpenAI 时原样保留。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponsesRequest {
+    pub model: String,
+    pub input: ResponsesInput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_response_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store: Option<bool>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// input 可以是字符串或输入项数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ResponsesInput {
+    Text(String),
+    Items(Vec<ResponseItem>),
+}
+
+/// 输入项或输出项（消息、函数调用等）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseItem {
+    /// 省略时表示消息
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<ItemContent>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 消息内容可以是字符串或内容块数组
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ItemContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+/// 内容块（input_text、output_text 等）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentPart {
+    #[serde(rename = "type")]
+    pub part_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ResponsesInput {
+    /// 转换为输入项列表（字符串视为一条用户消息）
+    pub fn items(&self) -> Vec<ResponseItem> {
+        match self {
+            ResponsesInput::Text(text) => vec![ResponseItem::message("user", text.clone())],
+            ResponsesInput::Items(items) => items.clone(),
+        }
+    }
+}
+
+impl ResponseItem {
+    /// 纯文本消息项
+    pub fn message(role: impl Into<String>, text: impl Into<String>) -> Self {
+        ResponseItem {
+            role: Some(role.into()),
+            content: Some(ItemContent::Text(text.into())),
+            ..Default::default()
+        }
+    }
+
+    /// 是否为消息项
+    pub fn is_message(&self) -> bool {
+        self.item_type.as_deref().is_none_or(|t| t == "message")
+    }
+
+    /// 消息的文本内容（拼接所有文本块）
+    pub fn text(&self) -> String {
+        match &self.content {
+            Some(ItemContent::Text(text)) => text.clone(),
+            Some(ItemContent::Parts(parts)) => parts
+                .iter()
+                .filter_map(|part| part.text.as_deref())
+                .collect(),
+            None => String::new(),
+        }
+    }
+
+    /// 助手输出的文本消息
+    pub fn output_message(id: impl Into<String>, text: impl Into<String>) -> Self {
+        ResponseItem {
+            item_type: Some("message".to_string()),
+            id: Some(id.into()),
+            status: Some("completed".to_string()),
+            role: Some("assistant".to_string()),
+            content: Some(ItemContent::Parts(vec![ContentPart::output_text(text)])),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl ContentPart {
+    /// 输出文本块
+    pub fn output_text(text: impl Into<String>) -> Self {
+        let mut extra = serde_json::Map::new();
+        extra.insert("annotations".to_string(), serde_json::json!([]));
+        ContentPart {
+            part_type: "output_text".to_string(),
+            text: Some(text.into()),
+            extra,
+        }
+    }
+}
+
+impl ResponsesRequest {
+    /// 验证请求参数范围
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if let Some(temp) = self.temperature {
+            if !(0.0..=2.0).contains(&temp) {
+                return Err(ValidationError::TemperatureOutOfRange(temp));
+            }
+        }
+
+        if let Some(top_p) = self.top_p {
+            if !(0.0..=1.0).contains(&top_p) {
+                return Err(ValidationError::TopPOutOfRange(top_p));
+            }
+        }
+
+        if matches!(&self.input, ResponsesInput::Items(items) if items.is_empty()) {
+            return Err(ValidationError::EmptyInput);
+        }
+
+        Ok(())
+    }
+}
+
+/// Responses API 响应对象
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseObject {
+    pub id: String,
+    pub object: String,
+    pub created_at: u64,
+    pub status: String,
+    pub model: String,
+    pub output: Vec<ResponseItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ResponseUsage>,
+    #[serde(default)]
+    pub previous_response_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Responses API 的 token 用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub total_tokens: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_items() {
+        let req: ResponsesRequest = serde_json::from_str(
+            r#"{
+            "model": "m",
+            "instructions": "Be brief",
+            "input": [
+                {"role": "user", "content": "Hi"},
+                {"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "Hello"}]},
+                {"type": "function_call", "call_id": "c1", "name": "f", "arguments": "{}"}
+            ],
+            "tools": [{"type": "web_search"}]
+        }"#,
+        )
+        .unwrap();
+
+        let ResponsesInput::Items(items) = &req.input else {
+            panic!("expected items");
+        };
+        assert!(items[0].is_message());
+        assert_eq!(items[0].text(), "Hi");
+        assert_eq!(items[1].text(), "Hello");
+        assert!(!items[2].is_message());
+        assert_eq!(items[2].extra["call_id"], "c1");
+        assert!(req.extra.contains_key("tools"));
+
+        // 未建模的字段原样序列化
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["tools"][0]["type"], "web_search");
+        assert_eq!(json["input"][2]["name"], "f");
+    }
+
+    #[test]
+    fn test_output_message_serialization() {
+        let item = ResponseItem::output_message("msg_1", "Hi");
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["type"], "message");
+        assert_eq!(json["content"][0]["type"], "output_text");
+        assert_eq!(json["content"][0]["annotations"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,283 @@
+use super::keys::validate_budget_duration;
+use super::ValidationError;
+use crate::auth::teams::{EntitySettings, SettingsUpdate, TeamMember};
+use serde::{Deserialize, Serialize};
+
+/// 团队和组织共有的设置字段（创建时）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsRequest {
+    /// 允许使用的 model_name 或访问组，空列表表示继承上一级
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// 预算上限（美元）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_budget: Option<f64>,
+    /// 预算周期（如 `30d`），到期后花费清零
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_duration: Option<String>,
+    /// 每分钟请求数限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm_limit: Option<u64>,
+    /// 每分钟 token 数限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm_limit: Option<u64>,
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<SettingsRequest> for EntitySettings {
+    fn from(req: SettingsRequest) -> Self {
+        EntitySettings {
+            models: req.models,
+            max_budget: req.max_budget,
+            budget_duration: req.budget_duration,
+            rpm_limit: req.rpm_limit,
+            tpm_limit: req.tpm_limit,
+            metadata: req.metadata,
+        }
+    }
+}
+
+/// 团队和组织共有的设置字段（修改时），只修改请求中出现的字段
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsUpdateRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_budget: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_duration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl From<SettingsUpdateRequest> for SettingsUpdate {
+    fn from(req: SettingsUpdateRequest) -> Self {
+        SettingsUpdate {
+            models: req.models,
+            max_budget: req.max_budget,
+            budget_duration: req.budget_duration,
+            rpm_limit: req.rpm_limit,
+            tpm_limit: req.tpm_limit,
+            metadata: req.metadata,
+        }
+    }
+}
+
+/// litellm 兼容的创建团队请求（/team/new）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewTeamRequest {
+    /// 未指定时自动生成
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    #[serde(default)]
+    pub members_with_roles: Vec<TeamMember>,
+    #[serde(flatten)]
+    pub settings: SettingsRequest,
+}
+
+impl NewTeamRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_budget_duration(self.settings.budget_duration.as_deref())
+    }
+}
+
+/// litellm 兼容的修改团队请求（/team/update）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateTeamRequest {
+    #[serde(default)]
+    pub team_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    #[serde(flatten)]
+    pub settings: SettingsUpdateRequest,
+}
+
+impl UpdateTeamRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.team_id.is_empty() {
+            return Err(ValidationError::MissingField("team_id"));
+        }
+        validate_budget_duration(self.settings.budget_duration.as_deref())
+    }
+}
+
+/// litellm 兼容的删除团队请求（/team/delete）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteTeamRequest {
+    #[serde(default)]
+    pub team_ids: Vec<String>,
+}
+
+impl DeleteTeamRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.team_ids.is_empty() {
+            return Err(ValidationError::MissingField("team_ids"));
+        }
+        Ok(())
+    }
+}
+
+/// 一个或多个成员（litellm 的 `member` 字段两种形式都接受）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Members {
+    One(TeamMember),
+    Many(Vec<TeamMember>),
+}
+
+impl Members {
+    pub fn into_vec(self) -> Vec<TeamMember> {
+        match self {
+            Members::One(member) => vec![member],
+            Members::Many(members) => members,
+        }
+    }
+}
+
+/// litellm 兼容的添加成员请求（/team/member_add）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamMemberAddRequest {
+    #[serde(default)]
+    pub team_id: String,
+    pub member: Members,
+}
+
+impl TeamMemberAddRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.team_id.is_empty() {
+            return Err(ValidationError::MissingField("team_id"));
+        }
+        Ok(())
+    }
+}
+
+/// litellm 兼容的移除成员请求（/team/member_delete）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TeamMemberDeleteRequest {
+    #[serde(default)]
+    pub team_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+}
+
+impl TeamMemberDeleteRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.team_id.is_empty() {
+            return Err(ValidationError::MissingField("team_id"));
+        }
+        if self.user_id.is_none() {
+            return Err(ValidationError::MissingField("user_id"));
+        }
+        Ok(())
+    }
+}
+
+/// 创建组织请求（/organization/new）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NewOrganizationRequest {
+    /// 未指定时自动生成
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_alias: Option<String>,
+    #[serde(flatten)]
+    pub settings: SettingsRequest,
+}
+
+impl NewOrganizationRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_budget_duration(self.settings.budget_duration.as_deref())
+    }
+}
+
+/// 修改组织请求（/organization/update）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateOrganizationRequest {
+    #[serde(default)]
+    pub organization_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_alias: Option<String>,
+    #[serde(flatten)]
+    pub settings: SettingsUpdateRequest,
+}
+
+impl UpdateOrganizationRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.organization_id.is_empty() {
+            return Err(ValidationError::MissingField("organization_id"));
+        }
+        validate_budget_duration(self.settings.budget_duration.as_deref())
+    }
+}
+
+/// 删除组织请求（/organization/delete）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteOrganizationRequest {
+    #[serde(default)]
+    pub organization_ids: Vec<String>,
+}
+
+impl DeleteOrganizationRequest {
+    /// 验证请求参数
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.organization_ids.is_empty() {
+            return Err(ValidationError::MissingField("organization_ids"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::teams::MemberRole;
+
+    #[test]
+    fn test_member_add_accepts_one_or_many() {
+        let req: TeamMemberAddRequest =
+            serde_json::from_str(r#"{"team_id": "search", "member": {"user_id": "alice", "role": "admin"}}"#).unwrap();
+        let members = req.member.into_vec();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].role, MemberRole::Admin);
+
+        let req: TeamMemberAddRequest =
+            serde_json::from_str(r#"{"team_id": "search", "member": [{"user_id": "alice"}, {"user_id": "bob"}]}"#)
+                .unwrap();
+        let members = req.member.into_vec();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[1].role, MemberRole::User);
+    }
+
+    #[test]
+    fn test_team_request_validation() {
+        let req: NewTeamRequest =
+            serde_json::from_str(r#"{"team_alias": "search", "models": ["gpt-4o"], "budget_duration": "1y"}"#).unwrap();
+        assert_eq!(req.settings.models, vec!["gpt-4o"]);
+        assert_eq!(
+            req.validate(),
+            Err(ValidationError::InvalidDuration("budget_duration", "1y".to_string()))
+        );
+
+        let req: UpdateTeamRequest = serde_json::from_str(r#"{"max_budget": 10}"#).unwrap();
+        assert_eq!(req.validate(), Err(ValidationError::MissingField("team_id")));
+        assert!(DeleteOrganizationRequest::default().validate().is_err());
+    }
+}
@@ -14,8 +14,11 @@ async fn test_streaming_endpoint_detection() {
                 model: "openai/gpt-4".to_string(),
                 api_key: "sk-test-key".to_string(),
                 api_base: "https://api.openai.com/v1".to_string(),
+                ..Default::default()
             },
+            ..Default::default()
         }],
+        ..Default::default()
     });
 
     let addr: std::net::SocketAddr = "127.0.0.1:18090".parse().unwrap();
@@ -70,8 +73,11 @@ async fn test_non_streaming_still_works() {
                 model: "openai/gpt-4".to_string(),
                 api_key: "sk-test".to_string(),
                 api_base: "https://api.openai.com/v1".to_string(),
+                ..Default::default()
             },
+            ..Default::default()
         }],
+        ..Default::default()
     });
 
     let addr: std::net::SocketAddr = "127.0.0.1:18091".parse().unwrap();
@@ -103,3 +109,120 @@ async fn test_non_streaming_still_works() {
     // 验证非流式请求仍然工作
     assert!(result.is_ok(), "非流式请求应该仍然工作");
 }
+
+/// 测试无效 JSON 请求体返回 400 和 OpenAI 错误格式
+#[tokio::test]
+async fn test_malformed_body_returns_400() {
+    let config = Arc::new(Config {
+        model_list: vec![ModelConfig {
+            model_name: "test-model".to_string(),
+            litellm_params: LitellmParams {
+                model: "openai/gpt-4".to_string(),
+                api_key: "sk-test".to_string(),
+                api_base: "https://api.openai.com/v1".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+
+    let addr: std::net::SocketAddr = "127.0.0.1:18092".parse().unwrap();
+
+    // 启动服务器
+    let server_config = Arc::clone(&config);
+    tokio::spawn(async move {
+        let _ = server::start_server_test(server_config, addr).await;
+    });
+
+    // 等待服务器启动
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let client = reqwest::Client::new();
+    let response = timeout(
+        Duration::from_secs(3),
+        client
+            .post("http://127.0.0.1:18092/v1/chat/completions")
+            .header("Content-Type", "application/json")
+            .body("{not json")
+            .send(),
+    )
+    .await
+    .expect("请求超时")
+    .expect("请求失败");
+
+    assert_eq!(response.status(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+    assert!(body["error"].get("param").is_some());
+    assert!(body["error"].get("code").is_some());
+}
+
+/// 测试语音转文本的 multipart 上传被流式转发到上游，并原样返回上游响应
+#[tokio::test]
+async fn test_audio_transcription_multipart_passthrough() {
+    let mut upstream = mockito::Server::new_async().await;
+    let mock = upstream
+        .mock("POST", "/audio/transcriptions")
+        .match_body(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::Regex("name=\"model\"\r\n\r\nwhisper-1".to_string()),
+            mockito::Matcher::Regex("name=\"response_format\"\r\n\r\ntext".to_string()),
+            mockito::Matcher::Regex("fake-wav-bytes".to_string()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "text/plain")
+        .with_body("hello world")
+        .create_async()
+        .await;
+
+    let config = Arc::new(Config {
+        model_list: vec![ModelConfig {
+            model_name: "whisper".to_string(),
+            litellm_params: LitellmParams {
+                model: "openai/whisper-1".to_string(),
+                api_key: "sk-test".to_string(),
+                api_base: upstream.url(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+
+    let addr: std::net::SocketAddr = "127.0.0.1:18093".parse().unwrap();
+
+    // 启动服务器
+    let server_config = Arc::clone(&config);
+    tokio::spawn(async move {
+        let _ = server::start_server_test(server_config, addr).await;
+    });
+
+    // 等待服务器启动
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let form = reqwest::multipart::Form::new()
+        .text("model", "whisper")
+        .text("response_format", "text")
+        .part(
+            "file",
+            reqwest::multipart::Part::bytes(b"fake-wav-bytes".to_vec()).file_name("a.wav"),
+        );
+
+    let client = reqwest::Client::new();
+    let response = timeout(
+        Duration::from_secs(3),
+        client
+            .post("http://127.0.0.1:18093/v1/audio/transcriptions")
+            .multipart(form)
+            .send(),
+    )
+    .await
+    .expect("请求超时")
+    .expect("请求失败");
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "text/plain");
+    assert_eq!(response.text().await.unwrap(), "hello world");
+
+    mock.assert_async().await;
+}